@@ -303,9 +303,15 @@ impl PatternMatchConstructible for OperandPattern {
             "imm8" => {
                 quote! { if #parent.is_constant() && matches!(#parent.ty, Type::i8) { #body } }
             }
+            "imm16" => {
+                quote! { if #parent.is_constant() && matches!(#parent.ty, Type::i16) { #body } }
+            }
             "imm32" => {
                 quote! { if #parent.is_constant() && matches!(#parent.ty, Type::i32) { #body } }
             }
+            "imm_f32" => {
+                quote! { if #parent.is_constant() && matches!(#parent.ty, Type::f32) {  #body } }
+            }
             "imm_f64" => {
                 quote! { if #parent.is_constant() && matches!(#parent.ty, Type::f64) {  #body } }
             }
@@ -329,8 +335,9 @@ impl PatternMatchConstructible for OperandPattern {
                     }
                 }
             }
-            "f64mem" => {
+            "f32mem" | "f64mem" => {
                 let ty = match self.name.as_str() {
+                    "f32mem" => quote! { Type::f32 },
                     "f64mem" => quote! { Type::f64 },
                     _ => unimplemented!(),
                 };
@@ -148,22 +148,26 @@ impl CodeGenerator {
     pub fn run_on_module(&mut self, module: parser::Module) {
         println!("Parsed: {:#?}", module);
 
-        // Declare struct
+        // Declare struct. Create every struct first so that they can refer to each other
         for (name, decls) in &module.structs {
-            let decls_ = decls
-                .iter()
-                .map(|(_, ty)| ty.into_cilk_type(&self.types, &mut self.module.types))
-                .collect();
             let name2idx: HashMap<String, (usize, parser::Type)> = decls
                 .iter()
                 .enumerate()
                 .map(|(i, (name, ty))| (name.clone(), (i, ty.clone())))
                 .collect();
-            let struct_ty = self.module.types.new_struct_ty(decls_);
+            let struct_ty = self.module.types.new_named_struct_ty(name.as_str());
             self.types
                 .records
                 .insert(name.clone(), (struct_ty, name2idx));
         }
+        for (name, decls) in &module.structs {
+            let decls_ = decls
+                .iter()
+                .map(|(_, ty)| ty.into_cilk_type(&self.types, &mut self.module.types))
+                .collect();
+            let struct_ty = self.types.records[name].0;
+            self.module.types.set_struct_body(struct_ty, decls_);
+        }
 
         // Create function prototypes
         let mut worklist = vec![];
@@ -419,6 +423,18 @@ impl<'a> CodeGeneratorForFunction<'a> {
             Node::TypeCast(val, to) => {
                 let (val, ty) = self.run_on_node(val);
                 let to_ = to.into_cilk_type(&self.types, &mut self.builder.func.module.types);
+                match (&ty, to) {
+                    (parser::Type::Pointer(_), parser::Type::Pointer(_)) => {
+                        return (self.builder.build_bitcast(val, to_), to.clone())
+                    }
+                    (parser::Type::Pointer(_), parser::Type::Int64) => {
+                        return (self.builder.build_ptrtoint(val, to_), to.clone())
+                    }
+                    (parser::Type::Int64, parser::Type::Pointer(_)) => {
+                        return (self.builder.build_inttoptr(val, to_), to.clone())
+                    }
+                    _ => {}
+                }
                 if ty.is_integer() {
                     return (self.builder.build_sitofp(val, to_), to.clone());
                 }
@@ -553,6 +553,55 @@ fn pass_struct() {
     );
 }
 
+#[test]
+fn linked_list() {
+    let input = r#"
+    struct Node {
+        val: i32,
+        next: * struct Node
+    }
+
+    function sum(head: * struct Node, n: i32): i32 {
+        var p: * struct Node;
+        var i: i32;
+        var s: i32;
+        p = head;
+        i = 0;
+        s = 0;
+        while i < n {
+            s = s + (*p).val;
+            p = (*p).next;
+            i = i + 1;
+        }
+        return s;
+    }
+    "#;
+    let mut codegen = codegen::CodeGenerator::new();
+    codegen.run(input);
+
+    cilk::ir::mem2reg::Mem2Reg::new().run_on_module(&mut codegen.module);
+
+    use cilk::codegen::x64::asm::print::MachineAsmPrinter;
+    use cilk::codegen::x64::standard_conversion_into_machine_module;
+    let machine_module = standard_conversion_into_machine_module(&mut codegen.module);
+    let mut printer = MachineAsmPrinter::new();
+    printer.run_on_module(&machine_module);
+    assemble_and_run(
+        "
+        #include <assert.h>
+        struct Node { int val; struct Node *next; };
+        extern int sum(struct Node *head, int n);
+        int main() {
+            struct Node c = {5, 0}, b = {4, &c}, a = {3, &b};
+            c.next = &a;
+            assert(sum(&a, 4) == 15);
+            return 0;
+        }",
+        printer.output.as_str(),
+        None,
+    );
+}
+
 #[test]
 fn rand_mandelbrot() {
     let input = r#"
@@ -126,6 +126,10 @@ impl<'a, BBS: BasicBlocksTrait> DominatorTreeConstructor<'a, BBS> {
             let mut s = pred;
 
             for v in self.basic_blocks.get_arena()[node].get_preds() {
+                // A predecessor not reachable from the entry doesn't affect dominance
+                if !self.dfnum.contains_key(v) {
+                    continue;
+                }
                 let s_ = if self.dfnum[v] <= self.dfnum[&node] {
                     *v
                 } else {
@@ -5,7 +5,10 @@ use crate::codegen::common::machine::{
     function::{InstIter, MachineFunction},
     module::MachineModule,
 };
-use crate::ir::{global_val::GlobalVariableId, types::TypeSize};
+use crate::ir::{
+    global_val::{GlobalVariableId, Symbol},
+    types::TypeSize,
+};
 use rustc_hash::FxHashMap;
 
 pub struct MachineAsmPrinter<'a> {
@@ -29,9 +32,57 @@ impl<'s> MachineAsmPrinter<'s> {
         for (id, g) in &m.global_vars.arena {
             let size = g.ty.size_in_byte(&m.types);
             let align = g.ty.align_in_byte(&m.types);
-            self.output
-                .push_str(format!("  .comm {},{},{}\n", g.name, size, align).as_str());
             self.global_var_name.insert(id, g.name.as_str());
+            if g.init.is_none() {
+                self.output
+                    .push_str(format!("  .comm {},{},{}\n", g.name, size, align).as_str());
+                continue;
+            }
+            self.output.push_str(if g.is_constant {
+                "  .section .rodata\n"
+            } else {
+                "  .data\n"
+            });
+            self.output.push_str(
+                format!(
+                    "  .globl {}\n  .p2align {}\n{}:\n",
+                    g.name,
+                    align.trailing_zeros(),
+                    g.name
+                )
+                .as_str(),
+            );
+            let bytes = g.init_bytes(&m.types);
+            let relocs = g.relocations(&m.types);
+            if relocs.is_empty() && bytes.iter().all(|&b| b == 0) {
+                self.output.push_str(format!("  .zero {}\n", size).as_str());
+            } else {
+                // Addresses are emitted as relocations between the runs of bytes
+                let mut start = 0;
+                for reloc in relocs.iter().map(Some).chain(vec![None]) {
+                    let end = reloc.map_or(bytes.len(), |r| r.offset);
+                    for chunk in bytes[start..end].chunks(16) {
+                        let chunk: Vec<String> = chunk.iter().map(|b| b.to_string()).collect();
+                        self.output
+                            .push_str(format!("  .byte {}\n", chunk.join(",")).as_str());
+                    }
+                    if let Some(reloc) = reloc {
+                        let name = match &reloc.symbol {
+                            Symbol::Global(id) => m.global_vars.arena[*id].name.as_str(),
+                            Symbol::Function(name) => name.as_str(),
+                        };
+                        self.output.push_str(
+                            match reloc.addend {
+                                0 => format!("  .quad {}\n", name),
+                                addend => format!("  .quad {}{:+}\n", name, addend),
+                            }
+                            .as_str(),
+                        );
+                        start = end + 8;
+                    }
+                }
+            }
+            self.output.push_str("  .text\n");
         }
 
         for (_, func) in &m.functions {
@@ -57,9 +108,13 @@ impl<'s> MachineAsmPrinter<'s> {
         // tys: &Types,
         f: &MachineFunction,
     ) {
-        for (id, _, inst_iter) in f.body.mbb_iter() {
+        for (id, bb, inst_iter) in f.body.mbb_iter() {
             self.output
-                .push_str(format!("{}:\n", self.bb_id_to_label_id(&id)).as_str());
+                .push_str(format!("{}:", self.bb_id_to_label_id(&id)).as_str());
+            if let Some(name) = &bb.name {
+                self.output.push_str(format!(" // {}", name).as_str());
+            }
+            self.output.push('\n');
             self.run_on_basic_block(inst_iter, &f.frame_objects.as_ref().unwrap());
         }
         self.cur_bb_id_base += f.body.basic_blocks.arena.len();
@@ -107,6 +162,11 @@ impl<'s> MachineAsmPrinter<'s> {
             }
         }
 
+        if let Some(cond) = inst.opcode.cond_suffix() {
+            self.output.push_str(", ");
+            self.output.push_str(cond);
+        }
+
         self.output.push('\n');
     }
 
@@ -113,6 +113,43 @@ impl<'a> ScheduleByBlock<'a> {
                     self.cur_bb,
                 ))
             }
+            NodeKind::IR(IRNodeKind::Selectcc) => {
+                let op0 = self.normal_operand(node.operand[1]);
+                let op1 = self.normal_operand(node.operand[2]);
+                // csel only takes registers
+                let t = self.operand_to_reg(node.operand[3]);
+                let f = self.operand_to_reg(node.operand[4]);
+
+                self.append_inst(MachineInst::new_simple(
+                    if op0.is_register() && op1.is_constant() {
+                        MachineOpcode::CMPri
+                    } else if op0.is_register() && op1.is_register() {
+                        unimplemented!()
+                    // MachineOpcode::CMPrr
+                    } else {
+                        unreachable!()
+                    },
+                    vec![op0, op1],
+                    self.cur_bb,
+                ));
+
+                let csel = MachineInst::new(
+                    &self.cur_func.regs_info,
+                    match cond_kind!(node.operand[0]) {
+                        CondKind::Eq => MachineOpcode::CSEL_EQ,
+                        CondKind::Ne => MachineOpcode::CSEL_NE,
+                        CondKind::Le => MachineOpcode::CSEL_LE,
+                        CondKind::Lt => MachineOpcode::CSEL_LT,
+                        CondKind::Ge => MachineOpcode::CSEL_GE,
+                        CondKind::Gt => MachineOpcode::CSEL_GT,
+                        _ => unimplemented!(),
+                    },
+                    vec![t, f],
+                    ty2rc(&node.ty),
+                    self.cur_bb,
+                );
+                self.append_inst(csel)
+            }
             NodeKind::IR(IRNodeKind::Ret) => self.convert_ret(&*node),
             NodeKind::IR(IRNodeKind::CopyToLiveOut) => self.convert_node_to_inst(node.operand[0]),
             e => panic!("{:?}, {:?}", e, node.ty),
@@ -138,6 +175,23 @@ impl<'a> ScheduleByBlock<'a> {
         ))
     }
 
+    fn operand_to_reg(&mut self, node: Raw<DAGNode>) -> MachineOperand {
+        let op = self.normal_operand(node);
+        if op.is_register() {
+            return op;
+        }
+        let inst = MachineInst::new(
+            &self.cur_func.regs_info,
+            opcode_copy2reg(&op),
+            vec![op],
+            ty2rc(&node.ty),
+            self.cur_bb,
+        );
+        let reg = MachineOperand::Register(inst.def[0]);
+        self.append_inst(inst);
+        reg
+    }
+
     fn move2reg(&self, r: RegisterId, src: MachineOperand) -> MachineInst {
         MachineInst::new_simple(opcode_copy2reg(&src), vec![src], self.cur_bb)
             .with_def(vec![RegisterOperand::new(r)])
@@ -255,8 +309,10 @@ impl<'a> ScheduleByBlock<'a> {
         match node.kind {
             NodeKind::Operand(OperandNodeKind::Constant(c)) => match c {
                 ConstantKind::Int8(i) => MachineOperand::Constant(MachineConstant::Int8(i)),
+                ConstantKind::Int16(i) => MachineOperand::Constant(MachineConstant::Int16(i)),
                 ConstantKind::Int32(i) => MachineOperand::Constant(MachineConstant::Int32(i)),
                 ConstantKind::Int64(i) => MachineOperand::Constant(MachineConstant::Int64(i)),
+                ConstantKind::F32(f) => MachineOperand::Constant(MachineConstant::F32(f)),
                 ConstantKind::F64(f) => MachineOperand::Constant(MachineConstant::F64(f)),
             },
             NodeKind::Operand(OperandNodeKind::FrameIndex(ref kind)) => {
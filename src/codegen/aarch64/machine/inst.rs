@@ -18,6 +18,10 @@ impl MachineOpcode {
         matches!(self, MachineOpcode::Copy | MachineOpcode::MOVrr)
     }
 
+    pub fn is_call(&self) -> bool {
+        matches!(self, MachineOpcode::CALL)
+    }
+
     pub fn is_terminator(&self) -> bool {
         matches!(
             self,
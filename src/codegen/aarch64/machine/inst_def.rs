@@ -64,6 +64,30 @@ mod inst {
         pub static ref CMPri: TargetInstDef = TargetInstDef::new("cmp", TargetOpcode::CMPri)
             .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
                            TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32))]);
+        pub static ref CSEL_EQ: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_EQ)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
+        pub static ref CSEL_NE: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_NE)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
+        pub static ref CSEL_LT: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_LT)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
+        pub static ref CSEL_LE: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_LE)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
+        pub static ref CSEL_GT: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_GT)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
+        pub static ref CSEL_GE: TargetInstDef = TargetInstDef::new("csel", TargetOpcode::CSEL_GE)
+            .set_uses(vec![TargetOperand::Register(TargetRegister::Any),
+                           TargetOperand::Register(TargetRegister::Any)])
+            .set_defs(vec![TargetRegister::Any]);
         pub static ref B_EQ: TargetInstDef = TargetInstDef::new("b.eq", TargetOpcode::B_EQ).set_uses(vec![TargetOperand::Block]);
         pub static ref B_NE: TargetInstDef = TargetInstDef::new("b.ne", TargetOpcode::B_NE).set_uses(vec![TargetOperand::Block]);
         pub static ref B_LT: TargetInstDef = TargetInstDef::new("b.lt", TargetOpcode::B_LT).set_uses(vec![TargetOperand::Block]);
@@ -117,6 +141,12 @@ pub enum TargetOpcode {
     MULrrr64,
     SDIVrrr32,
     CMPri,
+    CSEL_EQ,
+    CSEL_NE,
+    CSEL_LT,
+    CSEL_LE,
+    CSEL_GT,
+    CSEL_GE,
     B_EQ,
     B_NE,
     B_LT,
@@ -164,6 +194,19 @@ pub enum TargetOpcode {
 }
 
 impl TargetOpcode {
+    /// Returns the condition printed as the last operand of csel.
+    pub fn cond_suffix(&self) -> Option<&'static str> {
+        match self {
+            Self::CSEL_EQ => Some("eq"),
+            Self::CSEL_NE => Some("ne"),
+            Self::CSEL_LT => Some("lt"),
+            Self::CSEL_LE => Some("le"),
+            Self::CSEL_GT => Some("gt"),
+            Self::CSEL_GE => Some("ge"),
+            _ => None,
+        }
+    }
+
     pub fn inst_def(&self) -> Option<&TargetInstDef> {
         match self {
             Self::MOVrr => Some(&*inst::MOVrr),
@@ -181,6 +224,12 @@ impl TargetOpcode {
             Self::MULrrr64 => Some(&*inst::MULrrr64),
             Self::SDIVrrr32 => Some(&*inst::SDIVrrr32),
             Self::CMPri => Some(&*inst::CMPri),
+            Self::CSEL_EQ => Some(&*inst::CSEL_EQ),
+            Self::CSEL_NE => Some(&*inst::CSEL_NE),
+            Self::CSEL_LT => Some(&*inst::CSEL_LT),
+            Self::CSEL_LE => Some(&*inst::CSEL_LE),
+            Self::CSEL_GT => Some(&*inst::CSEL_GT),
+            Self::CSEL_GE => Some(&*inst::CSEL_GE),
             Self::B_EQ => Some(&*inst::B_EQ),
             Self::B_NE => Some(&*inst::B_NE),
             Self::B_LT => Some(&*inst::B_LT),
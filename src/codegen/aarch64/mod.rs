@@ -20,8 +20,10 @@ impl TypeSize for Type {
         match self {
             Type::i1 => 1,
             Type::i8 => 1,
+            Type::i16 => 2,
             Type::i32 => 4,
             Type::i64 => 8,
+            Type::f32 => 4,
             Type::f64 => 8,
             Type::Array(id) => tys.base.borrow().non_primitive_types[*id]
                 .as_array()
@@ -43,8 +45,10 @@ impl TypeSize for Type {
         match self {
             Type::i1 => 1,
             Type::i8 => 1,
+            Type::i16 => 2,
             Type::i32 => 4,
             Type::i64 => 8,
+            Type::f32 => 4,
             Type::f64 => 8,
             Type::Array(id) => tys.base.borrow().non_primitive_types[*id]
                 .as_array()
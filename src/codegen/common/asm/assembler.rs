@@ -4,6 +4,7 @@ use crate::codegen::common::machine::{
     inst::MachineInst,
     module::MachineModule,
 };
+use crate::ir::{global_val::Symbol, types::TypeSize};
 use faerie::*;
 use id_arena::{Arena, Id};
 use rustc_hash::FxHashMap;
@@ -76,14 +77,69 @@ impl<'a> Assembler<'a> {
 
     pub fn assemble(&mut self) {
         for (_, func) in &self.module.functions {
+            if func.is_internal {
+                // Resolved by the linker (e.g. against libc)
+                self.artifact
+                    .declare(&func.name, Decl::function_import())
+                    .unwrap();
+                continue;
+            }
             self.artifact
                 .declare(&func.name, Decl::function().global())
                 .unwrap();
         }
 
+        for (_, g) in &self.module.global_vars.arena {
+            if g.init.is_none() {
+                continue;
+            }
+            let align = g.ty.align_in_byte(&self.module.types) as u64;
+            self.artifact
+                .declare(
+                    &g.name,
+                    Decl::data()
+                        .global()
+                        .with_writable(!g.is_constant)
+                        .with_align(Some(align)),
+                )
+                .unwrap();
+            self.artifact
+                .define(&g.name, g.init_bytes(&self.module.types))
+                .unwrap();
+        }
+
+        // Every symbol must be declared before it is linked to
+        for (_, g) in &self.module.global_vars.arena {
+            if g.init.is_none() {
+                continue;
+            }
+            for reloc in g.relocations(&self.module.types) {
+                let to = match &reloc.symbol {
+                    Symbol::Global(id) => self.module.global_vars.arena[*id].name.as_str(),
+                    Symbol::Function(name) => name.as_str(),
+                };
+                self.artifact
+                    .link_with(
+                        Link {
+                            from: g.name.as_str(),
+                            to,
+                            at: reloc.offset as u64,
+                        },
+                        Reloc::Raw {
+                            reloc: 1, // R_X86_64_64
+                            addend: reloc.addend as i32,
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+
         let mut func_streams = FxHashMap::default();
 
         for (id, func) in &self.module.functions {
+            if func.is_internal {
+                continue;
+            }
             let mut func_asmer = FunctionAssembler::new(self.module, func, &mut self.labels);
             func_asmer.assemble();
             func_streams.insert(id, func_asmer.stream);
@@ -113,6 +169,9 @@ impl<'a> Assembler<'a> {
         }
 
         for (id, func) in &self.module.functions {
+            if func.is_internal {
+                continue;
+            }
             let stream = func_streams.remove(&id).unwrap();
             self.artifact
                 .define(func.name.as_str(), stream.bytes)
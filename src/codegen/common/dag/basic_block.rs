@@ -20,6 +20,9 @@ pub struct DAGBasicBlock {
 
     /// Root node
     pub root: Option<Raw<DAGNode>>,
+
+    /// Name of the IR basic block, if it has one
+    pub name: Option<String>,
 }
 
 impl DAGBasicBlock {
@@ -29,6 +32,7 @@ impl DAGBasicBlock {
             root: None,
             pred: FxHashSet::default(),
             succ: FxHashSet::default(),
+            name: None,
         }
     }
 
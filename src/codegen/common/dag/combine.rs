@@ -60,7 +60,11 @@ impl Combine {
         let mut replaced = match &node.kind {
             NodeKind::IR(IRNodeKind::Add) => self.combine_node_add(replace, heap, node),
             NodeKind::IR(IRNodeKind::Mul) => self.combine_node_mul(replace, heap, node),
+            NodeKind::IR(IRNodeKind::And)
+            | NodeKind::IR(IRNodeKind::Or)
+            | NodeKind::IR(IRNodeKind::Xor) => self.combine_node_bitwise(replace, heap, node),
             NodeKind::IR(IRNodeKind::BrCond) => self.combine_node_brcond(replace, heap, node),
+            NodeKind::IR(IRNodeKind::Select) => self.combine_node_select(replace, heap, node),
             _ => self.combine_operands(replace, heap, node),
         };
 
@@ -147,6 +151,20 @@ impl Combine {
         self.combine_operands(replace, heap, node)
     }
 
+    fn combine_node_bitwise(
+        &mut self,
+        replace: &mut FxHashMap<Raw<DAGNode>, Raw<DAGNode>>,
+        heap: &mut DAGHeap,
+        mut node: Raw<DAGNode>,
+    ) -> Raw<DAGNode> {
+        // (C op !C) -> (!C op C)
+        if node.operand[0].is_constant() && !node.operand[1].is_constant() {
+            node.operand.swap(0, 1);
+        }
+
+        self.combine_operands(replace, heap, node)
+    }
+
     fn combine_node_brcond(
         &mut self,
         replace: &mut FxHashMap<Raw<DAGNode>, Raw<DAGNode>>,
@@ -174,6 +192,34 @@ impl Combine {
         }
     }
 
+    fn combine_node_select(
+        &mut self,
+        replace: &mut FxHashMap<Raw<DAGNode>, Raw<DAGNode>>,
+        heap: &mut DAGHeap,
+        node: Raw<DAGNode>,
+    ) -> Raw<DAGNode> {
+        let cond = node.operand[0];
+        match cond.kind {
+            NodeKind::IR(IRNodeKind::Setcc) | NodeKind::IR(IRNodeKind::FCmp) => {
+                let cond_kind = cond.operand[0];
+                let lhs = self.combine_node(replace, heap, cond.operand[1]);
+                let rhs = self.combine_node(replace, heap, cond.operand[2]);
+                let t = self.combine_node(replace, heap, node.operand[1]);
+                let f = self.combine_node(replace, heap, node.operand[2]);
+                heap.alloc(DAGNode::new(
+                    match cond.kind {
+                        NodeKind::IR(IRNodeKind::Setcc) => NodeKind::IR(IRNodeKind::Selectcc),
+                        NodeKind::IR(IRNodeKind::FCmp) => NodeKind::IR(IRNodeKind::FPSelectcc),
+                        _ => unreachable!(),
+                    },
+                    vec![cond_kind, lhs, rhs, t, f],
+                    node.ty,
+                ))
+            }
+            _ => self.combine_operands(replace, heap, node),
+        }
+    }
+
     fn combine_operands(
         &mut self,
         replace: &mut FxHashMap<Raw<DAGNode>, Raw<DAGNode>>,
@@ -74,7 +74,11 @@ impl<'a> ConvertToDAGModule<'a> {
             name: self.module.name.to_owned(),
             functions,
             types: self.module.types.clone(),
-            global_vars: self.module.global_vars.clone(),
+            global_vars: {
+                let mut global_vars = self.module.global_vars.clone();
+                global_vars.resolve_addresses(self.module);
+                global_vars
+            },
         }
     }
 }
@@ -82,7 +86,9 @@ impl<'a> ConvertToDAGModule<'a> {
 impl<'a> ConvertToDAGFunction<'a> {
     pub fn run(mut self) -> DAGFunction {
         for &bb_id in &self.func.basic_blocks.order {
-            let dag_bb_id = self.bb_arena.alloc(DAGBasicBlock::new());
+            let mut dag_bb = DAGBasicBlock::new();
+            dag_bb.name = self.func.names.blocks.get(&bb_id).cloned();
+            let dag_bb_id = self.bb_arena.alloc(dag_bb);
             self.bb_order.push(dag_bb_id);
             self.bb_map.insert(bb_id, dag_bb_id);
         }
@@ -118,7 +124,7 @@ impl<'a> ConvertToDAGFunction<'a> {
             dag_heap: self.node_heap,
             local_mgr: self.local_mgr,
             regs_info: self.regs_info,
-            is_internal: self.func.is_internal,
+            is_internal: self.func.is_internal || self.func.is_prototype,
             types: self.func.types.clone(),
         }
     }
@@ -155,7 +161,7 @@ impl<'a> ConvertToDAGNode<'a> {
             self.copy_reg_args();
         }
 
-        for inst_val in self.block.iseq_ref().iter() {
+        for (i, inst_val) in self.block.iseq_ref().iter().enumerate() {
             let inst_id = inst_val.get_inst_id().unwrap();
             let inst = &self.func.inst_table[inst_id];
             match inst.opcode {
@@ -186,6 +192,10 @@ impl<'a> ConvertToDAGNode<'a> {
                     if self.block.liveness.borrow().live_out.contains(&inst_id) {
                         let copy_from_reg = self.make_chain_with_copying(load_id);
                         self.inst_to_node.insert(inst_id, copy_from_reg);
+                    } else if self.is_only_callee_of(inst_id, self.block.iseq_ref().get(i + 1)) {
+                        // Leave the load of a function pointer that is called right away out of
+                        // the chain so that the call can take it as a memory operand
+                        self.inst_to_node.insert(inst_id, load_id);
                     } else {
                         self.make_chain(load_id);
                         self.inst_to_node.insert(inst_id, load_id);
@@ -216,7 +226,20 @@ impl<'a> ConvertToDAGNode<'a> {
                         .iter()
                         .map(|v| self.get_node_from_value(v.as_value()))
                         .collect();
-                    operands.insert(0, self.get_node_from_value(inst.operands[0].as_value()));
+                    let callee = match inst.operands[0].as_value() {
+                        Value::Function(FunctionValue { func_id, .. }) => {
+                            let f = self.module.function_ref(*func_id);
+                            self.alloc_node(DAGNode::new(
+                                NodeKind::Operand(OperandNodeKind::Address(
+                                    AddressKind::FunctionName(f.name.to_string()),
+                                )),
+                                vec![],
+                                f.ty,
+                            ))
+                        }
+                        callee => self.get_node_from_value(callee),
+                    };
+                    operands.insert(0, callee);
                     let id = self.alloc_node_as_necessary(
                         inst_id,
                         DAGNode::new(NodeKind::IR(IRNodeKind::Call), operands, inst.ty.clone()),
@@ -235,7 +258,14 @@ impl<'a> ConvertToDAGNode<'a> {
                 | Opcode::Mul
                 | Opcode::Div
                 | Opcode::Rem
-                | Opcode::Shl => {
+                | Opcode::UDiv
+                | Opcode::URem
+                | Opcode::Shl
+                | Opcode::LShr
+                | Opcode::AShr
+                | Opcode::And
+                | Opcode::Or
+                | Opcode::Xor => {
                     let v1 = self.get_node_from_value(inst.operands[0].as_value());
                     let v2 = self.get_node_from_value(inst.operands[1].as_value());
                     let bin_id = self.alloc_node_as_necessary(
@@ -247,7 +277,14 @@ impl<'a> ConvertToDAGNode<'a> {
                                 Opcode::Mul => NodeKind::IR(IRNodeKind::Mul),
                                 Opcode::Div => NodeKind::IR(IRNodeKind::Div),
                                 Opcode::Rem => NodeKind::IR(IRNodeKind::Rem),
+                                Opcode::UDiv => NodeKind::IR(IRNodeKind::UDiv),
+                                Opcode::URem => NodeKind::IR(IRNodeKind::URem),
                                 Opcode::Shl => NodeKind::IR(IRNodeKind::Shl),
+                                Opcode::LShr => NodeKind::IR(IRNodeKind::LShr),
+                                Opcode::AShr => NodeKind::IR(IRNodeKind::AShr),
+                                Opcode::And => NodeKind::IR(IRNodeKind::And),
+                                Opcode::Or => NodeKind::IR(IRNodeKind::Or),
+                                Opcode::Xor => NodeKind::IR(IRNodeKind::Xor),
                                 _ => unreachable!(),
                             },
                             vec![v1, v2],
@@ -282,11 +319,22 @@ impl<'a> ConvertToDAGNode<'a> {
                         self.inst_to_node.insert(inst_id, inst);
                     }
                 }
-                Opcode::Sext => {
+                Opcode::Sext | Opcode::ZExt | Opcode::Trunc | Opcode::FPExt | Opcode::FPTrunc => {
                     let x = self.get_node_from_value(inst.operands[0].as_value());
                     let inst = self.alloc_node_as_necessary(
                         inst_id,
-                        DAGNode::new(NodeKind::IR(IRNodeKind::Sext), vec![x], inst.ty),
+                        DAGNode::new(
+                            match inst.opcode {
+                                Opcode::Sext => NodeKind::IR(IRNodeKind::Sext),
+                                Opcode::ZExt => NodeKind::IR(IRNodeKind::ZExt),
+                                Opcode::Trunc => NodeKind::IR(IRNodeKind::Trunc),
+                                Opcode::FPExt => NodeKind::IR(IRNodeKind::FPExt),
+                                Opcode::FPTrunc => NodeKind::IR(IRNodeKind::FPTrunc),
+                                _ => unreachable!(),
+                            },
+                            vec![x],
+                            inst.ty,
+                        ),
                     );
                     if self.block.liveness.borrow().live_out.contains(&inst_id) {
                         let copy_from_reg = self.make_chain_with_copying(inst);
@@ -295,6 +343,28 @@ impl<'a> ConvertToDAGNode<'a> {
                         self.inst_to_node.insert(inst_id, inst);
                     }
                 }
+                // Pointer casts don't change any bits, so a copy into a register of the new type
+                // is enough
+                Opcode::Bitcast | Opcode::PtrToInt | Opcode::IntToPtr => {
+                    let x = self.get_node_from_value(inst.operands[0].as_value());
+                    let reg = self.alloc_node_as_necessary(
+                        inst_id,
+                        DAGNode::new(
+                            NodeKind::Operand(OperandNodeKind::Register(
+                                self.regs_info.new_virt_reg(ty2rc(&inst.ty).unwrap()),
+                            )),
+                            vec![],
+                            inst.ty,
+                        ),
+                    );
+                    let copy = self.node_heap.alloc(DAGNode::new(
+                        NodeKind::IR(IRNodeKind::CopyToReg),
+                        vec![reg, x],
+                        Type::Void,
+                    ));
+                    self.make_chain(copy);
+                    self.inst_to_node.insert(inst_id, reg);
+                }
                 Opcode::Br => {
                     let bb = self.node_heap.alloc(DAGNode::new(
                         NodeKind::Operand(OperandNodeKind::BasicBlock(
@@ -342,6 +412,26 @@ impl<'a> ConvertToDAGNode<'a> {
                     };
                     self.make_chain(br);
                 }
+                Opcode::Switch => {
+                    let mut operands = vec![self.get_node_from_value(inst.operands[0].as_value())];
+                    for (i, operand) in inst.operands[1..].iter().enumerate() {
+                        operands.push(match operand {
+                            Operand::BasicBlock(bb) => self.node_heap.alloc(DAGNode::new(
+                                NodeKind::Operand(OperandNodeKind::BasicBlock(self.bb_map[bb])),
+                                vec![],
+                                Type::Void,
+                            )),
+                            Operand::Value(v) if i % 2 == 1 => self.get_node_from_value(v),
+                            _ => unreachable!(),
+                        });
+                    }
+                    let switch = self.node_heap.alloc(DAGNode::new(
+                        NodeKind::IR(IRNodeKind::Switch),
+                        operands,
+                        Type::Void,
+                    ));
+                    self.make_chain(switch);
+                }
                 Opcode::ICmp => {
                     let c = *inst.operands[0].as_icmp_kind();
                     let v1 = self.get_node_from_value(inst.operands[1].as_value());
@@ -382,6 +472,25 @@ impl<'a> ConvertToDAGNode<'a> {
                         self.inst_to_node.insert(inst_id, id);
                     }
                 }
+                Opcode::Select => {
+                    let cond = self.get_node_from_value(inst.operands[0].as_value());
+                    let v1 = self.get_node_from_value(inst.operands[1].as_value());
+                    let v2 = self.get_node_from_value(inst.operands[2].as_value());
+                    let id = self.alloc_node_as_necessary(
+                        inst_id,
+                        DAGNode::new(
+                            NodeKind::IR(IRNodeKind::Select),
+                            vec![cond, v1, v2],
+                            inst.ty,
+                        ),
+                    );
+                    if self.block.liveness.borrow().live_out.contains(&inst_id) {
+                        let copy_from_reg = self.make_chain_with_copying(id);
+                        self.inst_to_node.insert(inst_id, copy_from_reg);
+                    } else {
+                        self.inst_to_node.insert(inst_id, id);
+                    }
+                }
                 Opcode::Phi => {
                     let mut operands = vec![];
                     for i in (0..inst.operands.len()).step_by(2) {
@@ -421,6 +530,14 @@ impl<'a> ConvertToDAGNode<'a> {
                     ));
                     self.make_chain(ret)
                 }
+                Opcode::Unreachable => {
+                    let unreachable = self.alloc_node(DAGNode::new(
+                        NodeKind::IR(IRNodeKind::Unreachable),
+                        vec![],
+                        Type::Void,
+                    ));
+                    self.make_chain(unreachable)
+                }
             }
         }
 
@@ -449,9 +566,13 @@ impl<'a> ConvertToDAGNode<'a> {
             }
             Value::Immediate(imm) => {
                 let imm = match imm {
+                    // i1 lives in an 8-bit register
+                    ImmediateValue::Int1(b) => ConstantKind::Int8(*b as i8),
                     ImmediateValue::Int8(i) => ConstantKind::Int8(*i),
+                    ImmediateValue::Int16(i) => ConstantKind::Int16(*i),
                     ImmediateValue::Int32(i) => ConstantKind::Int32(*i),
                     ImmediateValue::Int64(i) => ConstantKind::Int64(*i),
+                    ImmediateValue::F32(f) => ConstantKind::F32(*f),
                     ImmediateValue::F64(f) => ConstantKind::F64(*f),
                 };
                 self.alloc_node(DAGNode::new(
@@ -506,14 +627,21 @@ impl<'a> ConvertToDAGNode<'a> {
                     ))
                 }
             }
-            Value::Function(FunctionValue { func_id, ty }) => {
+            // The address of a function used as a value, not as a callee
+            Value::Function(FunctionValue { func_id, .. }) => {
                 let f = self.module.function_ref(*func_id);
-                self.alloc_node(DAGNode::new(
+                let ty = self.func.types.new_pointer_ty(f.ty);
+                let f = self.alloc_node(DAGNode::new(
                     NodeKind::Operand(OperandNodeKind::Address(AddressKind::FunctionName(
                         f.name.to_string(),
                     ))),
                     vec![],
-                    *ty,
+                    ty,
+                ));
+                self.alloc_node(DAGNode::new(
+                    NodeKind::IR(IRNodeKind::GlobalAddr),
+                    vec![f],
+                    ty,
                 ))
             }
             Value::Global(GlobalValue { id, ty }) => {
@@ -528,6 +656,37 @@ impl<'a> ConvertToDAGNode<'a> {
                     *ty,
                 ))
             }
+            Value::ConstExpr(ConstExprValue { ty, .. }) => {
+                let const_exprs = &self.module.const_exprs;
+                if let Some(folded) = const_exprs.fold(v) {
+                    return self.get_node_from_value(&folded);
+                }
+                let (base, offset) = const_exprs
+                    .resolve_address(&self.module.types, v)
+                    .expect("constant expression must fold or resolve to an address");
+                let base = match base {
+                    Value::Global(GlobalValue { id, .. }) => {
+                        Value::Global(GlobalValue { id, ty: *ty })
+                    }
+                    base => base,
+                };
+                let base = self.get_node_from_value(&base);
+                if offset == 0 {
+                    return base;
+                }
+                let offset = self.alloc_node(DAGNode::new(
+                    NodeKind::Operand(OperandNodeKind::Constant(ConstantKind::Int32(
+                        offset as i32,
+                    ))),
+                    vec![],
+                    Type::i32,
+                ));
+                self.alloc_node(DAGNode::new(
+                    NodeKind::IR(IRNodeKind::Add),
+                    vec![base, offset],
+                    *ty,
+                ))
+            }
             Value::None => self.alloc_node(DAGNode::new(NodeKind::None, vec![], Type::Void)),
         }
     }
@@ -654,6 +813,17 @@ impl<'a> ConvertToDAGNode<'a> {
         self.node_heap.alloc(new)
     }
 
+    fn is_only_callee_of(&self, inst_id: InstructionId, call: Option<&Value>) -> bool {
+        let call_id = match call.and_then(|v| v.get_inst_id()) {
+            Some(call_id) => call_id,
+            None => return false,
+        };
+        let call = &self.func.inst_table[call_id];
+        call.opcode == Opcode::Call
+            && call.operands[0].as_value().get_inst_id() == Some(inst_id)
+            && *self.func.inst_table[inst_id].users.borrow() == [call_id]
+    }
+
     pub fn alloc_node_as_necessary(&mut self, id: InstructionId, new: DAGNode) -> Raw<DAGNode> {
         if let Some(node) = self.inst_to_node.get_mut(&id) {
             **node = new;
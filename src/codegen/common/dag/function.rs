@@ -49,7 +49,7 @@ impl DAGFunction {
         regs_info: RegistersInfo,
     ) -> Self {
         Self {
-            is_internal: func.is_internal,
+            is_internal: func.is_internal || func.is_prototype,
             name: func.name.clone(),
             ty: func.ty.clone(),
             dag_basic_block_arena,
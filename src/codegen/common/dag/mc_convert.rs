@@ -5,7 +5,7 @@ use crate::codegen::arch::machine::register::*;
 use crate::codegen::common::machine::inst::*;
 use crate::codegen::common::{
     dag::{basic_block::*, function::*, module::*},
-    machine::{basic_block::*, function::*, module::*},
+    machine::{basic_block::*, const_data::ConstDataArena, function::*, module::*},
 };
 use crate::ir::types::*;
 use crate::util::allocator::*;
@@ -22,6 +22,7 @@ pub struct ScheduleByBlock<'a> {
     pub iseq: &'a mut Vec<MachineInstId>,
     pub bb_map: &'a FxHashMap<DAGBasicBlockId, MachineBasicBlockId>,
     pub node2inst: &'a mut FxHashMap<Raw<DAGNode>, MachineInstId>,
+    pub const_data: &'a mut ConstDataArena,
 }
 
 pub fn convert_module(module: DAGModule) -> MachineModule {
@@ -49,10 +50,12 @@ pub fn convert_function(types: &Types, dag_func: DAGFunction) -> MachineFunction
         let mbb = &mut mbbs.arena[*machine];
         mbb.pred = dbb.pred.iter().map(|bb| *bb_map.get(bb).unwrap()).collect();
         mbb.succ = dbb.succ.iter().map(|bb| *bb_map.get(bb).unwrap()).collect();
+        mbb.name = dbb.name.clone();
     }
 
     let mut inst_arena = InstructionArena::new();
     let mut node2inst = FxHashMap::default();
+    let mut const_data = ConstDataArena::new();
 
     for dag_bb_id in &dag_func.dag_basic_blocks {
         let node = &dag_func.dag_basic_block_arena[*dag_bb_id];
@@ -73,13 +76,14 @@ pub fn convert_function(types: &Types, dag_func: DAGFunction) -> MachineFunction
             iseq: &mut iseq,
             bb_map: &bb_map,
             node2inst: &mut node2inst,
+            const_data: &mut const_data,
         }
         .convert(entry);
 
         mbbs.arena[bb_id].iseq = RefCell::new(iseq);
     }
 
-    MachineFunction::new(dag_func, mbbs, inst_arena)
+    MachineFunction::new(dag_func, mbbs, inst_arena, const_data)
 }
 
 impl<'a> ScheduleByBlock<'a> {
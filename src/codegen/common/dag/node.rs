@@ -57,11 +57,16 @@ pub enum IRNodeKind {
     Shl,
     AShr,
     LShr,
+    And,
+    Or,
+    Xor,
     Add,
     Sub,
     Mul,
     Div,
     Rem,
+    UDiv,
+    URem,
     SIToFP,
     FPToSI,
     Call,
@@ -70,9 +75,18 @@ pub enum IRNodeKind {
     BrCond,
     Brcc,
     FPBrcc,
+    Select,
+    Selectcc,
+    FPSelectcc,
     Br,
+    Switch,
     Ret,
+    Unreachable,
     Sext,
+    ZExt,
+    Trunc,
+    FPExt,
+    FPTrunc,
     FCmp,
 
     FIAddr,
@@ -91,8 +105,10 @@ pub enum IRNodeKind {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum ConstantKind {
     Int8(i8),
+    Int16(i16),
     Int32(i32),
     Int64(i64),
+    F32(f32),
     F64(f64),
 }
 
@@ -127,6 +143,10 @@ impl Into<CondKind> for ICmpKind {
             ICmpKind::Lt => CondKind::Lt,
             ICmpKind::Ge => CondKind::Ge,
             ICmpKind::Gt => CondKind::Gt,
+            ICmpKind::Ult => CondKind::ULt,
+            ICmpKind::Ule => CondKind::ULe,
+            ICmpKind::Ugt => CondKind::UGt,
+            ICmpKind::Uge => CondKind::UGe,
         }
     }
 }
@@ -149,8 +169,12 @@ impl CondKind {
         match self {
             Self::Le => Self::Ge,
             Self::Lt => Self::Gt,
+            Self::Ge => Self::Le,
+            Self::Gt => Self::Lt,
             Self::ULe => Self::UGe,
             Self::ULt => Self::UGt,
+            Self::UGe => Self::ULe,
+            Self::UGt => Self::ULt,
             e => e,
         }
     }
@@ -159,8 +183,10 @@ impl CondKind {
 impl ConstantKind {
     pub fn add(self, n: ConstantKind) -> ConstantKind {
         match (self, n) {
+            (ConstantKind::Int16(x), ConstantKind::Int16(y)) => ConstantKind::Int16(x + y),
             (ConstantKind::Int32(x), ConstantKind::Int32(y)) => ConstantKind::Int32(x + y),
             (ConstantKind::Int64(x), ConstantKind::Int64(y)) => ConstantKind::Int64(x + y),
+            (ConstantKind::F32(x), ConstantKind::F32(y)) => ConstantKind::F32(x + y),
             (ConstantKind::F64(x), ConstantKind::F64(y)) => ConstantKind::F64(x + y),
             _ => unimplemented!(),
         }
@@ -169,8 +195,10 @@ impl ConstantKind {
     pub fn neg(self) -> ConstantKind {
         match self {
             ConstantKind::Int8(i) => ConstantKind::Int8(-i),
+            ConstantKind::Int16(i) => ConstantKind::Int16(-i),
             ConstantKind::Int32(i) => ConstantKind::Int32(-i),
             ConstantKind::Int64(i) => ConstantKind::Int64(-i),
+            ConstantKind::F32(f) => ConstantKind::F32(-f),
             ConstantKind::F64(f) => ConstantKind::F64(-f),
         }
     }
@@ -186,24 +214,31 @@ impl ConstantKind {
     pub fn bits_within(&self, n: u32) -> Option<bool> {
         match self {
             ConstantKind::Int8(x) => Some((x << (8 - n)) >> (8 - n) == *x),
+            ConstantKind::Int16(x) => Some((x << (16 - n)) >> (16 - n) == *x),
             ConstantKind::Int32(x) => Some((x << (32 - n)) >> (32 - n) == *x),
             ConstantKind::Int64(x) => Some((x << (64 - n)) >> (64 - n) == *x),
-            ConstantKind::F64(_) => None,
+            ConstantKind::F32(_) | ConstantKind::F64(_) => None,
         }
     }
 
     pub fn get_type(&self) -> Type {
         match self {
             ConstantKind::Int8(_) => Type::i8,
+            ConstantKind::Int16(_) => Type::i16,
             ConstantKind::Int32(_) => Type::i32,
             ConstantKind::Int64(_) => Type::i64,
+            ConstantKind::F32(_) => Type::f32,
             ConstantKind::F64(_) => Type::f64,
         }
     }
 
     pub fn is_null(&self) -> bool {
         match self {
-            ConstantKind::Int8(0) | ConstantKind::Int32(0) | ConstantKind::Int64(0) => true,
+            ConstantKind::Int8(0)
+            | ConstantKind::Int16(0)
+            | ConstantKind::Int32(0)
+            | ConstantKind::Int64(0) => true,
+            ConstantKind::F32(f) if *f == 0.0 => true,
             ConstantKind::F64(f) if *f == 0.0 => true,
             _ => false,
         }
@@ -212,9 +247,10 @@ impl ConstantKind {
     pub fn is_int(&self, i: i8) -> bool {
         match self {
             ConstantKind::Int8(x) if *x == i => true,
+            ConstantKind::Int16(x) if *x as i8 == i => true,
             ConstantKind::Int32(x) if *x as i8 == i => true,
             ConstantKind::Int64(x) if *x as i8 == i => true,
-            ConstantKind::F64(_) => false,
+            ConstantKind::F32(_) | ConstantKind::F64(_) => false,
             _ => false,
         }
     }
@@ -32,6 +32,9 @@ pub struct MachineBasicBlock {
 
     /// Instruction list
     pub iseq: RefCell<Vec<MachineInstId>>,
+
+    /// Name of the IR basic block, if it has one
+    pub name: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -127,6 +130,7 @@ impl MachineBasicBlock {
             pred: FxHashSet::default(),
             succ: FxHashSet::default(),
             liveness: Rc::new(RefCell::new(LivenessInfo::new())),
+            name: None,
         }
     }
 
@@ -155,6 +155,21 @@ impl BranchFolding {
                     inst.replace_operand_block(block_to_remove, new_dst);
                 }
             }
+
+            // Jump tables refer to blocks as well
+            let entries: Vec<_> = f
+                .const_data
+                .id_and_data()
+                .filter_map(|(id, c)| match c {
+                    MachineConstant::JumpTableEntry(table, bb) if *bb == block_to_remove => {
+                        Some((id, *table))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for (id, table) in entries {
+                f.const_data[id] = MachineConstant::JumpTableEntry(table, new_dst);
+            }
         }
 
         debug!(println!("{} empty blocks removed", worklist.len(),));
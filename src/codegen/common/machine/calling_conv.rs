@@ -30,6 +30,12 @@ impl<'a, ABI: CallingConv> ArgumentRegisterOrder<'a, ABI> {
         self.abi.get_nth_arg_reg(rc, *nth - 1)
     }
 
+    /// Returns how many arguments of the register file of `rc` have been assigned so far,
+    /// including those that did not fit in registers
+    pub fn num_assigned(&self, rc: RegisterClassKind) -> usize {
+        *self.nths.get(&rc.register_file_base_class()).unwrap_or(&0)
+    }
+
     pub fn regs_available_for(&self, rcs: &[RegisterClassKind]) -> bool {
         let mut arg_regs_order: ArgumentRegisterOrder<'a, ABI> = (*self).clone();
         rcs.iter().all(|&rc| arg_regs_order.next(rc).is_some())
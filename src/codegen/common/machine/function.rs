@@ -26,7 +26,8 @@ pub struct MachineFunction {
     /// Include basic blocks and instruction arena
     pub body: MachineFunctionBody,
 
-    /// True if internal function
+    /// True if internal function or a function defined outside of the module, whose body is
+    /// not generated
     pub is_internal: bool,
 
     /// Local variables info
@@ -129,6 +130,7 @@ impl MachineFunction {
         f: DAGFunction,
         basic_blocks: MachineBasicBlocks,
         inst_arena: InstructionArena,
+        const_data: ConstDataArena,
     ) -> Self {
         Self {
             id: None,
@@ -142,7 +144,7 @@ impl MachineFunction {
             local_mgr: f.local_mgr,
             regs_info: f.regs_info,
             frame_objects: None,
-            const_data: ConstDataArena::new(),
+            const_data,
             types: f.types.clone(),
         }
     }
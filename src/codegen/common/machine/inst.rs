@@ -57,9 +57,13 @@ pub enum AddressKind {
 #[derive(Clone, Copy, PartialEq)]
 pub enum MachineConstant {
     Int8(i8),
+    Int16(i16),
     Int32(i32),
     Int64(i64),
+    F32(f32),
     F64(f64),
+    /// An entry of a jump table: the offset of a block from the table that begins at `DataId`
+    JumpTableEntry(DataId, MachineBasicBlockId),
 }
 
 impl MachineInst {
@@ -458,9 +462,12 @@ impl MachineOperand {
         match self {
             MachineOperand::Branch(_) => None,
             MachineOperand::Constant(MachineConstant::Int8(_)) => Some(Type::i8),
+            MachineOperand::Constant(MachineConstant::Int16(_)) => Some(Type::i16),
             MachineOperand::Constant(MachineConstant::Int32(_)) => Some(Type::i32),
             MachineOperand::Constant(MachineConstant::Int64(_)) => Some(Type::i64),
+            MachineOperand::Constant(MachineConstant::F32(_)) => Some(Type::f32),
             MachineOperand::Constant(MachineConstant::F64(_)) => Some(Type::f64),
+            MachineOperand::Constant(MachineConstant::JumpTableEntry(_, _)) => None,
             MachineOperand::FrameIndex(fi) => Some(fi.ty),
             MachineOperand::Mem(mem) => mem.get_type(),
             MachineOperand::None => None, // TODO
@@ -537,9 +544,12 @@ impl MachineConstant {
     pub fn size_in_byte(&self) -> usize {
         match self {
             MachineConstant::Int8(_) => 1,
+            MachineConstant::Int16(_) => 2,
             MachineConstant::Int32(_) => 4,
             MachineConstant::Int64(_) => 8,
+            MachineConstant::F32(_) => 4,
             MachineConstant::F64(_) => 8,
+            MachineConstant::JumpTableEntry(_, _) => 4,
         }
     }
 
@@ -556,6 +566,13 @@ impl MachineConstant {
         }
     }
 
+    pub fn as_i16(&self) -> i16 {
+        match self {
+            Self::Int16(i) => *i,
+            _ => panic!(),
+        }
+    }
+
     pub fn as_i32(&self) -> i32 {
         match self {
             Self::Int32(i) => *i,
@@ -570,6 +587,13 @@ impl MachineConstant {
         }
     }
 
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            Self::F32(f) => *f,
+            _ => panic!(),
+        }
+    }
+
     pub fn as_f64(&self) -> f64 {
         match self {
             Self::F64(f) => *f,
@@ -719,9 +743,12 @@ impl fmt::Debug for MachineConstant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Int8(x) => write!(f, "i8 {}", x),
+            Self::Int16(x) => write!(f, "i16 {}", x),
             Self::Int32(x) => write!(f, "i32 {}", x),
             Self::Int64(x) => write!(f, "i64 {}", x),
+            Self::F32(x) => write!(f, "f32 {}", x),
             Self::F64(x) => write!(f, "f64 {}", x),
+            Self::JumpTableEntry(table, bb) => write!(f, "BB#{} - {}", bb.index(), table),
         }
     }
 }
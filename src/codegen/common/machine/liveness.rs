@@ -784,7 +784,7 @@ impl LivenessAnalysis {
     }
 
     fn set_def_on_inst(&mut self, bb: &MachineBasicBlock, inst: &MachineInst) {
-        bb.liveness_ref_mut().has_call |= inst.opcode == MachineOpcode::CALL;
+        bb.liveness_ref_mut().has_call |= inst.opcode.is_call();
         for &reg in &inst.def {
             bb.liveness.borrow_mut().add_def(reg.id);
         }
@@ -300,7 +300,7 @@ impl RegisterAllocator {
         for (_, bb) in cur_func.body.basic_blocks.id_and_block() {
             for inst_id in bb.iseq_ref().iter() {
                 let inst = &cur_func.body.inst_arena[*inst_id];
-                if inst.opcode == MachineOpcode::CALL {
+                if inst.opcode.is_call() {
                     call_inst_id.push(*inst_id)
                 }
             }
@@ -26,7 +26,7 @@ pub trait TargetRegisterTrait: Copy + Clone {
 }
 
 #[derive(Debug, Clone)]
-pub struct PhysRegSet(pub [u64; 1]); // 8*8*1 > PHYS_REGISTERS_NUM
+pub struct PhysRegSet(pub [u128; 1]); // 8*16*1 > PHYS_REGISTERS_NUM
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct VirtReg(pub usize);
@@ -242,7 +242,7 @@ impl PhysRegSet {
 
     pub fn to_phys_set(&self) -> FxHashSet<PhysReg> {
         let mut set = FxHashSet::default();
-        for i in 0..self.0.len() * 8 * 8 {
+        for i in 0..self.0.len() * 8 * 16 {
             if (self.0[0] & (1 << i)) != 0 {
                 set.insert(PhysReg(i));
             }
@@ -103,6 +103,7 @@ impl JITCompiler {
                     cilk_i32_to_f64_i32_ as _,
                     cilk_f64_to_i32_f64_ as _,
                     cilk_malloc_i32_ as _,
+                    cilk_trap_ as _,
                 ];
                 assert!(
                     internal_names.len() == internals.len(),
@@ -1126,6 +1127,12 @@ pub extern "C" fn cilk_malloc_i32_(i: i32) -> *mut i64 {
     Box::into_raw(vec.into_boxed_slice()) as *mut i64
 }
 
+// Internal function cilk.trap
+#[no_mangle]
+pub extern "C" fn cilk_trap_() {
+    ::std::process::abort()
+}
+
 // EXPERIMENTAL Internal function cilk.memset.p0i32.i32
 #[no_mangle]
 pub extern "C" fn cilk_memset_p0i32_i32_(p: *mut i32, x: i32, count: i32) {
@@ -16,6 +16,10 @@ impl MachineOpcode {
         matches!(self, MachineOpcode::MV | MachineOpcode::Copy)
     }
 
+    pub fn is_call(&self) -> bool {
+        matches!(self, MachineOpcode::CALL)
+    }
+
     pub fn is_terminator(&self) -> bool {
         matches!(
             self,
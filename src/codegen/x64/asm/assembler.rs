@@ -8,6 +8,7 @@ impl<'a> InstAssembler<'a> {
             MachineOpcode::PUSH64 => self.gen_push64(),
             MachineOpcode::POP64 => self.gen_pop64(),
 
+            MachineOpcode::MOVri8 => self.gen_movri8(),
             MachineOpcode::MOVri32 => self.gen_movri32(),
             MachineOpcode::MOVrm32 => self.gen_movrm32(),
             MachineOpcode::MOVrr32 => self.gen_mov_rr32(),
@@ -24,14 +25,29 @@ impl<'a> InstAssembler<'a> {
 
             MachineOpcode::CDQ => self.gen_cdq(),
 
+            MachineOpcode::LEAr64m => self.gen_lea_r64m(),
+
             MachineOpcode::CALL => self.gen_call(),
+            MachineOpcode::CALLr64 => self.gen_call_r64(),
 
             MachineOpcode::CMPri => self.gen_cmp_ri(),
 
+            MachineOpcode::SETE
+            | MachineOpcode::SETNE
+            | MachineOpcode::SETL
+            | MachineOpcode::SETLE
+            | MachineOpcode::SETG
+            | MachineOpcode::SETGE
+            | MachineOpcode::SETB
+            | MachineOpcode::SETBE
+            | MachineOpcode::SETA
+            | MachineOpcode::SETAE => self.gen_setcc(),
+
             MachineOpcode::JMP => self.gen_jmp(),
             MachineOpcode::JNE => self.gen_jne(),
 
             MachineOpcode::RET => self.gen_ret(),
+            MachineOpcode::UD2 => self.gen_ud2(),
             op => unimplemented!("{:?}", op),
         };
     }
@@ -46,6 +62,18 @@ impl<'a> InstAssembler<'a> {
         self.stream.push_u8(0x58 + rd);
     }
 
+    fn gen_movri8(&mut self) {
+        let r = reg_code(&self.inst.def[0].id);
+        if r > 7 {
+            self.stream.push_u8(0x41); // REX.B
+        } else if r > 3 {
+            self.stream.push_u8(0x40); // REX to access SPL, BPL, SIL and DIL
+        }
+        self.stream.push_u8(0xb0 + (r & 7));
+        self.stream
+            .push_u8(self.inst.operand[0].as_constant().as_i8() as u8);
+    }
+
     fn gen_movri32(&mut self) {
         self.stream.push_u8(0xc7);
         self.stream
@@ -165,6 +193,37 @@ impl<'a> InstAssembler<'a> {
         self.stream.push_u32_le(0);
     }
 
+    fn gen_call_r64(&mut self) {
+        let r = reg_code(&self.inst.operand[0].as_register().id);
+        if r > 7 {
+            self.stream.push_u8(0x41); // REX.B
+        }
+        self.stream.push_u8(0xff);
+        self.stream.push_u8(mod_rm(Mod::Reg, 2, r & 7));
+    }
+
+    fn gen_lea_r64m(&mut self) {
+        let r = reg_code(&self.inst.def[0].id);
+        self.stream
+            .push_u8(if r > 7 { 0b01001100 } else { 0b01001000 }); // REX.W (+ REX.R)
+        self.stream.push_u8(0x8d);
+
+        match self.inst.operand[0].as_mem() {
+            MachineMemOperand::Address(AddressKind::FunctionName(n)) => {
+                // [rip + disp32]
+                self.stream.push_u8(mod_rm(Mod::Base, r & 7, 0b101));
+                let callee_id = self.module.find_function_by_name(n.as_str()).unwrap();
+                let label = self.labels.get_func_label(callee_id);
+                self.labels.add_disp32_to_replace(
+                    Offset(self.function.id.unwrap(), self.stream.data().len()),
+                    label,
+                );
+                self.stream.push_u32_le(0);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
     fn gen_cmp_ri(&mut self) {
         self.stream.push_u8(0x81);
         self.stream.push_u8(mod_rm(
@@ -176,6 +235,30 @@ impl<'a> InstAssembler<'a> {
             .push_u32_le(self.inst.operand[1].as_constant().as_i32() as u32);
     }
 
+    fn gen_setcc(&mut self) {
+        let r = reg_code(&self.inst.def[0].id);
+        if r > 7 {
+            self.stream.push_u8(0x41); // REX.B
+        } else if r > 3 {
+            self.stream.push_u8(0x40); // REX to access SPL, BPL, SIL and DIL
+        }
+        self.stream.push_u8(0x0f);
+        self.stream.push_u8(match self.inst.opcode {
+            MachineOpcode::SETB => 0x92,
+            MachineOpcode::SETAE => 0x93,
+            MachineOpcode::SETE => 0x94,
+            MachineOpcode::SETNE => 0x95,
+            MachineOpcode::SETBE => 0x96,
+            MachineOpcode::SETA => 0x97,
+            MachineOpcode::SETL => 0x9c,
+            MachineOpcode::SETGE => 0x9d,
+            MachineOpcode::SETLE => 0x9e,
+            MachineOpcode::SETG => 0x9f,
+            _ => unreachable!(),
+        });
+        self.stream.push_u8(mod_rm(Mod::Reg, 0, r & 7));
+    }
+
     fn gen_jmp(&mut self) {
         self.stream.push_u8(0xe9);
 
@@ -215,6 +298,11 @@ impl<'a> InstAssembler<'a> {
         // TODO: What's the difference between Near return and Far return?
         self.stream.push_u8(0xc3);
     }
+
+    fn gen_ud2(&mut self) {
+        self.stream.push_u8(0x0f);
+        self.stream.push_u8(0x0b);
+    }
 }
 
 pub fn reg_code(r: &RegisterId) -> u8 {
@@ -6,7 +6,10 @@ use crate::codegen::common::machine::{
     function::{InstIter, MachineFunction},
     module::MachineModule,
 };
-use crate::ir::{global_val::GlobalVariableId, types::TypeSize};
+use crate::ir::{
+    global_val::{GlobalVariableId, Symbol},
+    types::TypeSize,
+};
 use rustc_hash::FxHashMap;
 
 pub struct MachineAsmPrinter {
@@ -31,9 +34,57 @@ impl MachineAsmPrinter {
         for (id, g) in &m.global_vars.arena {
             let size = g.ty.size_in_byte(&m.types);
             let align = g.ty.align_in_byte(&m.types);
-            self.output
-                .push_str(format!("  .comm {},{},{}\n", g.name, size, align).as_str());
             self.id_to_global_name.insert(id, g.name.clone());
+            if g.init.is_none() {
+                self.output
+                    .push_str(format!("  .comm {},{},{}\n", g.name, size, align).as_str());
+                continue;
+            }
+            self.output.push_str(if g.is_constant {
+                "  .section .rodata\n"
+            } else {
+                "  .data\n"
+            });
+            self.output.push_str(
+                format!(
+                    "  .globl {}\n  .p2align {}\n{}:\n",
+                    g.name,
+                    align.trailing_zeros(),
+                    g.name
+                )
+                .as_str(),
+            );
+            let bytes = g.init_bytes(&m.types);
+            let relocs = g.relocations(&m.types);
+            if relocs.is_empty() && bytes.iter().all(|&b| b == 0) {
+                self.output.push_str(format!("  .zero {}\n", size).as_str());
+            } else {
+                // Addresses are emitted as relocations between the runs of bytes
+                let mut start = 0;
+                for reloc in relocs.iter().map(Some).chain(vec![None]) {
+                    let end = reloc.map_or(bytes.len(), |r| r.offset);
+                    for chunk in bytes[start..end].chunks(16) {
+                        let chunk: Vec<String> = chunk.iter().map(|b| b.to_string()).collect();
+                        self.output
+                            .push_str(format!("  .byte {}\n", chunk.join(",")).as_str());
+                    }
+                    if let Some(reloc) = reloc {
+                        let name = match &reloc.symbol {
+                            Symbol::Global(id) => m.global_vars.arena[*id].name.as_str(),
+                            Symbol::Function(name) => name.as_str(),
+                        };
+                        self.output.push_str(
+                            match reloc.addend {
+                                0 => format!("  .quad {}\n", name),
+                                addend => format!("  .quad {}{:+}\n", name, addend),
+                            }
+                            .as_str(),
+                        );
+                        start = end + 8;
+                    }
+                }
+            }
+            self.output.push_str("  .text\n");
         }
 
         for (_, func) in &m.functions {
@@ -50,9 +101,18 @@ impl MachineAsmPrinter {
             self.output
                 .push_str(format!(".Lconst{}{}:\n", id.arena_id(), id.id()).as_str());
             self.output.push_str(
-                format!("  .quad {}\n", unsafe {
-                    ::std::mem::transmute::<f64, u64>(data.as_f64())
-                })
+                match data {
+                    MachineConstant::F32(f) => format!("  .long {}\n", f.to_bits()),
+                    MachineConstant::JumpTableEntry(table, bb) => format!(
+                        "  .long {} - .Lconst{}{}\n",
+                        self.bb_id_to_label_id(bb),
+                        table.arena_id(),
+                        table.id()
+                    ),
+                    _ => format!("  .quad {}\n", unsafe {
+                        ::std::mem::transmute::<f64, u64>(data.as_f64())
+                    }),
+                }
                 .as_str(),
             )
         }
@@ -66,9 +126,13 @@ impl MachineAsmPrinter {
     }
 
     fn run_on_basic_blocks(&mut self, f: &MachineFunction, fo: &FrameObjectsInfo) {
-        for (id, _, inst_iter) in f.body.mbb_iter() {
+        for (id, bb, inst_iter) in f.body.mbb_iter() {
             self.output
-                .push_str(format!("{}:\n", self.bb_id_to_label_id(&id)).as_str());
+                .push_str(format!("{}:", self.bb_id_to_label_id(&id)).as_str());
+            if let Some(name) = &bb.name {
+                self.output.push_str(format!(" # {}", name).as_str());
+            }
+            self.output.push('\n');
             self.run_on_basic_block(inst_iter, fo);
         }
         self.cur_bb_id_base += f.body.basic_blocks.arena.len();
@@ -130,13 +194,25 @@ impl MachineAsmPrinter {
             MachineOperand::Constant(MachineConstant::Int8(i)) => {
                 self.output.push_str(format!("{}", i).as_str())
             }
+            MachineOperand::Constant(MachineConstant::Int16(i)) => {
+                self.output.push_str(format!("{}", i).as_str())
+            }
+            MachineOperand::Constant(MachineConstant::Int64(i)) => {
+                self.output.push_str(format!("{}", i).as_str())
+            }
             MachineOperand::Register(r) => self.output.push_str(r.id.as_phys_reg().name()),
             MachineOperand::FrameIndex(i) => self
                 .output
                 .push_str(format!("{}", fo.offset(i.idx).unwrap()).as_str()),
-            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::FunctionName(name))) => {
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::FunctionName(name)))
+                if *opcode == MachineOpcode::CALL =>
+            {
                 self.output.push_str(name.replace('.', "_").as_str())
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::FunctionName(name))) => {
+                self.output
+                    .push_str(format!("[rip + {}]", name.replace('.', "_")).as_str())
+            }
             MachineOperand::Mem(_) => {
                 let word = opcode2word(opcode);
                 self.run_on_mem_operand(operand, fo, word)
@@ -147,9 +223,9 @@ impl MachineAsmPrinter {
 
     fn run_on_mem_operand(&mut self, op: &MachineOperand, fo: &FrameObjectsInfo, word: &str) {
         match op {
-            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Label(id))) => {
-                self.output.push_str(self.data_id_to_label_id(id).as_str())
-            }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Label(id))) => self
+                .output
+                .push_str(self.data_id_to_label_id(id, word).as_str()),
             MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => self
                 .output
                 .push_str(format!("{} ptr [{}]", word, self.global_var_name(id)).as_str()),
@@ -242,9 +318,10 @@ impl MachineAsmPrinter {
         format!(".L{}", bb_id.index() + self.cur_bb_id_base)
     }
 
-    fn data_id_to_label_id(&self, data_id: &DataId) -> String {
+    fn data_id_to_label_id(&self, data_id: &DataId, word: &str) -> String {
         format!(
-            "qword ptr [rip + .Lconst{}{}]",
+            "{} ptr [rip + .Lconst{}{}]",
+            word,
             data_id.arena_id(),
             data_id.id()
         )
@@ -266,6 +343,7 @@ fn off_s(off: i32) -> String {
 fn opcode2word(opcode: &MachineOpcode) -> &'static str {
     let byte = match opcode {
         MachineOpcode::LEAr64m
+        | MachineOpcode::CALLm64
         | MachineOpcode::MOVSDrm64
         | MachineOpcode::MOVSDmr
         | MachineOpcode::MOVmr64
@@ -280,12 +358,17 @@ fn opcode2word(opcode: &MachineOpcode) -> &'static str {
         | MachineOpcode::ADDSDrm
         | MachineOpcode::SUBSDrm
         | MachineOpcode::MULSDrm
-        | MachineOpcode::DIVSDrr => 4,
+        | MachineOpcode::DIVSDrr
+        | MachineOpcode::MOVSSrm32
+        | MachineOpcode::MOVSSrm
+        | MachineOpcode::MOVSSmr => 4,
+        MachineOpcode::MOVrm16 | MachineOpcode::MOVmr16 | MachineOpcode::MOVmi16 => 2,
         MachineOpcode::MOVrm8 | MachineOpcode::MOVmr8 | MachineOpcode::MOVmi8 => 1,
         _ => 0,
     };
     match byte {
         1 => "byte",
+        2 => "word",
         4 => "dword",
         8 => "qword",
         _ => "",
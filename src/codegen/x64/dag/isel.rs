@@ -68,11 +68,19 @@ impl MISelector {
         let mut selected = isel_pat!(
             // TODO: Refactoring
             (ir.Call _a) => { self.select_call(tys, regs_info, heap, node) }
+            (ir.Add a, b): f32 {
+                XMM a {
+                    imm_f32 b => (mi.ADDSSrr a, (mi.MOVSSrm32 b):f32)
+                    XMM     b => (mi.ADDSSrr a, b) }
+            }
             (ir.Add a, b) {
                 GR8 a {
                     GR8 b => (mi.ADDrr8 a, b)
                     imm8 b => (mi.ADDri8 a, b)
                 }
+                GR16 a {
+                    GR16  b => (mi.ADDrr16 a, b)
+                    imm16 b => (mi.ADDri16 a, b) }
                 GR32 a {
                     GR32  b => (mi.ADDrr32   a, b)
                     imm32 b => (mi.ADDri32   a, b) }
@@ -87,11 +95,21 @@ impl MISelector {
                     XMM    b => (mi.ADDSDrr a, b)
                 }
             }
+            (ir.Sub a, b): f32 {
+                XMM a {
+                    imm_f32 b => (mi.SUBSSrr a, (mi.MOVSSrm32 b):f32)
+                    XMM     b => (mi.SUBSSrr a, b) }
+                imm_f32 a {
+                    XMM b => (mi.SUBSSrr (mi.MOVSSrm32 a):f32, b) }
+            }
             (ir.Sub a, b) {
                 GR8 a {
                     GR8 b => (mi.SUBrr8 a, b)
                     imm8 b => (mi.SUBri8 a, b)
                 }
+                GR16 a {
+                    GR16  b => (mi.SUBrr16 a, b)
+                    imm16 b => (mi.SUBri16 a, b) }
                 GR32 a {
                     GR32  b => (mi.SUBrr32   a, b)
                     imm32 b => (mi.SUBri32   a, b) }
@@ -111,10 +129,18 @@ impl MISelector {
                     XMM b => (mi.SUBSDrr (mi.MOVSDrm64 a), b)
                 }
             }
+            (ir.Mul a, b): f32 {
+                XMM a {
+                    imm_f32 b => (mi.MULSSrr a, (mi.MOVSSrm32 b):f32)
+                    XMM     b => (mi.MULSSrr a, b) }
+            }
             (ir.Mul a, b) {
                 GR8 a {
                     GR8  b => (ir.RegClass (mi.IMULrr32 (ir.RegClass a):i32, (ir.RegClass b):i32))
                     imm8 b => (mi.IMULrri8 a, b) }
+                GR16 a {
+                    GR16  b => (ir.RegClass (mi.IMULrr32 (ir.RegClass a):i32, (ir.RegClass b):i32))
+                    imm16 b => (ir.RegClass (mi.IMULrr32 (ir.RegClass a):i32, (ir.RegClass (mi.MOVri16 b)):i32)) }
                 GR32 a {
                     GR32  b => (mi.IMULrr32  a, b)
                     imm32 b => (mi.IMULrri32 a, b) }
@@ -128,6 +154,13 @@ impl MISelector {
                     XMM    b => (mi.MULSDrr a, b)
                 }
             }
+            (ir.Div a, b): f32 {
+                XMM a {
+                    imm_f32 b => (mi.DIVSSrr a, (mi.MOVSSrm32 b):f32)
+                    XMM     b => (mi.DIVSSrr a, b) }
+                imm_f32 a {
+                    XMM b => (mi.DIVSSrr (mi.MOVSSrm32 a):f32, b) }
+            }
             (ir.Div a, b) {
                 XMM a {
                     (ir.Load c) b {
@@ -140,28 +173,117 @@ impl MISelector {
                     XMM b => (mi.DIVSDrr (mi.MOVSDrm64 a), b)
                 }
             }
+            (ir.And a, b) {
+                GR8 a {
+                    GR8  b => (mi.ANDrr8 a, b)
+                    imm8 b => (mi.ANDri8 a, b) }
+                GR16 a {
+                    GR16  b => (ir.RegClass (mi.ANDrr32 (ir.RegClass a):i32, (ir.RegClass b):i32))
+                    imm16 b => (ir.RegClass (mi.ANDrr32 (ir.RegClass a):i32, (ir.RegClass (mi.MOVri16 b)):i32)) }
+                GR32 a {
+                    GR32  b => (mi.ANDrr32 a, b)
+                    imm32 b => (mi.ANDri32 a, b) }
+                GR64 a {
+                    GR64  b => (mi.ANDrr64 a, b)
+                    imm32 b => (mi.ANDr64i32 a, b)
+                    imm64 b => (mi.ANDrr64 a, (mi.MOVri64 b)) }
+            }
+            (ir.Or a, b) {
+                GR8 a {
+                    GR8  b => (mi.ORrr8 a, b)
+                    imm8 b => (mi.ORri8 a, b) }
+                GR16 a {
+                    GR16  b => (ir.RegClass (mi.ORrr32 (ir.RegClass a):i32, (ir.RegClass b):i32))
+                    imm16 b => (ir.RegClass (mi.ORrr32 (ir.RegClass a):i32, (ir.RegClass (mi.MOVri16 b)):i32)) }
+                GR32 a {
+                    GR32  b => (mi.ORrr32 a, b)
+                    imm32 b => (mi.ORri32 a, b) }
+                GR64 a {
+                    GR64  b => (mi.ORrr64 a, b)
+                    imm32 b => (mi.ORr64i32 a, b)
+                    imm64 b => (mi.ORrr64 a, (mi.MOVri64 b)) }
+            }
+            (ir.Xor a, b) {
+                GR8 a {
+                    GR8  b => (mi.XORrr8 a, b)
+                    imm8 b => (mi.XORri8 a, b) }
+                GR16 a {
+                    GR16  b => (ir.RegClass (mi.XORrr32 (ir.RegClass a):i32, (ir.RegClass b):i32))
+                    imm16 b => (ir.RegClass (mi.XORrr32 (ir.RegClass a):i32, (ir.RegClass (mi.MOVri16 b)):i32)) }
+                GR32 a {
+                    GR32  b => (mi.XORrr32 a, b)
+                    imm32 b => (mi.XORri32 a, b) }
+                GR64 a {
+                    GR64  b => (mi.XORrr64 a, b)
+                    imm32 b => (mi.XORr64i32 a, b)
+                    imm64 b => (mi.XORrr64 a, (mi.MOVri64 b)) }
+            }
             (ir.Shl a, b) {
+                GR8 a {
+                    imm8 b => (mi.SHLr8i8 a, b) }
+                GR32 a {
+                    imm8 b => (mi.SHLr32i8 a, b) }
                 GR64 a {
                     imm8 b => (mi.SHLr64i8 a, b) }
+            }
+            (ir.LShr a, b) {
+                GR8 a {
+                    imm8 b => (mi.SHRr8i8 a, b) }
                 GR32 a {
-                    imm8 b => (mi.SHLr32i8 a, b) }
+                    imm8 b => (mi.SHRr32i8 a, b) }
+                GR64 a {
+                    imm8 b => (mi.SHRr64i8 a, b) }
+            }
+            (ir.AShr a, b) {
+                GR8 a {
+                    imm8 b => (mi.SARr8i8 a, b) }
+                GR32 a {
+                    imm8 b => (mi.SARr32i8 a, b) }
+                GR64 a {
+                    imm8 b => (mi.SARr64i8 a, b) }
             }
             (ir.SIToFP x): f64 { GR32 x => (mi.CVTSI2SDrr32 x) }
-            (ir.FPToSI x): i32 { XMM x => (mi.CVTTSD2SIr32r x) }
-            (ir.Sext x): i32 { GR8 x => (mi.MOVSXr32r8 x) }
+            (ir.SIToFP x): f32 { GR32 x => (mi.CVTSI2SSrr32 x) }
+            (ir.FPToSI x): i32 { XMM:f32 x => (mi.CVTTSS2SIr32r x)
+                                 XMM     x => (mi.CVTTSD2SIr32r x) }
+            (ir.FPExt x): f64 { XMM x => (mi.CVTSS2SDrr x) }
+            (ir.FPTrunc x): f32 { XMM x => (mi.CVTSD2SSrr x) }
+            (ir.Sext x): i16 { GR8  x => (ir.RegClass (mi.MOVSXr32r8 x)) }
+            (ir.Sext x): i32 { GR8  x => (mi.MOVSXr32r8 x)
+                               GR16 x => (mi.MOVSXr32r16 x) }
+            (ir.Sext x): i64 { GR8  x => (mi.MOVSXDr64r32 (mi.MOVSXr32r8 x))
+                               GR16 x => (mi.MOVSXDr64r32 (mi.MOVSXr32r16 x))
+                               GR32 x => (mi.MOVSXDr64r32 x) }
+            (ir.ZExt x): i16 { GR8  x => (ir.RegClass (mi.MOVZXr32r8 x)) }
+            (ir.ZExt x): i32 { GR8  x => (mi.MOVZXr32r8 x)
+                               GR16 x => (mi.MOVZXr32r16 x) }
+            (ir.ZExt x): i64 { GR8  x => (mi.MOVZXr64r8 x)
+                               GR16 x => (ir.RegClass (mi.MOVZXr32r16 x))
+                               GR32 x => (ir.RegClass (mi.MOVZXr64r32 x)) }
+            (ir.Trunc x) { GR16 x => (ir.RegClass x)
+                           GR32 x => (ir.RegClass x)
+                           GR64 x => (ir.RegClass x) }
             (ir.Load a): i8     { (ir.FIAddr     b) a => (mi.MOVrm8  [BaseFi %rbp, b])
                                                GR64 a => (mi.MOVrm8  [Base a]) }
+            (ir.Load a): i16    { (ir.FIAddr     b) a => (mi.MOVrm16 [BaseFi %rbp, b])
+                                                 GR64 a => (mi.MOVrm16 [Base a]) }
             (ir.Load a): i64    { (ir.FIAddr     b) a => (mi.MOVrm64 [BaseFi %rbp, b])
                                                  GR64 a => (mi.MOVrm64 [Base a]) }
             (ir.Load a): i32    { (ir.FIAddr     b) a => (mi.MOVrm32 [BaseFi %rbp, b])
                                     (ir.GlobalAddr b) a => (mi.MOVrm32 [Address b])
                                                  GR64 a => (mi.MOVrm32 [Base a]) }
+            (ir.Load a): f32      { (ir.FIAddr     b) a => (mi.MOVSSrm [BaseFi %rbp, b])
+                                                 GR64 a => (mi.MOVSSrm [Base a]) }
             (ir.Load a): f64      { (ir.FIAddr     b) a => (mi.MOVSDrm [BaseFi %rbp, b])
                                                  GR64 a => (mi.MOVSDrm [Base a]) }
             (ir.Load a): Pointer! { (ir.FIAddr     b) a => (mi.MOVrm64 [BaseFi %rbp, b])
                                                  GR64 a => (mi.MOVrm64 [Base a]) }
             (ir.Store a, b) {
                 (ir.FIAddr c) a {
+                    f32mem c {
+                        imm_f32 b => (mi.MOVSSmr [BaseFi %rbp, c], (mi.MOVSSrm32 b):f32)
+                        XMM     b => (mi.MOVSSmr [BaseFi %rbp, c], b)
+                    }
                     f64mem c {
                         imm_f64 b => (mi.MOVSDmr [BaseFi %rbp, c], (mi.MOVSDrm64 b))
                     }
@@ -173,12 +295,16 @@ impl MISelector {
                     mem c {
                         GR8 b => (mi.MOVmr8 [BaseFi %rbp, c], b)
                         imm8 b => (mi.MOVmi8 [BaseFi %rbp, c], b)
+                        GR16  b => (mi.MOVmr16 [BaseFi %rbp, c], b)
+                        imm16 b => (mi.MOVmi16 [BaseFi %rbp, c], b)
                     }
                 }
                 (ir.GlobalAddr c) a {
                     imm32 b   => (mi.MOVmi32 [Address c], b)
                     GR32 b    => (mi.MOVmr32 [Address c], b)
                     GR64  b   => (mi.MOVmr64 [Address c], b)
+                    imm_f32 b => (mi.MOVSSmr [Address c], (mi.MOVSSrm32 b):f32)
+                    XMM:f32 b => (mi.MOVSSmr [Address c], b)
                     imm_f64 b => (mi.MOVSDmr [Address c], (mi.MOVSDrm64 b))
                     XMM    b  => (mi.MOVSDmr [Address c], b)
                 }
@@ -186,12 +312,16 @@ impl MISelector {
                     imm32 b => (mi.MOVmi32 [Base a], b)
                     GR32  b => (mi.MOVmr32 [Base a], b)
                     GR64  b => (mi.MOVmr64 [Base a], b)
+                    imm16 b => (mi.MOVmi16 [Base a], b)
+                    GR16  b => (mi.MOVmr16 [Base a], b)
+                    imm_f32 b => (mi.MOVSSmr [Base a], (mi.MOVSSrm32 b):f32)
+                    XMM:f32 b => (mi.MOVSSmr [Base a], b)
                     imm_f64 b => (mi.MOVSDmr [Base a], (mi.MOVSDrm64 b))
                     XMM    b => (mi.MOVSDmr [Base a], b)
                 }
             }
             (ir.FIAddr a) { mem a => (mi.LEAr64m [BaseFi %rbp, a]) }
-            // (ir.GlobalAddr a) => (mi.Copy a)  TODO
+            (ir.GlobalAddr a) => (mi.LEAr64m [Address a])
             (ir.Br dst) => (mi.JMP dst)
             (ir.CopyFromReg a) => (mi.Copy a)
         );
@@ -213,7 +343,8 @@ impl MISelector {
         mut node: Raw<DAGNode>,
     ) -> Raw<DAGNode> {
         const SQRT_F64: &str = "cilk.sqrt.f64";
-        let supported = [SQRT_F64];
+        const TRAP: &str = "cilk.trap";
+        let supported = [SQRT_F64, TRAP];
 
         let name = match &node.operand[0].kind {
             NodeKind::Operand(OperandNodeKind::Address(AddressKind::FunctionName(name)))
@@ -222,11 +353,11 @@ impl MISelector {
                 name.as_str()
             }
             _ => {
-                node.operand = node
-                    .operand
-                    .iter()
-                    .map(|op| self.run_on_node(tys, regs_info, heap, *op))
-                    .collect();
+                let mut operands = vec![self.select_callee(tys, regs_info, heap, node.operand[0])];
+                for op in &node.operand[1..] {
+                    operands.push(self.run_on_node(tys, regs_info, heap, *op));
+                }
+                node.operand = operands;
                 return node;
             }
         };
@@ -240,7 +371,44 @@ impl MISelector {
                     Type::f64,
                 ))
             }
+            TRAP => heap.alloc(DAGNode::new(
+                NodeKind::MI(MINodeKind::UD2),
+                vec![],
+                Type::Void,
+            )),
             _ => unreachable!(),
         }
     }
+
+    // A function pointer loaded only to be called is folded into the call (CALL m64)
+    fn select_callee(
+        &mut self,
+        tys: &Types,
+        regs_info: &RegistersInfo,
+        heap: &mut DAGHeap,
+        callee: Raw<DAGNode>,
+    ) -> Raw<DAGNode> {
+        if callee.kind != NodeKind::IR(IRNodeKind::Load) || callee.next.is_some() {
+            return self.run_on_node(tys, regs_info, heap, callee);
+        }
+
+        let addr = callee.operand[0];
+        let mut mem = match addr.kind {
+            NodeKind::IR(IRNodeKind::FIAddr) => {
+                let fi = self.run_on_node(tys, regs_info, heap, addr.operand[0]);
+                let rbp = heap.alloc_phys_reg(regs_info, GR64::RBP);
+                DAGNode::new_mem(MemNodeKind::BaseFi, vec![rbp, fi])
+            }
+            NodeKind::IR(IRNodeKind::GlobalAddr) => {
+                DAGNode::new_mem(MemNodeKind::Address, vec![addr.operand[0]])
+            }
+            _ => {
+                let addr = self.run_on_node(tys, regs_info, heap, addr);
+                DAGNode::new_mem(MemNodeKind::Base, vec![addr])
+            }
+        };
+        // The call still needs the type of the callee to lower its arguments
+        mem.ty = callee.ty;
+        heap.alloc(mem)
+    }
 }
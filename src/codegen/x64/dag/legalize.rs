@@ -69,6 +69,9 @@ impl Legalize {
             NodeKind::IR(IRNodeKind::Sext) => self.run_on_node_sext(tys, regs_info, heap, node),
             NodeKind::IR(IRNodeKind::Brcc) => self.run_on_node_brcc(tys, regs_info, heap, node),
             NodeKind::IR(IRNodeKind::FPBrcc) => self.run_on_node_fpbrcc(tys, regs_info, heap, node),
+            NodeKind::IR(IRNodeKind::Selectcc) | NodeKind::IR(IRNodeKind::FPSelectcc) => {
+                self.run_on_node_selectcc(tys, regs_info, heap, node)
+            }
             _ => {
                 self.run_on_node_operand(tys, regs_info, heap, node);
                 node
@@ -157,6 +160,12 @@ impl Legalize {
                 GR64 x {
                     imm32 y => (mi.MOVrm8 [BaseOff x, y])
                     GR64  y => (mi.MOVrm8 [BaseAlignOff x, $1, y]) } } }
+        (ir.Load dst): f32 {
+            (ir.Add x, y) dst {
+                (ir.FIAddr fi) x {
+                    imm32 y => (mi.MOVSSrm [BaseFiOff %rbp, fi, y])
+                    (ir.Mul z, u) y {
+                        imm32 u => (mi.MOVSSrm [BaseFiAlignOff %rbp, fi, u, z]) } } } }
         (ir.Load dst): f64 {
             (ir.Add x, y) dst {
                 (ir.FIAddr fi) x {
@@ -180,6 +189,8 @@ impl Legalize {
                     imm32   src => (mi.MOVmi32 [BaseFi %rbp, fi], src)
                     GR32    src => (mi.MOVmr32 [BaseFi %rbp, fi], src)
                     GR64    src => (mi.MOVmr64 [BaseFi %rbp, fi], src)
+                    XMM:f32 src => (mi.MOVSSmr [BaseFi %rbp, fi], src)
+                    imm_f32 src => (mi.MOVSSmr [BaseFi %rbp, fi], (mi.MOVSSrm32 src):f32)
                     XMM     src => (mi.MOVSDmr [BaseFi %rbp, fi], src)
                     imm_f64 src => (mi.MOVSDmr [BaseFi %rbp, fi], (mi.MOVSDrm64 src)) } }
             (ir.Add a1, a2) dst {
@@ -189,6 +200,8 @@ impl Legalize {
                             imm32   src => (mi.MOVmi32 [BaseFiOff %rbp, fi, a2], src)
                             GR32    src => (mi.MOVmr32 [BaseFiOff %rbp, fi, a2], src)
                             GR64    src => (mi.MOVmr64 [BaseFiOff %rbp, fi, a2], src)
+                            XMM:f32 src => (mi.MOVSSmr [BaseFiOff %rbp, fi, a2], src)
+                            imm_f32 src => (mi.MOVSSmr [BaseFiOff %rbp, fi, a2], (mi.MOVSSrm32 src):f32)
                             XMM     src => (mi.MOVSDmr [BaseFiOff %rbp, fi, a2], src)
                             imm_f64 src => (mi.MOVSDmr [BaseFiOff %rbp, fi, a2], (mi.MOVSDrm64 src)) } }
                     mem fi {
@@ -351,9 +364,13 @@ impl Legalize {
         if lhs.is_maybe_register() && rhs.is_constant() {
             let lhs = self.run_on_node(tys, regs_info, heap, lhs);
             let rhs = heap.alloc(DAGNode::new(
-                NodeKind::MI(MINodeKind::MOVSDrm64),
+                NodeKind::MI(if rhs.ty == Type::f32 {
+                    MINodeKind::MOVSSrm32
+                } else {
+                    MINodeKind::MOVSDrm64
+                }),
                 vec![rhs],
-                Type::f64,
+                rhs.ty,
             ));
             return heap.alloc(DAGNode::new(
                 NodeKind::IR(IRNodeKind::FPBrcc),
@@ -366,6 +383,44 @@ impl Legalize {
         node
     }
 
+    fn run_on_node_selectcc(
+        &mut self,
+        tys: &Types,
+        regs_info: &RegistersInfo,
+        heap: &mut DAGHeap,
+        mut node: Raw<DAGNode>,
+    ) -> Raw<DAGNode> {
+        let lhs = node.operand[1];
+        let rhs = node.operand[2];
+
+        // lhs must be register. The condition may be shared with a Brcc, so flip a copy of it
+        if lhs.is_constant() && rhs.is_maybe_register() {
+            let kind = node.operand[0].as_cond_kind().flip();
+            node.operand[0] = heap.alloc(DAGNode::new(
+                NodeKind::Operand(OperandNodeKind::CondKind(kind)),
+                vec![],
+                Type::Void,
+            ));
+            node.operand.swap(1, 2);
+            return self.run_on_node_selectcc(tys, regs_info, heap, node);
+        }
+
+        if node.kind == NodeKind::IR(IRNodeKind::FPSelectcc) && rhs.is_constant() {
+            node.operand[2] = heap.alloc(DAGNode::new(
+                NodeKind::MI(if rhs.ty == Type::f32 {
+                    MINodeKind::MOVSSrm32
+                } else {
+                    MINodeKind::MOVSDrm64
+                }),
+                vec![rhs],
+                rhs.ty,
+            ));
+        }
+
+        self.run_on_node_operand(tys, regs_info, heap, node);
+        node
+    }
+
     fn run_on_node_operand(
         &mut self,
         tys: &Types,
@@ -79,6 +79,11 @@ impl<'a> ScheduleByBlock<'a> {
                 );
                 self.append_inst(phi_inst)
             }
+            NodeKind::IR(IRNodeKind::Div) | NodeKind::IR(IRNodeKind::Rem)
+                if node.ty == Type::i16 =>
+            {
+                self.convert_sdiv_srem_i16(&node)
+            }
             NodeKind::IR(IRNodeKind::Div) => {
                 let regs = match node.ty {
                     Type::i8 => to_phys!(GR32::EAX, GR32::EDX),
@@ -186,22 +191,48 @@ impl<'a> ScheduleByBlock<'a> {
                     self.cur_bb,
                 ))
             }
+            NodeKind::IR(IRNodeKind::UDiv) | NodeKind::IR(IRNodeKind::URem) => {
+                self.convert_udiv_urem(&*node)
+            }
+            NodeKind::IR(IRNodeKind::Shl)
+            | NodeKind::IR(IRNodeKind::LShr)
+            | NodeKind::IR(IRNodeKind::AShr) => self.convert_shift(&*node),
             NodeKind::IR(IRNodeKind::Setcc) => {
-                let new_op1 = self.normal_operand(node.operand[1]);
-                let new_op2 = self.normal_operand(node.operand[2]);
-                let inst = MachineInst::new(
+                let op0 = self.normal_operand(node.operand[1]);
+                let op1 = self.normal_operand(node.operand[2]);
+
+                self.append_inst(MachineInst::new_simple(
+                    if op0.is_register() && op1.is_constant() {
+                        MachineOpcode::CMPri
+                    } else if op0.is_register() && op1.is_register() {
+                        MachineOpcode::CMPrr
+                    } else {
+                        unreachable!()
+                    },
+                    vec![op0, op1],
+                    self.cur_bb,
+                ));
+
+                // i1 lives in a GR8 register as 0 or 1
+                self.append_inst(MachineInst::new(
                     &self.cur_func.regs_info,
                     match cond_kind!(node.operand[0]) {
-                        CondKind::Eq => MachineOpcode::Seteq,
-                        CondKind::Le => MachineOpcode::Setle,
-                        CondKind::Lt => MachineOpcode::Setlt,
-                        _ => unimplemented!(),
+                        CondKind::Eq => MachineOpcode::SETE,
+                        CondKind::Ne => MachineOpcode::SETNE,
+                        CondKind::Le => MachineOpcode::SETLE,
+                        CondKind::Lt => MachineOpcode::SETL,
+                        CondKind::Ge => MachineOpcode::SETGE,
+                        CondKind::Gt => MachineOpcode::SETG,
+                        CondKind::ULe => MachineOpcode::SETBE,
+                        CondKind::ULt => MachineOpcode::SETB,
+                        CondKind::UGe => MachineOpcode::SETAE,
+                        CondKind::UGt => MachineOpcode::SETA,
+                        _ => unreachable!(),
                     },
-                    vec![new_op1, new_op2],
-                    ty2rc(&node.ty),
+                    vec![],
+                    Some(RegisterClassKind::GR8),
                     self.cur_bb,
-                );
-                self.append_inst(inst)
+                ))
             }
             NodeKind::IR(IRNodeKind::Brcc) => {
                 let op0 = self.normal_operand(node.operand[1]);
@@ -227,6 +258,10 @@ impl<'a> ScheduleByBlock<'a> {
                         CondKind::Lt => MachineOpcode::JL,
                         CondKind::Ge => MachineOpcode::JGE,
                         CondKind::Gt => MachineOpcode::JG,
+                        CondKind::ULe => MachineOpcode::JBE,
+                        CondKind::ULt => MachineOpcode::JB,
+                        CondKind::UGe => MachineOpcode::JAE,
+                        CondKind::UGt => MachineOpcode::JA,
                         _ => unreachable!(),
                     },
                     vec![MachineOperand::Branch(
@@ -240,7 +275,11 @@ impl<'a> ScheduleByBlock<'a> {
                 let op1 = self.normal_operand(node.operand[2]);
 
                 self.append_inst(MachineInst::new_simple(
-                    MachineOpcode::UCOMISDrr,
+                    if node.operand[1].ty == Type::f32 {
+                        MachineOpcode::UCOMISSrr
+                    } else {
+                        MachineOpcode::UCOMISDrr
+                    },
                     vec![op0, op1],
                     self.cur_bb,
                 ));
@@ -261,7 +300,16 @@ impl<'a> ScheduleByBlock<'a> {
                     self.cur_bb,
                 ))
             }
+            NodeKind::IR(IRNodeKind::Select)
+            | NodeKind::IR(IRNodeKind::Selectcc)
+            | NodeKind::IR(IRNodeKind::FPSelectcc) => self.convert_select(&*node),
+            NodeKind::IR(IRNodeKind::Switch) => self.convert_switch(&*node),
             NodeKind::IR(IRNodeKind::Ret) => self.convert_ret(&*node),
+            NodeKind::IR(IRNodeKind::Unreachable) => self.append_inst(MachineInst::new_simple(
+                MachineOpcode::UD2,
+                vec![],
+                self.cur_bb,
+            )),
             NodeKind::IR(IRNodeKind::CopyToLiveOut) => self.convert_node_to_inst(node.operand[0]),
             e => panic!("{:?}, {:?}", e, node.ty),
         };
@@ -294,6 +342,613 @@ impl<'a> ScheduleByBlock<'a> {
         ))
     }
 
+    // select is lowered to `dst = f; cmp; cmovcc dst, t`. Floats take a detour through
+    // general purpose registers since there is no conditional move between XMM registers, and
+    // i8 is zero-extended to 32 bits since there is no 8-bit conditional move.
+    fn convert_select(&mut self, node: &DAGNode) -> MachineInstId {
+        let (cond, t, f) = match node.kind {
+            NodeKind::IR(IRNodeKind::Select) => (CondKind::Ne, node.operand[1], node.operand[2]),
+            _ => (
+                node.operand[0].as_cond_kind(),
+                node.operand[3],
+                node.operand[4],
+            ),
+        };
+        let rc = match node.ty {
+            Type::i16 => RegisterClassKind::GR16,
+            Type::i8 | Type::i32 | Type::f32 => RegisterClassKind::GR32,
+            Type::i64 | Type::f64 => RegisterClassKind::GR64,
+            Type::Pointer(_) => RegisterClassKind::GR64,
+            _ => unimplemented!(),
+        };
+
+        // Both values must be in registers before the flags are set
+        let t = self.select_operand_to_gpr(rc, t);
+        let f = self.select_operand_to_gpr(rc, f);
+        let f_reg = *f.as_register();
+
+        match node.kind {
+            NodeKind::IR(IRNodeKind::Select) => {
+                let c = self.normal_operand(node.operand[0]);
+                self.append_inst(MachineInst::new_simple(
+                    MachineOpcode::CMPri,
+                    vec![c, MachineOperand::Constant(MachineConstant::Int8(0))],
+                    self.cur_bb,
+                ));
+            }
+            NodeKind::IR(IRNodeKind::Selectcc) => {
+                let op0 = self.normal_operand(node.operand[1]);
+                let op1 = self.normal_operand(node.operand[2]);
+                self.append_inst(MachineInst::new_simple(
+                    if op0.is_register() && op1.is_constant() {
+                        MachineOpcode::CMPri
+                    } else if op0.is_register() && op1.is_register() {
+                        MachineOpcode::CMPrr
+                    } else {
+                        unreachable!()
+                    },
+                    vec![op0, op1],
+                    self.cur_bb,
+                ));
+            }
+            _ => {
+                let op0 = self.normal_operand(node.operand[1]);
+                let op1 = self.normal_operand(node.operand[2]);
+                self.append_inst(MachineInst::new_simple(
+                    if node.operand[1].ty == Type::f32 {
+                        MachineOpcode::UCOMISSrr
+                    } else {
+                        MachineOpcode::UCOMISDrr
+                    },
+                    vec![op0, op1],
+                    self.cur_bb,
+                ));
+            }
+        }
+
+        let cmov = MachineInst::new(
+            &self.cur_func.regs_info,
+            match cond {
+                CondKind::Eq | CondKind::UEq => MachineOpcode::CMOVErr,
+                CondKind::Ne | CondKind::UNe => MachineOpcode::CMOVNErr,
+                CondKind::Le => MachineOpcode::CMOVLErr,
+                CondKind::Lt => MachineOpcode::CMOVLrr,
+                CondKind::Ge => MachineOpcode::CMOVGErr,
+                CondKind::Gt => MachineOpcode::CMOVGrr,
+                CondKind::ULe => MachineOpcode::CMOVBErr,
+                CondKind::ULt => MachineOpcode::CMOVBrr,
+                CondKind::UGe => MachineOpcode::CMOVAErr,
+                CondKind::UGt => MachineOpcode::CMOVArr,
+            },
+            vec![f, t],
+            Some(rc),
+            self.cur_bb,
+        )
+        .set_tie_with_def(f_reg);
+        let dst_reg = cmov.def[0];
+        let dst = MachineOperand::Register(dst_reg);
+        let cmov = self.append_inst(cmov);
+
+        match node.ty {
+            Type::i8 => self.append_inst(MachineInst::new(
+                &self.cur_func.regs_info,
+                MachineOpcode::Copy,
+                vec![MachineOperand::Register(
+                    dst_reg.sub_super(Some(RegisterClassKind::GR8)),
+                )],
+                Some(RegisterClassKind::GR8),
+                self.cur_bb,
+            )),
+            Type::f32 | Type::f64 => self.append_inst(MachineInst::new(
+                &self.cur_func.regs_info,
+                if node.ty == Type::f32 {
+                    MachineOpcode::MOVDxr
+                } else {
+                    MachineOpcode::MOVQxr
+                },
+                vec![dst],
+                Some(RegisterClassKind::XMM),
+                self.cur_bb,
+            )),
+            _ => cmov,
+        }
+    }
+
+    // Switches left by CodegenPrepare are dense enough for a jump table. Each entry holds the
+    // offset of a block from the table; values out of range are clamped to the last entry,
+    // which holds the default block.
+    fn convert_switch(&mut self, node: &DAGNode) -> MachineInstId {
+        let default = self.get_machine_bb(node.operand[1].as_basic_block());
+        let cases: Vec<(i64, MachineBasicBlockId)> = node.operand[2..]
+            .chunks(2)
+            .map(|c| {
+                let v = match c[0].as_constant() {
+                    ConstantKind::Int32(i) => i as i64,
+                    ConstantKind::Int64(i) => i,
+                    _ => unreachable!(),
+                };
+                (v, self.get_machine_bb(c[1].as_basic_block()))
+            })
+            .collect();
+        let min = cases.iter().map(|&(v, _)| v).min().unwrap();
+        let max = cases.iter().map(|&(v, _)| v).max().unwrap();
+        let len = (max - min) as usize + 1;
+
+        let mut entries = vec![default; len + 1];
+        for &(v, bb) in &cases {
+            entries[(v - min) as usize] = bb;
+        }
+        let table = self.const_data.alloc(MachineConstant::Int32(0));
+        self.const_data[table] = MachineConstant::JumpTableEntry(table, entries[0]);
+        for &bb in &entries[1..] {
+            self.const_data
+                .alloc(MachineConstant::JumpTableEntry(table, bb));
+        }
+
+        let cond = self.normal_operand(node.operand[0]);
+        let mut idx = if node.operand[0].ty == Type::i32 {
+            let sext = MachineInst::new(
+                &self.cur_func.regs_info,
+                MachineOpcode::MOVSXDr64r32,
+                vec![cond],
+                Some(RegisterClassKind::GR64),
+                self.cur_bb,
+            );
+            let idx = sext.def[0];
+            self.append_inst(sext);
+            idx
+        } else {
+            *cond.as_register()
+        };
+
+        if min != 0 {
+            let sub = MachineInst::new(
+                &self.cur_func.regs_info,
+                MachineOpcode::SUBr64i32,
+                vec![
+                    MachineOperand::Register(idx),
+                    MachineOperand::Constant(MachineConstant::Int32(min as i32)),
+                ],
+                Some(RegisterClassKind::GR64),
+                self.cur_bb,
+            )
+            .set_tie_with_def(idx);
+            idx = sub.def[0];
+            self.append_inst(sub);
+        }
+
+        let default_idx = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::MOVri64,
+            vec![MachineOperand::Constant(MachineConstant::Int64(len as i64))],
+            Some(RegisterClassKind::GR64),
+            self.cur_bb,
+        );
+        let default_idx_reg = default_idx.def[0];
+        self.append_inst(default_idx);
+        self.append_inst(MachineInst::new_simple(
+            MachineOpcode::CMPri,
+            vec![
+                MachineOperand::Register(idx),
+                MachineOperand::Constant(MachineConstant::Int32(len as i32 - 1)),
+            ],
+            self.cur_bb,
+        ));
+        let cmov = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::CMOVArr,
+            vec![
+                MachineOperand::Register(idx),
+                MachineOperand::Register(default_idx_reg),
+            ],
+            Some(RegisterClassKind::GR64),
+            self.cur_bb,
+        )
+        .set_tie_with_def(idx);
+        let idx = cmov.def[0];
+        self.append_inst(cmov);
+
+        let lea = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::LEAr64m,
+            vec![MachineOperand::Mem(MachineMemOperand::Address(
+                inst::AddressKind::Label(table),
+            ))],
+            Some(RegisterClassKind::GR64),
+            self.cur_bb,
+        );
+        let base = lea.def[0];
+        self.append_inst(lea);
+        let load = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::MOVrm32,
+            vec![MachineOperand::Mem(MachineMemOperand::BaseAlignOff(
+                base, 4, idx,
+            ))],
+            Some(RegisterClassKind::GR32),
+            self.cur_bb,
+        );
+        let offset = MachineOperand::Register(load.def[0]);
+        self.append_inst(load);
+        let sext = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::MOVSXDr64r32,
+            vec![offset],
+            Some(RegisterClassKind::GR64),
+            self.cur_bb,
+        );
+        let offset = sext.def[0];
+        self.append_inst(sext);
+        let add = MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::ADDrr64,
+            vec![
+                MachineOperand::Register(offset),
+                MachineOperand::Register(base),
+            ],
+            Some(RegisterClassKind::GR64),
+            self.cur_bb,
+        )
+        .set_tie_with_def(offset);
+        let dst = add.def[0];
+        self.append_inst(add);
+
+        self.append_inst(MachineInst::new_simple(
+            MachineOpcode::JMPr,
+            vec![MachineOperand::Register(dst)],
+            self.cur_bb,
+        ))
+    }
+
+    fn select_operand_to_gpr(
+        &mut self,
+        rc: RegisterClassKind,
+        node: Raw<DAGNode>,
+    ) -> MachineOperand {
+        let (opcode, op) = match self.normal_operand(node) {
+            op if node.ty == Type::i8 => self.zext_to_rc(rc, op),
+            op @ MachineOperand::Register(_) if !node.ty.is_float() => return op,
+            op @ MachineOperand::Register(_) if node.ty == Type::f32 => (MachineOpcode::MOVDrx, op),
+            op @ MachineOperand::Register(_) => (MachineOpcode::MOVQrx, op),
+            // Float constants are moved as their bit patterns
+            MachineOperand::Constant(MachineConstant::F32(f)) => (
+                MachineOpcode::MOVri32,
+                MachineOperand::Constant(MachineConstant::Int32(f.to_bits() as i32)),
+            ),
+            MachineOperand::Constant(MachineConstant::F64(f)) => (
+                MachineOpcode::MOVri64,
+                MachineOperand::Constant(MachineConstant::Int64(f.to_bits() as i64)),
+            ),
+            op => (mov_r_x(rc, &op).unwrap(), op),
+        };
+        let inst = MachineInst::new(
+            &self.cur_func.regs_info,
+            opcode,
+            vec![op],
+            Some(rc),
+            self.cur_bb,
+        );
+        let dst = MachineOperand::Register(inst.def[0]);
+        self.append_inst(inst);
+        dst
+    }
+
+    fn convert_udiv_urem(&mut self, node: &DAGNode) -> MachineInstId {
+        // i8 and i16 are divided as i32 after zero extension
+        let (div, regs) = match node.ty {
+            Type::i8 | Type::i16 | Type::i32 => {
+                (MachineOpcode::DIVr32, to_phys!(GR32::EAX, GR32::EDX))
+            }
+            Type::i64 => (MachineOpcode::DIVr64, to_phys!(GR64::RAX, GR64::RDX)),
+            _ => unimplemented!(),
+        };
+        let rc = regs[0].reg_class();
+        let rax = RegisterOperand::new(self.cur_func.regs_info.get_phys_reg(regs[0]));
+        let rdx = RegisterOperand::new(self.cur_func.regs_info.get_phys_reg(regs[1]));
+
+        let op1 = self.normal_operand(node.operand[0]);
+        let op2 = self.normal_operand(node.operand[1]);
+        let (opcode1, op1) = self.zext_to_rc(rc, op1);
+        let (opcode2, op2) = self.zext_to_rc(rc, op2);
+
+        self.append_inst(
+            MachineInst::new_simple(opcode1, vec![op1], self.cur_bb).with_def(vec![rax]),
+        );
+
+        // Writing to EDX clears the upper half of RDX as well
+        self.append_inst(
+            MachineInst::new_simple(
+                MachineOpcode::MOVri32,
+                vec![MachineOperand::Constant(MachineConstant::Int32(0))],
+                self.cur_bb,
+            )
+            .with_def(vec![RegisterOperand::new(
+                self.cur_func.regs_info.get_phys_reg(GR32::EDX),
+            )]),
+        );
+
+        let inst1 = MachineInst::new(
+            &self.cur_func.regs_info,
+            opcode2,
+            vec![op2],
+            Some(rc),
+            self.cur_bb,
+        );
+        let op2 = MachineOperand::Register(inst1.def[0]);
+        self.append_inst(inst1);
+
+        self.append_inst(
+            MachineInst::new_simple(div, vec![op2], self.cur_bb)
+                .with_imp_defs(vec![rax, rdx])
+                .with_imp_uses(vec![rax, rdx]),
+        );
+
+        let ret = match node.kind {
+            NodeKind::IR(IRNodeKind::UDiv) => regs[0],
+            _ => regs[1],
+        };
+        let ret = match node.ty {
+            Type::i8 => ret.reg_class_as(RegisterClassKind::GR8),
+            Type::i16 => ret.reg_class_as(RegisterClassKind::GR16),
+            _ => ret,
+        };
+        self.append_inst(MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::Copy,
+            vec![MachineOperand::Register(RegisterOperand::new(
+                self.cur_func.regs_info.get_phys_reg(ret),
+            ))],
+            ty2rc(&node.ty),
+            self.cur_bb,
+        ))
+    }
+
+    /// Returns an instruction (and its operand) that puts ``op`` zero-extended into a register of ``rc``.
+    fn zext_to_rc(
+        &self,
+        rc: RegisterClassKind,
+        op: MachineOperand,
+    ) -> (MachineOpcode, MachineOperand) {
+        match op {
+            MachineOperand::Constant(MachineConstant::Int8(i)) => (
+                MachineOpcode::MOVri32,
+                MachineOperand::Constant(MachineConstant::Int32(i as u8 as i32)),
+            ),
+            MachineOperand::Constant(MachineConstant::Int16(i)) => (
+                MachineOpcode::MOVri32,
+                MachineOperand::Constant(MachineConstant::Int32(i as u16 as i32)),
+            ),
+            MachineOperand::Register(r) => {
+                match self.cur_func.regs_info.arena_ref()[r.id].reg_class {
+                    RegisterClassKind::GR8 => (MachineOpcode::MOVZXr32r8, op),
+                    RegisterClassKind::GR16 => (MachineOpcode::MOVZXr32r16, op),
+                    _ => (mov_r_x(rc, &op).unwrap(), op),
+                }
+            }
+            op => (mov_r_x(rc, &op).unwrap(), op),
+        }
+    }
+
+    /// Returns an instruction (and its operand) that puts the i16 ``op`` sign-extended into a GR32
+    /// register.
+    fn sext_i16_to_gr32(&self, op: MachineOperand) -> (MachineOpcode, MachineOperand) {
+        match op {
+            MachineOperand::Constant(MachineConstant::Int16(i)) => (
+                MachineOpcode::MOVri32,
+                MachineOperand::Constant(MachineConstant::Int32(i as i32)),
+            ),
+            op => (MachineOpcode::MOVSXr32r16, op),
+        }
+    }
+
+    // i16 is divided as i32 after sign extension, so that e.g. -32768 / -1 doesn't trap
+    fn convert_sdiv_srem_i16(&mut self, node: &DAGNode) -> MachineInstId {
+        let eax = RegisterOperand::new(self.cur_func.regs_info.get_phys_reg(GR32::EAX));
+        let edx = RegisterOperand::new(self.cur_func.regs_info.get_phys_reg(GR32::EDX));
+
+        let op1 = self.normal_operand(node.operand[0]);
+        let op2 = self.normal_operand(node.operand[1]);
+        let (opcode1, op1) = self.sext_i16_to_gr32(op1);
+        let (opcode2, op2) = self.sext_i16_to_gr32(op2);
+
+        self.append_inst(
+            MachineInst::new_simple(opcode1, vec![op1], self.cur_bb).with_def(vec![eax]),
+        );
+
+        self.append_inst(
+            MachineInst::new_simple(MachineOpcode::CDQ, vec![], self.cur_bb)
+                .with_imp_defs(vec![eax, edx])
+                .with_imp_use(eax),
+        );
+
+        let inst1 = MachineInst::new(
+            &self.cur_func.regs_info,
+            opcode2,
+            vec![op2],
+            Some(RegisterClassKind::GR32),
+            self.cur_bb,
+        );
+        let op2 = MachineOperand::Register(inst1.def[0]);
+        self.append_inst(inst1);
+
+        self.append_inst(
+            MachineInst::new_simple(MachineOpcode::IDIV, vec![op2], self.cur_bb)
+                .with_imp_defs(vec![eax, edx])
+                .with_imp_uses(vec![eax, edx]),
+        );
+
+        let ret = match node.kind {
+            NodeKind::IR(IRNodeKind::Div) => GR16::AX,
+            _ => GR16::DX,
+        };
+        self.append_inst(MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::Copy,
+            vec![MachineOperand::Register(RegisterOperand::new(
+                self.cur_func.regs_info.get_phys_reg(ret),
+            ))],
+            Some(RegisterClassKind::GR16),
+            self.cur_bb,
+        ))
+    }
+
+    // Shifts that isel couldn't select, i.e. shifting a constant, shifting by a register or
+    // shifting i16. There are no 16-bit shifts here, so i16 is extended and shifted in 32 bits.
+    fn convert_shift(&mut self, node: &DAGNode) -> MachineInstId {
+        let is_i16 = node.ty == Type::i16;
+        let rc = if is_i16 {
+            RegisterClassKind::GR32
+        } else {
+            ty2rc(&node.ty).unwrap()
+        };
+        let mut op1 = self.normal_operand(node.operand[0]);
+        let op2 = self.normal_operand(node.operand[1]);
+
+        if is_i16 {
+            let (opcode, op) = match node.kind {
+                NodeKind::IR(IRNodeKind::AShr) => self.sext_i16_to_gr32(op1),
+                _ => self.zext_to_rc(rc, op1),
+            };
+            let ext = MachineInst::new(
+                &self.cur_func.regs_info,
+                opcode,
+                vec![op],
+                Some(rc),
+                self.cur_bb,
+            );
+            op1 = MachineOperand::Register(ext.def[0]);
+            self.append_inst(ext);
+        } else if op1.is_constant() {
+            let mov = MachineInst::new(
+                &self.cur_func.regs_info,
+                mov_r_x(rc, &op1).unwrap(),
+                vec![op1],
+                Some(rc),
+                self.cur_bb,
+            );
+            op1 = MachineOperand::Register(mov.def[0]);
+            self.append_inst(mov);
+        }
+
+        let (opcode, amount) = match op2 {
+            // x64 masks the shift amount, so truncating it to i8 doesn't change the result
+            MachineOperand::Constant(c) => {
+                let amount = match c {
+                    MachineConstant::Int8(i) => i,
+                    MachineConstant::Int16(i) => i as i8,
+                    MachineConstant::Int32(i) => i as i8,
+                    MachineConstant::Int64(i) => i as i8,
+                    MachineConstant::F32(_)
+                    | MachineConstant::F64(_)
+                    | MachineConstant::JumpTableEntry(_, _) => unreachable!(),
+                };
+                let opcode = match (&node.kind, rc) {
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR8) => {
+                        MachineOpcode::SHLr8i8
+                    }
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR32) => {
+                        MachineOpcode::SHLr32i8
+                    }
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR64) => {
+                        MachineOpcode::SHLr64i8
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR8) => {
+                        MachineOpcode::SHRr8i8
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR32) => {
+                        MachineOpcode::SHRr32i8
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR64) => {
+                        MachineOpcode::SHRr64i8
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR8) => {
+                        MachineOpcode::SARr8i8
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR32) => {
+                        MachineOpcode::SARr32i8
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR64) => {
+                        MachineOpcode::SARr64i8
+                    }
+                    _ => unimplemented!(),
+                };
+                (
+                    opcode,
+                    MachineOperand::Constant(MachineConstant::Int8(amount)),
+                )
+            }
+            // The shift amount must be in CL
+            MachineOperand::Register(r) => {
+                let cl = RegisterOperand::new(self.cur_func.regs_info.get_phys_reg(GR8::CL));
+                self.append_inst(
+                    MachineInst::new_simple(
+                        MachineOpcode::MOVrr8,
+                        vec![MachineOperand::Register(
+                            r.sub_super(Some(RegisterClassKind::GR8)),
+                        )],
+                        self.cur_bb,
+                    )
+                    .with_def(vec![cl]),
+                );
+                let opcode = match (&node.kind, rc) {
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR8) => {
+                        MachineOpcode::SHLr8CL
+                    }
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR32) => {
+                        MachineOpcode::SHLr32CL
+                    }
+                    (NodeKind::IR(IRNodeKind::Shl), RegisterClassKind::GR64) => {
+                        MachineOpcode::SHLr64CL
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR8) => {
+                        MachineOpcode::SHRr8CL
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR32) => {
+                        MachineOpcode::SHRr32CL
+                    }
+                    (NodeKind::IR(IRNodeKind::LShr), RegisterClassKind::GR64) => {
+                        MachineOpcode::SHRr64CL
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR8) => {
+                        MachineOpcode::SARr8CL
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR32) => {
+                        MachineOpcode::SARr32CL
+                    }
+                    (NodeKind::IR(IRNodeKind::AShr), RegisterClassKind::GR64) => {
+                        MachineOpcode::SARr64CL
+                    }
+                    _ => unimplemented!(),
+                };
+                (opcode, MachineOperand::Register(cl))
+            }
+            _ => unreachable!(),
+        };
+
+        let mut inst = MachineInst::new(
+            &self.cur_func.regs_info,
+            opcode,
+            vec![op1, amount],
+            Some(rc),
+            self.cur_bb,
+        );
+        inst.tie_regs(inst.def[0], *inst.operand[0].as_register());
+        let dst = inst.def[0];
+        let inst = self.append_inst(inst);
+        if !is_i16 {
+            return inst;
+        }
+        self.append_inst(MachineInst::new(
+            &self.cur_func.regs_info,
+            MachineOpcode::Copy,
+            vec![MachineOperand::Register(
+                dst.sub_super(Some(RegisterClassKind::GR16)),
+            )],
+            Some(RegisterClassKind::GR16),
+            self.cur_bb,
+        ))
+    }
+
     fn move2reg(&self, r: RegisterId, src: MachineOperand) -> MachineInst {
         let opcode = mov_rx(self.types, &self.cur_func.regs_info, &src).unwrap();
         MachineInst::new_simple(opcode, vec![src], self.cur_bb)
@@ -315,7 +970,7 @@ impl<'a> ScheduleByBlock<'a> {
                 .types
                 .base
                 .borrow()
-                .as_function_ty(f_ty)
+                .as_callee_ty(f_ty)
                 .unwrap()
                 .params_attr
                 .get(&i)
@@ -330,12 +985,24 @@ impl<'a> ScheduleByBlock<'a> {
         let abi = SystemV::new();
         let mut arg_regs_order = ArgumentRegisterOrder::new(&abi);
 
+        let is_var_arg = self
+            .types
+            .base
+            .borrow()
+            .as_callee_ty(f_ty)
+            .unwrap()
+            .is_var_arg;
+
         for (i, arg) in args.into_iter().enumerate() {
             let (ty, byval) = {
                 let base = self.types.base.borrow();
-                let f = &base.as_function_ty(f_ty).unwrap();
+                let f = &base.as_callee_ty(f_ty).unwrap();
                 (
-                    *f.params_ty.get(i).unwrap(),
+                    // Arguments passed to '...' take the type of the value itself
+                    f.params_ty
+                        .get(i)
+                        .copied()
+                        .unwrap_or(node.operand[1 + i].ty),
                     f.params_attr.get(&i).map_or(false, |attr| attr.byval),
                 )
             };
@@ -354,7 +1021,14 @@ impl<'a> ScheduleByBlock<'a> {
 
             if !matches!(
                 ty,
-                Type::i8 | Type::i32 | Type::i64 | Type::f64 | Type::Pointer(_) | Type::Array(_)
+                Type::i8
+                    | Type::i16
+                    | Type::i32
+                    | Type::i64
+                    | Type::f32
+                    | Type::f64
+                    | Type::Pointer(_)
+                    | Type::Array(_)
             ) {
                 unimplemented!()
             };
@@ -403,14 +1077,36 @@ impl<'a> ScheduleByBlock<'a> {
             )),
         );
 
+        if is_var_arg {
+            // AL tells the callee how many vector registers hold arguments
+            let al = self.cur_func.regs_info.get_phys_reg(GR8::AL);
+            let num = abi.num_vector_regs_used(&arg_regs_order);
+            self.append_inst(
+                MachineInst::new_simple(
+                    MachineOpcode::MOVri8,
+                    vec![MachineOperand::Constant(MachineConstant::Int8(num as i8))],
+                    self.cur_bb,
+                )
+                .with_def(vec![RegisterOperand::new(al)]),
+            );
+            arg_regs.push(RegisterOperand::new(al));
+        }
+
         let callee = self.normal_operand(node.operand[0]);
+        let call = match callee {
+            MachineOperand::Mem(MachineMemOperand::Address(inst::AddressKind::FunctionName(_))) => {
+                MachineOpcode::CALL
+            }
+            MachineOperand::Register(_) => MachineOpcode::CALLr64,
+            _ => MachineOpcode::CALLm64,
+        };
         let ret_reg = self.cur_func.regs_info.get_phys_reg(
             ty2rc(&node.ty)
                 .unwrap_or(RegisterClassKind::GR32)
                 .return_value_register(),
         );
         let call_inst = self.append_inst(
-            MachineInst::new_simple(MachineOpcode::CALL, vec![callee], self.cur_bb)
+            MachineInst::new_simple(call, vec![callee], self.cur_bb)
                 .with_imp_uses(arg_regs)
                 .with_imp_defs({
                     let mut defs = vec![RegisterOperand::new(
@@ -493,8 +1189,9 @@ impl<'a> ScheduleByBlock<'a> {
                     match rc {
                         RegisterClassKind::GR32 => MachineOpcode::MOVrm32,
                         RegisterClassKind::GR64 => MachineOpcode::MOVrm64,
+                        RegisterClassKind::XMM if sz - off < 8 => MachineOpcode::MOVSSrm,
                         RegisterClassKind::XMM => MachineOpcode::MOVSDrm,
-                        RegisterClassKind::GR8 => unimplemented!(),
+                        RegisterClassKind::GR8 | RegisterClassKind::GR16 => unimplemented!(),
                     },
                     vec![mem],
                     self.cur_bb,
@@ -549,8 +1246,10 @@ impl<'a> ScheduleByBlock<'a> {
         match node.kind {
             NodeKind::Operand(OperandNodeKind::Constant(c)) => match c {
                 ConstantKind::Int8(i) => MachineOperand::Constant(MachineConstant::Int8(i)),
+                ConstantKind::Int16(i) => MachineOperand::Constant(MachineConstant::Int16(i)),
                 ConstantKind::Int32(i) => MachineOperand::Constant(MachineConstant::Int32(i)),
                 ConstantKind::Int64(i) => MachineOperand::Constant(MachineConstant::Int64(i)),
+                ConstantKind::F32(f) => MachineOperand::Constant(MachineConstant::F32(f)),
                 ConstantKind::F64(f) => MachineOperand::Constant(MachineConstant::F64(f)),
             },
             NodeKind::Operand(OperandNodeKind::FrameIndex(ref kind)) => {
@@ -560,10 +1259,9 @@ impl<'a> ScheduleByBlock<'a> {
                 node::AddressKind::FunctionName(n) => MachineOperand::Mem(
                     MachineMemOperand::Address(inst::AddressKind::FunctionName(n.clone())),
                 ),
-                _ => unreachable!()
-                // node::AddressKind::GlobalName(n) => MachineOperand::Mem(
-                //     MachineMemOperand::Address(inst::AddressKind::GlobalName(n.clone())),
-                // ),
+                _ => unreachable!(), // node::AddressKind::GlobalName(n) => MachineOperand::Mem(
+                                     //     MachineMemOperand::Address(inst::AddressKind::GlobalName(n.clone())),
+                                     // ),
             },
             NodeKind::Operand(OperandNodeKind::BasicBlock(id)) => {
                 MachineOperand::Branch(self.get_machine_bb(id))
@@ -639,6 +1337,7 @@ impl<'a> ScheduleByBlock<'a> {
 
 pub fn mov_r_x(rc: RegisterClassKind, x: &MachineOperand) -> Option<MachineOpcode> {
     let mov8rx = [MachineOpcode::MOVrr8, MachineOpcode::MOVri8];
+    let mov16rx = [MachineOpcode::MOVrr16, MachineOpcode::MOVri16];
     let mov32rx = [MachineOpcode::MOVrr32, MachineOpcode::MOVri32];
     let mov64rx = [MachineOpcode::MOVrr64, MachineOpcode::MOVri64];
     let movsdrx = [MachineOpcode::MOVSDrr, MachineOpcode::MOVSDrm64];
@@ -647,8 +1346,12 @@ pub fn mov_r_x(rc: RegisterClassKind, x: &MachineOperand) -> Option<MachineOpcod
         MachineOperand::Constant(_) => 1,
         _ => return None,
     };
+    if let MachineOperand::Constant(MachineConstant::F32(_)) = x {
+        return Some(MachineOpcode::MOVSSrm32);
+    }
     match rc {
         RegisterClassKind::GR8 => Some(mov8rx[idx]),
+        RegisterClassKind::GR16 => Some(mov16rx[idx]),
         RegisterClassKind::GR32 => Some(mov32rx[idx]),
         RegisterClassKind::GR64 => Some(mov64rx[idx]),
         RegisterClassKind::XMM => Some(movsdrx[idx]),
@@ -674,6 +1377,15 @@ pub fn mov_n_rx(bit: usize, x: &MachineOperand) -> Option<MachineOpcode> {
 // TODO: Will be deprecated
 pub fn mov_rx(tys: &Types, regs_info: &RegistersInfo, x: &MachineOperand) -> Option<MachineOpcode> {
     // TODO: special handling for float
+    if x.get_type(regs_info).unwrap() == Type::f32 {
+        return match x {
+            MachineOperand::Constant(_) => Some(MachineOpcode::MOVSSrm32),
+            MachineOperand::FrameIndex(_) | MachineOperand::Mem(_) => Some(MachineOpcode::MOVSSrm),
+            MachineOperand::Register(_) => Some(MachineOpcode::MOVSDrr),
+            _ => None,
+        };
+    }
+
     if x.get_type(regs_info).unwrap() == Type::f64 {
         return match x {
             MachineOperand::Constant(_) => Some(MachineOpcode::MOVSDrm64),
@@ -684,6 +1396,11 @@ pub fn mov_rx(tys: &Types, regs_info: &RegistersInfo, x: &MachineOperand) -> Opt
     }
 
     let mov8rx = [MachineOpcode::MOVrr8];
+    let mov16rx = [
+        MachineOpcode::MOVrr16,
+        MachineOpcode::MOVri16,
+        MachineOpcode::MOVrm16,
+    ];
     let mov32rx = [
         MachineOpcode::MOVrr32,
         MachineOpcode::MOVri32,
@@ -708,6 +1425,7 @@ pub fn mov_rx(tys: &Types, regs_info: &RegistersInfo, x: &MachineOperand) -> Opt
     };
     match bit {
         8 => Some(mov8rx[xidx]),
+        16 => Some(mov16rx[xidx]),
         32 => Some(mov32rx[xidx]),
         64 => Some(mov64rx[xidx]),
         _ => None,
@@ -722,6 +1440,7 @@ pub fn mov_mx(regs_info: &RegistersInfo, x: &MachineOperand) -> Option<MachineOp
         };
     }
 
+    let mov16mx = [MachineOpcode::MOVmr16, MachineOpcode::MOVmi16];
     let mov32mx = [MachineOpcode::MOVmr32, MachineOpcode::MOVmi32];
     let mov64mx = [MachineOpcode::MOVmr64, MachineOpcode::MOVmi64];
     // let mov64rx = [
@@ -741,6 +1460,7 @@ pub fn mov_mx(regs_info: &RegistersInfo, x: &MachineOperand) -> Option<MachineOp
         _ => return None, // TODO: Support Address?
     };
     match bit {
+        16 => Some(mov16mx[n]),
         32 => Some(mov32mx[n]),
         64 => Some(mov64mx[n]),
         _ => None,
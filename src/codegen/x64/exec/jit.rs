@@ -14,9 +14,13 @@ use crate::{
         },
     },
     ir,
-    ir::types::*,
+    ir::{
+        global_val::{GlobalVariableId, Symbol},
+        types::*,
+    },
 };
 use dynasmrt::*;
+use mmap::{MapOption, MemoryMap};
 use rustc_hash::FxHashMap;
 
 #[rustfmt::skip]
@@ -33,7 +37,11 @@ fn phys_reg_to_dynasm_reg(r: PhysReg) -> u8 {
 #[derive(Debug, Clone, PartialEq)]
 pub enum GenericValue {
     Address(*mut u8),
+    Int8(i8),
+    Int16(i16),
     Int32(i32),
+    Int64(i64),
+    F32(f32),
     F64(f64),
     None,
 }
@@ -47,6 +55,11 @@ pub struct JITCompiler {
     asm: x64::Assembler,
     labels: FxHashMap<LabelKey, DynamicLabel>,
     internal_functions: FxHashMap<String, u64>, // name -> fn address
+    global_vars: FxHashMap<GlobalVariableId, i32>, // id -> address
+    global_memory: Option<MemoryMap>,
+    // Places in global variables to store the address of a function (plus an addend) at. They are
+    // patched on every run since the code may move when it grows.
+    function_relocs: Vec<(*mut u8, DynamicLabel, i64)>,
 }
 
 impl JITExecutor {
@@ -105,6 +118,7 @@ impl JITCompiler {
                     cilk_i32_to_f64_i32_ as _,
                     cilk_f64_to_i32_f64_ as _,
                     cilk_malloc_i32_ as _,
+                    cilk_trap_ as _,
                 ];
                 assert!(
                     internal_names.len() == internals.len(),
@@ -117,6 +131,9 @@ impl JITCompiler {
                     .into_iter()
                     .collect::<FxHashMap<_, _>>()
             },
+            global_vars: FxHashMap::default(),
+            global_memory: None,
+            function_relocs: vec![],
         }
     }
 
@@ -132,11 +149,34 @@ impl JITCompiler {
 
         for (_idx, arg) in args.iter().enumerate() {
             match arg {
+                GenericValue::Int8(i) => {
+                    let r = arg_reg_order.next(RegisterClassKind::GR8).unwrap();
+                    dynasm!(self.asm; mov Rb(phys_reg_to_dynasm_reg(r)), *i)
+                }
+                GenericValue::Int16(i) => {
+                    let r = arg_reg_order.next(RegisterClassKind::GR16).unwrap();
+                    dynasm!(self.asm; mov Rw(phys_reg_to_dynasm_reg(r)), *i)
+                }
                 GenericValue::Int32(i) => {
                     let r = arg_reg_order.next(RegisterClassKind::GR32).unwrap();
                     dynasm!(self.asm; mov Rd(phys_reg_to_dynasm_reg(r)), *i)
                 }
-                GenericValue::F64(_) => unimplemented!(),
+                GenericValue::Int64(i) => {
+                    let r = arg_reg_order.next(RegisterClassKind::GR64).unwrap();
+                    dynasm!(self.asm; mov Rq(phys_reg_to_dynasm_reg(r)), QWORD *i)
+                }
+                GenericValue::F32(f) => {
+                    let r = arg_reg_order.next(RegisterClassKind::XMM).unwrap();
+                    dynasm!(self.asm
+                        ; mov eax, f.to_bits() as i32
+                        ; movd Rx(phys_reg_to_dynasm_reg(r)), eax)
+                }
+                GenericValue::F64(f) => {
+                    let r = arg_reg_order.next(RegisterClassKind::XMM).unwrap();
+                    dynasm!(self.asm
+                        ; mov rax, QWORD f.to_bits() as i64
+                        ; movq Rx(phys_reg_to_dynasm_reg(r)), rax)
+                }
                 GenericValue::Address(addr) => {
                     let r = arg_reg_order.next(RegisterClassKind::GR64).unwrap();
                     dynasm!(self.asm; mov Rq(phys_reg_to_dynasm_reg(r)), QWORD *addr as i64)
@@ -156,6 +196,12 @@ impl JITCompiler {
         let executor = self.asm.reader();
         let buf = executor.lock();
 
+        for &(at, label, addend) in &self.function_relocs {
+            let offset = self.asm.get_dynamic_label_offset(label).unwrap();
+            let addr = buf.ptr(offset) as i64 + addend;
+            unsafe { ::std::ptr::write_unaligned(at as *mut i64, addr) };
+        }
+
         match module
             .types
             .base
@@ -164,10 +210,26 @@ impl JITCompiler {
             .unwrap()
             .ret_ty
         {
+            Type::i8 => {
+                let f: extern "C" fn() -> i8 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
+                GenericValue::Int8(f())
+            }
+            Type::i16 => {
+                let f: extern "C" fn() -> i16 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
+                GenericValue::Int16(f())
+            }
             Type::i32 => {
                 let f: extern "C" fn() -> i32 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
                 GenericValue::Int32(f())
             }
+            Type::i64 => {
+                let f: extern "C" fn() -> i64 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
+                GenericValue::Int64(f())
+            }
+            Type::f32 => {
+                let f: extern "C" fn() -> f32 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
+                GenericValue::F32(f())
+            }
             Type::f64 => {
                 let f: extern "C" fn() -> f64 = unsafe { ::std::mem::transmute(buf.ptr(entry)) };
                 GenericValue::F64(f() as f64)
@@ -182,22 +244,120 @@ impl JITCompiler {
     }
 
     pub fn compile_module(&mut self, module: &MachineModule) {
+        self.allocate_global_vars(module);
+
         // Place constant data in memory
         for (_id, f) in &module.functions {
             for (id, c) in f.const_data.id_and_data() {
-                let x = unsafe { ::std::mem::transmute::<f64, u64>(c.as_f64()) }; // TODO: now support only for f64
+                let label = self.get_label(id);
+                match c {
+                    MachineConstant::F32(f) => {
+                        dynasm!(self.asm; =>label; .dword f.to_bits() as i32);
+                        continue;
+                    }
+                    // Emitted after the function body, whose block offsets they refer to
+                    MachineConstant::JumpTableEntry(_, _) => continue,
+                    _ => {}
+                }
+                let x = unsafe { ::std::mem::transmute::<f64, u64>(c.as_f64()) };
                 let h = (x >> 32) as i32;
                 let l = (x & 0xffff_ffff) as i32;
-                let label = self.get_label(id);
                 dynasm!(self.asm; =>label; .dword l, h);
             }
         }
 
         for (f_id, _) in &module.functions {
             self.compile_function(module, f_id);
+            self.compile_jump_tables(module.function_ref(f_id));
         }
     }
 
+    fn compile_jump_tables(&mut self, f: &MachineFunction) {
+        let mut table_offset = 0;
+        for (id, c) in f.const_data.id_and_data() {
+            if let MachineConstant::JumpTableEntry(table, bb) = c {
+                if *table == id {
+                    let label = self.get_label(id);
+                    dynasm!(self.asm; .align 4; =>label);
+                    table_offset = self.asm.offset().0;
+                }
+                let label = self.get_label(*bb);
+                let bb_offset = self.asm.get_dynamic_label_offset(label).unwrap().0;
+                dynasm!(self.asm; .dword bb_offset as i32 - table_offset as i32);
+            }
+        }
+    }
+
+    fn allocate_global_vars(&mut self, module: &MachineModule) {
+        let mut offsets = vec![];
+        let mut size = 0;
+        for (id, g) in &module.global_vars.arena {
+            let align = g.ty.align_in_byte(&module.types);
+            size += (align - size % align) % align;
+            offsets.push((id, size));
+            size += g.ty.size_in_byte(&module.types);
+        }
+
+        if offsets.is_empty() {
+            return;
+        }
+
+        // Globals are accessed with 32-bit absolute addresses, so they must be placed in the low
+        // 2GiB of the address space. (MAP_PRIVATE | MAP_ANONYMOUS | MAP_32BIT)
+        let memory = MemoryMap::new(
+            ::std::cmp::max(size, 1),
+            &[
+                MapOption::MapReadable,
+                MapOption::MapWritable,
+                MapOption::MapNonStandardFlags(0x02 | 0x20 | 0x40),
+            ],
+        )
+        .unwrap();
+
+        for (id, offset) in offsets {
+            let bytes = module.global_vars.arena[id].init_bytes(&module.types);
+            unsafe {
+                ::std::ptr::copy(bytes.as_ptr(), memory.data().add(offset), bytes.len());
+            }
+            self.global_vars
+                .insert(id, memory.data() as i32 + offset as i32);
+        }
+
+        // Resolve addresses in initializers now that every global variable has its place
+        for (id, g) in &module.global_vars.arena {
+            for reloc in g.relocations(&module.types) {
+                let at = (self.global_addr(id) as usize + reloc.offset) as *mut u8;
+                let addr = match &reloc.symbol {
+                    Symbol::Global(id) => self.global_addr(*id) as i64 + reloc.addend,
+                    Symbol::Function(name) => {
+                        let f_id = module.find_function_by_name(name).unwrap();
+                        if module.function_ref(f_id).is_internal {
+                            self.internal_function_addr(name) as i64 + reloc.addend
+                        } else {
+                            let label = self.get_label(f_id);
+                            self.function_relocs.push((at, label, reloc.addend));
+                            continue;
+                        }
+                    }
+                };
+                unsafe { ::std::ptr::write_unaligned(at as *mut i64, addr) };
+            }
+        }
+
+        self.global_memory = Some(memory);
+    }
+
+    fn internal_function_addr(&self, name: &str) -> u64 {
+        *self
+            .internal_functions
+            .get(name)
+            .unwrap_or_else(|| panic!("JIT cannot resolve external function '{}'", name))
+    }
+
+    fn global_addr(&self, id: GlobalVariableId) -> i32 {
+        *self.global_vars.get(&id).unwrap()
+    }
+
     fn compile_function(&mut self, module: &MachineModule, id: MachineFunctionId) {
         let f = module.function_ref(id);
 
@@ -222,6 +382,13 @@ impl JITCompiler {
             for inst in &*bb.iseq_ref() {
                 let inst = &f.body.inst_arena[*inst];
                 match inst.opcode {
+                    MachineOpcode::MOVri8 => self.compile_mov_ri8(inst),
+                    MachineOpcode::MOVrr8 => self.compile_mov_rr8(inst),
+                    MachineOpcode::MOVri16 => self.compile_mov_ri16(inst),
+                    MachineOpcode::MOVrr16 => self.compile_mov_rr16(inst),
+                    MachineOpcode::MOVrm16 => self.compile_mov_rm16(&frame_objects, inst),
+                    MachineOpcode::MOVmr16 => self.compile_mov_mr16(&frame_objects, inst),
+                    MachineOpcode::MOVmi16 => self.compile_mov_mi16(&frame_objects, inst),
                     MachineOpcode::MOVri32 => self.compile_mov_ri32(inst),
                     MachineOpcode::MOVrr32 => self.compile_mov_rr32(inst),
                     MachineOpcode::MOVri64 => self.compile_mov_ri64(inst),
@@ -231,16 +398,34 @@ impl JITCompiler {
                     MachineOpcode::MOVmr32 => self.compile_mov_mr32(&frame_objects, inst),
                     MachineOpcode::MOVmr64 => self.compile_mov_mr64(&frame_objects, inst),
                     MachineOpcode::MOVmi32 => self.compile_mov_mi32(&frame_objects, inst),
+                    MachineOpcode::MOVSXr32r8 => self.compile_movsx_r32r8(inst),
+                    MachineOpcode::MOVZXr32r8 => self.compile_movzx_r32r8(inst),
+                    MachineOpcode::MOVSXr32r16 => self.compile_movsx_r32r16(inst),
+                    MachineOpcode::MOVZXr32r16 => self.compile_movzx_r32r16(inst),
+                    MachineOpcode::MOVZXr64r8 => self.compile_movzx_r64r8(inst),
+                    MachineOpcode::MOVZXr64r32 => self.compile_movzx_r64r32(inst),
                     MachineOpcode::MOVSXDr64m32 => self.compile_movsxd_r64m32(&frame_objects, inst),
                     MachineOpcode::MOVSXDr64r32 => self.compile_movsxd_r64r32(inst),
                     MachineOpcode::MOVSDrm64 => self.compile_movsd_rm64(inst),
                     MachineOpcode::MOVSDrm => self.compile_movsd_rm(&frame_objects, inst),
                     MachineOpcode::MOVSDmr => self.compile_movsd_mr(&frame_objects, inst),
                     MachineOpcode::MOVSDrr => self.compile_movsd_rr(inst),
-                    MachineOpcode::LEAr64m => self.compile_lea_r64m(&frame_objects, inst),
+                    MachineOpcode::MOVSSrm32 => self.compile_movss_rm32(inst),
+                    MachineOpcode::MOVSSrm => self.compile_movss_rm(&frame_objects, inst),
+                    MachineOpcode::MOVSSmr => self.compile_movss_mr(&frame_objects, inst),
+                    MachineOpcode::CVTSS2SDrr => self.compile_cvtss2sd_rr(inst),
+                    MachineOpcode::CVTSD2SSrr => self.compile_cvtsd2ss_rr(inst),
+                    MachineOpcode::MOVQrx | MachineOpcode::MOVDrx => self.compile_mov_rx(inst),
+                    MachineOpcode::MOVQxr | MachineOpcode::MOVDxr => self.compile_mov_xr(inst),
+                    MachineOpcode::CVTSI2SSrr32 => self.compile_cvtsi2ss_rr32(inst),
+                    MachineOpcode::CVTTSS2SIr32r => self.compile_cvttss2si_r32r(inst),
+                    MachineOpcode::LEAr64m => self.compile_lea_r64m(module, &frame_objects, inst),
                     MachineOpcode::RET => self.compile_ret(),
+                    MachineOpcode::UD2 => dynasm!(self.asm; ud2),
                     MachineOpcode::PUSH64 => self.compile_push64(inst),
                     MachineOpcode::POP64 => self.compile_pop64(inst),
+                    MachineOpcode::ADDrr16 => self.compile_add_rr16(inst),
+                    MachineOpcode::ADDri16 => self.compile_add_ri16(inst),
                     MachineOpcode::ADDrr32 => self.compile_add_rr32(inst),
                     MachineOpcode::ADDmr32 => self.compile_add_mr32(&frame_objects, inst),
                     MachineOpcode::ADDmi32 => self.compile_add_mi32(&frame_objects, inst),
@@ -249,6 +434,9 @@ impl JITCompiler {
                     MachineOpcode::ADDr64i32 => self.compile_add_r64i32(inst),
                     MachineOpcode::ADDSDrr => self.compile_addsd_rr(inst),
                     MachineOpcode::ADDSDrm => self.compile_addsd_rm(&frame_objects, inst),
+                    MachineOpcode::ADDSSrr => self.compile_addss_rr(inst),
+                    MachineOpcode::SUBrr16 => self.compile_sub_rr16(inst),
+                    MachineOpcode::SUBri16 => self.compile_sub_ri16(inst),
                     MachineOpcode::SUBrr32 => self.compile_sub_rr32(inst),
                     MachineOpcode::SUBri32 => self.compile_sub_ri32(inst),
                     MachineOpcode::SUBmr32 => self.compile_sub_mr32(&frame_objects, inst),
@@ -256,22 +444,65 @@ impl JITCompiler {
                     MachineOpcode::SUBr64i32 => self.compile_sub_r64i32(inst),
                     MachineOpcode::SUBSDrr => self.compile_subsd_rr(inst),
                     MachineOpcode::SUBSDrm => self.compile_subsd_rm(&frame_objects, inst),
+                    MachineOpcode::SUBSSrr => self.compile_subss_rr(inst),
                     MachineOpcode::IMULrr32 => self.compile_imul_rr32(inst),
                     MachineOpcode::IMULrri32 => self.compile_imul_rri32(inst),
                     MachineOpcode::IMULrr64i32 => self.compile_imul_rr64i32(inst),
                     MachineOpcode::MULSDrr => self.compile_mulsd_rr(inst),
                     MachineOpcode::MULSDrm => self.compile_mulsd_rm(&frame_objects, inst),
+                    MachineOpcode::MULSSrr => self.compile_mulss_rr(inst),
                     MachineOpcode::DIVSDrr => self.compile_divsd_rr(inst),
                     MachineOpcode::DIVSDrm => self.compile_divsd_rm(&frame_objects, inst),
+                    MachineOpcode::DIVSSrr => self.compile_divss_rr(inst),
                     MachineOpcode::SQRTSDrr => self.compile_sqrtsd_rr(inst),
                     MachineOpcode::IDIV => self.compile_idiv(&frame_objects, inst),
+                    MachineOpcode::DIVr32 => self.compile_div_r32(inst),
+                    MachineOpcode::DIVr64 => self.compile_div_r64(inst),
                     MachineOpcode::CDQ => self.compile_cdq(&frame_objects, inst),
                     MachineOpcode::SHLr32i8 => self.compile_shl_r32i8(inst),
                     MachineOpcode::SHLr64i8 => self.compile_shl_r64i8(inst),
+                    MachineOpcode::SHLr8i8
+                    | MachineOpcode::SHLr8CL
+                    | MachineOpcode::SHLr32CL
+                    | MachineOpcode::SHLr64CL => self.compile_shl(inst),
+                    MachineOpcode::SHRr8i8
+                    | MachineOpcode::SHRr32i8
+                    | MachineOpcode::SHRr64i8
+                    | MachineOpcode::SHRr8CL
+                    | MachineOpcode::SHRr32CL
+                    | MachineOpcode::SHRr64CL => self.compile_shr(inst),
+                    MachineOpcode::SARr8i8
+                    | MachineOpcode::SARr32i8
+                    | MachineOpcode::SARr64i8
+                    | MachineOpcode::SARr8CL
+                    | MachineOpcode::SARr32CL
+                    | MachineOpcode::SARr64CL => self.compile_sar(inst),
+                    MachineOpcode::ANDrr8
+                    | MachineOpcode::ANDri8
+                    | MachineOpcode::ANDrr32
+                    | MachineOpcode::ANDri32
+                    | MachineOpcode::ANDrr64
+                    | MachineOpcode::ANDr64i32 => self.compile_and(inst),
+                    MachineOpcode::ORrr8
+                    | MachineOpcode::ORri8
+                    | MachineOpcode::ORrr32
+                    | MachineOpcode::ORri32
+                    | MachineOpcode::ORrr64
+                    | MachineOpcode::ORr64i32 => self.compile_or(inst),
+                    MachineOpcode::XORrr8
+                    | MachineOpcode::XORri8
+                    | MachineOpcode::XORrr32
+                    | MachineOpcode::XORri32
+                    | MachineOpcode::XORrr64
+                    | MachineOpcode::XORr64i32 => self.compile_xor(inst),
                     MachineOpcode::CALL => self.compile_call(module, &frame_objects, inst),
+                    MachineOpcode::CALLr64 | MachineOpcode::CALLm64 => {
+                        self.compile_call_indirect(&frame_objects, inst)
+                    }
                     MachineOpcode::CMPri => self.compile_cmp_ri(inst),
                     MachineOpcode::CMPrr => self.compile_cmp_rr(inst),
                     MachineOpcode::UCOMISDrr => self.compile_ucomisd_rr(inst),
+                    MachineOpcode::UCOMISSrr => self.compile_ucomiss_rr(inst),
                     MachineOpcode::JE => self.compile_je(inst),
                     MachineOpcode::JNE => self.compile_jne(inst),
                     MachineOpcode::JBE => self.compile_jbe(inst),
@@ -279,10 +510,30 @@ impl JITCompiler {
                     MachineOpcode::JLE => self.compile_jle(inst),
                     MachineOpcode::JL => self.compile_jl(inst),
                     MachineOpcode::JAE => self.compile_jae(inst),
+                    MachineOpcode::SETE
+                    | MachineOpcode::SETNE
+                    | MachineOpcode::SETL
+                    | MachineOpcode::SETLE
+                    | MachineOpcode::SETG
+                    | MachineOpcode::SETGE
+                    | MachineOpcode::SETB
+                    | MachineOpcode::SETBE
+                    | MachineOpcode::SETA
+                    | MachineOpcode::SETAE => self.compile_setcc(inst),
+                    MachineOpcode::CMOVErr
+                    | MachineOpcode::CMOVNErr
+                    | MachineOpcode::CMOVLrr
+                    | MachineOpcode::CMOVLErr
+                    | MachineOpcode::CMOVGrr
+                    | MachineOpcode::CMOVGErr
+                    | MachineOpcode::CMOVBrr
+                    | MachineOpcode::CMOVBErr
+                    | MachineOpcode::CMOVArr
+                    | MachineOpcode::CMOVAErr => self.compile_cmov_rr(inst),
                     MachineOpcode::JA => self.compile_ja(inst),
                     MachineOpcode::JG => self.compile_jg(inst),
                     MachineOpcode::JGE => self.compile_jge(inst),
-                    MachineOpcode::JMP => self.compile_jmp(inst),
+                    MachineOpcode::JMP | MachineOpcode::JMPr => self.compile_jmp(inst),
                     MachineOpcode::Ret => self.compile_return(&frame_objects, inst),
                     op => unimplemented!("{:?}", op),
                 }
@@ -341,6 +592,26 @@ impl JITCompiler {
                 let i2 = *off;
                 dynasm!(self.asm; mov Rd(r0), DWORD [Rq(r1) + i2]);
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m1 = self.global_addr(*id);
+                dynasm!(self.asm; mov Rd(r0), DWORD [m1]);
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressOff(AddressKind::Global(id), off)) => {
+                let m1 = self.global_addr(*id) + *off;
+                dynasm!(self.asm; mov Rd(r0), DWORD [m1]);
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressAlignOff(
+                AddressKind::Global(id),
+                align,
+                off,
+            )) => {
+                let m1 = self.global_addr(*id);
+                let r2 = phys_reg_to_dynasm_reg(off.id.as_phys_reg());
+                match align {
+                    4 => dynasm!(self.asm; mov Rd(r0), DWORD [m1 + 4*Rq(r2)]),
+                    _ => unimplemented!(),
+                }
+            }
             e => panic!("{:?}", e),
         }
     }
@@ -449,6 +720,29 @@ impl JITCompiler {
                 let r2 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
                 dynasm!(self.asm; mov DWORD [Rq(r0) + i1], Rd(r2))
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; mov DWORD [m0], Rd(r1));
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressOff(AddressKind::Global(id), off)) => {
+                let m0 = self.global_addr(*id) + *off;
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; mov DWORD [m0], Rd(r1));
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressAlignOff(
+                AddressKind::Global(id),
+                align,
+                off,
+            )) => {
+                let m0 = self.global_addr(*id);
+                let r1 = phys_reg_to_dynasm_reg(off.id.as_phys_reg());
+                let r2 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                match align {
+                    4 => dynasm!(self.asm; mov DWORD [m0 + 4*Rq(r1)], Rd(r2)),
+                    _ => unimplemented!(),
+                }
+            }
             e => panic!("{:?}", e),
         }
     }
@@ -511,10 +805,132 @@ impl JITCompiler {
                 let i2 = inst.operand[1].as_constant().as_i32();
                 dynasm!(self.asm; mov DWORD [Rq(r0) + i1], i2)
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; mov DWORD [m0], i1);
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressOff(AddressKind::Global(id), off)) => {
+                let m0 = self.global_addr(*id) + *off;
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; mov DWORD [m0], i1);
+            }
+            MachineOperand::Mem(MachineMemOperand::AddressAlignOff(
+                AddressKind::Global(id),
+                align,
+                off,
+            )) => {
+                let m0 = self.global_addr(*id);
+                let r1 = phys_reg_to_dynasm_reg(off.id.as_phys_reg());
+                let i2 = inst.operand[1].as_constant().as_i32();
+                match align {
+                    4 => dynasm!(self.asm; mov DWORD [m0 + 4*Rq(r1)], i2),
+                    _ => unimplemented!(),
+                }
+            }
             e => panic!("{:?}", e),
         }
     }
 
+    fn compile_mov_ri8(&mut self, inst: &MachineInst) {
+        let r = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let i = inst.operand[0].as_constant().as_i8();
+        dynasm!(self.asm; mov Rb(r), i);
+    }
+
+    fn compile_mov_rr8(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        self.reg_copy(RegisterClassKind::GR8, r0, r1);
+    }
+
+    fn compile_mov_ri16(&mut self, inst: &MachineInst) {
+        let r = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let i = inst.operand[0].as_constant().as_i16();
+        dynasm!(self.asm; mov Rw(r), i);
+    }
+
+    fn compile_mov_rr16(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        self.reg_copy(RegisterClassKind::GR16, r0, r1);
+    }
+
+    fn compile_mov_rm16(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m2 = fi.idx;
+                dynasm!(self.asm; mov Rw(r0), WORD [Rq(r1) + fo.offset(m2).unwrap()]);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseFiOff(base, fi, off)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m2 = fi.idx;
+                dynasm!(self.asm; mov Rw(r0), WORD [Rq(r1) + fo.offset(m2).unwrap() + off]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov Rw(r0), WORD [Rq(r1)]);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseOff(base, off)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov Rw(r0), WORD [Rq(r1) + *off]);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_mov_mr16(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        let r2 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; mov WORD [Rq(r0) + m1], Rw(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseFiOff(base, fi, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; mov WORD [Rq(r0) + m1 + off], Rw(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov WORD [Rq(r0)], Rw(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseOff(base, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov WORD [Rq(r0) + *off], Rw(r2));
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_mov_mi16(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        let i2 = inst.operand[1].as_constant().as_i16();
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; mov WORD [Rq(r0) + m1], i2);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseFiOff(base, fi, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; mov WORD [Rq(r0) + m1 + off], i2);
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov WORD [Rq(r0)], i2);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseOff(base, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; mov WORD [Rq(r0) + *off], i2);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
     fn compile_mov_ri32(&mut self, inst: &MachineInst) {
         assert!(matches!(inst.operand[0], MachineOperand::Constant(_)));
         assert!(matches!(
@@ -533,17 +949,15 @@ impl JITCompiler {
         self.reg_copy(RegisterClassKind::GR32, r0, r1);
     }
 
-    fn compile_mov_ri64(&mut self, _inst: &MachineInst) {
-        unimplemented!()
-        // assert!(matches!(inst.operand[0], MachineOperand::Constant(_)));
-        // assert!(matches!(
-        //     inst.operand[0].as_constant(),
-        //     MachineConstant::Int64(_)
-        // ));
-        //
-        // let r = inst.def[0].id.as_phys_reg().get() as u8;
-        // let i = inst.operand[0].as_constant().as_i64();
-        // dynasm!(self.asm; mov Ra(r), i);
+    fn compile_mov_ri64(&mut self, inst: &MachineInst) {
+        assert!(matches!(inst.operand[0], MachineOperand::Constant(_)));
+        assert!(matches!(
+            inst.operand[0].as_constant(),
+            MachineConstant::Int64(_)
+        ));
+        let r = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let i = inst.operand[0].as_constant().as_i64();
+        dynasm!(self.asm; mov Rq(r), QWORD i);
     }
 
     fn compile_mov_mr64(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
@@ -593,6 +1007,11 @@ impl JITCompiler {
                 let r2 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
                 dynasm!(self.asm; mov QWORD [Rq(r0) + i1], Rq(r2))
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; mov QWORD [m0], Rq(r1));
+            }
             e => panic!("{:?}", e),
         }
     }
@@ -670,11 +1089,128 @@ impl JITCompiler {
                 let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
                 dynasm!(self.asm; movsd [Rq(r0) + *off], Rx(r1));
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; movsd [m0], Rx(r1));
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_movss_rm32(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Label(id))) => {
+                let l1 = self.get_label(*id);
+                dynasm!(self.asm; movss Rx(r0), [=>l1]);
+            }
             _ => unimplemented!(),
         }
     }
 
-    fn compile_lea_r64m(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+    fn compile_movss_rm(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m2 = fi.idx;
+                dynasm!(self.asm; movss Rx(r0), [Rq(r1) + fo.offset(m2).unwrap()]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; movss Rx(r0), [Rq(r1)]);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseFiOff(base, fi, off)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m2 = fi.idx;
+                dynasm!(self.asm; movss Rx(r0), [Rq(r1) + fo.offset(m2).unwrap() + off]);
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseOff(base, off)) => {
+                let r1 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; movss Rx(r0), [Rq(r1) + *off]);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_movss_mr(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        let r2 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        match &inst.operand[0] {
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; movss [Rq(r0) + m1], Rx(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; movss [Rq(r0)], Rx(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseFiOff(base, fi, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; movss [Rq(r0) + m1 + off], Rx(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::BaseOff(base, off)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; movss [Rq(r0) + *off], Rx(r2));
+            }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                dynasm!(self.asm; movss [m0], Rx(r2));
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_cvtss2sd_rr(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; cvtss2sd Rx(r0), Rx(r1));
+    }
+
+    fn compile_cvtsd2ss_rr(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; cvtsd2ss Rx(r0), Rx(r1));
+    }
+
+    fn compile_mov_rx(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::MOVDrx => dynasm!(self.asm; movd Rd(r0), Rx(r1)),
+            _ => dynasm!(self.asm; movq Rq(r0), Rx(r1)),
+        }
+    }
+
+    fn compile_mov_xr(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::MOVDxr => dynasm!(self.asm; movd Rx(r0), Rd(r1)),
+            _ => dynasm!(self.asm; movq Rx(r0), Rq(r1)),
+        }
+    }
+
+    fn compile_cvtsi2ss_rr32(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; cvtsi2ss Rx(r0), Rd(r1));
+    }
+
+    fn compile_cvttss2si_r32r(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; cvttss2si Rd(r0), Rx(r1));
+    }
+
+    fn compile_lea_r64m(
+        &mut self,
+        module: &MachineModule,
+        fo: &FrameObjectsInfo,
+        inst: &MachineInst,
+    ) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         match &inst.operand[0] {
             MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
@@ -728,6 +1264,25 @@ impl JITCompiler {
                 let i2 = *off;
                 dynasm!(self.asm; lea Rq(r0),[Rq(r1) + i2]);
             }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m1 = self.global_addr(*id);
+                dynasm!(self.asm; lea Rq(r0), [m1]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Label(id))) => {
+                let l1 = self.get_label(*id);
+                dynasm!(self.asm; lea Rq(r0), [=>l1]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::FunctionName(n))) => {
+                let id = module.find_function_by_name(n.as_str()).unwrap();
+                let f = module.function_ref(id);
+                if f.is_internal {
+                    let addr = self.internal_function_addr(&f.name);
+                    dynasm!(self.asm; mov Rq(r0), QWORD addr as _);
+                } else {
+                    let l1 = self.get_label(id);
+                    dynasm!(self.asm; lea Rq(r0), [=>l1]);
+                }
+            }
             _ => panic!(),
         }
     }
@@ -767,8 +1322,16 @@ impl JITCompiler {
             .reg_class()
             .size_in_bits();
         let r0 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
-        let i1 = inst.operand[1].as_constant().as_i32();
+        let i1 = match inst.operand[1].as_constant() {
+            MachineConstant::Int8(i) => *i as i32,
+            MachineConstant::Int16(i) => *i as i32,
+            // cmp sign-extends imm32
+            MachineConstant::Int64(i) if *i as i32 as i64 == *i => *i as i32,
+            c => c.as_i32(),
+        };
         match bits {
+            8 => dynasm!(self.asm; cmp Rb(r0), i1 as i8),
+            16 => dynasm!(self.asm; cmp Rw(r0), i1 as i16),
             32 => dynasm!(self.asm; cmp Rd(r0), i1),
             64 => dynasm!(self.asm; cmp Rq(r0), i1),
             _ => unimplemented!(),
@@ -785,6 +1348,8 @@ impl JITCompiler {
         let r0 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
         let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
         match bits {
+            8 => dynasm!(self.asm; cmp Rb(r0), Rb(r1)),
+            16 => dynasm!(self.asm; cmp Rw(r0), Rw(r1)),
             32 => dynasm!(self.asm; cmp Rd(r0), Rd(r1)),
             64 => dynasm!(self.asm; cmp Rq(r0), Rq(r1)),
             _ => unimplemented!(),
@@ -797,6 +1362,59 @@ impl JITCompiler {
         dynasm!(self.asm; ucomisd Rx(r0), Rx(r1));
     }
 
+    fn compile_ucomiss_rr(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; ucomiss Rx(r0), Rx(r1));
+    }
+
+    fn compile_cmov_rr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let bits = inst.def[0].id.as_phys_reg().reg_class().size_in_bits();
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        macro_rules! cmov {
+            ($op:ident) => {
+                match bits {
+                    16 => dynasm!(self.asm; $op Rw(r0), Rw(r1)),
+                    32 => dynasm!(self.asm; $op Rd(r0), Rd(r1)),
+                    64 => dynasm!(self.asm; $op Rq(r0), Rq(r1)),
+                    _ => unimplemented!(),
+                }
+            };
+        }
+        match inst.opcode {
+            MachineOpcode::CMOVErr => cmov!(cmove),
+            MachineOpcode::CMOVNErr => cmov!(cmovne),
+            MachineOpcode::CMOVLrr => cmov!(cmovl),
+            MachineOpcode::CMOVLErr => cmov!(cmovle),
+            MachineOpcode::CMOVGrr => cmov!(cmovg),
+            MachineOpcode::CMOVGErr => cmov!(cmovge),
+            MachineOpcode::CMOVBrr => cmov!(cmovb),
+            MachineOpcode::CMOVBErr => cmov!(cmovbe),
+            MachineOpcode::CMOVArr => cmov!(cmova),
+            MachineOpcode::CMOVAErr => cmov!(cmovae),
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_setcc(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::SETE => dynasm!(self.asm; sete Rb(r0)),
+            MachineOpcode::SETNE => dynasm!(self.asm; setne Rb(r0)),
+            MachineOpcode::SETL => dynasm!(self.asm; setl Rb(r0)),
+            MachineOpcode::SETLE => dynasm!(self.asm; setle Rb(r0)),
+            MachineOpcode::SETG => dynasm!(self.asm; setg Rb(r0)),
+            MachineOpcode::SETGE => dynasm!(self.asm; setge Rb(r0)),
+            MachineOpcode::SETB => dynasm!(self.asm; setb Rb(r0)),
+            MachineOpcode::SETBE => dynasm!(self.asm; setbe Rb(r0)),
+            MachineOpcode::SETA => dynasm!(self.asm; seta Rb(r0)),
+            MachineOpcode::SETAE => dynasm!(self.asm; setae Rb(r0)),
+            _ => unreachable!(),
+        }
+    }
+
     fn compile_je(&mut self, inst: &MachineInst) {
         let l = self.get_label(inst.operand[0].as_basic_block());
         dynasm!(self.asm; je => l);
@@ -859,6 +1477,43 @@ impl JITCompiler {
         }
     }
 
+    fn compile_movsx_r32r8(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; movsx Rd(r0), Rb(r1));
+    }
+
+    fn compile_movzx_r32r8(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; movzx Rd(r0), Rb(r1));
+    }
+
+    fn compile_movsx_r32r16(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; movsx Rd(r0), Rw(r1));
+    }
+
+    fn compile_movzx_r32r16(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; movzx Rd(r0), Rw(r1));
+    }
+
+    fn compile_movzx_r64r8(&mut self, inst: &MachineInst) {
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; movzx Rq(r0), Rb(r1));
+    }
+
+    fn compile_movzx_r64r32(&mut self, inst: &MachineInst) {
+        // Unlike reg_copy, this must be emitted even if r0 == r1 to clear the upper half
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; mov Rd(r0), Rd(r1));
+    }
+
     fn compile_movsxd_r64r32(&mut self, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         let r1 = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
@@ -877,9 +1532,9 @@ impl JITCompiler {
         let callee_entity = module.function_ref(callee_id);
 
         if callee_entity.is_internal {
-            let callee = self.internal_functions.get(&callee_entity.name).unwrap();
+            let callee = self.internal_function_addr(&callee_entity.name);
             dynasm!(self.asm
-                ; mov rax, QWORD *callee as _
+                ; mov rax, QWORD callee as _
                 ; call rax
             );
         } else {
@@ -888,6 +1543,40 @@ impl JITCompiler {
         }
     }
 
+    fn compile_call_indirect(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
+        match &inst.operand[0] {
+            MachineOperand::Register(r) => dynasm!(self.asm; call Rq(register!(r.id))),
+            MachineOperand::Mem(MachineMemOperand::BaseFi(base, fi)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                let m1 = fo.offset(fi.idx).unwrap();
+                dynasm!(self.asm; call QWORD [Rq(r0) + m1]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Base(base)) => {
+                let r0 = phys_reg_to_dynasm_reg(base.id.as_phys_reg());
+                dynasm!(self.asm; call QWORD [Rq(r0)]);
+            }
+            MachineOperand::Mem(MachineMemOperand::Address(AddressKind::Global(id))) => {
+                let m0 = self.global_addr(*id);
+                dynasm!(self.asm; call QWORD [m0]);
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn compile_add_rr16(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; add Rw(r0), Rw(r1));
+    }
+
+    fn compile_add_ri16(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let i1 = inst.operand[1].as_constant().as_i16();
+        dynasm!(self.asm; add Rw(r0), i1);
+    }
+
     fn compile_add_rr32(&mut self, inst: &MachineInst) {
         // inst.operand[0] must be the same as inst.def[0].id (they're tied)
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
@@ -1047,6 +1736,13 @@ impl JITCompiler {
         dynasm!(self.asm; addsd Rx(r0), Rx(r1));
     }
 
+    fn compile_addss_rr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; addss Rx(r0), Rx(r1));
+    }
+
     fn compile_addsd_rm(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         match &inst.operand[1] {
@@ -1059,6 +1755,20 @@ impl JITCompiler {
         }
     }
 
+    fn compile_sub_rr16(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; sub Rw(r0), Rw(r1));
+    }
+
+    fn compile_sub_ri16(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let i1 = inst.operand[1].as_constant().as_i16();
+        dynasm!(self.asm; sub Rw(r0), i1);
+    }
+
     fn compile_sub_rr32(&mut self, inst: &MachineInst) {
         // inst.operand[0] must be the same as inst.def[0].id (they're tied)
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
@@ -1211,6 +1921,13 @@ impl JITCompiler {
         dynasm!(self.asm; subsd Rx(r0), Rx(r1));
     }
 
+    fn compile_subss_rr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; subss Rx(r0), Rx(r1));
+    }
+
     fn compile_subsd_rm(&mut self, _fo: &FrameObjectsInfo, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         match &inst.operand[1] {
@@ -1249,6 +1966,13 @@ impl JITCompiler {
         dynasm!(self.asm; mulsd Rx(r0), Rx(r1));
     }
 
+    fn compile_mulss_rr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; mulss Rx(r0), Rx(r1));
+    }
+
     fn compile_mulsd_rm(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         match &inst.operand[1] {
@@ -1271,6 +1995,13 @@ impl JITCompiler {
         dynasm!(self.asm; divsd Rx(r0), Rx(r1));
     }
 
+    fn compile_divss_rr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+        dynasm!(self.asm; divss Rx(r0), Rx(r1));
+    }
+
     fn compile_divsd_rm(&mut self, fo: &FrameObjectsInfo, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         match &inst.operand[1] {
@@ -1301,6 +2032,16 @@ impl JITCompiler {
         dynasm!(self.asm; idiv Rd(r)) // TODO: for Rq
     }
 
+    fn compile_div_r32(&mut self, inst: &MachineInst) {
+        let r = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; div Rd(r))
+    }
+
+    fn compile_div_r64(&mut self, inst: &MachineInst) {
+        let r = phys_reg_to_dynasm_reg(inst.operand[0].as_register().id.as_phys_reg());
+        dynasm!(self.asm; div Rq(r))
+    }
+
     fn compile_shl_r32i8(&mut self, inst: &MachineInst) {
         let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
         let i1 = inst.operand[1].as_constant().as_i8();
@@ -1313,12 +2054,163 @@ impl JITCompiler {
         dynasm!(self.asm; shl Rq(r0), i1);
     }
 
+    fn compile_shl(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::SHLr8i8 => {
+                dynasm!(self.asm; shl Rb(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SHLr8CL => dynasm!(self.asm; shl Rb(r0), cl),
+            MachineOpcode::SHLr32CL => dynasm!(self.asm; shl Rd(r0), cl),
+            MachineOpcode::SHLr64CL => dynasm!(self.asm; shl Rq(r0), cl),
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_shr(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::SHRr8i8 => {
+                dynasm!(self.asm; shr Rb(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SHRr32i8 => {
+                dynasm!(self.asm; shr Rd(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SHRr64i8 => {
+                dynasm!(self.asm; shr Rq(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SHRr8CL => dynasm!(self.asm; shr Rb(r0), cl),
+            MachineOpcode::SHRr32CL => dynasm!(self.asm; shr Rd(r0), cl),
+            MachineOpcode::SHRr64CL => dynasm!(self.asm; shr Rq(r0), cl),
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_sar(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::SARr8i8 => {
+                dynasm!(self.asm; sar Rb(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SARr32i8 => {
+                dynasm!(self.asm; sar Rd(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SARr64i8 => {
+                dynasm!(self.asm; sar Rq(r0), inst.operand[1].as_constant().as_i8())
+            }
+            MachineOpcode::SARr8CL => dynasm!(self.asm; sar Rb(r0), cl),
+            MachineOpcode::SARr32CL => dynasm!(self.asm; sar Rd(r0), cl),
+            MachineOpcode::SARr64CL => dynasm!(self.asm; sar Rq(r0), cl),
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_and(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::ANDrr8 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; and Rb(r0), Rb(r1))
+            }
+            MachineOpcode::ANDrr32 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; and Rd(r0), Rd(r1))
+            }
+            MachineOpcode::ANDrr64 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; and Rq(r0), Rq(r1))
+            }
+            MachineOpcode::ANDri8 => {
+                let i1 = inst.operand[1].as_constant().as_i8();
+                dynasm!(self.asm; and Rb(r0), i1)
+            }
+            MachineOpcode::ANDri32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; and Rd(r0), i1)
+            }
+            MachineOpcode::ANDr64i32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; and Rq(r0), i1)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_or(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::ORrr8 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; or Rb(r0), Rb(r1))
+            }
+            MachineOpcode::ORrr32 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; or Rd(r0), Rd(r1))
+            }
+            MachineOpcode::ORrr64 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; or Rq(r0), Rq(r1))
+            }
+            MachineOpcode::ORri8 => {
+                let i1 = inst.operand[1].as_constant().as_i8();
+                dynasm!(self.asm; or Rb(r0), i1)
+            }
+            MachineOpcode::ORri32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; or Rd(r0), i1)
+            }
+            MachineOpcode::ORr64i32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; or Rq(r0), i1)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_xor(&mut self, inst: &MachineInst) {
+        // inst.operand[0] must be the same as inst.def[0].id (they're tied)
+        let r0 = phys_reg_to_dynasm_reg(inst.def[0].id.as_phys_reg());
+        match inst.opcode {
+            MachineOpcode::XORrr8 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; xor Rb(r0), Rb(r1))
+            }
+            MachineOpcode::XORrr32 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; xor Rd(r0), Rd(r1))
+            }
+            MachineOpcode::XORrr64 => {
+                let r1 = phys_reg_to_dynasm_reg(inst.operand[1].as_register().id.as_phys_reg());
+                dynasm!(self.asm; xor Rq(r0), Rq(r1))
+            }
+            MachineOpcode::XORri8 => {
+                let i1 = inst.operand[1].as_constant().as_i8();
+                dynasm!(self.asm; xor Rb(r0), i1)
+            }
+            MachineOpcode::XORri32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; xor Rd(r0), i1)
+            }
+            MachineOpcode::XORr64i32 => {
+                let i1 = inst.operand[1].as_constant().as_i32();
+                dynasm!(self.asm; xor Rq(r0), i1)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     fn compile_jmp(&mut self, inst: &MachineInst) {
         match &inst.operand[0] {
             MachineOperand::Branch(bb) => {
                 let label = self.get_label(*bb);
                 dynasm!(self.asm; jmp =>label)
             }
+            MachineOperand::Register(r) => dynasm!(self.asm; jmp Rq(register!(r.id))),
             _ => unimplemented!(),
         }
     }
@@ -1350,6 +2242,8 @@ impl JITCompiler {
         }
 
         match rc {
+            RegisterClassKind::GR8 => dynasm!(self.asm; mov Rb(r0), Rb(r1)),
+            RegisterClassKind::GR16 => dynasm!(self.asm; mov Rw(r0), Rw(r1)),
             RegisterClassKind::GR32 => dynasm!(self.asm; mov Rd(r0), Rd(r1)),
             RegisterClassKind::GR64 => dynasm!(self.asm; mov Rq(r0), Rq(r1)),
             _ => unimplemented!(),
@@ -1476,6 +2370,12 @@ pub extern "C" fn cilk_malloc_i32_(i: i32) -> *mut i64 {
     Box::into_raw(vec.into_boxed_slice()) as *mut i64
 }
 
+// Internal function cilk.trap
+#[no_mangle]
+pub extern "C" fn cilk_trap_() {
+    ::std::process::abort()
+}
+
 // EXPERIMENTAL Internal function cilk.memset.p0i32.i32
 #[no_mangle]
 pub extern "C" fn cilk_memset_p0i32_i32_(p: *mut i32, x: i32, count: i32) {
@@ -1,11 +1,15 @@
 use crate::{
-    codegen::{arch::machine::register::*, common::machine::calling_conv::CallingConv},
+    codegen::{
+        arch::machine::register::*,
+        common::machine::calling_conv::{ArgumentRegisterOrder, CallingConv},
+    },
     ir::types::{StructType, Type},
 };
 
 #[derive(Clone)]
 pub struct SystemV {
     gr8: Vec<PhysReg>,
+    gr16: Vec<PhysReg>,
     gr32: Vec<PhysReg>,
     gr64: Vec<PhysReg>,
     xmm: Vec<PhysReg>,
@@ -16,6 +20,7 @@ impl SystemV {
     pub fn new() -> Self {
         Self {
             gr8:  to_phys![GR8::DIL,  GR8::SIL,  GR8::DL,   GR8::CL,   GR8::R8B,  GR8::R9B ],
+            gr16: to_phys![GR16::DI,  GR16::SI,  GR16::DX,  GR16::CX,  GR16::R8W, GR16::R9W],
             gr32: to_phys![GR32::EDI, GR32::ESI, GR32::EDX, GR32::ECX, GR32::R8D, GR32::R9D],
             gr64: to_phys![GR64::RDI, GR64::RSI, GR64::RDX, GR64::RCX, GR64::R8,  GR64::R9 ],
             xmm:  to_phys![XMM::XMM0, XMM::XMM1, XMM::XMM2, XMM::XMM3, XMM::XMM4, XMM::XMM5, XMM::XMM6, XMM::XMM7],
@@ -23,10 +28,21 @@ impl SystemV {
     }
 }
 
+impl SystemV {
+    /// Returns the value passed in AL when calling a variadic function: an upper bound on the
+    /// number of vector registers used for the arguments
+    pub fn num_vector_regs_used(&self, order: &ArgumentRegisterOrder<Self>) -> usize {
+        order
+            .num_assigned(RegisterClassKind::XMM)
+            .min(self.xmm.len())
+    }
+}
+
 impl CallingConv for SystemV {
     fn get_nth_arg_reg(&self, rc: RegisterClassKind, nth: usize) -> Option<PhysReg> {
         match rc {
             RegisterClassKind::GR8 => self.gr8.get(nth),
+            RegisterClassKind::GR16 => self.gr16.get(nth),
             RegisterClassKind::GR32 => self.gr32.get(nth),
             RegisterClassKind::GR64 => self.gr64.get(nth),
             RegisterClassKind::XMM => self.xmm.get(nth),
@@ -49,7 +65,13 @@ impl CallingConv for SystemV {
                 (moves_by_4_bytes, 4, RegisterClassKind::GR32),
             ] {
                 for _ in 0..count {
-                    let float = struct_ty.get_type_at(off) == Some(&Type::f64);
+                    let float = match struct_ty.get_type_at(off) {
+                        Some(Type::f64) => true,
+                        Some(Type::f32) => {
+                            size == 4 || struct_ty.get_type_at(off + 4) == Some(&Type::f32)
+                        }
+                        _ => false,
+                    };
                     regs.push(if float { RegisterClassKind::XMM } else { rc });
                     off += size;
                 }
@@ -28,6 +28,14 @@ impl MachineOpcode {
                 | MachineOpcode::Copy
                 | MachineOpcode::MOVSDrr
                 | MachineOpcode::MOVrr8
+                | MachineOpcode::MOVrr16
+        )
+    }
+
+    pub fn is_call(&self) -> bool {
+        matches!(
+            self,
+            MachineOpcode::CALL | MachineOpcode::CALLr64 | MachineOpcode::CALLm64
         )
     }
 
@@ -37,6 +45,7 @@ impl MachineOpcode {
             MachineOpcode::Ret
                 | MachineOpcode::RET
                 | MachineOpcode::JMP
+                | MachineOpcode::JMPr
                 | MachineOpcode::BrCond
                 | MachineOpcode::JE
                 | MachineOpcode::JNE
@@ -30,11 +30,52 @@ mod inst {
                 TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
             ])
         };
+        pub static ref MOVSSrm32: TargetInstDef = {
+            TargetInstDef::new("movss", TargetOpcode::MOVSSrm32)
+                .set_uses(vec![TargetOperand::Mem])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref MOVSSrm: TargetInstDef = {
+            TargetInstDef::new("movss", TargetOpcode::MOVSSrm)
+                .set_uses(vec![TargetOperand::Mem])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref MOVSSmr: TargetInstDef = {
+            TargetInstDef::new("movss", TargetOpcode::MOVSSmr).set_uses(vec![
+                TargetOperand::Mem,
+                TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+            ])
+        };
         pub static ref MOVSXr32r8: TargetInstDef = {
             TargetInstDef::new("movsx", TargetOpcode::MOVSXr32r8)
                 .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8))])
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
         };
+        pub static ref MOVZXr32r8: TargetInstDef = {
+            TargetInstDef::new("movzx", TargetOpcode::MOVZXr32r8)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
+        pub static ref MOVSXr32r16: TargetInstDef = {
+            TargetInstDef::new("movsx", TargetOpcode::MOVSXr32r16)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
+        pub static ref MOVZXr32r16: TargetInstDef = {
+            TargetInstDef::new("movzx", TargetOpcode::MOVZXr32r16)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
+        pub static ref MOVZXr64r8: TargetInstDef = {
+            TargetInstDef::new("movzx", TargetOpcode::MOVZXr64r8)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+        };
+        pub static ref MOVZXr64r32: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVZXr64r32)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
         pub static ref MOVSXDr64m32: TargetInstDef = {
             TargetInstDef::new("movsxd", TargetOpcode::MOVSXDr64m32)
                 .set_uses(vec![TargetOperand::FrameIndex])
@@ -68,6 +109,24 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref ADDrr16: TargetInstDef = {
+            TargetInstDef::new("add", TargetOpcode::ADDrr16)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ADDri16: TargetInstDef = {
+            TargetInstDef::new("add", TargetOpcode::ADDri16)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                    TargetOperand::Immediate(TargetImmediate::I16),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref ADDrr32: TargetInstDef = {
             TargetInstDef::new("add", TargetOpcode::ADDrr32)
                 .set_uses(vec![
@@ -129,6 +188,15 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref ADDSSrr: TargetInstDef = {
+            TargetInstDef::new("addss", TargetOpcode::ADDSSrr)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref ADDSDrm: TargetInstDef = {
             TargetInstDef::new("addsd", TargetOpcode::ADDSDrm)
                 .set_uses(vec![
@@ -156,6 +224,24 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref SUBrr16: TargetInstDef = {
+            TargetInstDef::new("sub", TargetOpcode::SUBrr16)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SUBri16: TargetInstDef = {
+            TargetInstDef::new("sub", TargetOpcode::SUBri16)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+                    TargetOperand::Immediate(TargetImmediate::I16),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref SUBrr32: TargetInstDef = {
             TargetInstDef::new("sub", TargetOpcode::SUBrr32)
                 .set_uses(vec![
@@ -208,6 +294,15 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref SUBSSrr: TargetInstDef = {
+            TargetInstDef::new("subss", TargetOpcode::SUBSSrr)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref SUBSDrm: TargetInstDef = {
             TargetInstDef::new("subsd", TargetOpcode::SUBSDrm)
                 .set_uses(vec![
@@ -269,6 +364,15 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref MULSSrr: TargetInstDef = {
+            TargetInstDef::new("mulss", TargetOpcode::MULSSrr)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref MULSDrm: TargetInstDef = {
             TargetInstDef::new("mulsd", TargetOpcode::MULSDrm)
                 .set_uses(vec![
@@ -287,6 +391,15 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref DIVSSrr: TargetInstDef = {
+            TargetInstDef::new("divss", TargetOpcode::DIVSSrr)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref DIVSDrm: TargetInstDef = {
             TargetInstDef::new("divsd", TargetOpcode::DIVSDrm)
                 .set_uses(vec![
@@ -314,6 +427,312 @@ mod inst {
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
                 .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
         };
+        pub static ref ANDrr8: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDrr8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ANDri8: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDri8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ANDrr32: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDrr32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ANDri32: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDri32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ANDrr64: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDrr64)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ANDr64i32: TargetInstDef = {
+            TargetInstDef::new("and", TargetOpcode::ANDr64i32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORrr8: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORrr8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORri8: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORri8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORrr32: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORrr32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORri32: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORri32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORrr64: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORrr64)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref ORr64i32: TargetInstDef = {
+            TargetInstDef::new("or", TargetOpcode::ORr64i32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORrr8: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORrr8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORri8: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORri8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORrr32: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORrr32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORri32: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORri32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORrr64: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORrr64)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref XORr64i32: TargetInstDef = {
+            TargetInstDef::new("xor", TargetOpcode::XORr64i32)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Immediate(TargetImmediate::I32),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHLr8i8: TargetInstDef = {
+            TargetInstDef::new("shl", TargetOpcode::SHLr8i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHLr8CL: TargetInstDef = {
+            TargetInstDef::new("shl", TargetOpcode::SHLr8CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHLr32CL: TargetInstDef = {
+            TargetInstDef::new("shl", TargetOpcode::SHLr32CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHLr64CL: TargetInstDef = {
+            TargetInstDef::new("shl", TargetOpcode::SHLr64CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr8i8: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr8i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr32i8: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr32i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr64i8: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr64i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr8CL: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr8CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr32CL: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr32CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SHRr64CL: TargetInstDef = {
+            TargetInstDef::new("shr", TargetOpcode::SHRr64CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr8i8: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr8i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr32i8: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr32i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr64i8: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr64i8)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Immediate(TargetImmediate::I8),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr8CL: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr8CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr32CL: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr32CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref SARr64CL: TargetInstDef = {
+            TargetInstDef::new("sar", TargetOpcode::SARr64CL)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64)),
+                    TargetOperand::Register(TargetRegister::Specific(GR8::CL.as_phys_reg())),
+                ])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref SQRTSDrr: TargetInstDef = {
             TargetInstDef::new("sqrtsd", TargetOpcode::SQRTSDrr)
                 .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(
@@ -337,6 +756,26 @@ mod inst {
                 .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
                 .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
         };
+        pub static ref CVTSI2SSrr32: TargetInstDef = {
+            TargetInstDef::new("cvtsi2ss", TargetOpcode::CVTSI2SSrr32)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref CVTTSS2SIr32r: TargetInstDef = {
+            TargetInstDef::new("cvttss2si", TargetOpcode::CVTTSS2SIr32r)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
+        pub static ref CVTSS2SDrr: TargetInstDef = {
+            TargetInstDef::new("cvtss2sd", TargetOpcode::CVTSS2SDrr)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref CVTSD2SSrr: TargetInstDef = {
+            TargetInstDef::new("cvtsd2ss", TargetOpcode::CVTSD2SSrr)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
         pub static ref MOVrr8: TargetInstDef = {
             TargetInstDef::new("mov", TargetOpcode::MOVrr8)
                 .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8))])
@@ -376,6 +815,33 @@ mod inst {
                 TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR8)),
             ])
         };
+        pub static ref MOVrr16: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVrr16)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+        };
+        pub static ref MOVri16: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVri16)
+                .set_uses(vec![TargetOperand::Immediate(TargetImmediate::I16)])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+        };
+        pub static ref MOVrm16: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVrm16)
+                .set_uses(vec![TargetOperand::Mem])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR16)])
+        };
+        pub static ref MOVmi16: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVmi16).set_uses(vec![
+                TargetOperand::Mem,
+                TargetOperand::Immediate(TargetImmediate::I16),
+            ])
+        };
+        pub static ref MOVmr16: TargetInstDef = {
+            TargetInstDef::new("mov", TargetOpcode::MOVmr16).set_uses(vec![
+                TargetOperand::Mem,
+                TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR16)),
+            ])
+        };
         pub static ref MOVrm32: TargetInstDef = {
             TargetInstDef::new("mov", TargetOpcode::MOVrm32)
                 .set_uses(vec![TargetOperand::Mem])
@@ -436,6 +902,34 @@ mod inst {
                     TargetRegister::Specific(GR32::EDX.as_phys_reg()),
                 ])
         };
+        pub static ref DIVr32: TargetInstDef = {
+            TargetInstDef::new("div", TargetOpcode::DIVr32)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(
+                    RegisterClassKind::GR32,
+                ))])
+                .set_imp_def(vec![
+                    TargetRegister::Specific(GR32::EAX.as_phys_reg()),
+                    TargetRegister::Specific(GR32::EDX.as_phys_reg()),
+                ])
+                .set_imp_use(vec![
+                    TargetRegister::Specific(GR32::EAX.as_phys_reg()),
+                    TargetRegister::Specific(GR32::EDX.as_phys_reg()),
+                ])
+        };
+        pub static ref DIVr64: TargetInstDef = {
+            TargetInstDef::new("div", TargetOpcode::DIVr64)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(
+                    RegisterClassKind::GR64,
+                ))])
+                .set_imp_def(vec![
+                    TargetRegister::Specific(GR64::RAX.as_phys_reg()),
+                    TargetRegister::Specific(GR64::RDX.as_phys_reg()),
+                ])
+                .set_imp_use(vec![
+                    TargetRegister::Specific(GR64::RAX.as_phys_reg()),
+                    TargetRegister::Specific(GR64::RDX.as_phys_reg()),
+                ])
+        };
         pub static ref PUSH64: TargetInstDef = {
             TargetInstDef::new("push", TargetOpcode::PUSH64).set_uses(vec![TargetOperand::Register(
                 TargetRegister::RegClass(RegisterClassKind::GR64),
@@ -479,6 +973,10 @@ mod inst {
         pub static ref JMP: TargetInstDef = {
             TargetInstDef::new("jmp", TargetOpcode::JMP).set_uses(vec![TargetOperand::Block])
         };
+        pub static ref JMPr: TargetInstDef = {
+            TargetInstDef::new("jmp", TargetOpcode::JMPr)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64))])
+        };
         pub static ref CMPri: TargetInstDef = {
             TargetInstDef::new("cmp", TargetOpcode::CMPri)
                 .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
@@ -487,6 +985,126 @@ mod inst {
             TargetInstDef::new("cmp", TargetOpcode::CMPrr)
                 .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
         };
+        pub static ref SETE: TargetInstDef = {
+            TargetInstDef::new("sete", TargetOpcode::SETE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETNE: TargetInstDef = {
+            TargetInstDef::new("setne", TargetOpcode::SETNE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETL: TargetInstDef = {
+            TargetInstDef::new("setl", TargetOpcode::SETL)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETLE: TargetInstDef = {
+            TargetInstDef::new("setle", TargetOpcode::SETLE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETG: TargetInstDef = {
+            TargetInstDef::new("setg", TargetOpcode::SETG)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETGE: TargetInstDef = {
+            TargetInstDef::new("setge", TargetOpcode::SETGE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETB: TargetInstDef = {
+            TargetInstDef::new("setb", TargetOpcode::SETB)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETBE: TargetInstDef = {
+            TargetInstDef::new("setbe", TargetOpcode::SETBE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETA: TargetInstDef = {
+            TargetInstDef::new("seta", TargetOpcode::SETA)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref SETAE: TargetInstDef = {
+            TargetInstDef::new("setae", TargetOpcode::SETAE)
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR8)])
+        };
+        pub static ref MOVQrx: TargetInstDef = {
+            TargetInstDef::new("movq", TargetOpcode::MOVQrx)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR64)])
+        };
+        pub static ref MOVQxr: TargetInstDef = {
+            TargetInstDef::new("movq", TargetOpcode::MOVQxr)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref MOVDrx: TargetInstDef = {
+            TargetInstDef::new("movd", TargetOpcode::MOVDrx)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::GR32)])
+        };
+        pub static ref MOVDxr: TargetInstDef = {
+            TargetInstDef::new("movd", TargetOpcode::MOVDxr)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR32))])
+                .set_defs(vec![TargetRegister::RegClass(RegisterClassKind::XMM)])
+        };
+        pub static ref CMOVErr: TargetInstDef = {
+            TargetInstDef::new("cmove", TargetOpcode::CMOVErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVNErr: TargetInstDef = {
+            TargetInstDef::new("cmovne", TargetOpcode::CMOVNErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVLrr: TargetInstDef = {
+            TargetInstDef::new("cmovl", TargetOpcode::CMOVLrr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVLErr: TargetInstDef = {
+            TargetInstDef::new("cmovle", TargetOpcode::CMOVLErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVGrr: TargetInstDef = {
+            TargetInstDef::new("cmovg", TargetOpcode::CMOVGrr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVGErr: TargetInstDef = {
+            TargetInstDef::new("cmovge", TargetOpcode::CMOVGErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVBrr: TargetInstDef = {
+            TargetInstDef::new("cmovb", TargetOpcode::CMOVBrr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVBErr: TargetInstDef = {
+            TargetInstDef::new("cmovbe", TargetOpcode::CMOVBErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVArr: TargetInstDef = {
+            TargetInstDef::new("cmova", TargetOpcode::CMOVArr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
+        pub static ref CMOVAErr: TargetInstDef = {
+            TargetInstDef::new("cmovae", TargetOpcode::CMOVAErr)
+                .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
+                .set_defs(vec![TargetRegister::Any])
+                .add_tie(DefOrUseReg::Def(0), DefOrUseReg::Use(0))
+        };
         pub static ref UCOMISDrr: TargetInstDef = {
             TargetInstDef::new("ucomisd", TargetOpcode::UCOMISDrr)
                 .set_uses(vec![
@@ -494,12 +1112,27 @@ mod inst {
                     TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))
                 ])
         };
+        pub static ref UCOMISSrr: TargetInstDef = {
+            TargetInstDef::new("ucomiss", TargetOpcode::UCOMISSrr)
+                .set_uses(vec![
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM)),
+                    TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::XMM))
+                ])
+        };
         pub static ref CALL: TargetInstDef = {
             TargetInstDef::new("call", TargetOpcode::CALL) // TODO: defs and uses info
                 // .set_uses(vec![TargetOperand::Any, TargetOperand::Any])
                 // .set_imp_use(vec![]
         };
+        pub static ref CALLr64: TargetInstDef = {
+            TargetInstDef::new("call", TargetOpcode::CALLr64)
+                .set_uses(vec![TargetOperand::Register(TargetRegister::RegClass(RegisterClassKind::GR64))])
+        };
+        pub static ref CALLm64: TargetInstDef = {
+            TargetInstDef::new("call", TargetOpcode::CALLm64).set_uses(vec![TargetOperand::Mem])
+        };
         pub static ref RET: TargetInstDef = TargetInstDef::new("ret", TargetOpcode::RET);
+        pub static ref UD2: TargetInstDef = TargetInstDef::new("ud2", TargetOpcode::UD2);
     }
 }
 
@@ -513,6 +1146,9 @@ pub enum TargetOpcode {
     MOVSDmr,   // movsd MEM, r
     MOVSDrm,   // movsd r, MEM
     MOVSDrr,
+    MOVSSrm32, // out(xmm) = movss [memory32]
+    MOVSSmr,   // movss MEM, r
+    MOVSSrm,   // movss r, MEM
 
     // TODO: MachineMemOperand is introduced, this is no longer correct info
     // out = mov [rbp  - fi.off              ] | out = mov rbp,  fi,   none,  none
@@ -521,8 +1157,10 @@ pub enum TargetOpcode {
     // out = mov [base          + align * off] | out = mov base, none, align, off
     // out = mov [base                       ] | out = mov base, none, none,  none
     MOVrm32,
+    MOVrm16,
     MOVrm8,
     MOVri8,
+    MOVri16,
 
     // mov [rbp  - fi.off              ], r | mov rbp,  fi,   none,  none, r
     // mov [rbp  - fi.off + const.off  ], r | mov rbp,  fi,   none,  off,  r
@@ -531,12 +1169,19 @@ pub enum TargetOpcode {
     // mov [base                       ], r | mov base, none, none,  none, r
     MOVmr8,
     MOVmi8,
+    MOVmr16,
+    MOVmi16,
     MOVmr32,
     MOVmi32,
     MOVmr64,
     MOVmi64,
 
     MOVSXr32r8,
+    MOVSXr32r16,
+    MOVZXr32r8,
+    MOVZXr32r16,
+    MOVZXr64r8,
+    MOVZXr64r32, // r64 = zext r32. emitted as 'mov r32, r32', so the def is the 32-bit subregister
     MOVSXDr64m32, // out = movsxd [rbp - fi.off]
     MOVSXDr64r32, // r64 = movsxd r32
 
@@ -549,6 +1194,8 @@ pub enum TargetOpcode {
 
     ADDrr8,
     ADDri8,
+    ADDrr16,
+    ADDri16,
     ADDrr32,
     ADDmr32,
     ADDmi32,
@@ -557,8 +1204,11 @@ pub enum TargetOpcode {
     ADDr64i32,
     ADDSDrr,
     ADDSDrm,
+    ADDSSrr,
     SUBrr8,
     SUBri8,
+    SUBrr16,
+    SUBri16,
     SUBrr32,
     SUBmr32,
     SUBmi32,
@@ -566,6 +1216,7 @@ pub enum TargetOpcode {
     SUBr64i32,
     SUBSDrr,
     SUBSDrm,
+    SUBSSrr,
     // IMULrr8,
     IMULrri8,
     IMULrr32,
@@ -573,16 +1224,63 @@ pub enum TargetOpcode {
     IMULrr64i32,
     MULSDrr,
     MULSDrm,
+    MULSSrr,
     CDQ,
     IDIV,
+    DIVr32,
+    DIVr64,
     DIVSDrr,
     DIVSDrm,
+    DIVSSrr,
     SHLr64i8,
     SHLr32i8,
+    ANDrr8,
+    ANDri8,
+    ANDrr32,
+    ANDri32,
+    ANDrr64,
+    ANDr64i32,
+    ORrr8,
+    ORri8,
+    ORrr32,
+    ORri32,
+    ORrr64,
+    ORr64i32,
+    XORrr8,
+    XORri8,
+    XORrr32,
+    XORri32,
+    XORrr64,
+    XORr64i32,
+    SHLr8i8,
+    SHLr8CL,
+    SHLr32CL,
+    SHLr64CL,
+    SHRr8i8,
+    SHRr32i8,
+    SHRr64i8,
+    SHRr8CL,
+    SHRr32CL,
+    SHRr64CL,
+    SARr8i8,
+    SARr32i8,
+    SARr64i8,
+    SARr8CL,
+    SARr32CL,
+    SARr64CL,
     CVTTSD2SIr32r,
     CVTSI2SDrr32,
+    CVTTSS2SIr32r,
+    CVTSI2SSrr32,
+    CVTSS2SDrr, // fpext
+    CVTSD2SSrr, // fptrunc
     SQRTSDrr,
+    MOVQrx,
+    MOVQxr,
+    MOVDrx,
+    MOVDxr,
     MOVrr8,
+    MOVrr16,
     MOVrr32,
     MOVri32,
     MOVrr64,
@@ -591,13 +1289,23 @@ pub enum TargetOpcode {
     PUSH64,
     POP64,
     RET,
+    UD2,
 
     CALL,
+    CALLr64,
+    CALLm64,
 
     // Comparison
-    Seteq,
-    Setle,
-    Setlt,
+    SETE,
+    SETNE,
+    SETL,
+    SETLE,
+    SETG,
+    SETGE,
+    SETB,
+    SETBE,
+    SETA,
+    SETAE,
 
     // BrccEq,
     // BrccLe,
@@ -605,6 +1313,7 @@ pub enum TargetOpcode {
     CMPrr,
     CMPri,
     UCOMISDrr,
+    UCOMISSrr,
     JE,
     JNE,
     JBE,
@@ -616,6 +1325,17 @@ pub enum TargetOpcode {
     JG,
     JGE,
     JMP,
+    JMPr,
+    CMOVErr,
+    CMOVNErr,
+    CMOVLrr,
+    CMOVLErr,
+    CMOVGrr,
+    CMOVGErr,
+    CMOVBrr,
+    CMOVBErr,
+    CMOVArr,
+    CMOVAErr,
 
     Phi,
     Ret,
@@ -633,12 +1353,22 @@ impl TargetOpcode {
             Self::MOVSDmr => Some(&*inst::MOVSDmr),
             Self::MOVSDrm => Some(&*inst::MOVSDrm),
             Self::MOVSDrr => Some(&*inst::MOVSDrr),
+            Self::MOVSSrm32 => Some(&*inst::MOVSSrm32),
+            Self::MOVSSrm => Some(&*inst::MOVSSrm),
+            Self::MOVSSmr => Some(&*inst::MOVSSmr),
             Self::MOVSXr32r8 => Some(&*inst::MOVSXr32r8),
+            Self::MOVSXr32r16 => Some(&*inst::MOVSXr32r16),
+            Self::MOVZXr32r8 => Some(&*inst::MOVZXr32r8),
+            Self::MOVZXr32r16 => Some(&*inst::MOVZXr32r16),
+            Self::MOVZXr64r8 => Some(&*inst::MOVZXr64r8),
+            Self::MOVZXr64r32 => Some(&*inst::MOVZXr64r32),
             Self::MOVSXDr64m32 => Some(&*inst::MOVSXDr64m32),
             Self::MOVSXDr64r32 => Some(&*inst::MOVSXDr64r32),
             Self::LEAr64m => Some(&*inst::LEAr64m),
             Self::ADDrr8 => Some(&*inst::ADDrr8),
             Self::ADDri8 => Some(&*inst::ADDri8),
+            Self::ADDrr16 => Some(&*inst::ADDrr16),
+            Self::ADDri16 => Some(&*inst::ADDri16),
             Self::ADDrr32 => Some(&*inst::ADDrr32),
             Self::ADDmr32 => Some(&*inst::ADDmr32),
             Self::ADDmi32 => Some(&*inst::ADDmi32),
@@ -647,8 +1377,11 @@ impl TargetOpcode {
             Self::ADDr64i32 => Some(&*inst::ADDr64i32),
             Self::ADDSDrr => Some(&*inst::ADDSDrr),
             Self::ADDSDrm => Some(&*inst::ADDSDrm),
+            Self::ADDSSrr => Some(&*inst::ADDSSrr),
             Self::SUBrr8 => Some(&*inst::SUBrr8),
             Self::SUBri8 => Some(&*inst::SUBri8),
+            Self::SUBrr16 => Some(&*inst::SUBrr16),
+            Self::SUBri16 => Some(&*inst::SUBri16),
             Self::SUBrr32 => Some(&*inst::SUBrr32),
             Self::SUBri32 => Some(&*inst::SUBri32),
             Self::SUBmr32 => Some(&*inst::SUBmr32),
@@ -656,6 +1389,7 @@ impl TargetOpcode {
             Self::SUBr64i32 => Some(&*inst::SUBr64i32),
             Self::SUBSDrr => Some(&*inst::SUBSDrr),
             Self::SUBSDrm => Some(&*inst::SUBSDrm),
+            Self::SUBSSrr => Some(&*inst::SUBSSrr),
             // Self::IMULrr8 => Some(&*inst::IMULrr8),
             Self::IMULrri8 => Some(&*inst::IMULrri8),
             Self::IMULrr32 => Some(&*inst::IMULrr32),
@@ -663,19 +1397,68 @@ impl TargetOpcode {
             Self::IMULrr64i32 => Some(&*inst::IMULrr64i32),
             Self::MULSDrr => Some(&*inst::MULSDrr),
             Self::MULSDrm => Some(&*inst::MULSDrm),
+            Self::MULSSrr => Some(&*inst::MULSSrr),
             Self::CDQ => Some(&*inst::CDQ),
             Self::DIVSDrr => Some(&*inst::DIVSDrr),
             Self::DIVSDrm => Some(&*inst::DIVSDrm),
+            Self::DIVSSrr => Some(&*inst::DIVSSrr),
             Self::SHLr64i8 => Some(&*inst::SHLr64i8),
             Self::SHLr32i8 => Some(&*inst::SHLr32i8),
+            Self::ANDrr8 => Some(&*inst::ANDrr8),
+            Self::ANDri8 => Some(&*inst::ANDri8),
+            Self::ANDrr32 => Some(&*inst::ANDrr32),
+            Self::ANDri32 => Some(&*inst::ANDri32),
+            Self::ANDrr64 => Some(&*inst::ANDrr64),
+            Self::ANDr64i32 => Some(&*inst::ANDr64i32),
+            Self::ORrr8 => Some(&*inst::ORrr8),
+            Self::ORri8 => Some(&*inst::ORri8),
+            Self::ORrr32 => Some(&*inst::ORrr32),
+            Self::ORri32 => Some(&*inst::ORri32),
+            Self::ORrr64 => Some(&*inst::ORrr64),
+            Self::ORr64i32 => Some(&*inst::ORr64i32),
+            Self::XORrr8 => Some(&*inst::XORrr8),
+            Self::XORri8 => Some(&*inst::XORri8),
+            Self::XORrr32 => Some(&*inst::XORrr32),
+            Self::XORri32 => Some(&*inst::XORri32),
+            Self::XORrr64 => Some(&*inst::XORrr64),
+            Self::XORr64i32 => Some(&*inst::XORr64i32),
+            Self::SHLr8i8 => Some(&*inst::SHLr8i8),
+            Self::SHLr8CL => Some(&*inst::SHLr8CL),
+            Self::SHLr32CL => Some(&*inst::SHLr32CL),
+            Self::SHLr64CL => Some(&*inst::SHLr64CL),
+            Self::SHRr8i8 => Some(&*inst::SHRr8i8),
+            Self::SHRr32i8 => Some(&*inst::SHRr32i8),
+            Self::SHRr64i8 => Some(&*inst::SHRr64i8),
+            Self::SHRr8CL => Some(&*inst::SHRr8CL),
+            Self::SHRr32CL => Some(&*inst::SHRr32CL),
+            Self::SHRr64CL => Some(&*inst::SHRr64CL),
+            Self::SARr8i8 => Some(&*inst::SARr8i8),
+            Self::SARr32i8 => Some(&*inst::SARr32i8),
+            Self::SARr64i8 => Some(&*inst::SARr64i8),
+            Self::SARr8CL => Some(&*inst::SARr8CL),
+            Self::SARr32CL => Some(&*inst::SARr32CL),
+            Self::SARr64CL => Some(&*inst::SARr64CL),
             Self::CVTSI2SDrr32 => Some(&*inst::CVTSI2SDrr32),
             Self::CVTTSD2SIr32r => Some(&*inst::CVTTSD2SIr32r),
+            Self::CVTSI2SSrr32 => Some(&*inst::CVTSI2SSrr32),
+            Self::CVTTSS2SIr32r => Some(&*inst::CVTTSS2SIr32r),
+            Self::CVTSS2SDrr => Some(&*inst::CVTSS2SDrr),
+            Self::CVTSD2SSrr => Some(&*inst::CVTSD2SSrr),
+            Self::MOVQrx => Some(&*inst::MOVQrx),
+            Self::MOVQxr => Some(&*inst::MOVQxr),
+            Self::MOVDrx => Some(&*inst::MOVDrx),
+            Self::MOVDxr => Some(&*inst::MOVDxr),
             Self::SQRTSDrr => Some(&*inst::SQRTSDrr),
             Self::MOVrr8 => Some(&*inst::MOVrr8),
             Self::MOVri8 => Some(&*inst::MOVri8),
             Self::MOVrm8 => Some(&*inst::MOVrm8),
             Self::MOVmr8 => Some(&*inst::MOVmr8),
             Self::MOVmi8 => Some(&*inst::MOVmi8),
+            Self::MOVrr16 => Some(&*inst::MOVrr16),
+            Self::MOVri16 => Some(&*inst::MOVri16),
+            Self::MOVrm16 => Some(&*inst::MOVrm16),
+            Self::MOVmr16 => Some(&*inst::MOVmr16),
+            Self::MOVmi16 => Some(&*inst::MOVmi16),
             Self::MOVrr32 => Some(&*inst::MOVrr32),
             Self::MOVri32 => Some(&*inst::MOVri32),
             Self::MOVrm32 => Some(&*inst::MOVrm32),
@@ -687,9 +1470,12 @@ impl TargetOpcode {
             Self::MOVri64 => Some(&*inst::MOVri64),
             Self::MOVrm64 => Some(&*inst::MOVrm64),
             Self::IDIV => Some(&*inst::IDIV),
+            Self::DIVr32 => Some(&*inst::DIVr32),
+            Self::DIVr64 => Some(&*inst::DIVr64),
             Self::PUSH64 => Some(&*inst::PUSH64),
             Self::POP64 => Some(&*inst::POP64),
             Self::JMP => Some(&*inst::JMP),
+            Self::JMPr => Some(&*inst::JMPr),
             Self::JG => Some(&*inst::JG),
             Self::JGE => Some(&*inst::JGE),
             Self::JE => Some(&*inst::JE),
@@ -702,9 +1488,33 @@ impl TargetOpcode {
             Self::JAE => Some(&*inst::JAE),
             Self::CMPri => Some(&*inst::CMPri),
             Self::CMPrr => Some(&*inst::CMPrr),
+            Self::SETE => Some(&*inst::SETE),
+            Self::SETNE => Some(&*inst::SETNE),
+            Self::SETL => Some(&*inst::SETL),
+            Self::SETLE => Some(&*inst::SETLE),
+            Self::SETG => Some(&*inst::SETG),
+            Self::SETGE => Some(&*inst::SETGE),
+            Self::SETB => Some(&*inst::SETB),
+            Self::SETBE => Some(&*inst::SETBE),
+            Self::SETA => Some(&*inst::SETA),
+            Self::SETAE => Some(&*inst::SETAE),
             Self::CALL => Some(&*inst::CALL),
+            Self::CALLr64 => Some(&*inst::CALLr64),
+            Self::CALLm64 => Some(&*inst::CALLm64),
             Self::RET => Some(&*inst::RET),
+            Self::UD2 => Some(&*inst::UD2),
             Self::UCOMISDrr => Some(&*inst::UCOMISDrr),
+            Self::UCOMISSrr => Some(&*inst::UCOMISSrr),
+            Self::CMOVErr => Some(&*inst::CMOVErr),
+            Self::CMOVNErr => Some(&*inst::CMOVNErr),
+            Self::CMOVLrr => Some(&*inst::CMOVLrr),
+            Self::CMOVLErr => Some(&*inst::CMOVLErr),
+            Self::CMOVGrr => Some(&*inst::CMOVGrr),
+            Self::CMOVGErr => Some(&*inst::CMOVGErr),
+            Self::CMOVBrr => Some(&*inst::CMOVBrr),
+            Self::CMOVBErr => Some(&*inst::CMOVBErr),
+            Self::CMOVArr => Some(&*inst::CMOVArr),
+            Self::CMOVAErr => Some(&*inst::CMOVAErr),
             _ => None,
         }
     }
@@ -138,9 +138,10 @@ impl<'a> LiveIntervalSplitter<'a> {
             reg_intvl.end_point().unwrap(),
         ));
 
+        // `before_load` itself may read `reg` (e.g. an indirect call), so only later uses are renamed
         let mut new_reg_uses = FxHashSet::default();
         for &id in &self.func.regs_info.arena_ref_mut()[*reg].uses {
-            if self.matrix.get_program_point(id).unwrap() > store_pp {
+            if self.matrix.get_program_point(id).unwrap() > before_load_pp {
                 new_reg_uses.insert(id);
             }
         }
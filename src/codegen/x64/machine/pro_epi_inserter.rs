@@ -254,9 +254,10 @@ impl<'a> CopyArgs<'a> {
             }
             match ty {
                 Type::i8 => self.copy_int(ty, &mut arg_regs_order, i, 8),
+                Type::i16 => self.copy_int(ty, &mut arg_regs_order, i, 16),
                 Type::i32 => self.copy_int(ty, &mut arg_regs_order, i, 32),
                 Type::i64 | Type::Pointer(_) => self.copy_int(ty, &mut arg_regs_order, i, 64),
-                Type::f64 => self.copy_f64(&mut arg_regs_order, i),
+                Type::f32 | Type::f64 => self.copy_float(ty, &mut arg_regs_order, i),
                 _ => unimplemented!(),
             }
         }
@@ -301,8 +302,9 @@ impl<'a> CopyArgs<'a> {
                     match rc {
                         RegisterClassKind::GR32 => MachineOpcode::MOVmr32,
                         RegisterClassKind::GR64 => MachineOpcode::MOVmr64,
+                        RegisterClassKind::XMM if sz - off < 8 => MachineOpcode::MOVSSmr,
                         RegisterClassKind::XMM => MachineOpcode::MOVSDmr,
-                        RegisterClassKind::GR8 => unimplemented!(),
+                        RegisterClassKind::GR8 | RegisterClassKind::GR16 => unimplemented!(),
                     },
                     vec![mem, MachineOperand::Register(RegisterOperand::new(r))],
                     self.builder.get_cur_bb().unwrap(),
@@ -363,19 +365,27 @@ impl<'a> CopyArgs<'a> {
         }
     }
 
-    fn copy_f64<ABI>(&mut self, arg_regs_order: &mut ArgumentRegisterOrder<ABI>, i: usize)
-    where
+    fn copy_float<ABI>(
+        &mut self,
+        ty: Type,
+        arg_regs_order: &mut ArgumentRegisterOrder<ABI>,
+        i: usize,
+    ) where
         ABI: CallingConv,
     {
+        let (movrm, movmr) = match ty {
+            Type::f32 => (MachineOpcode::MOVSSrm, MachineOpcode::MOVSSmr),
+            _ => (MachineOpcode::MOVSDrm, MachineOpcode::MOVSDmr),
+        };
         let ret_reg = XMM::XMM0.as_phys_reg();
-        let dst = FrameIndexInfo::new(Type::f64, FrameIndexKind::Arg(i));
+        let dst = FrameIndexInfo::new(ty, FrameIndexKind::Arg(i));
         let src = match arg_regs_order.next(RegisterClassKind::XMM) {
             Some(_arg_reg) => return, // MachineOperand::phys_reg(&self.builder.function.regs_info, arg_reg),
             None => {
                 let ax =
                     RegisterOperand::new(self.builder.function.regs_info.get_phys_reg(ret_reg));
                 let inst = MachineInst::new_simple(
-                    MachineOpcode::MOVSDrm,
+                    movrm,
                     vec![MachineOperand::Mem(MachineMemOperand::BaseOff(
                         RegisterOperand::new(
                             self.builder.function.regs_info.get_phys_reg(GR64::RBP),
@@ -391,7 +401,7 @@ impl<'a> CopyArgs<'a> {
             }
         };
         let inst = MachineInst::new_simple(
-            MachineOpcode::MOVSDmr,
+            movmr,
             vec![
                 MachineOperand::Mem(MachineMemOperand::BaseFi(
                     RegisterOperand::new(self.builder.function.regs_info.get_phys_reg(GR64::RBP)),
@@ -419,6 +429,11 @@ impl<'a> CopyArgs<'a> {
                 RegisterClassKind::GR8,
                 MachineOpcode::MOVrm8,
             ),
+            16 => (
+                GR16::AX.as_phys_reg(),
+                RegisterClassKind::GR16,
+                MachineOpcode::MOVrm16,
+            ),
             32 => (
                 GR32::EAX.as_phys_reg(),
                 RegisterClassKind::GR32,
@@ -20,8 +20,10 @@ impl TypeSize for Type {
         match self {
             Type::i1 => 1,
             Type::i8 => 1,
+            Type::i16 => 2,
             Type::i32 => 4,
             Type::i64 => 8,
+            Type::f32 => 4,
             Type::f64 => 8,
             Type::Array(id) => tys.base.borrow().non_primitive_types[*id]
                 .as_array()
@@ -43,8 +45,10 @@ impl TypeSize for Type {
         match self {
             Type::i1 => 1,
             Type::i8 => 1,
+            Type::i16 => 2,
             Type::i32 => 4,
             Type::i64 => 8,
+            Type::f32 => 4,
             Type::f64 => 8,
             Type::Array(id) => tys.base.borrow().non_primitive_types[*id]
                 .as_array()
@@ -96,10 +100,12 @@ impl TypeSize for StructType {
 }
 
 pub fn standard_conversion_into_machine_module(module: &mut Module) -> MachineModule {
-    ir::merge_ret::MergeReturns::new().run_on_module(module);
-    ir::const_folding::ConstantFolding::new().run_on_module(module);
-    ir::inst_combine::InstructionCombine::new().run_on_module(module);
-    ir::codegen_prepare::CodegenPrepare::new().run_on_module(module);
+    let mut pass_mgr = ModulePassManager::new();
+    pass_mgr.add_pass(ir::merge_ret::MergeReturns::new());
+    pass_mgr.add_pass(ir::const_folding::ConstantFolding::new());
+    pass_mgr.add_pass(ir::inst_combine::InstructionCombine::new());
+    pass_mgr.add_pass(ir::codegen_prepare::CodegenPrepare::new());
+    pass_mgr.run_on_module(module);
 
     let mut dag_module = convert::ConvertToDAGModule::new(module).run();
 
@@ -0,0 +1,848 @@
+// Compact binary form of a module.
+//
+// The stream starts with `MAGIC` and `VERSION`, followed by the module name, types, the numbers
+// of global variables and functions, constant expressions, global variables and functions.
+// Integers are LEB128 (signed ones zigzag encoded) and every arena is written in allocation order
+// with its ids as indices, so a module read back has the same ids as the one written. Increment
+// `VERSION` whenever the layout changes.
+
+use super::{
+    basic_block::*, const_expr::ConstExpr, function::*, global_val::*, module::Module, opcode::*,
+    types::*, value::*,
+};
+use id_arena::{Arena, ArenaBehavior, DefaultArenaBehavior};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    cell::RefCell,
+    io::{self, Read, Write},
+};
+
+const MAGIC: &[u8; 4] = b"CILK";
+const VERSION: u32 = 5;
+
+// Opcodes and comparison kinds are written as indices into these tables
+const OPCODES: &[Opcode] = &[
+    Opcode::Alloca,
+    Opcode::Load,
+    Opcode::Store,
+    Opcode::GetElementPtr,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::Rem,
+    Opcode::UDiv,
+    Opcode::URem,
+    Opcode::Shl,
+    Opcode::LShr,
+    Opcode::AShr,
+    Opcode::And,
+    Opcode::Or,
+    Opcode::Xor,
+    Opcode::SIToFP,
+    Opcode::FPToSI,
+    Opcode::Sext,
+    Opcode::ZExt,
+    Opcode::Trunc,
+    Opcode::FPExt,
+    Opcode::FPTrunc,
+    Opcode::Bitcast,
+    Opcode::PtrToInt,
+    Opcode::IntToPtr,
+    Opcode::ICmp,
+    Opcode::FCmp,
+    Opcode::Select,
+    Opcode::Br,
+    Opcode::CondBr,
+    Opcode::Switch,
+    Opcode::Phi,
+    Opcode::Call,
+    Opcode::Ret,
+    Opcode::Unreachable,
+];
+
+const ICMP_KINDS: &[ICmpKind] = &[
+    ICmpKind::Eq,
+    ICmpKind::Ne,
+    ICmpKind::Le,
+    ICmpKind::Lt,
+    ICmpKind::Ge,
+    ICmpKind::Gt,
+    ICmpKind::Ult,
+    ICmpKind::Ule,
+    ICmpKind::Ugt,
+    ICmpKind::Uge,
+];
+
+const FCMP_KINDS: &[FCmpKind] = &[
+    FCmpKind::UEq,
+    FCmpKind::UNe,
+    FCmpKind::ULe,
+    FCmpKind::ULt,
+    FCmpKind::UGe,
+    FCmpKind::UGt,
+];
+
+impl Module {
+    /// Writes the module in the binary form read by `Module::read_bitcode`
+    pub fn write_bitcode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut writer = Writer { w };
+        writer.bytes(MAGIC)?;
+        writer.uint(VERSION as u64)?;
+        writer.module(self)
+    }
+
+    /// Reads a module written by `Module::write_bitcode`
+    pub fn read_bitcode<R: Read>(r: R) -> io::Result<Module> {
+        let mut reader = Reader {
+            r,
+            module: Module::new(""),
+            num_types: 0,
+            num_funcs: 0,
+            num_globals: 0,
+            num_const_exprs: 0,
+            num_blocks: 0,
+            num_insts: 0,
+        };
+        let mut magic = [0; 4];
+        reader.r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a cilk bitcode"));
+        }
+        let version = reader.uint()?;
+        if version != VERSION as usize {
+            return Err(invalid_data(&format!(
+                "unsupported bitcode version {} (expected {})",
+                version, VERSION
+            )));
+        }
+        reader.module()?;
+        Ok(reader.module)
+    }
+}
+
+struct Writer<'a, W: Write> {
+    w: &'a mut W,
+}
+
+impl<'a, W: Write> Writer<'a, W> {
+    fn module(&mut self, m: &Module) -> io::Result<()> {
+        self.str(&m.name)?;
+
+        let base = m.types.base.borrow();
+        self.uint(base.non_primitive_types.len() as u64)?;
+        for (_, t) in &base.non_primitive_types {
+            self.non_primitive_ty(t)?;
+        }
+
+        // Constant expressions may refer to any global variable or function
+        self.uint(m.global_vars.arena.len() as u64)?;
+        self.uint(m.functions.len() as u64)?;
+        self.uint(m.const_exprs.arena.len() as u64)?;
+        for (_, expr) in &m.const_exprs.arena {
+            let opcode = OPCODES.iter().position(|&o| o == expr.opcode).unwrap();
+            self.uint(opcode as u64)?;
+            self.ty(expr.ty)?;
+            self.uint(expr.operands.len() as u64)?;
+            for v in &expr.operands {
+                self.value(v)?;
+            }
+        }
+
+        for (_, g) in &m.global_vars.arena {
+            self.global_var(g)?;
+        }
+
+        for (_, f) in &m.functions {
+            self.function(f)?;
+        }
+        Ok(())
+    }
+
+    fn non_primitive_ty(&mut self, t: &NonPrimitiveType) -> io::Result<()> {
+        match t {
+            NonPrimitiveType::Pointer(elem_ty) => {
+                self.uint(0)?;
+                self.ty(*elem_ty)
+            }
+            NonPrimitiveType::Array(ArrayType { elem_ty, len }) => {
+                self.uint(1)?;
+                self.ty(*elem_ty)?;
+                self.uint(*len as u64)
+            }
+            NonPrimitiveType::Function(f) => {
+                self.uint(2)?;
+                self.ty(f.ret_ty)?;
+                self.uint(f.params_ty.len() as u64)?;
+                for &ty in &f.params_ty {
+                    self.ty(ty)?;
+                }
+                let mut attrs = f.params_attr.iter().collect::<Vec<_>>();
+                attrs.sort_by_key(|(i, _)| **i);
+                self.uint(attrs.len() as u64)?;
+                for (i, attr) in attrs {
+                    self.uint(*i as u64)?;
+                    self.bool(attr.byval)?;
+                }
+                self.bool(f.is_var_arg)
+            }
+            NonPrimitiveType::Struct(s) => {
+                self.uint(3)?;
+                self.bool(s.name().is_some())?;
+                if let Some(name) = s.name() {
+                    self.str(name)?;
+                }
+                self.bool(s.is_opaque())?;
+                self.uint(s.fields_ty().len() as u64)?;
+                for (&ty, &offset) in s.fields_ty().iter().zip(s.fields_offset()) {
+                    self.ty(ty)?;
+                    self.uint(offset as u64)?;
+                }
+                self.uint(s.align() as u64)?;
+                self.uint(s.size() as u64)
+            }
+        }
+    }
+
+    fn ty(&mut self, ty: Type) -> io::Result<()> {
+        match ty {
+            Type::Void => self.uint(0),
+            Type::i1 => self.uint(1),
+            Type::i8 => self.uint(2),
+            Type::i16 => self.uint(3),
+            Type::i32 => self.uint(4),
+            Type::i64 => self.uint(5),
+            Type::f32 => self.uint(6),
+            Type::f64 => self.uint(7),
+            Type::Pointer(id) => self.uint2(8, id.index()),
+            Type::Array(id) => self.uint2(9, id.index()),
+            Type::Function(id) => self.uint2(10, id.index()),
+            Type::Struct(id) => self.uint2(11, id.index()),
+        }
+    }
+
+    fn global_var(&mut self, g: &GlobalVariable) -> io::Result<()> {
+        self.str(&g.name)?;
+        self.ty(g.ty)?;
+        self.uint(match g.linkage {
+            Linkage::Common => 0,
+            Linkage::External => 1,
+        })?;
+        self.bool(g.is_constant)?;
+        match &g.init {
+            Some(init) => {
+                self.bool(true)?;
+                self.initializer(init)
+            }
+            None => self.bool(false),
+        }
+    }
+
+    fn initializer(&mut self, init: &Initializer) -> io::Result<()> {
+        match init {
+            Initializer::Zero => self.uint(0),
+            Initializer::Immediate(imm) => {
+                self.uint(1)?;
+                self.imm(imm)
+            }
+            Initializer::Array(elems) | Initializer::Struct(elems) => {
+                self.uint(if matches!(init, Initializer::Array(_)) {
+                    2
+                } else {
+                    3
+                })?;
+                self.uint(elems.len() as u64)?;
+                for e in elems {
+                    self.initializer(e)?;
+                }
+                Ok(())
+            }
+            Initializer::Expr(v) => {
+                self.uint(4)?;
+                self.value(v)
+            }
+            Initializer::Address(_, _) => Err(invalid_data(
+                "resolved initializers are only for the code generator",
+            )),
+        }
+    }
+
+    fn function(&mut self, f: &Function) -> io::Result<()> {
+        self.str(&f.name)?;
+        self.ty(f.ty)?;
+        self.bool(f.is_internal)?;
+        self.bool(f.is_prototype)?;
+
+        self.uint(f.basic_blocks.arena.len() as u64)?;
+        self.uint(f.inst_table.len() as u64)?;
+
+        for (_, block) in &f.basic_blocks.arena {
+            self.ids(block.pred.iter().map(|id| id.index()))?;
+            self.ids(block.succ.iter().map(|id| id.index()))?;
+            let iseq = block.iseq_ref();
+            self.uint(iseq.len() as u64)?;
+            for val in &*iseq {
+                self.value(val)?;
+            }
+            let liveness = block.liveness.borrow();
+            self.ids(liveness.def.iter().map(|id| id.index()))?;
+            self.ids(liveness.live_in.iter().map(|id| id.index()))?;
+            self.ids(liveness.live_out.iter().map(|id| id.index()))?;
+        }
+        self.uint(f.basic_blocks.order.len() as u64)?;
+        for id in &f.basic_blocks.order {
+            self.uint(id.index() as u64)?;
+        }
+
+        for (_, inst) in &f.inst_table {
+            self.inst(inst)?;
+        }
+
+        self.names(f.names.insts.iter().map(|(id, name)| (id.index(), name)))?;
+        self.names(f.names.blocks.iter().map(|(id, name)| (id.index(), name)))?;
+        self.names(f.names.params.iter().map(|(&i, name)| (i, name)))
+    }
+
+    fn names<'b>(&mut self, names: impl Iterator<Item = (usize, &'b String)>) -> io::Result<()> {
+        let mut names = names.collect::<Vec<_>>();
+        names.sort_unstable();
+        self.uint(names.len() as u64)?;
+        for (i, name) in names {
+            self.uint(i as u64)?;
+            self.str(name)?;
+        }
+        Ok(())
+    }
+
+    fn inst(&mut self, inst: &Instruction) -> io::Result<()> {
+        let opcode = OPCODES.iter().position(|&o| o == inst.opcode).unwrap();
+        self.uint(opcode as u64)?;
+        self.ty(inst.ty)?;
+        match inst.id {
+            Some(id) => self.uint(id.index() as u64 + 1)?,
+            None => self.uint(0)?,
+        }
+        self.uint(inst.parent.index() as u64)?;
+        let users = inst.users.borrow();
+        self.uint(users.len() as u64)?;
+        for id in &*users {
+            self.uint(id.index() as u64)?;
+        }
+        self.uint(inst.operands.len() as u64)?;
+        for operand in &inst.operands {
+            match operand {
+                Operand::Type(ty) => {
+                    self.uint(0)?;
+                    self.ty(*ty)?
+                }
+                Operand::Value(v) => {
+                    self.uint(1)?;
+                    self.value(v)?
+                }
+                Operand::BasicBlock(id) => self.uint2(2, id.index())?,
+                Operand::ICmpKind(kind) => {
+                    self.uint2(3, ICMP_KINDS.iter().position(|k| k == kind).unwrap())?
+                }
+                Operand::FCmpKind(kind) => {
+                    self.uint2(4, FCMP_KINDS.iter().position(|k| k == kind).unwrap())?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, v: &Value) -> io::Result<()> {
+        match v {
+            Value::Argument(ArgumentValue { func_id, index, ty }) => {
+                self.uint2(0, func_id.index())?;
+                self.uint(*index as u64)?;
+                self.ty(*ty)
+            }
+            Value::Instruction(InstructionValue { func_id, id, ty }) => {
+                self.uint2(1, func_id.index())?;
+                self.uint(id.index() as u64)?;
+                self.ty(*ty)
+            }
+            Value::Function(FunctionValue { func_id, ty }) => {
+                self.uint2(2, func_id.index())?;
+                self.ty(*ty)
+            }
+            Value::Global(GlobalValue { id, ty }) => {
+                self.uint2(3, id.index())?;
+                self.ty(*ty)
+            }
+            Value::Immediate(imm) => {
+                self.uint(4)?;
+                self.imm(imm)
+            }
+            Value::None => self.uint(5),
+            Value::ConstExpr(ConstExprValue { id, ty }) => {
+                self.uint2(6, id.index())?;
+                self.ty(*ty)
+            }
+        }
+    }
+
+    fn imm(&mut self, imm: &ImmediateValue) -> io::Result<()> {
+        match imm {
+            ImmediateValue::Int1(b) => self.int2(6, *b as i64),
+            ImmediateValue::Int8(i) => self.int2(0, *i as i64),
+            ImmediateValue::Int16(i) => self.int2(1, *i as i64),
+            ImmediateValue::Int32(i) => self.int2(2, *i as i64),
+            ImmediateValue::Int64(i) => self.int2(3, *i),
+            ImmediateValue::F32(f) => {
+                self.uint(4)?;
+                self.bytes(&f.to_bits().to_le_bytes())
+            }
+            ImmediateValue::F64(f) => {
+                self.uint(5)?;
+                self.bytes(&f.to_bits().to_le_bytes())
+            }
+        }
+    }
+
+    /// Writes a set of ids sorted so that the output doesn't depend on the hash order
+    fn ids(&mut self, ids: impl Iterator<Item = usize>) -> io::Result<()> {
+        let mut ids = ids.collect::<Vec<_>>();
+        ids.sort_unstable();
+        self.uint(ids.len() as u64)?;
+        for id in ids {
+            self.uint(id as u64)?;
+        }
+        Ok(())
+    }
+
+    fn uint2(&mut self, tag: u64, x: usize) -> io::Result<()> {
+        self.uint(tag)?;
+        self.uint(x as u64)
+    }
+
+    fn int2(&mut self, tag: u64, x: i64) -> io::Result<()> {
+        self.uint(tag)?;
+        self.uint(((x << 1) ^ (x >> 63)) as u64)
+    }
+
+    fn uint(&mut self, mut x: u64) -> io::Result<()> {
+        loop {
+            let byte = (x & 0x7f) as u8;
+            x >>= 7;
+            if x == 0 {
+                return self.bytes(&[byte]);
+            }
+            self.bytes(&[byte | 0x80])?;
+        }
+    }
+
+    fn bool(&mut self, b: bool) -> io::Result<()> {
+        self.bytes(&[b as u8])
+    }
+
+    fn str(&mut self, s: &str) -> io::Result<()> {
+        self.uint(s.len() as u64)?;
+        self.bytes(s.as_bytes())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.w.write_all(bytes)
+    }
+}
+
+struct Reader<R: Read> {
+    r: R,
+    module: Module,
+    num_types: usize,
+    num_funcs: usize,
+    num_globals: usize,
+    num_const_exprs: usize,
+    // Sizes of the arenas of the function being read
+    num_blocks: usize,
+    num_insts: usize,
+}
+
+impl<R: Read> Reader<R> {
+    fn module(&mut self) -> io::Result<()> {
+        self.module.name = self.str()?;
+
+        self.num_types = self.uint()?;
+        for _ in 0..self.num_types {
+            let t = self.non_primitive_ty()?;
+            // Allocate directly to keep the ids even if two types happen to be equal
+            self.module
+                .types
+                .base
+                .borrow_mut()
+                .non_primitive_types
+                .alloc(t);
+        }
+
+        self.num_globals = self.uint()?;
+        self.num_funcs = self.uint()?;
+        self.num_const_exprs = self.uint()?;
+        for _ in 0..self.num_const_exprs {
+            let opcode = self.uint()?;
+            let opcode = *OPCODES
+                .get(opcode)
+                .ok_or_else(|| invalid_tag("opcode", opcode))?;
+            let ty = self.ty()?;
+            // No instruction can be referred to since `num_insts` is zero
+            let operands = self.vec(|r| r.value(0))?;
+            self.module.const_expr(opcode, operands, ty);
+        }
+        if self.module.const_exprs.arena.len() != self.num_const_exprs {
+            return Err(invalid_data("duplicate constant expression"));
+        }
+
+        for _ in 0..self.num_globals {
+            let g = self.global_var()?;
+            self.module.global_vars.arena.alloc(g);
+        }
+
+        for _ in 0..self.num_funcs {
+            let f = self.function()?;
+            self.module.add_function(f);
+        }
+        Ok(())
+    }
+
+    fn non_primitive_ty(&mut self) -> io::Result<NonPrimitiveType> {
+        Ok(match self.uint()? {
+            0 => NonPrimitiveType::Pointer(self.ty()?),
+            1 => NonPrimitiveType::Array(ArrayType::new(self.ty()?, self.uint()?)),
+            2 => {
+                let ret_ty = self.ty()?;
+                let params_ty = self.vec(|r| r.ty())?;
+                let mut params_attr = FxHashMap::default();
+                for _ in 0..self.uint()? {
+                    let i = self.uint()?;
+                    let byval = self.bool()?;
+                    params_attr.insert(i, ParamAttribute { byval });
+                }
+                let mut f = FunctionType::new(ret_ty, params_ty, params_attr);
+                f.is_var_arg = self.bool()?;
+                NonPrimitiveType::Function(f)
+            }
+            3 => {
+                let name = if self.bool()? {
+                    Some(self.str()?)
+                } else {
+                    None
+                };
+                let is_opaque = self.bool()?;
+                let (fields_ty, fields_offset) =
+                    self.vec(|r| Ok((r.ty()?, r.uint()?)))?.into_iter().unzip();
+                let align = self.uint()?;
+                let size = self.uint()?;
+                NonPrimitiveType::Struct(match name {
+                    Some(name) if is_opaque => StructType::opaque(&name),
+                    _ => StructType::with_layout(name, fields_ty, fields_offset, align, size),
+                })
+            }
+            tag => return Err(invalid_tag("type", tag)),
+        })
+    }
+
+    fn ty(&mut self) -> io::Result<Type> {
+        let tag = self.uint()?;
+        if tag < 8 {
+            return Ok([
+                Type::Void,
+                Type::i1,
+                Type::i8,
+                Type::i16,
+                Type::i32,
+                Type::i64,
+                Type::f32,
+                Type::f64,
+            ][tag]);
+        }
+        let id = self.index(self.num_types, "type")?;
+        let id = {
+            let base = self.module.types.base.borrow();
+            let arena_id = DefaultArenaBehavior::arena_id(base.non_primitive_types.next_id());
+            DefaultArenaBehavior::<NonPrimitiveType>::new_id(arena_id, id)
+        };
+        Ok(match tag {
+            8 => Type::Pointer(id),
+            9 => Type::Array(id),
+            10 => Type::Function(id),
+            11 => Type::Struct(id),
+            tag => return Err(invalid_tag("type", tag)),
+        })
+    }
+
+    fn global_var(&mut self) -> io::Result<GlobalVariable> {
+        let name = self.str()?;
+        let ty = self.ty()?;
+        let linkage = match self.uint()? {
+            0 => Linkage::Common,
+            1 => Linkage::External,
+            tag => return Err(invalid_tag("linkage", tag)),
+        };
+        let is_constant = self.bool()?;
+        let init = if self.bool()? {
+            Some(self.initializer()?)
+        } else {
+            None
+        };
+        Ok(GlobalVariable {
+            ty,
+            linkage,
+            name,
+            init,
+            is_constant,
+        })
+    }
+
+    fn initializer(&mut self) -> io::Result<Initializer> {
+        Ok(match self.uint()? {
+            0 => Initializer::Zero,
+            1 => Initializer::Immediate(self.imm()?),
+            2 => Initializer::Array(self.vec(|r| r.initializer())?),
+            3 => Initializer::Struct(self.vec(|r| r.initializer())?),
+            4 => Initializer::Expr(self.value(0)?),
+            tag => return Err(invalid_tag("initializer", tag)),
+        })
+    }
+
+    fn function(&mut self) -> io::Result<Function> {
+        let name = self.str()?;
+        let ty = self.ty()?;
+        if !matches!(ty, Type::Function(_)) {
+            return Err(invalid_data("function has a non-function type"));
+        }
+        let is_internal = self.bool()?;
+        let is_prototype = self.bool()?;
+        self.num_blocks = self.uint()?;
+        self.num_insts = self.uint()?;
+
+        let mut basic_blocks = BasicBlocks::new();
+        let block_arena_id = DefaultArenaBehavior::arena_id(basic_blocks.arena.next_id());
+        let mut inst_table: Arena<Instruction> = Arena::new();
+        let inst_arena_id = DefaultArenaBehavior::arena_id(inst_table.next_id());
+        let block_id = |n| DefaultArenaBehavior::<BasicBlock>::new_id(block_arena_id, n);
+        let inst_id = |n| DefaultArenaBehavior::<Instruction>::new_id(inst_arena_id, n);
+        let block_ids = |r: &mut Self| -> io::Result<FxHashSet<BasicBlockId>> {
+            let ids = r.vec(|r| r.index(r.num_blocks, "block"))?;
+            Ok(ids.into_iter().map(block_id).collect())
+        };
+        let inst_ids = |r: &mut Self| -> io::Result<FxHashSet<InstructionId>> {
+            let ids = r.vec(|r| r.index(r.num_insts, "instruction"))?;
+            Ok(ids.into_iter().map(inst_id).collect())
+        };
+
+        for _ in 0..self.num_blocks {
+            let mut block = BasicBlock::new();
+            block.pred = block_ids(self)?;
+            block.succ = block_ids(self)?;
+            block.iseq = RefCell::new(self.vec(|r| r.value(inst_arena_id))?);
+            block.liveness = RefCell::new(LivenessInfo {
+                def: inst_ids(self)?,
+                live_in: inst_ids(self)?,
+                live_out: inst_ids(self)?,
+            });
+            basic_blocks.arena.alloc(block);
+        }
+        basic_blocks.order = self
+            .vec(|r| r.index(r.num_blocks, "block"))?
+            .into_iter()
+            .map(block_id)
+            .collect();
+
+        for _ in 0..self.num_insts {
+            let opcode = self.uint()?;
+            let opcode = *OPCODES
+                .get(opcode)
+                .ok_or_else(|| invalid_tag("opcode", opcode))?;
+            let ty = self.ty()?;
+            let id = match self.uint()? {
+                0 => None,
+                n if n <= self.num_insts => Some(inst_id(n - 1)),
+                _ => return Err(invalid_data("instruction index out of range")),
+            };
+            let parent = block_id(self.index(self.num_blocks, "block")?);
+            let users = self
+                .vec(|r| r.index(r.num_insts, "instruction"))?
+                .into_iter()
+                .map(inst_id)
+                .collect();
+            let operands = self.vec(|r| {
+                Ok(match r.uint()? {
+                    0 => Operand::Type(r.ty()?),
+                    1 => Operand::Value(r.value(inst_arena_id)?),
+                    2 => Operand::BasicBlock(block_id(r.index(r.num_blocks, "block")?)),
+                    3 => Operand::ICmpKind(ICMP_KINDS[r.index(ICMP_KINDS.len(), "icmp kind")?]),
+                    4 => Operand::FCmpKind(FCMP_KINDS[r.index(FCMP_KINDS.len(), "fcmp kind")?]),
+                    tag => return Err(invalid_tag("operand", tag)),
+                })
+            })?;
+            inst_table.alloc(Instruction {
+                opcode,
+                operands,
+                ty,
+                id,
+                parent,
+                users: RefCell::new(users),
+            });
+        }
+
+        let mut names = Names::default();
+        for (n, name) in self.vec(|r| Ok((r.index(r.num_insts, "instruction")?, r.str()?)))? {
+            names.set_inst_name(inst_id(n), &name);
+        }
+        for (n, name) in self.vec(|r| Ok((r.index(r.num_blocks, "block")?, r.str()?)))? {
+            names.set_block_name(block_id(n), &name);
+        }
+        for (i, name) in self.vec(|r| Ok((r.uint()?, r.str()?)))? {
+            names.set_param_name(i, &name);
+        }
+
+        Ok(Function {
+            name,
+            ty,
+            basic_blocks,
+            inst_table,
+            id: None,
+            analyses: vec![],
+            types: self.module.types.clone(),
+            is_internal,
+            is_prototype,
+            names,
+        })
+    }
+
+    fn value(&mut self, inst_arena_id: u32) -> io::Result<Value> {
+        let func_id = |r: &mut Self| -> io::Result<FunctionId> {
+            let n = r.index(r.num_funcs, "function")?;
+            let arena_id = DefaultArenaBehavior::arena_id(r.module.functions.next_id());
+            Ok(DefaultArenaBehavior::<Function>::new_id(arena_id, n))
+        };
+        Ok(match self.uint()? {
+            0 => Value::Argument(ArgumentValue {
+                func_id: func_id(self)?,
+                index: self.uint()?,
+                ty: self.ty()?,
+            }),
+            1 => Value::Instruction(InstructionValue {
+                func_id: func_id(self)?,
+                id: DefaultArenaBehavior::<Instruction>::new_id(
+                    inst_arena_id,
+                    self.index(self.num_insts, "instruction")?,
+                ),
+                ty: self.ty()?,
+            }),
+            2 => Value::Function(FunctionValue {
+                func_id: func_id(self)?,
+                ty: self.ty()?,
+            }),
+            3 => {
+                let n = self.index(self.num_globals, "global variable")?;
+                let arena_id =
+                    DefaultArenaBehavior::arena_id(self.module.global_vars.arena.next_id());
+                Value::Global(GlobalValue {
+                    id: DefaultArenaBehavior::<GlobalVariable>::new_id(arena_id, n),
+                    ty: self.ty()?,
+                })
+            }
+            4 => Value::Immediate(self.imm()?),
+            5 => Value::None,
+            6 => {
+                let n = self.index(self.num_const_exprs, "constant expression")?;
+                let arena_id =
+                    DefaultArenaBehavior::arena_id(self.module.const_exprs.arena.next_id());
+                Value::ConstExpr(ConstExprValue {
+                    id: DefaultArenaBehavior::<ConstExpr>::new_id(arena_id, n),
+                    ty: self.ty()?,
+                })
+            }
+            tag => return Err(invalid_tag("value", tag)),
+        })
+    }
+
+    fn imm(&mut self) -> io::Result<ImmediateValue> {
+        Ok(match self.uint()? {
+            0 => ImmediateValue::Int8(self.int()? as i8),
+            1 => ImmediateValue::Int16(self.int()? as i16),
+            2 => ImmediateValue::Int32(self.int()? as i32),
+            3 => ImmediateValue::Int64(self.int()?),
+            4 => {
+                let mut buf = [0; 4];
+                self.r.read_exact(&mut buf)?;
+                ImmediateValue::F32(f32::from_bits(u32::from_le_bytes(buf)))
+            }
+            5 => {
+                let mut buf = [0; 8];
+                self.r.read_exact(&mut buf)?;
+                ImmediateValue::F64(f64::from_bits(u64::from_le_bytes(buf)))
+            }
+            6 => ImmediateValue::Int1(self.int()? != 0),
+            tag => return Err(invalid_tag("immediate", tag)),
+        })
+    }
+
+    fn vec<T>(&mut self, mut f: impl FnMut(&mut Self) -> io::Result<T>) -> io::Result<Vec<T>> {
+        let len = self.uint()?;
+        // Don't trust the length for preallocation, since the input may be broken
+        let mut v = vec![];
+        for _ in 0..len {
+            v.push(f(self)?);
+        }
+        Ok(v)
+    }
+
+    fn index(&mut self, len: usize, what: &str) -> io::Result<usize> {
+        let n = self.uint()?;
+        if n < len {
+            Ok(n)
+        } else {
+            Err(invalid_data(&format!("{} index out of range", what)))
+        }
+    }
+
+    fn int(&mut self) -> io::Result<i64> {
+        let x = self.uint64()?;
+        Ok((x >> 1) as i64 ^ -((x & 1) as i64))
+    }
+
+    fn uint(&mut self) -> io::Result<usize> {
+        self.uint64().map(|x| x as usize)
+    }
+
+    fn uint64(&mut self) -> io::Result<u64> {
+        let mut x = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0];
+            self.r.read_exact(&mut byte)?;
+            x |= ((byte[0] & 0x7f) as u64) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(x);
+            }
+        }
+        Err(invalid_data("integer too long"))
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.uint()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid boolean")),
+        }
+    }
+
+    fn str(&mut self) -> io::Result<String> {
+        let len = self.uint()?;
+        let mut buf = vec![];
+        (&mut self.r).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(buf).map_err(|_| invalid_data("invalid UTF-8 string"))
+    }
+}
+
+fn invalid_tag(what: &str, tag: usize) -> io::Error {
+    invalid_data(&format!("invalid {} tag {}", what, tag))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -1,4 +1,5 @@
-use super::{basic_block::*, function::*, module::Module, opcode::*, types::*, value::*};
+use super::{basic_block::*, function::*, module::Module, opcode::*, types::*, value::*, verify};
+use std::fmt;
 
 #[derive(Debug)]
 pub struct Builder<F: FuncRef> {
@@ -7,6 +8,17 @@ pub struct Builder<F: FuncRef> {
     insert_point: usize,
 }
 
+/// Error returned by the `try_build_*` methods instead of building an invalid instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// No insert point has been set
+    NoInsertPoint,
+    /// The insert point is after the terminator of the block
+    AfterTerminator(BasicBlockId),
+    /// The operands don't have the types the instruction needs
+    InvalidOperands { opcode: Opcode, msg: String },
+}
+
 pub struct FunctionEntity<'a>(pub &'a mut Function);
 
 pub struct FunctionIdWithModule<'a> {
@@ -45,6 +57,38 @@ impl<'a> FuncRef for FunctionIdWithModule<'a> {
     }
 }
 
+macro_rules! try_build_binary_op {
+    ($($name:ident, $build:ident, $opcode:ident;)*) => {$(
+        pub fn $name(&mut self, v1: Value, v2: Value) -> Result<Value, BuildError> {
+            self.check(
+                Opcode::$opcode,
+                vec![Operand::Value(v1), Operand::Value(v2)],
+                v1.get_type(),
+            )?;
+            Ok(self.$build(v1, v2))
+        }
+    )*};
+}
+
+macro_rules! try_build_cast {
+    ($($name:ident, $build:ident, $opcode:ident;)*) => {$(
+        pub fn $name(&mut self, v: Value, ty: Type) -> Result<Value, BuildError> {
+            self.check(Opcode::$opcode, vec![Operand::Value(v)], ty)?;
+            Ok(self.$build(v, ty))
+        }
+    )*};
+}
+
+macro_rules! build_named {
+    ($($name:ident, $build:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        pub fn $name(&mut self, $($arg: $ty,)* name: &str) -> Value {
+            let v = self.$build($($arg),*);
+            self.set_name(v, name);
+            v
+        }
+    )*};
+}
+
 impl<F: FuncRef> Builder<F> {
     pub fn new(func: F) -> Self {
         Self {
@@ -62,6 +106,28 @@ impl<F: FuncRef> Builder<F> {
         self.func.func_ref_mut().append_basic_block()
     }
 
+    /// Appends a basic block printed as `name` instead of `label.N`
+    pub fn append_basic_block_named(&mut self, name: &str) -> BasicBlockId {
+        let id = self.append_basic_block();
+        self.func.func_ref_mut().names.set_block_name(id, name);
+        id
+    }
+
+    /// Names an instruction or a parameter of the function. `name` gets a suffix like `.1` if
+    /// it's already used in the function. Other values are ignored.
+    pub fn set_name(&mut self, v: Value, name: &str) {
+        let names = &mut self.func.func_ref_mut().names;
+        match v {
+            Value::Instruction(InstructionValue { id, .. }) => {
+                names.set_inst_name(id, name);
+            }
+            Value::Argument(ArgumentValue { index, .. }) => {
+                names.set_param_name(index, name);
+            }
+            _ => {}
+        }
+    }
+
     pub fn set_insert_point(&mut self, id: BasicBlockId) {
         self.cur_bb = Some(id);
         let iseq_len = self
@@ -218,10 +284,38 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    pub fn build_udiv(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_udiv(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::UDiv,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_urem(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_urem(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::URem,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
     pub fn build_shl(&mut self, v1: Value, v2: Value) -> Value {
-        // if let Some(konst) = v1.const_shl(&v2) {
-        //     return konst;
-        // }
+        if let Some(konst) = v1.const_shl(&v2) {
+            return konst;
+        }
 
         let inst = self.create_inst_value(
             Opcode::Shl,
@@ -232,6 +326,76 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    pub fn build_lshr(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_lshr(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::LShr,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_ashr(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_ashr(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::AShr,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_and(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_and(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::And,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_or(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_or(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::Or,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_xor(&mut self, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = v1.const_xor(&v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::Xor,
+            vec![Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
     pub fn build_sitofp(&mut self, v: Value, ty: Type) -> Value {
         let inst = self.create_inst_value(Opcode::SIToFP, vec![Operand::Value(v)], ty);
         self.append_inst_to_cur_bb(inst);
@@ -250,6 +414,68 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    pub fn build_zext(&mut self, v: Value, ty: Type) -> Value {
+        if let Some(konst) = v.const_zext(ty) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(Opcode::ZExt, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_trunc(&mut self, v: Value, ty: Type) -> Value {
+        if let Some(konst) = v.const_trunc(ty) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(Opcode::Trunc, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_fpext(&mut self, v: Value, ty: Type) -> Value {
+        if let Some(konst) = v.const_fpcast(ty) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(Opcode::FPExt, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_fptrunc(&mut self, v: Value, ty: Type) -> Value {
+        if let Some(konst) = v.const_fpcast(ty) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(Opcode::FPTrunc, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_bitcast(&mut self, v: Value, ty: Type) -> Value {
+        if let Some(konst) = v.const_bitcast(ty) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(Opcode::Bitcast, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_ptrtoint(&mut self, v: Value, ty: Type) -> Value {
+        let inst = self.create_inst_value(Opcode::PtrToInt, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    pub fn build_inttoptr(&mut self, v: Value, ty: Type) -> Value {
+        let inst = self.create_inst_value(Opcode::IntToPtr, vec![Operand::Value(v)], ty);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
     pub fn build_icmp(&mut self, kind: ICmpKind, v1: Value, v2: Value) -> Value {
         let inst = self.create_inst_value(
             Opcode::ICmp,
@@ -278,6 +504,20 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    pub fn build_select(&mut self, cond: Value, v1: Value, v2: Value) -> Value {
+        if let Some(konst) = cond.const_select(&v1, &v2) {
+            return konst;
+        }
+
+        let inst = self.create_inst_value(
+            Opcode::Select,
+            vec![Operand::Value(cond), Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        );
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
     pub fn build_br(&mut self, dst_id: BasicBlockId) -> Value {
         let inst =
             self.create_inst_value(Opcode::Br, vec![Operand::BasicBlock(dst_id)], Type::Void);
@@ -317,6 +557,34 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    pub fn build_switch(
+        &mut self,
+        cond: Value,
+        default: BasicBlockId,
+        cases: Vec<(Value, BasicBlockId)>,
+    ) -> Value {
+        let cur_bb_id = self.cur_bb.unwrap();
+        let mut operands = vec![Operand::Value(cond), Operand::BasicBlock(default)];
+        for &(v, bb) in &cases {
+            operands.push(Operand::Value(v));
+            operands.push(Operand::BasicBlock(bb));
+        }
+        let inst = self.create_inst_value(Opcode::Switch, operands, Type::Void);
+        self.append_inst_to_cur_bb(inst);
+
+        self.with_function(|f| {
+            for dst in Some(default)
+                .into_iter()
+                .chain(cases.iter().map(|&(_, bb)| bb))
+            {
+                f.basic_block_ref_mut(cur_bb_id).succ.insert(dst);
+                f.basic_block_ref_mut(dst).pred.insert(cur_bb_id);
+            }
+        });
+
+        inst
+    }
+
     pub fn build_phi(&mut self, pairs: Vec<(Value, BasicBlockId)>) -> Value {
         let ty = pairs.get(0).unwrap().0.get_type();
         let mut operands = vec![];
@@ -336,7 +604,7 @@ impl<F: FuncRef> Builder<F> {
             .types
             .base
             .borrow()
-            .as_function_ty(f.get_type())
+            .as_callee_ty(f.get_type())
             .unwrap()
             .ret_ty;
         let mut operands = vec![Operand::Value(f)];
@@ -352,6 +620,245 @@ impl<F: FuncRef> Builder<F> {
         inst
     }
 
+    /// Marks the end of a path that is never executed, e.g. the one after a call to `cilk.trap`
+    pub fn build_unreachable(&mut self) -> Value {
+        let inst = self.create_inst_value(Opcode::Unreachable, vec![], Type::Void);
+        self.append_inst_to_cur_bb(inst);
+        inst
+    }
+
+    // Same as the `build_*` methods but also name the result
+
+    build_named! {
+        build_alloca_named, build_alloca(ty: Type);
+        build_gep_named, build_gep(v: Value, indices: Vec<Value>);
+        build_load_named, build_load(v: Value);
+        build_add_named, build_add(v1: Value, v2: Value);
+        build_sub_named, build_sub(v1: Value, v2: Value);
+        build_mul_named, build_mul(v1: Value, v2: Value);
+        build_div_named, build_div(v1: Value, v2: Value);
+        build_rem_named, build_rem(v1: Value, v2: Value);
+        build_udiv_named, build_udiv(v1: Value, v2: Value);
+        build_urem_named, build_urem(v1: Value, v2: Value);
+        build_shl_named, build_shl(v1: Value, v2: Value);
+        build_lshr_named, build_lshr(v1: Value, v2: Value);
+        build_ashr_named, build_ashr(v1: Value, v2: Value);
+        build_and_named, build_and(v1: Value, v2: Value);
+        build_or_named, build_or(v1: Value, v2: Value);
+        build_xor_named, build_xor(v1: Value, v2: Value);
+        build_sitofp_named, build_sitofp(v: Value, ty: Type);
+        build_fptosi_named, build_fptosi(v: Value, ty: Type);
+        build_sext_named, build_sext(v: Value, ty: Type);
+        build_zext_named, build_zext(v: Value, ty: Type);
+        build_trunc_named, build_trunc(v: Value, ty: Type);
+        build_fpext_named, build_fpext(v: Value, ty: Type);
+        build_fptrunc_named, build_fptrunc(v: Value, ty: Type);
+        build_bitcast_named, build_bitcast(v: Value, ty: Type);
+        build_ptrtoint_named, build_ptrtoint(v: Value, ty: Type);
+        build_inttoptr_named, build_inttoptr(v: Value, ty: Type);
+        build_icmp_named, build_icmp(kind: ICmpKind, v1: Value, v2: Value);
+        build_fcmp_named, build_fcmp(kind: FCmpKind, v1: Value, v2: Value);
+        build_select_named, build_select(cond: Value, v1: Value, v2: Value);
+        build_phi_named, build_phi(pairs: Vec<(Value, BasicBlockId)>);
+        build_call_named, build_call(f: Value, args: Vec<Value>);
+    }
+
+    // Checked versions of the `build_*` methods. They build nothing and return an error if the
+    // instruction would be invalid, and otherwise do what the unchecked version does.
+
+    pub fn try_build_alloca(&mut self, ty: Type) -> Result<Value, BuildError> {
+        self.check_insert_point()?;
+        Ok(self.build_alloca(ty))
+    }
+
+    pub fn try_build_gep(&mut self, v: Value, indices: Vec<Value>) -> Result<Value, BuildError> {
+        let mut operands = vec![Operand::Value(v)];
+        operands.extend(indices.iter().map(|v| Operand::Value(*v)));
+        let types = &self.func.func_ref().types;
+        let ty = verify::gep_elem_ty(types, &operands)
+            .map(|elem_ty| types.new_pointer_ty(elem_ty))
+            .map_err(|msg| BuildError::InvalidOperands {
+                opcode: Opcode::GetElementPtr,
+                msg,
+            })?;
+        self.check(Opcode::GetElementPtr, operands, ty)?;
+        Ok(self.build_gep(v, indices))
+    }
+
+    pub fn try_build_load(&mut self, v: Value) -> Result<Value, BuildError> {
+        let ty = match v.get_type() {
+            ty @ Type::Pointer(_) => self.func.func_ref().types.get_element_ty(ty, None).unwrap(),
+            _ => Type::Void,
+        };
+        self.check(Opcode::Load, vec![Operand::Value(v)], ty)?;
+        Ok(self.build_load(v))
+    }
+
+    pub fn try_build_store(&mut self, src: Value, dst: Value) -> Result<Value, BuildError> {
+        self.check(
+            Opcode::Store,
+            vec![Operand::Value(src), Operand::Value(dst)],
+            Type::Void,
+        )?;
+        Ok(self.build_store(src, dst))
+    }
+
+    try_build_binary_op! {
+        try_build_add, build_add, Add;
+        try_build_sub, build_sub, Sub;
+        try_build_mul, build_mul, Mul;
+        try_build_div, build_div, Div;
+        try_build_rem, build_rem, Rem;
+        try_build_udiv, build_udiv, UDiv;
+        try_build_urem, build_urem, URem;
+        try_build_shl, build_shl, Shl;
+        try_build_lshr, build_lshr, LShr;
+        try_build_ashr, build_ashr, AShr;
+        try_build_and, build_and, And;
+        try_build_or, build_or, Or;
+        try_build_xor, build_xor, Xor;
+    }
+
+    try_build_cast! {
+        try_build_sitofp, build_sitofp, SIToFP;
+        try_build_fptosi, build_fptosi, FPToSI;
+        try_build_sext, build_sext, Sext;
+        try_build_zext, build_zext, ZExt;
+        try_build_trunc, build_trunc, Trunc;
+        try_build_fpext, build_fpext, FPExt;
+        try_build_fptrunc, build_fptrunc, FPTrunc;
+        try_build_bitcast, build_bitcast, Bitcast;
+        try_build_ptrtoint, build_ptrtoint, PtrToInt;
+        try_build_inttoptr, build_inttoptr, IntToPtr;
+    }
+
+    pub fn try_build_icmp(
+        &mut self,
+        kind: ICmpKind,
+        v1: Value,
+        v2: Value,
+    ) -> Result<Value, BuildError> {
+        self.check(
+            Opcode::ICmp,
+            vec![
+                Operand::ICmpKind(kind),
+                Operand::Value(v1),
+                Operand::Value(v2),
+            ],
+            Type::i1,
+        )?;
+        Ok(self.build_icmp(kind, v1, v2))
+    }
+
+    pub fn try_build_fcmp(
+        &mut self,
+        kind: FCmpKind,
+        v1: Value,
+        v2: Value,
+    ) -> Result<Value, BuildError> {
+        self.check(
+            Opcode::FCmp,
+            vec![
+                Operand::FCmpKind(kind),
+                Operand::Value(v1),
+                Operand::Value(v2),
+            ],
+            Type::i1,
+        )?;
+        Ok(self.build_fcmp(kind, v1, v2))
+    }
+
+    pub fn try_build_select(
+        &mut self,
+        cond: Value,
+        v1: Value,
+        v2: Value,
+    ) -> Result<Value, BuildError> {
+        self.check(
+            Opcode::Select,
+            vec![Operand::Value(cond), Operand::Value(v1), Operand::Value(v2)],
+            v1.get_type(),
+        )?;
+        Ok(self.build_select(cond, v1, v2))
+    }
+
+    pub fn try_build_br(&mut self, dst_id: BasicBlockId) -> Result<Value, BuildError> {
+        self.check(Opcode::Br, vec![Operand::BasicBlock(dst_id)], Type::Void)?;
+        Ok(self.build_br(dst_id))
+    }
+
+    pub fn try_build_cond_br(
+        &mut self,
+        cond: Value,
+        bb1: BasicBlockId,
+        bb2: BasicBlockId,
+    ) -> Result<Value, BuildError> {
+        self.check(
+            Opcode::CondBr,
+            vec![
+                Operand::Value(cond),
+                Operand::BasicBlock(bb1),
+                Operand::BasicBlock(bb2),
+            ],
+            Type::Void,
+        )?;
+        Ok(self.build_cond_br(cond, bb1, bb2))
+    }
+
+    pub fn try_build_switch(
+        &mut self,
+        cond: Value,
+        default: BasicBlockId,
+        cases: Vec<(Value, BasicBlockId)>,
+    ) -> Result<Value, BuildError> {
+        let mut operands = vec![Operand::Value(cond), Operand::BasicBlock(default)];
+        for &(v, bb) in &cases {
+            operands.push(Operand::Value(v));
+            operands.push(Operand::BasicBlock(bb));
+        }
+        self.check(Opcode::Switch, operands, Type::Void)?;
+        Ok(self.build_switch(cond, default, cases))
+    }
+
+    pub fn try_build_phi(
+        &mut self,
+        pairs: Vec<(Value, BasicBlockId)>,
+    ) -> Result<Value, BuildError> {
+        let ty = pairs.first().map_or(Type::Void, |(v, _)| v.get_type());
+        let mut operands = vec![];
+        for &(v, bb) in &pairs {
+            operands.push(Operand::Value(v));
+            operands.push(Operand::BasicBlock(bb));
+        }
+        self.check(Opcode::Phi, operands, ty)?;
+        Ok(self.build_phi(pairs))
+    }
+
+    pub fn try_build_call(&mut self, f: Value, args: Vec<Value>) -> Result<Value, BuildError> {
+        let ret_ty = self
+            .func
+            .func_ref()
+            .types
+            .base
+            .borrow()
+            .as_callee_ty(f.get_type())
+            .map_or(Type::Void, |f_ty| f_ty.ret_ty);
+        let mut operands = vec![Operand::Value(f)];
+        operands.extend(args.iter().map(|&v| Operand::Value(v)));
+        self.check(Opcode::Call, operands, ret_ty)?;
+        Ok(self.build_call(f, args))
+    }
+
+    pub fn try_build_ret(&mut self, v: Value) -> Result<Value, BuildError> {
+        self.check(Opcode::Ret, vec![Operand::Value(v)], Type::Void)?;
+        Ok(self.build_ret(v))
+    }
+
+    pub fn try_build_unreachable(&mut self) -> Result<Value, BuildError> {
+        self.check(Opcode::Unreachable, vec![], Type::Void)?;
+        Ok(self.build_unreachable())
+    }
+
     pub fn is_last_inst_terminator(&self) -> bool {
         let bb = self.func.func_ref().basic_block_ref(self.cur_bb.unwrap());
         bb.iseq_ref().last().map_or(false, |i| {
@@ -367,6 +874,34 @@ impl<F: FuncRef> Builder<F> {
 
     // Utils
 
+    fn check_insert_point(&self) -> Result<(), BuildError> {
+        let bb_id = self.cur_bb.ok_or(BuildError::NoInsertPoint)?;
+        let func = self.func.func_ref();
+        let iseq = func.basic_block_ref(bb_id).iseq_ref();
+        let after_terminator = self
+            .insert_point
+            .checked_sub(1)
+            .and_then(|i| iseq.get(i))
+            .map_or(false, |v| {
+                func.inst_table[v.as_instruction().id]
+                    .opcode
+                    .is_terminator()
+            });
+        if after_terminator {
+            Err(BuildError::AfterTerminator(bb_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check(&self, opcode: Opcode, operands: Vec<Operand>, ty: Type) -> Result<(), BuildError> {
+        self.check_insert_point()?;
+        let func = self.func.func_ref();
+        let inst = Instruction::new(opcode, operands, ty, self.cur_bb.unwrap());
+        verify::check_inst_types(&func.types, func.get_return_type(), &inst)
+            .map_err(|msg| BuildError::InvalidOperands { opcode, msg })
+    }
+
     fn create_inst_value(&mut self, opcode: Opcode, operands: Vec<Operand>, ret_ty: Type) -> Value {
         let inst = Instruction::new(opcode, operands, ret_ty, self.cur_bb.unwrap());
         let inst_id = self.func.func_ref_mut().alloc_inst(inst);
@@ -394,3 +929,19 @@ impl<F: FuncRef> Builder<F> {
         f(function)
     }
 }
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoInsertPoint => write!(f, "no insert point"),
+            Self::AfterTerminator(bb) => {
+                write!(
+                    f,
+                    "cannot insert after the terminator of label.{}",
+                    bb.index()
+                )
+            }
+            Self::InvalidOperands { opcode, msg } => write!(f, "{}: {}", opcode.to_string(), msg),
+        }
+    }
+}
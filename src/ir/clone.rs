@@ -0,0 +1,187 @@
+// Copies functions and basic blocks, remapping every reference to the copied values.
+//
+// `value_map` maps values of the source function to the values that replace them in the copy.
+// Entries given by the caller are used as they are (e.g. an argument mapped to a constant when
+// specializing a function), and the argument and instruction values of the copy are added to it.
+
+use super::{
+    basic_block::{BasicBlockId, BasicBlocks},
+    function::{Function, FunctionId, Names},
+    module::Module,
+    opcode::{Instruction, InstructionId, Operand},
+    value::{ArgumentValue, InstructionValue, Value},
+};
+use id_arena::{Arena, ArenaBehavior, DefaultArenaBehavior};
+use rustc_hash::FxHashMap;
+
+/// Clones `src` into a new function named `new_name` and returns its id. Calls to `src` in the
+/// body still call `src` unless `value_map` says otherwise.
+pub fn clone_function(
+    module: &mut Module,
+    src: FunctionId,
+    new_name: &str,
+    value_map: &mut FxHashMap<Value, Value>,
+) -> FunctionId {
+    let f = module.function_ref(src);
+    let params_ty = (0..f.get_params_len())
+        .map(|i| f.get_param_type(i).unwrap())
+        .collect::<Vec<_>>();
+    let order = f.basic_blocks.order.clone();
+    let param_names = f.names.params.clone();
+    let new = Function {
+        name: new_name.to_string(),
+        ty: f.ty,
+        basic_blocks: BasicBlocks::new(),
+        inst_table: Arena::new(),
+        id: None,
+        analyses: vec![],
+        types: f.types.clone(),
+        is_internal: f.is_internal,
+        is_prototype: f.is_prototype,
+        names: Names::default(),
+    };
+    let new = module.add_function(new);
+    for (idx, name) in param_names {
+        module
+            .function_ref_mut(new)
+            .names
+            .set_param_name(idx, &name);
+    }
+
+    for (index, ty) in params_ty.into_iter().enumerate() {
+        value_map
+            .entry(Value::Argument(ArgumentValue {
+                func_id: src,
+                index,
+                ty,
+            }))
+            .or_insert(Value::Argument(ArgumentValue {
+                func_id: new,
+                index,
+                ty,
+            }));
+    }
+    clone_blocks(module, src, &order, new, value_map);
+    new
+}
+
+/// Clones `blocks` of `src` to the end of `dst` and returns the new blocks in the same order.
+/// Branches to blocks outside `blocks` are left as they are, so they still point to the original
+/// blocks. Arguments of `src` that aren't in `value_map` are left as they are too, so they should
+/// be mapped unless `src` is `dst`.
+pub fn clone_blocks(
+    module: &mut Module,
+    src: FunctionId,
+    blocks: &[BasicBlockId],
+    dst: FunctionId,
+    value_map: &mut FxHashMap<Value, Value>,
+) -> Vec<BasicBlockId> {
+    // Take copies first since `src` may be `dst`
+    let src_f = module.function_ref(src);
+    let insts = blocks
+        .iter()
+        .map(|&b| {
+            src_f.basic_blocks.arena[b]
+                .iseq_ref()
+                .iter()
+                .map(|v| {
+                    let id = v.get_inst_id().unwrap();
+                    let name = src_f.names.insts.get(&id).cloned();
+                    (id, src_f.inst_table[id].clone(), name)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let edges = blocks
+        .iter()
+        .map(|&b| {
+            let block = &src_f.basic_blocks.arena[b];
+            let name = src_f.names.blocks.get(&b).cloned();
+            (block.pred.clone(), block.succ.clone(), name)
+        })
+        .collect::<Vec<_>>();
+
+    let dst_f = module.function_ref_mut(dst);
+    let new_blocks = blocks
+        .iter()
+        .map(|_| dst_f.append_basic_block())
+        .collect::<Vec<_>>();
+    let block_map = blocks
+        .iter()
+        .copied()
+        .zip(new_blocks.iter().copied())
+        .collect::<FxHashMap<_, _>>();
+
+    // Instructions may refer to ones that come later (e.g. phi in a loop), so decide every id
+    // before allocating any of them
+    let arena_id = DefaultArenaBehavior::arena_id(dst_f.inst_table.next_id());
+    for (index, &(id, ref inst, _)) in (dst_f.inst_table.len()..).zip(insts.iter().flatten()) {
+        let new_id: InstructionId = DefaultArenaBehavior::new_id(arena_id, index);
+        value_map.insert(
+            Value::Instruction(InstructionValue {
+                func_id: src,
+                id,
+                ty: inst.ty,
+            }),
+            Value::Instruction(InstructionValue {
+                func_id: dst,
+                id: new_id,
+                ty: inst.ty,
+            }),
+        );
+    }
+
+    let map_value = |v: &Value| *value_map.get(v).unwrap_or(v);
+    let map_block = |b: &BasicBlockId| *block_map.get(b).unwrap_or(b);
+    let mut new_ids = vec![];
+    for (insts, &new_block) in insts.iter().zip(new_blocks.iter()) {
+        for (_, inst, name) in insts {
+            let operands = inst
+                .operands
+                .iter()
+                .map(|operand| match operand {
+                    Operand::Value(v) => Operand::Value(map_value(v)),
+                    Operand::BasicBlock(b) => Operand::BasicBlock(map_block(b)),
+                    _ => *operand,
+                })
+                .collect();
+            let mut new_inst = Instruction::new(inst.opcode, operands, inst.ty, new_block);
+            let id = dst_f.inst_table.next_id();
+            new_inst.set_id(id);
+            dst_f.inst_table.alloc(new_inst);
+            new_ids.push(id);
+            if let Some(name) = name {
+                dst_f.names.set_inst_name(id, name);
+            }
+            dst_f.basic_blocks.arena[new_block]
+                .iseq_ref_mut()
+                .push(Value::Instruction(InstructionValue {
+                    func_id: dst,
+                    id,
+                    ty: inst.ty,
+                }));
+        }
+    }
+    for id in new_ids {
+        dst_f.inst_table[id].set_users(&dst_f.inst_table);
+    }
+
+    for ((pred, succ, name), &new_block) in edges.into_iter().zip(new_blocks.iter()) {
+        if let Some(name) = name {
+            dst_f.names.set_block_name(new_block, &name);
+        }
+        let block = &mut dst_f.basic_blocks.arena[new_block];
+        block.pred = pred
+            .iter()
+            .filter_map(|b| block_map.get(b).copied())
+            .collect();
+        block.succ = succ.iter().map(map_block).collect();
+        if src == dst {
+            for s in succ.iter().filter(|s| !block_map.contains_key(s)) {
+                dst_f.basic_blocks.arena[*s].pred.insert(new_block);
+            }
+        }
+    }
+
+    new_blocks
+}
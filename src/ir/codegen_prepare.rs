@@ -1,4 +1,14 @@
-use crate::ir::{builder::*, function::*, module::*, opcode::*};
+use crate::ir::{
+    basic_block::*, builder::*, function::*, module::*, opcode::*, types::*, value::*,
+};
+use crate::traits::pass::ModulePassTrait;
+
+/// Switches with fewer cases than this are always lowered to a compare tree.
+pub const JUMP_TABLE_MIN_CASES: usize = 4;
+
+/// Minimum percentage of the case value range that must be covered by cases
+/// for a switch to be lowered to a jump table.
+pub const JUMP_TABLE_MIN_DENSITY: usize = 40;
 
 pub struct CodegenPrepare {}
 
@@ -6,6 +16,18 @@ pub struct CodegenPrepareOnFunction<'a> {
     func: &'a mut Function,
 }
 
+impl ModulePassTrait for CodegenPrepare {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "CodegenPrepare"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl CodegenPrepare {
     pub fn new() -> Self {
         Self {}
@@ -13,7 +35,7 @@ impl CodegenPrepare {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
 
@@ -24,6 +46,11 @@ impl CodegenPrepare {
 
 impl<'a> CodegenPrepareOnFunction<'a> {
     pub fn run(&mut self) {
+        self.lower_sparse_switches();
+        self.sink_geps();
+    }
+
+    fn sink_geps(&mut self) {
         let mut geps_to_be_sunk = vec![];
 
         for (_, block) in &self.func.basic_blocks.arena {
@@ -72,4 +99,169 @@ impl<'a> CodegenPrepareOnFunction<'a> {
                 .all(|us| self.func.inst_table[us[0]].parent == self.func.inst_table[us[1]].parent)
         }
     }
+
+    fn lower_sparse_switches(&mut self) {
+        let mut switches = vec![];
+
+        for (_, block) in &self.func.basic_blocks.arena {
+            for inst_id in block.iseq.borrow().iter().map(|i| i.as_instruction().id) {
+                let inst = &self.func.inst_table[inst_id];
+                if inst.opcode == Opcode::Switch && !use_jump_table(inst) {
+                    switches.push(inst_id);
+                }
+            }
+        }
+
+        debug!(println!(
+            "CodegenPrepare: {} switches to be lowered",
+            switches.len()
+        ));
+
+        for switch_id in switches {
+            self.lower_switch(switch_id)
+        }
+    }
+
+    /// Replaces a switch with a binary tree of compares and conditional branches.
+    fn lower_switch(&mut self, switch_id: InstructionId) {
+        let switch = &self.func.inst_table[switch_id];
+        let block = switch.parent;
+        let cond = *switch.operands[0].as_value();
+        let default = *switch.operands[1].as_basic_block();
+        let mut cases: Vec<(i64, Value, BasicBlockId)> = switch.operands[2..]
+            .chunks(2)
+            .map(|c| {
+                let v = *c[0].as_value();
+                (v.as_imm().as_i64().unwrap(), v, *c[1].as_basic_block())
+            })
+            .collect();
+        cases.sort_by_key(|&(i, _, _)| i);
+
+        let targets = self.func.basic_blocks.arena[block].succ.clone();
+        for &target in &targets {
+            self.func.basic_blocks.arena[target].pred.remove(&block);
+        }
+        self.func.basic_blocks.arena[block].succ.clear();
+        self.func.remove_inst(switch_id);
+
+        let next = {
+            let order = &self.func.basic_blocks.order;
+            let pos = order.iter().position(|&b| b == block).unwrap();
+            order.get(pos + 1).copied()
+        };
+        let mut new_blocks = vec![block];
+        let mut builder = Builder::new(FunctionEntity(self.func));
+        build_compare_tree(
+            &mut builder,
+            block,
+            next,
+            cond,
+            default,
+            &cases,
+            &mut new_blocks,
+        );
+
+        // Phis in the targets now have to take their incoming values from the new blocks
+        for target in targets {
+            let mut preds: Vec<BasicBlockId> = new_blocks
+                .iter()
+                .filter(|b| self.func.basic_blocks.arena[target].pred.contains(b))
+                .copied()
+                .collect();
+            preds.sort_by_key(|b| b.index());
+            for val in self.func.basic_blocks.arena[target].iseq_ref().iter() {
+                let phi = &mut self.func.inst_table[val.as_instruction().id];
+                if phi.opcode != Opcode::Phi {
+                    break;
+                }
+                let pos = match phi
+                    .operands
+                    .iter()
+                    .position(|op| *op == Operand::BasicBlock(block))
+                {
+                    Some(pos) => pos,
+                    None => continue,
+                };
+                let incoming = phi.operands[pos - 1];
+                phi.operands.drain(pos - 1..=pos);
+                for &pred in &preds {
+                    phi.operands.push(incoming);
+                    phi.operands.push(Operand::BasicBlock(pred));
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if `switch` is dense enough to be lowered to a jump table.
+pub fn use_jump_table(switch: &Instruction) -> bool {
+    let cond = switch.operands[0].as_value();
+    if !matches!(cond.get_type(), Type::i32 | Type::i64) || cond.get_imm().is_some() {
+        return false;
+    }
+    let cases: Vec<i64> = switch.operands[2..]
+        .iter()
+        .step_by(2)
+        .map(|v| v.as_value().as_imm().as_i64().unwrap())
+        .collect();
+    if cases.len() < JUMP_TABLE_MIN_CASES {
+        return false;
+    }
+    let min = *cases.iter().min().unwrap();
+    let max = *cases.iter().max().unwrap();
+    match max.checked_sub(min) {
+        Some(range) if min as i32 as i64 == min && range < i32::MAX as i64 => {
+            cases.len() * 100 >= (range as usize + 1) * JUMP_TABLE_MIN_DENSITY
+        }
+        _ => false,
+    }
+}
+
+fn build_compare_tree(
+    builder: &mut Builder<FunctionEntity>,
+    block: BasicBlockId,
+    next: Option<BasicBlockId>,
+    cond: Value,
+    default: BasicBlockId,
+    cases: &[(i64, Value, BasicBlockId)],
+    new_blocks: &mut Vec<BasicBlockId>,
+) {
+    let mut new_block = |builder: &mut Builder<FunctionEntity>| {
+        let id = match next {
+            Some(next) => builder.func.func_ref_mut().append_basic_block_before(next),
+            None => builder.append_basic_block(),
+        };
+        new_blocks.push(id);
+        id
+    };
+
+    builder.set_insert_point(block);
+
+    if cases.is_empty() {
+        builder.build_br(default);
+        return;
+    }
+
+    // Few cases are simply tested one by one
+    if cases.len() <= 3 {
+        for (i, &(_, val, target)) in cases.iter().enumerate() {
+            let else_ = if i == cases.len() - 1 {
+                default
+            } else {
+                new_block(builder)
+            };
+            let c = builder.build_icmp(ICmpKind::Eq, cond, val);
+            builder.build_cond_br(c, target, else_);
+            builder.set_insert_point(else_);
+        }
+        return;
+    }
+
+    let mid = cases.len() / 2;
+    let lhs = new_block(builder);
+    let rhs = new_block(builder);
+    let c = builder.build_icmp(ICmpKind::Lt, cond, cases[mid].1);
+    builder.build_cond_br(c, lhs, rhs);
+    build_compare_tree(builder, lhs, next, cond, default, &cases[..mid], new_blocks);
+    build_compare_tree(builder, rhs, next, cond, default, &cases[mid..], new_blocks);
 }
@@ -0,0 +1,182 @@
+// Constant expressions: operations on constants that can be used as operands of instructions and
+// as initializers of global variables.
+//
+// An expression that only involves immediates folds into an immediate. One that refers to the
+// address of a global variable or a function (e.g. a field of a global struct) is left to the
+// code generator, which emits it as a relocation against the symbol, or to the JIT, which
+// resolves it when it lays out memory.
+
+use super::{
+    module::Module,
+    opcode::Opcode,
+    types::{Type, TypeSize, Types},
+    value::{ConstExprValue, Value},
+};
+use id_arena::{Arena, Id};
+use rustc_hash::FxHashMap;
+
+pub type ConstExprId = Id<ConstExpr>;
+
+/// Expressions are interned, so equal ones share the same id
+#[derive(Clone, Default)]
+pub struct ConstExprs {
+    pub arena: Arena<ConstExpr>,
+    ids: FxHashMap<ConstExpr, ConstExprId>,
+}
+
+/// `operands` are the pointer and the indices for `GetElementPtr`, the operand for casts and the
+/// two operands for binary operations. All of them are constants.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConstExpr {
+    pub opcode: Opcode,
+    pub operands: Vec<Value>,
+    pub ty: Type,
+}
+
+impl ConstExprs {
+    pub fn get(&mut self, expr: ConstExpr) -> ConstExprId {
+        if let Some(id) = self.ids.get(&expr) {
+            return *id;
+        }
+        let id = self.arena.alloc(expr.clone());
+        self.ids.insert(expr, id);
+        id
+    }
+
+    /// Folds the expression `v` into an immediate, or into the global variable or the function
+    /// it refers to if it only changes the type of them. Returns None if `v` is not an expression
+    /// or cannot be folded.
+    pub fn fold(&self, v: &Value) -> Option<Value> {
+        let id = match v {
+            Value::ConstExpr(ConstExprValue { id, .. }) => *id,
+            _ => return None,
+        };
+        let expr = &self.arena[id];
+        let operands = expr
+            .operands
+            .iter()
+            .map(|op| self.fold(op).unwrap_or(*op))
+            .collect::<Vec<_>>();
+        let folded = match expr.opcode {
+            Opcode::GetElementPtr if operands[1..].iter().all(is_zero) => {
+                operands[0].const_bitcast(expr.ty)
+            }
+            Opcode::Add if is_zero(&operands[1]) => Some(operands[0]),
+            Opcode::Add if is_zero(&operands[0]) => Some(operands[1]),
+            Opcode::Sub if is_zero(&operands[1]) => Some(operands[0]),
+            opcode => opcode.fold_const(&operands, expr.ty),
+        }?;
+        // e.g. `ptrtoint` of a global doesn't fold into the global itself
+        if folded.get_type() == expr.ty {
+            Some(folded)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the global variable or the function (as a value of pointer type) whose address
+    /// `v` is, and the byte offset from it
+    pub fn resolve_address(&self, types: &Types, v: &Value) -> Option<(Value, i64)> {
+        let expr = match v {
+            Value::Global(_) | Value::Function(_) => return Some((*v, 0)),
+            Value::ConstExpr(ConstExprValue { id, .. }) => &self.arena[*id],
+            _ => return None,
+        };
+        let operands = &expr.operands;
+        match expr.opcode {
+            Opcode::Bitcast | Opcode::PtrToInt | Opcode::IntToPtr => {
+                self.resolve_address(types, &operands[0])
+            }
+            Opcode::GetElementPtr => {
+                let (base, mut offset) = self.resolve_address(types, &operands[0])?;
+                let mut ty = operands[0].get_type();
+                for idx in &operands[1..] {
+                    let i = idx.get_imm()?.as_i64()?;
+                    offset += match ty {
+                        Type::Struct(_) => *types
+                            .base
+                            .borrow()
+                            .as_struct_ty(ty)?
+                            .get_elem_offset(i as usize)?
+                            as i64,
+                        _ => {
+                            let elem_ty = types.get_element_ty(ty, None)?;
+                            i * elem_ty.size_in_byte(types) as i64
+                        }
+                    };
+                    ty = types.get_element_ty(ty, Some(idx))?;
+                }
+                Some((base, offset))
+            }
+            Opcode::Add | Opcode::Sub => {
+                let (base, offset, imm) = match (
+                    self.resolve_address(types, &operands[0]),
+                    operands[1].get_imm(),
+                ) {
+                    (Some((base, offset)), Some(imm)) => (base, offset, imm.as_i64()?),
+                    _ if expr.opcode == Opcode::Add => {
+                        let (base, offset) = self.resolve_address(types, &operands[1])?;
+                        (base, offset, operands[0].get_imm()?.as_i64()?)
+                    }
+                    _ => return None,
+                };
+                Some((
+                    base,
+                    if expr.opcode == Opcode::Add {
+                        offset + imm
+                    } else {
+                        offset - imm
+                    },
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_string(&self, parent: &Module, id: ConstExprId) -> String {
+        let expr = &self.arena[id];
+        format!(
+            "{} ({})",
+            expr.opcode.to_string(),
+            expr.operands
+                .iter()
+                .map(|op| op.to_string(parent, false))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Module {
+    /// Returns the constant expression `opcode operands` of type `ty`. It is not folded; see
+    /// `ConstExprs::fold`.
+    pub fn const_expr(&mut self, opcode: Opcode, operands: Vec<Value>, ty: Type) -> Value {
+        let id = self.const_exprs.get(ConstExpr {
+            opcode,
+            operands,
+            ty,
+        });
+        Value::ConstExpr(ConstExprValue { id, ty })
+    }
+
+    /// Returns `getelementptr ptr, indices` as a constant expression. Returns None if `ptr` is
+    /// not a pointer or any of `indices` is not an immediate.
+    pub fn const_gep(&mut self, ptr: Value, indices: Vec<Value>) -> Option<Value> {
+        if !matches!(ptr.get_type(), Type::Pointer(_))
+            || !indices.iter().all(|idx| matches!(idx, Value::Immediate(_)))
+        {
+            return None;
+        }
+        let ty = self
+            .types
+            .get_element_ty_with_indices(ptr.get_type(), &indices)?;
+        let ty = self.types.new_pointer_ty(ty);
+        let mut operands = vec![ptr];
+        operands.extend(indices);
+        Some(self.const_expr(Opcode::GetElementPtr, operands, ty))
+    }
+}
+
+fn is_zero(v: &Value) -> bool {
+    v.get_imm().and_then(|imm| imm.as_i64()) == Some(0)
+}
@@ -1,16 +1,32 @@
 use crate::ir::{
+    const_expr::ConstExprs,
     function::Function,
+    global_val::Initializer,
     module::Module,
     opcode::{Instruction, Opcode, Operand},
-    value::Value,
+    value::{ImmediateValue, Value},
 };
 use std::collections::VecDeque;
 // use rustc_hash::FxHashMap;
+use crate::traits::pass::ModulePassTrait;
 
 pub struct ConstantFolding {}
 
 struct ConstantFoldingOnFunction<'a> {
     cur_func: &'a mut Function,
+    const_exprs: &'a ConstExprs,
+}
+
+impl ModulePassTrait for ConstantFolding {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "ConstantFolding"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
 }
 
 impl ConstantFolding {
@@ -19,18 +35,44 @@ impl ConstantFolding {
     }
 
     pub fn run_on_module(&mut self, module: &mut Module) {
+        for (_, g) in &mut module.global_vars.arena {
+            if let Some(init) = &mut g.init {
+                fold_initializer(init, &module.const_exprs)
+            }
+        }
         for (_, func) in &mut module.functions {
-            ConstantFoldingOnFunction::new(func).run()
+            ConstantFoldingOnFunction::new(func, &module.const_exprs).run()
         }
     }
 }
 
+fn fold_initializer(init: &mut Initializer, const_exprs: &ConstExprs) {
+    match init {
+        Initializer::Expr(v) => match const_exprs.fold(v) {
+            Some(Value::Immediate(imm)) => *init = Initializer::Immediate(imm),
+            Some(folded) => *v = folded,
+            None => {}
+        },
+        Initializer::Array(elems) | Initializer::Struct(elems) => {
+            for e in elems {
+                fold_initializer(e, const_exprs)
+            }
+        }
+        Initializer::Zero | Initializer::Immediate(_) | Initializer::Address(_, _) => {}
+    }
+}
+
 impl<'a> ConstantFoldingOnFunction<'a> {
-    pub fn new(cur_func: &'a mut Function) -> Self {
-        Self { cur_func }
+    pub fn new(cur_func: &'a mut Function, const_exprs: &'a ConstExprs) -> Self {
+        Self {
+            cur_func,
+            const_exprs,
+        }
     }
 
     pub fn run(&mut self) {
+        self.fold_const_exprs();
+
         let mut foldable = VecDeque::new();
         // TODO: Had better implement a conversion from Mul/Div to Shl/Shr in instcombine pass
         let mut to_shift = VecDeque::new();
@@ -89,16 +131,85 @@ impl<'a> ConstantFoldingOnFunction<'a> {
                     .unwrap() as i8,
             ))
         }
+
+        if self.fold_cond_br() {
+            self.cur_func.remove_unreachable_blocks();
+        }
+    }
+
+    /// Replaces conditional branches on a constant with unconditional ones. Returns true if any
+    /// branch is replaced.
+    fn fold_cond_br(&mut self) -> bool {
+        let mut changed = false;
+        for block in self.cur_func.basic_blocks.order.clone() {
+            let br_id = match self.cur_func.basic_blocks.arena[block].iseq_ref().last() {
+                Some(br) => br.get_inst_id().unwrap(),
+                None => continue,
+            };
+            let br = &self.cur_func.inst_table[br_id];
+            if br.opcode != Opcode::CondBr {
+                continue;
+            }
+            let cond = match br.operands[0].get_value().and_then(|v| v.get_imm()) {
+                Some(ImmediateValue::Int1(cond)) => *cond,
+                _ => continue,
+            };
+            let taken = *br.operands[if cond { 1 } else { 2 }].as_basic_block();
+            self.cur_func.replace_terminator_with_br(block, taken);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Replaces constant expressions in operands with what they fold into
+    fn fold_const_exprs(&mut self) {
+        for (_, inst) in &mut self.cur_func.inst_table {
+            for operand in &mut inst.operands {
+                if let Operand::Value(v) = operand {
+                    if let Some(folded) = self.const_exprs.fold(v) {
+                        *v = folded
+                    }
+                }
+            }
+        }
     }
 
     fn is_foldable(inst: &Instruction) -> bool {
+        // A bitcast may fold away even if its operand is not an immediate (e.g. a global)
+        if inst.opcode == Opcode::Bitcast {
+            return inst.fold_const().is_some();
+        }
         matches!(
             inst.opcode,
-            Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div
-        ) && inst
-            .operands
-            .iter()
-            .all(|op| matches!(op, Operand::Value(Value::Immediate(_))))
+            Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::Rem
+                | Opcode::UDiv
+                | Opcode::URem
+                | Opcode::SIToFP
+                | Opcode::FPToSI
+                | Opcode::Sext
+                | Opcode::ZExt
+                | Opcode::Trunc
+                | Opcode::FPExt
+                | Opcode::FPTrunc
+                | Opcode::Shl
+                | Opcode::LShr
+                | Opcode::AShr
+                | Opcode::And
+                | Opcode::Or
+                | Opcode::Xor
+                | Opcode::Select
+                | Opcode::ICmp
+                | Opcode::FCmp
+        ) && inst.operands.iter().all(|op| {
+            matches!(
+                op,
+                Operand::Value(Value::Immediate(_)) | Operand::ICmpKind(_) | Operand::FCmpKind(_)
+            )
+        })
     }
 
     fn is_mul_power_of_two(inst: &Instruction) -> bool {
@@ -7,6 +7,7 @@ use crate::ir::{
     value::{InstructionValue, Value},
 };
 // use crate::traits::basic_block::*;
+use crate::traits::pass::ModulePassTrait;
 use id_arena::Arena;
 use rustc_hash::{FxHashMap, FxHashSet};
 
@@ -19,6 +20,18 @@ struct GlobalCommonSubexprEliminationOnFunction<'a> {
     removal_list: Vec<InstructionId>,
 }
 
+impl ModulePassTrait for CommonSubexprElimination {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "CommonSubexprElimination"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl CommonSubexprElimination {
     pub fn new() -> Self {
         Self {}
@@ -26,7 +39,7 @@ impl CommonSubexprElimination {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
 
@@ -92,10 +105,20 @@ impl<'a> GlobalCommonSubexprEliminationOnFunction<'a> {
                     | Opcode::Mul
                     | Opcode::Div
                     | Opcode::Rem
+                    | Opcode::UDiv
+                    | Opcode::URem
                     | Opcode::Shl
+                    | Opcode::LShr
+                    | Opcode::AShr
+                    | Opcode::And
+                    | Opcode::Or
+                    | Opcode::Xor
                     | Opcode::SIToFP
                     | Opcode::FPToSI
                     | Opcode::Sext
+                    | Opcode::FPExt
+                    | Opcode::FPTrunc
+                    | Opcode::Select
             ) {
                 commons
                     .entry(inst.opcode)
@@ -0,0 +1,136 @@
+// Dead code elimination.
+//
+// The default mode removes instructions whose results have no users and that have no side effects,
+// then the instructions that only they used, and so on. The aggressive mode instead assumes every
+// instruction is dead until an instruction with side effects (a store, a call or a terminator)
+// uses it, so values that only use each other (e.g. a phi cycle of an unused induction variable)
+// are removed as well. It also removes the blocks that are not reachable from the entry block.
+
+use crate::ir::{
+    function::Function,
+    module::Module,
+    opcode::{Instruction, InstructionId, Operand},
+    value::{InstructionValue, Value},
+};
+use crate::traits::pass::ModulePassTrait;
+use rustc_hash::FxHashSet;
+
+#[derive(Default)]
+pub struct DeadCodeElimination {
+    aggressive: bool,
+}
+
+struct DeadCodeEliminationOnFunction<'a> {
+    func: &'a mut Function,
+}
+
+impl ModulePassTrait for DeadCodeElimination {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        if self.aggressive {
+            "AggressiveDeadCodeElimination"
+        } else {
+            "DeadCodeElimination"
+        }
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
+impl DeadCodeElimination {
+    pub fn new() -> Self {
+        Self { aggressive: false }
+    }
+
+    pub fn new_aggressive() -> Self {
+        Self { aggressive: true }
+    }
+
+    pub fn run_on_module(&mut self, module: &mut Module) {
+        for (_, func) in &mut module.functions {
+            if func.is_internal || func.is_prototype {
+                continue;
+            }
+            let mut dce = DeadCodeEliminationOnFunction { func };
+            if self.aggressive {
+                dce.run_aggressive()
+            } else {
+                dce.run()
+            }
+        }
+    }
+}
+
+impl<'a> DeadCodeEliminationOnFunction<'a> {
+    pub fn run(&mut self) {
+        let mut worklist = self.insts();
+        let mut removed = FxHashSet::default();
+        while let Some(id) = worklist.pop() {
+            if removed.contains(&id) || !Self::is_trivially_dead(&self.func.inst_table[id]) {
+                continue;
+            }
+            // Operands may become dead once this is removed
+            worklist.extend(operand_insts(&self.func.inst_table[id]));
+            self.func.remove_inst(id);
+            removed.insert(id);
+        }
+    }
+
+    pub fn run_aggressive(&mut self) {
+        self.func.remove_unreachable_blocks();
+
+        let mut live = FxHashSet::default();
+        let mut worklist = vec![];
+        for id in self.insts() {
+            if self.func.inst_table[id].opcode.has_side_effects() {
+                live.insert(id);
+                worklist.push(id);
+            }
+        }
+        while let Some(id) = worklist.pop() {
+            for op in operand_insts(&self.func.inst_table[id]) {
+                if live.insert(op) {
+                    worklist.push(op)
+                }
+            }
+        }
+
+        for id in self.insts() {
+            if !live.contains(&id) {
+                self.func.remove_inst(id)
+            }
+        }
+    }
+
+    fn insts(&self) -> Vec<InstructionId> {
+        self.func
+            .basic_blocks
+            .order
+            .iter()
+            .flat_map(|&b| {
+                self.func.basic_blocks.arena[b]
+                    .iseq_ref()
+                    .iter()
+                    .map(|v| v.get_inst_id().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn is_trivially_dead(inst: &Instruction) -> bool {
+        !inst.opcode.has_side_effects() && inst.users.borrow().is_empty()
+    }
+}
+
+fn operand_insts(inst: &Instruction) -> Vec<InstructionId> {
+    inst.operands
+        .iter()
+        .filter_map(|op| match op {
+            Operand::Value(Value::Instruction(InstructionValue { id, .. })) => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
@@ -3,6 +3,7 @@ use crate::analysis::Analysis;
 use crate::codegen::is_internal_function;
 use crate::traits::function::FunctionTrait;
 use id_arena::*;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 pub type FunctionId = Id<Function>;
 
@@ -41,6 +42,22 @@ pub struct Function {
     pub types: Types,
 
     pub is_internal: bool,
+
+    /// True if the function is only declared here and defined outside of the module (e.g. libc)
+    pub is_prototype: bool,
+
+    /// Names given to instructions, blocks and parameters
+    pub names: Names,
+}
+
+/// Optional names of the instructions, basic blocks and parameters of a function. They are only
+/// used for printing, and are unique in the function.
+#[derive(Debug, Clone, Default)]
+pub struct Names {
+    pub insts: FxHashMap<InstructionId, String>,
+    pub blocks: FxHashMap<BasicBlockId, String>,
+    pub params: FxHashMap<usize, String>,
+    used: FxHashSet<String>,
 }
 
 impl Function {
@@ -55,6 +72,8 @@ impl Function {
             analyses: vec![],
             types: module.types.clone(),
             is_internal: is_internal_function(name),
+            is_prototype: false,
+            names: Names::default(),
         })
     }
 
@@ -147,6 +166,106 @@ impl Function {
         self.basic_blocks.arena[block_id].iseq_ref_mut().remove(pos)
     }
 
+    /// Removes the incoming values from `pred` of the phis in `block`
+    pub fn remove_phi_incoming(&mut self, block: BasicBlockId, pred: BasicBlockId) {
+        let phis = self.basic_blocks.arena[block]
+            .iseq_ref()
+            .iter()
+            .map(|v| v.get_inst_id().unwrap())
+            .take_while(|&id| self.inst_table[id].opcode == Opcode::Phi)
+            .collect::<Vec<_>>();
+        for phi in phis {
+            let operands = &mut self.inst_table[phi].operands;
+            let pos = match operands
+                .iter()
+                .position(|op| *op == Operand::BasicBlock(pred))
+            {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let incoming = operands[pos - 1];
+            operands.drain(pos - 1..=pos);
+            // The value may still come from another block
+            if !operands.contains(&incoming) {
+                incoming.remove_from_users(&self.inst_table, phi);
+            }
+        }
+    }
+
+    /// Replaces the terminator of `block` with `br target` and removes the edges to the other
+    /// successors
+    pub fn replace_terminator_with_br(&mut self, block: BasicBlockId, target: BasicBlockId) {
+        let term = self.basic_blocks.arena[block]
+            .iseq_ref()
+            .last()
+            .unwrap()
+            .get_inst_id()
+            .unwrap();
+        self.change_inst(
+            term,
+            Instruction::new(
+                Opcode::Br,
+                vec![Operand::BasicBlock(target)],
+                Type::Void,
+                block,
+            ),
+        );
+        let succ = ::std::mem::take(&mut self.basic_blocks.arena[block].succ);
+        for s in succ {
+            if s == target {
+                continue;
+            }
+            self.basic_blocks.arena[s].pred.remove(&block);
+            self.remove_phi_incoming(s, block);
+        }
+        self.basic_blocks.arena[block].succ.insert(target);
+    }
+
+    /// Removes the blocks that are not reachable from the entry block together with their
+    /// instructions. Returns true if any block is removed.
+    pub fn remove_unreachable_blocks(&mut self) -> bool {
+        let entry = match self.basic_blocks.order.first() {
+            Some(entry) => *entry,
+            None => return false,
+        };
+        let mut reachable = FxHashSet::default();
+        let mut worklist = vec![entry];
+        while let Some(block) = worklist.pop() {
+            if reachable.insert(block) {
+                worklist.extend(self.basic_blocks.arena[block].succ.iter().copied());
+            }
+        }
+        let dead = self
+            .basic_blocks
+            .order
+            .iter()
+            .filter(|b| !reachable.contains(b))
+            .copied()
+            .collect::<Vec<_>>();
+        if dead.is_empty() {
+            return false;
+        }
+
+        for &block in &dead {
+            let succ = ::std::mem::take(&mut self.basic_blocks.arena[block].succ);
+            for s in succ {
+                self.basic_blocks.arena[s].pred.remove(&block);
+                if reachable.contains(&s) {
+                    self.remove_phi_incoming(s, block);
+                }
+            }
+        }
+        for &block in &dead {
+            self.basic_blocks.arena[block].pred.clear();
+            let iseq = ::std::mem::take(&mut *self.basic_blocks.arena[block].iseq_ref_mut());
+            for v in iseq {
+                self.remove_inst_left_in_bb(v.get_inst_id().unwrap());
+            }
+        }
+        self.basic_blocks.order.retain(|b| reachable.contains(b));
+        true
+    }
+
     pub fn alloc_inst(&mut self, inst: Instruction) -> InstructionId {
         // TODO
         let id = self.inst_table.alloc(inst);
@@ -202,6 +321,86 @@ impl Function {
     }
 }
 
+impl Names {
+    /// Names an instruction and returns the name actually given, which differs from `name` if
+    /// it is already used in the function
+    pub fn set_inst_name(&mut self, id: InstructionId, name: &str) -> String {
+        let name = self.unique(name, self.insts.get(&id).cloned());
+        self.insts.insert(id, name.clone());
+        name
+    }
+
+    /// Like `set_inst_name`, but for a basic block
+    pub fn set_block_name(&mut self, id: BasicBlockId, name: &str) -> String {
+        let name = self.unique(name, self.blocks.get(&id).cloned());
+        self.blocks.insert(id, name.clone());
+        name
+    }
+
+    /// Like `set_inst_name`, but for the `idx`-th parameter
+    pub fn set_param_name(&mut self, idx: usize, name: &str) -> String {
+        let name = self.unique(name, self.params.get(&idx).cloned());
+        self.params.insert(idx, name.clone());
+        name
+    }
+
+    /// Returns the name printed for an instruction, e.g. `sum` or `3` for `%sum` and `%3`
+    pub fn inst_name(&self, id: InstructionId) -> String {
+        self.insts
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| id.index().to_string())
+    }
+
+    /// Returns the name printed for a basic block, e.g. `loop` or `label.3`
+    pub fn block_name(&self, id: BasicBlockId) -> String {
+        self.blocks
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| format!("label.{}", id.index()))
+    }
+
+    /// Returns the name printed for a parameter, e.g. `n` or `arg.0`
+    pub fn param_name(&self, idx: usize) -> String {
+        self.params
+            .get(&idx)
+            .cloned()
+            .unwrap_or_else(|| format!("arg.{}", idx))
+    }
+
+    /// Turns `name` into one the parser can read back and that doesn't clash with the names of
+    /// unnamed values (`%3`, `%arg.0` and `%label.1`) or other names in the function
+    fn unique(&mut self, name: &str, old: Option<String>) -> String {
+        if let Some(old) = old {
+            self.used.remove(&old);
+        }
+        let mut name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '$') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if name.is_empty()
+            || name.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+            || name.starts_with("arg.")
+            || name.starts_with("label.")
+        {
+            name.insert(0, '_');
+        }
+        let mut unique = name.clone();
+        let mut n = 1;
+        while !self.used.insert(unique.clone()) {
+            unique = format!("{}.{}", name, n);
+            n += 1;
+        }
+        unique
+    }
+}
+
 impl FunctionTrait for Function {
     type BBS = BasicBlocks;
 
@@ -214,26 +413,26 @@ impl DumpToString for &Function {
     fn dump(&self, module: &Module) -> String {
         let base = module.types.base.borrow();
         let ty = base.as_function_ty(self.ty).unwrap();
+        if self.is_prototype {
+            return format!(
+                "declare {} {}({})",
+                base.to_string(ty.ret_ty),
+                self.name,
+                ty.params_to_string(&base)
+            );
+        }
         format!(
             "define {} {}({}) {}",
             base.to_string(ty.ret_ty),
             self.name,
-            ty.params_ty
-                .iter()
-                .enumerate()
-                .fold("".to_string(), |mut s, (i, p)| {
-                    s += &(base.to_string(*p)
-                        + ty.params_attr
-                            .get(&i)
-                            .map_or("", |a| if a.byval { " byval" } else { "" })
-                        + ", ");
-                    s
-                })
-                .trim_matches(&[',', ' '][0..]),
+            ty.params_to_string_with_names(&base, &self.names.params),
             if self.is_internal {
                 "internal;".to_owned()
             } else {
-                format!("{{\n{}}}", self.basic_blocks.dump(module))
+                format!(
+                    "{{\n{}}}",
+                    self.basic_blocks.dump_with_names(module, &self.names)
+                )
             },
         )
     }
@@ -247,38 +446,44 @@ impl DumpToString for FunctionId {
 
 impl DumpToString for BasicBlocks {
     fn dump(&self, module: &Module) -> String {
+        self.dump_with_names(module, &Names::default())
+    }
+}
+
+impl BasicBlocks {
+    /// Prints the blocks with their names in `names`
+    pub fn dump_with_names(&self, module: &Module, names: &Names) -> String {
+        // Sort ids so that the output doesn't depend on the order of hash sets. Named ones are
+        // printed as their names.
+        fn list<'a, T: 'a>(
+            ids: impl Iterator<Item = &'a Id<T>>,
+            names: &FxHashMap<Id<T>, String>,
+        ) -> String {
+            let mut ids: Vec<&Id<T>> = ids.collect();
+            ids.sort_by_key(|id| id.index());
+            ids.iter()
+                .map(|id| {
+                    names
+                        .get(id)
+                        .cloned()
+                        .unwrap_or_else(|| id.index().to_string())
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+
         self.order.iter().fold("".to_string(), |s, &id| {
             let b = &self.arena[id];
+            let liveness = b.liveness.borrow();
             format!(
-                "{}label.{}:\t// pred({}), succ({}), def({}), in({}), out({})\n{}\n",
+                "{}{}:\t// pred({}), succ({}), def({}), in({}), out({})\n{}\n",
                 s,
-                id.index(),
-                &b.pred
-                    .iter()
-                    .fold("".to_string(), |s, x| format!("{}{},", s, x.index()))
-                    .trim_matches(','),
-                &b.succ
-                    .iter()
-                    .fold("".to_string(), |s, x| format!("{}{},", s, x.index()))
-                    .trim_matches(','),
-                &b.liveness
-                    .borrow()
-                    .def
-                    .iter()
-                    .fold("".to_string(), |s, x| format!("{}{},", s, x.index()))
-                    .trim_matches(','),
-                &b.liveness
-                    .borrow()
-                    .live_in
-                    .iter()
-                    .fold("".to_string(), |s, x| format!("{}{},", s, x.index()))
-                    .trim_matches(','),
-                &b.liveness
-                    .borrow()
-                    .live_out
-                    .iter()
-                    .fold("".to_string(), |s, x| format!("{}{},", s, x.index()))
-                    .trim_matches(','),
+                names.block_name(id),
+                list(b.pred.iter(), &names.blocks),
+                list(b.succ.iter(), &names.blocks),
+                list(liveness.def.iter(), &names.insts),
+                list(liveness.live_in.iter(), &names.insts),
+                list(liveness.live_out.iter(), &names.insts),
                 b.dump(module)
             )
         })
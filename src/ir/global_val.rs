@@ -1,4 +1,8 @@
-use super::types::{Type, Types};
+use super::{
+    module::Module,
+    types::{Type, TypeSize, Types},
+    value::{ImmediateValue, Value},
+};
 use id_arena::{Arena, Id};
 use std::fmt;
 
@@ -15,6 +19,36 @@ pub struct GlobalVariable {
     pub ty: Type,
     pub linkage: Linkage,
     pub name: String,
+    pub init: Option<Initializer>,
+    pub is_constant: bool,
+}
+
+/// Constant initializer of a global variable. `Array` and `Struct` may have fewer elements than
+/// the type; the rest is filled with zero.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Initializer {
+    Zero,
+    Immediate(ImmediateValue),
+    Array(Vec<Initializer>),
+    Struct(Vec<Initializer>),
+    /// The address of a global variable or a function, or a constant expression on it
+    Expr(Value),
+    /// `Expr` resolved for the code generator by `GlobalVariables::resolve_addresses`
+    Address(Symbol, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Symbol {
+    Global(GlobalVariableId),
+    Function(String),
+}
+
+/// `addend` bytes past the address of `symbol` is stored at `offset` of a global variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relocation {
+    pub offset: usize,
+    pub symbol: Symbol,
+    pub addend: i64,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
@@ -42,6 +76,25 @@ impl GlobalVariables {
             ty,
             linkage,
             name: name.to_string(),
+            init: None,
+            is_constant: false,
+        })
+    }
+
+    pub fn new_global_var_with_init(
+        &mut self,
+        ty: Type,
+        linkage: Linkage,
+        name: &str,
+        init: Initializer,
+        is_constant: bool,
+    ) -> GlobalVariableId {
+        self.arena.alloc(GlobalVariable {
+            ty,
+            linkage,
+            name: name.to_string(),
+            init: Some(init),
+            is_constant,
         })
     }
 
@@ -50,22 +103,232 @@ impl GlobalVariables {
             ty,
             linkage,
             name: "anony".to_string(),
+            init: None,
+            is_constant: false,
         })
     }
-}
 
-impl fmt::Debug for GlobalVariables {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn to_string(&self, module: &Module) -> String {
+        let mut output = "".to_string();
         for (_, g) in &self.arena {
-            writeln!(
-                f,
-                "@{} = {:?} global {}",
+            output.push_str(&format!(
+                "@{} = {:?} {} {}",
                 g.name,
                 g.linkage,
+                if g.is_constant { "constant" } else { "global" },
                 self.types.to_string(g.ty)
-            )?;
+            ));
+            if let Some(init) = &g.init {
+                output.push_str(&format!(" {}", init.to_string(module)));
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Replaces every `Initializer::Expr` with `Initializer::Address` (or an immediate if the
+    /// expression folds into one). Panics if an expression doesn't resolve to an address.
+    pub fn resolve_addresses(&mut self, module: &Module) {
+        for (_, g) in &mut self.arena {
+            if let Some(init) = &mut g.init {
+                init.resolve_addresses(module);
+            }
+        }
+    }
+}
+
+impl GlobalVariable {
+    /// Returns the initial content of the variable laid out in memory (little endian). A variable
+    /// without an initializer is zero-filled.
+    pub fn init_bytes(&self, types: &Types) -> Vec<u8> {
+        let mut bytes = vec![0; self.ty.size_in_byte(types)];
+        if let Some(init) = &self.init {
+            init.write_bytes(self.ty, types, &mut bytes, 0, &mut vec![]);
+        }
+        bytes
+    }
+
+    /// Returns the addresses in the initializer, which are left zero in `init_bytes`. The
+    /// initializer must have been resolved by `GlobalVariables::resolve_addresses`.
+    pub fn relocations(&self, types: &Types) -> Vec<Relocation> {
+        let mut bytes = vec![0; self.ty.size_in_byte(types)];
+        let mut relocs = vec![];
+        if let Some(init) = &self.init {
+            init.write_bytes(self.ty, types, &mut bytes, 0, &mut relocs);
+        }
+        relocs
+    }
+}
+
+impl Initializer {
+    fn write_bytes(
+        &self,
+        ty: Type,
+        types: &Types,
+        buf: &mut [u8],
+        offset: usize,
+        relocs: &mut Vec<Relocation>,
+    ) {
+        match self {
+            Self::Zero => {}
+            Self::Expr(_) => panic!("unresolved initializer"),
+            Self::Address(symbol, addend) => relocs.push(Relocation {
+                offset,
+                symbol: symbol.clone(),
+                addend: *addend,
+            }),
+            Self::Immediate(imm) => {
+                let bytes = match imm {
+                    ImmediateValue::Int1(b) => vec![*b as u8],
+                    ImmediateValue::Int8(i) => i.to_le_bytes().to_vec(),
+                    ImmediateValue::Int16(i) => i.to_le_bytes().to_vec(),
+                    ImmediateValue::Int32(i) => i.to_le_bytes().to_vec(),
+                    ImmediateValue::Int64(i) => i.to_le_bytes().to_vec(),
+                    ImmediateValue::F32(f) => f.to_bits().to_le_bytes().to_vec(),
+                    ImmediateValue::F64(f) => f.to_bits().to_le_bytes().to_vec(),
+                };
+                buf[offset..offset + bytes.len()].copy_from_slice(&bytes)
+            }
+            Self::Array(elems) => {
+                let elem_ty = types.get_element_ty(ty, None).unwrap();
+                let size = elem_ty.size_in_byte(types);
+                for (i, elem) in elems.iter().enumerate() {
+                    elem.write_bytes(elem_ty, types, buf, offset + i * size, relocs);
+                }
+            }
+            Self::Struct(fields) => {
+                for (i, field) in fields.iter().enumerate() {
+                    let field_ty = types
+                        .get_element_ty(ty, Some(&Value::new_imm_int32(i as i32)))
+                        .unwrap();
+                    let field_offset = *types
+                        .base
+                        .borrow()
+                        .as_struct_ty(ty)
+                        .unwrap()
+                        .get_elem_offset(i)
+                        .unwrap();
+                    field.write_bytes(field_ty, types, buf, offset + field_offset, relocs);
+                }
+            }
+        }
+    }
+
+    /// Returns true if the initializer has the shape of `ty`
+    pub fn is_valid_for(&self, ty: Type, types: &Types) -> bool {
+        match (self, ty) {
+            (Self::Zero, _) => true,
+            // Addresses are stored as 64-bit values
+            (Self::Expr(v), ty) => v.get_type() == ty && matches!(ty, Type::Pointer(_) | Type::i64),
+            (Self::Address(_, _), ty) => matches!(ty, Type::Pointer(_) | Type::i64),
+            (Self::Immediate(imm), ty) => *imm.get_type() == ty,
+            (Self::Array(elems), Type::Array(id)) => {
+                let (elem_ty, len) = {
+                    let base = types.base.borrow();
+                    let arr = base.non_primitive_types[id].as_array();
+                    (arr.elem_ty, arr.len)
+                };
+                elems.len() <= len && elems.iter().all(|e| e.is_valid_for(elem_ty, types))
+            }
+            (Self::Struct(fields), Type::Struct(id)) => {
+                let num_fields = {
+                    let base = types.base.borrow();
+                    let struct_ty = base.non_primitive_types[id].as_struct();
+                    (0..)
+                        .take_while(|&i| struct_ty.get_elem_offset(i).is_some())
+                        .count()
+                };
+                fields.len() <= num_fields
+                    && fields.iter().enumerate().all(|(i, f)| {
+                        let field_ty = types
+                            .get_element_ty(ty, Some(&Value::new_imm_int32(i as i32)))
+                            .unwrap();
+                        f.is_valid_for(field_ty, types)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    fn resolve_addresses(&mut self, module: &Module) {
+        match self {
+            Self::Expr(v) => {
+                let v = module.const_exprs.fold(v).unwrap_or(*v);
+                if let Value::Immediate(imm) = v {
+                    *self = Self::Immediate(imm);
+                    return;
+                }
+                let (base, addend) = module
+                    .const_exprs
+                    .resolve_address(&module.types, &v)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "cannot resolve initializer '{}' to an address",
+                            v.to_string(module, false)
+                        )
+                    });
+                let symbol = match base {
+                    Value::Global(g) => Symbol::Global(g.id),
+                    Value::Function(f) => {
+                        Symbol::Function(module.function_ref(f.func_id).name.clone())
+                    }
+                    _ => unreachable!(),
+                };
+                *self = Self::Address(symbol, addend);
+            }
+            Self::Array(elems) | Self::Struct(elems) => {
+                for e in elems {
+                    e.resolve_addresses(module)
+                }
+            }
+            Self::Zero | Self::Immediate(_) | Self::Address(_, _) => {}
+        }
+    }
+
+    pub fn to_string(&self, module: &Module) -> String {
+        match self {
+            Self::Zero => "zeroinitializer".to_string(),
+            Self::Immediate(imm) => match imm {
+                ImmediateValue::Int1(b) => format!("{}", b),
+                ImmediateValue::Int8(i) => format!("{}", i),
+                ImmediateValue::Int16(i) => format!("{}", i),
+                ImmediateValue::Int32(i) => format!("{}", i),
+                ImmediateValue::Int64(i) => format!("{}", i),
+                ImmediateValue::F32(f) => format!("{}", f),
+                ImmediateValue::F64(f) => format!("{}", f),
+            },
+            Self::Array(elems) => format!(
+                "[{}]",
+                elems
+                    .iter()
+                    .map(|e| e.to_string(module))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Struct(fields) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|e| e.to_string(module))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            // Printed without the type, which is the one of the variable
+            Self::Expr(v) => {
+                let ty = module.types.to_string(v.get_type());
+                v.to_string(module, false)[ty.len() + 1..].to_string()
+            }
+            Self::Address(symbol, addend) => {
+                let name = match symbol {
+                    Symbol::Global(id) => format!("@{}", module.global_vars.arena[*id].name),
+                    Symbol::Function(name) => name.clone(),
+                };
+                match addend {
+                    0 => name,
+                    _ => format!("{}{:+}", name, addend),
+                }
+            }
         }
-        fmt::Result::Ok(())
     }
 }
 
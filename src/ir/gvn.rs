@@ -0,0 +1,359 @@
+// Global value numbering with partial redundancy elimination.
+//
+// Value numbering walks the dominator tree with a scoped table from expressions to the
+// instruction that first computed them. An instruction whose expression is already in the table
+// is replaced with that instruction. Operands of commutative operations are compared in either
+// order.
+//
+// Then, for each expression computed in a block with several predecessors, the expression is
+// translated into each predecessor by replacing the phis of the block with their incoming values,
+// and looked up in the table at the end of the predecessor. If it is available in every
+// predecessor, the instruction becomes a phi of those values. If it is missing in exactly one
+// predecessor, it is computed there first (on a new block if that edge is critical). Both steps are
+// repeated until no more expressions can be eliminated.
+//
+// Loop headers are left alone; hoisting out of loops is what LICM does.
+
+use crate::analysis::dom_tree::{DominatorTree, DominatorTreeConstructor};
+use crate::ir::{
+    basic_block::{BasicBlock, BasicBlockId},
+    builder::{Builder, FunctionEntity},
+    function::Function,
+    module::Module,
+    opcode::{ICmpKind, Instruction, InstructionId, Opcode, Operand},
+    types::Type,
+    value::{InstructionValue, Value},
+};
+use crate::traits::pass::ModulePassTrait;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+#[derive(Default)]
+pub struct GlobalValueNumbering {}
+
+struct GlobalValueNumberingOnFunction<'a> {
+    func: &'a mut Function,
+    /// The expressions available at the end of each block
+    avail_out: FxHashMap<BasicBlockId, ExprTable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Expr {
+    opcode: Opcode,
+    ty: Type,
+    operands: Vec<Operand>,
+}
+
+type ExprTable = FxHashMap<Expr, InstructionId>;
+
+impl ModulePassTrait for GlobalValueNumbering {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "GlobalValueNumbering"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
+impl GlobalValueNumbering {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn run_on_module(&mut self, module: &mut Module) {
+        for (_, func) in &mut module.functions {
+            if func.is_internal || func.is_prototype || func.basic_blocks.order.is_empty() {
+                continue;
+            }
+            GlobalValueNumberingOnFunction {
+                func,
+                avail_out: FxHashMap::default(),
+            }
+            .run();
+        }
+    }
+}
+
+impl<'a> GlobalValueNumberingOnFunction<'a> {
+    pub fn run(&mut self) {
+        loop {
+            let dom_tree = DominatorTreeConstructor::new(&self.func.basic_blocks).construct();
+            self.avail_out.clear();
+            self.number(
+                &dom_tree,
+                self.func.basic_blocks.order[0],
+                ExprTable::default(),
+            );
+            // Inserting computations invalidates the tables, so start over after each
+            if !self.eliminate_partial_redundancy(&dom_tree) {
+                break;
+            }
+        }
+    }
+
+    fn number(
+        &mut self,
+        dom_tree: &DominatorTree<BasicBlock>,
+        block: BasicBlockId,
+        mut table: ExprTable,
+    ) {
+        for id in self.block_insts(block) {
+            let expr = match self.expr_of(id) {
+                Some(expr) => expr,
+                None => continue,
+            };
+            if let Some(leader) = lookup(&table, &expr) {
+                let leader = self.inst_value(leader);
+                Instruction::replace_all_uses(
+                    &mut self.func.inst_table,
+                    id,
+                    Operand::Value(leader),
+                );
+                self.func.remove_inst(id);
+                continue;
+            }
+            table.insert(expr, id);
+        }
+
+        self.avail_out.insert(block, table.clone());
+
+        for &child in dom_tree.children_of(block).unwrap_or(&FxHashSet::default()) {
+            self.number(dom_tree, child, table.clone())
+        }
+    }
+
+    /// Replaces one instruction whose expression is available in all but at most one predecessor
+    /// with a phi. Returns true if an instruction is replaced.
+    fn eliminate_partial_redundancy(&mut self, dom_tree: &DominatorTree<BasicBlock>) -> bool {
+        for block in self.func.basic_blocks.order.clone() {
+            let mut preds = self.func.basic_blocks.arena[block]
+                .pred
+                .iter()
+                .copied()
+                .collect::<Vec<_>>();
+            if preds.len() < 2
+                || preds
+                    .iter()
+                    .any(|p| !self.avail_out.contains_key(p) || dom_tree.dominate_bb(block, *p))
+            {
+                continue;
+            }
+            let order = &self.func.basic_blocks.order;
+            preds.sort_by_key(|p| order.iter().position(|b| b == p));
+
+            for id in self.block_insts(block) {
+                let expr = match self.expr_of(id) {
+                    Some(expr) => expr,
+                    None => continue,
+                };
+                let mut leaders = vec![];
+                let mut missing = vec![];
+                for &pred in &preds {
+                    let translated = match self.translate(&expr, block, pred) {
+                        Some(translated) => translated,
+                        None => break,
+                    };
+                    match lookup(&self.avail_out[&pred], &translated) {
+                        Some(leader) => leaders.push((leader, pred)),
+                        None => missing.push((translated, pred)),
+                    }
+                }
+                if leaders.len() + missing.len() != preds.len()
+                    || leaders.is_empty()
+                    || missing.len() > 1
+                    || (!missing.is_empty() && may_trap(expr.opcode))
+                {
+                    continue;
+                }
+
+                for (expr, pred) in missing {
+                    leaders.push(self.insert_on_edge(expr, pred, block))
+                }
+                let incoming = leaders
+                    .into_iter()
+                    .map(|(leader, pred)| (self.inst_value(leader), pred))
+                    .collect();
+                let mut builder = Builder::new(FunctionEntity(self.func));
+                builder.set_insert_point_at(0, block);
+                let phi = builder.build_phi(incoming);
+                Instruction::replace_all_uses(&mut self.func.inst_table, id, Operand::Value(phi));
+                self.func.remove_inst(id);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns `expr` as computed at the end of `pred` by replacing the phis in `block` with their
+    /// incoming values from `pred`, or None if `expr` uses other values defined in `block`
+    fn translate(&self, expr: &Expr, block: BasicBlockId, pred: BasicBlockId) -> Option<Expr> {
+        let mut translated = expr.clone();
+        for op in &mut translated.operands {
+            let id = match op {
+                Operand::Value(Value::Instruction(InstructionValue { id, .. })) => *id,
+                _ => continue,
+            };
+            let inst = &self.func.inst_table[id];
+            if inst.parent != block {
+                continue;
+            }
+            if inst.opcode != Opcode::Phi {
+                return None;
+            }
+            let pair = inst
+                .operands
+                .chunks(2)
+                .find(|pair| pair[1] == Operand::BasicBlock(pred))?;
+            *op = pair[0];
+        }
+        Some(translated)
+    }
+
+    /// Computes `expr` at the end of `pred`, or on a new block between `pred` and `block` if `pred`
+    /// has other successors. Returns the new instruction and the block it is in.
+    fn insert_on_edge(
+        &mut self,
+        expr: Expr,
+        pred: BasicBlockId,
+        block: BasicBlockId,
+    ) -> (InstructionId, BasicBlockId) {
+        let pred = if self.func.basic_blocks.arena[pred].succ.len() > 1 {
+            self.split_edge(pred, block)
+        } else {
+            pred
+        };
+        let id = self
+            .func
+            .alloc_inst(Instruction::new(expr.opcode, expr.operands, expr.ty, pred));
+        let val = self.inst_value(id);
+        let mut iseq = self.func.basic_blocks.arena[pred].iseq_ref_mut();
+        let pos = iseq.len() - 1;
+        iseq.insert(pos, val);
+        (id, pred)
+    }
+
+    fn split_edge(&mut self, pred: BasicBlockId, block: BasicBlockId) -> BasicBlockId {
+        let new = self.func.append_basic_block_before(block);
+        let term = self.block_insts(pred).pop().unwrap();
+        for op in &mut self.func.inst_table[term].operands {
+            if *op == Operand::BasicBlock(block) {
+                *op = Operand::BasicBlock(new)
+            }
+        }
+        let pred_block = &mut self.func.basic_blocks.arena[pred];
+        pred_block.succ.remove(&block);
+        pred_block.succ.insert(new);
+        self.func.basic_blocks.arena[new].pred.insert(pred);
+        self.func.basic_blocks.arena[block].pred.remove(&pred);
+        for id in self.block_insts(block) {
+            if self.func.inst_table[id].opcode != Opcode::Phi {
+                break;
+            }
+            for op in &mut self.func.inst_table[id].operands {
+                if *op == Operand::BasicBlock(pred) {
+                    *op = Operand::BasicBlock(new)
+                }
+            }
+        }
+        let mut builder = Builder::new(FunctionEntity(self.func));
+        builder.set_insert_point(new);
+        builder.build_br(block);
+        new
+    }
+
+    /// Returns the expression `id` computes, or None if it can't be numbered
+    fn expr_of(&self, id: InstructionId) -> Option<Expr> {
+        let inst = &self.func.inst_table[id];
+        if !matches!(
+            inst.opcode,
+            Opcode::GetElementPtr
+                | Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::Rem
+                | Opcode::UDiv
+                | Opcode::URem
+                | Opcode::Shl
+                | Opcode::LShr
+                | Opcode::AShr
+                | Opcode::And
+                | Opcode::Or
+                | Opcode::Xor
+                | Opcode::SIToFP
+                | Opcode::FPToSI
+                | Opcode::Sext
+                | Opcode::ZExt
+                | Opcode::Trunc
+                | Opcode::FPExt
+                | Opcode::FPTrunc
+                | Opcode::Bitcast
+                | Opcode::PtrToInt
+                | Opcode::IntToPtr
+                | Opcode::ICmp
+                | Opcode::FCmp
+                | Opcode::Select
+        ) {
+            return None;
+        }
+        Some(Expr {
+            opcode: inst.opcode,
+            ty: inst.ty,
+            operands: inst.operands.clone(),
+        })
+    }
+
+    fn inst_value(&self, id: InstructionId) -> Value {
+        Value::Instruction(InstructionValue {
+            func_id: self.func.id.unwrap(),
+            id,
+            ty: self.func.inst_table[id].ty,
+        })
+    }
+
+    fn block_insts(&self, block: BasicBlockId) -> Vec<InstructionId> {
+        self.func.basic_blocks.arena[block]
+            .iseq_ref()
+            .iter()
+            .map(|v| v.get_inst_id().unwrap())
+            .collect()
+    }
+}
+
+impl Expr {
+    /// Returns the same expression with the operands swapped if the operation is commutative
+    fn commuted(&self) -> Option<Expr> {
+        let (a, b) = match self.opcode {
+            Opcode::Add | Opcode::Mul | Opcode::And | Opcode::Or | Opcode::Xor => (0, 1),
+            Opcode::ICmp
+                if matches!(
+                    self.operands[0],
+                    Operand::ICmpKind(ICmpKind::Eq) | Operand::ICmpKind(ICmpKind::Ne)
+                ) =>
+            {
+                (1, 2)
+            }
+            _ => return None,
+        };
+        let mut commuted = self.clone();
+        commuted.operands.swap(a, b);
+        Some(commuted)
+    }
+}
+
+fn lookup(table: &ExprTable, expr: &Expr) -> Option<InstructionId> {
+    table
+        .get(expr)
+        .or_else(|| expr.commuted().and_then(|e| table.get(&e)))
+        .copied()
+}
+
+/// Division by zero traps, so it is never computed on a path where it wasn't before
+fn may_trap(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Div | Opcode::Rem | Opcode::UDiv | Opcode::URem
+    )
+}
@@ -0,0 +1,303 @@
+// Function inlining.
+//
+// Functions are visited callees first (a post-order walk of the call graph), so a callee has
+// already had its own calls inlined when it is inlined into its callers. Calls to a function that
+// hasn't been visited yet, i.e. recursive calls, are never inlined.
+//
+// Whether a call is inlined is decided by `inline_cost`: the size of the callee minus what the
+// call itself costs, which must not exceed the threshold.
+
+use crate::ir::{
+    basic_block::BasicBlockId,
+    builder::{Builder, FunctionEntity},
+    clone::clone_blocks,
+    function::{Function, FunctionId},
+    module::Module,
+    opcode::{Instruction, InstructionId, Opcode, Operand},
+    types::Type,
+    value::{ArgumentValue, FunctionValue, Value},
+};
+use crate::traits::pass::ModulePassTrait;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// The default maximum of `inline_cost` for a call to be inlined
+pub const DEFAULT_THRESHOLD: isize = 20;
+
+// What a call costs besides the callee: the call, the return and the prologue and epilogue
+const CALL_COST: isize = 6;
+// Passing an argument. Constant arguments count more since they may fold in the inlined body.
+const ARG_COST: isize = 1;
+const CONST_ARG_COST: isize = 3;
+
+pub struct Inliner {
+    threshold: isize,
+}
+
+impl ModulePassTrait for Inliner {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "Inliner"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
+impl Default for Inliner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inliner {
+    pub fn new() -> Self {
+        Self::with_threshold(DEFAULT_THRESHOLD)
+    }
+
+    pub fn with_threshold(threshold: isize) -> Self {
+        Self { threshold }
+    }
+
+    pub fn run_on_module(&mut self, module: &mut Module) {
+        let mut done = FxHashSet::default();
+        for caller in post_order(module) {
+            let calls = direct_calls(module.function_ref(caller));
+            for (call_id, callee) in calls {
+                if !done.contains(&callee) {
+                    continue;
+                }
+                let f = module.function_ref(caller);
+                let args = f.inst_table[call_id].operands[1..]
+                    .iter()
+                    .map(|op| *op.as_value())
+                    .collect::<Vec<_>>();
+                match inline_cost(module.function_ref(callee), &args) {
+                    Some(cost) if cost <= self.threshold => {
+                        inline_call(module, caller, call_id, callee, &args)
+                    }
+                    _ => {}
+                }
+            }
+            done.insert(caller);
+        }
+    }
+}
+
+/// Returns the cost of inlining `callee` at a call with `args`, or None if it can't be inlined
+pub fn inline_cost(callee: &Function, args: &[Value]) -> Option<isize> {
+    if callee.is_internal || callee.is_prototype || callee.basic_blocks.order.is_empty() {
+        return None;
+    }
+    {
+        let base = callee.types.base.borrow();
+        let f_ty = base.as_function_ty(callee.ty).unwrap();
+        // A byval argument would have to be copied, and a phi can't merge aggregates
+        if f_ty.is_var_arg
+            || f_ty.params_attr.values().any(|a| a.byval)
+            || matches!(f_ty.ret_ty, Type::Struct(_) | Type::Array(_))
+        {
+            return None;
+        }
+    }
+    // The entry block of the inlined body is jumped to from the caller only
+    let entry = callee.basic_blocks.order[0];
+    if !callee.basic_blocks.arena[entry].pred.is_empty() {
+        return None;
+    }
+
+    let mut size = 0;
+    let mut returns = false;
+    for &block in &callee.basic_blocks.order {
+        for v in callee.basic_blocks.arena[block].iseq_ref().iter() {
+            let inst = &callee.inst_table[v.get_inst_id().unwrap()];
+            returns |= inst.opcode == Opcode::Ret;
+            size += 1;
+        }
+    }
+    // The code after the call would be unreachable
+    if !returns {
+        return None;
+    }
+
+    let args_cost = args
+        .iter()
+        .map(|a| match a {
+            Value::Immediate(_) => CONST_ARG_COST,
+            _ => ARG_COST,
+        })
+        .sum::<isize>();
+    Some(size - CALL_COST - args_cost)
+}
+
+/// Returns the functions defined in the module so that callees come before their callers
+fn post_order(module: &Module) -> Vec<FunctionId> {
+    fn visit(
+        module: &Module,
+        f: FunctionId,
+        visited: &mut FxHashSet<FunctionId>,
+        order: &mut Vec<FunctionId>,
+    ) {
+        if !visited.insert(f) {
+            return;
+        }
+        for (_, callee) in direct_calls(module.function_ref(f)) {
+            visit(module, callee, visited, order)
+        }
+        order.push(f);
+    }
+
+    let mut visited = FxHashSet::default();
+    let mut order = vec![];
+    for (id, f) in &module.functions {
+        if !f.is_internal && !f.is_prototype {
+            visit(module, id, &mut visited, &mut order)
+        }
+    }
+    order
+}
+
+/// Returns the calls in `f` whose callee is known
+fn direct_calls(f: &Function) -> Vec<(InstructionId, FunctionId)> {
+    let mut calls = vec![];
+    for &block in &f.basic_blocks.order {
+        for v in f.basic_blocks.arena[block].iseq_ref().iter() {
+            let id = v.get_inst_id().unwrap();
+            let inst = &f.inst_table[id];
+            if inst.opcode != Opcode::Call {
+                continue;
+            }
+            if let Operand::Value(Value::Function(FunctionValue { func_id, .. })) = inst.operands[0]
+            {
+                calls.push((id, func_id))
+            }
+        }
+    }
+    calls
+}
+
+/// Replaces the call `call_id` in `caller` with the body of `callee`
+fn inline_call(
+    module: &mut Module,
+    caller: FunctionId,
+    call_id: InstructionId,
+    callee: FunctionId,
+    args: &[Value],
+) {
+    let mut value_map = FxHashMap::default();
+    {
+        let callee = module.function_ref(callee);
+        for (index, arg) in args.iter().enumerate() {
+            let ty = callee.get_param_type(index).unwrap();
+            let param = Value::Argument(ArgumentValue {
+                func_id: callee.id.unwrap(),
+                index,
+                ty,
+            });
+            value_map.insert(param, *arg);
+        }
+    }
+
+    let (block, pos) = module.function_ref(caller).find_inst_pos(call_id).unwrap();
+    let cont = split_block(module.function_ref_mut(caller), block, pos + 1);
+    let body = module.function_ref(callee).basic_blocks.order.clone();
+    let body = clone_blocks(module, callee, &body, caller, &mut value_map);
+
+    let f = module.function_ref_mut(caller);
+    // Lay out the body between the call and the code after it
+    f.basic_blocks
+        .order
+        .retain(|b| *b != cont && !body.contains(b));
+    let pos = f
+        .basic_blocks
+        .order
+        .iter()
+        .position(|&b| b == block)
+        .unwrap()
+        + 1;
+    f.basic_blocks
+        .order
+        .splice(pos..pos, body.iter().copied().chain(Some(cont)));
+
+    // Returns jump to the code after the call
+    let mut returns = vec![];
+    for &b in &body {
+        let ret = f.basic_blocks.arena[b]
+            .iseq_ref()
+            .last()
+            .unwrap()
+            .get_inst_id()
+            .unwrap();
+        if f.inst_table[ret].opcode != Opcode::Ret {
+            continue;
+        }
+        returns.push((f.inst_table[ret].operands.first().map(|v| *v.as_value()), b));
+        f.remove_inst(ret);
+        let mut builder = Builder::new(FunctionEntity(f));
+        builder.set_insert_point(b);
+        builder.build_br(cont);
+    }
+
+    if f.inst_table[call_id].ty != Type::Void {
+        let result = if returns.len() == 1 {
+            returns[0].0.unwrap()
+        } else {
+            let mut builder = Builder::new(FunctionEntity(f));
+            builder.set_insert_point_at(0, cont);
+            builder.build_phi(returns.into_iter().map(|(v, b)| (v.unwrap(), b)).collect())
+        };
+        Instruction::replace_all_uses(&mut f.inst_table, call_id, Operand::Value(result));
+    }
+    f.remove_inst(call_id);
+    let mut builder = Builder::new(FunctionEntity(f));
+    builder.set_insert_point(block);
+    builder.build_br(body[0]);
+
+    // Allocas in the entry block of the callee are only done once, so do them once in the caller
+    // too. This also keeps them promotable by mem2reg.
+    let entry = f.basic_blocks.order[0];
+    let allocas = f.basic_blocks.arena[body[0]]
+        .iseq_ref()
+        .iter()
+        .map(|v| v.get_inst_id().unwrap())
+        .filter(|&id| f.inst_table[id].opcode == Opcode::Alloca)
+        .collect::<Vec<_>>();
+    for (i, id) in allocas.into_iter().enumerate() {
+        let v = f.remove_inst_from_block(id);
+        f.inst_table[id].parent = entry;
+        f.basic_blocks.arena[entry].iseq_ref_mut().insert(i, v);
+    }
+}
+
+/// Moves the instructions of `block` from `pos` on into a new block, which takes over the
+/// successors of `block`
+fn split_block(f: &mut Function, block: BasicBlockId, pos: usize) -> BasicBlockId {
+    let new = f.append_basic_block();
+    let tail = f.basic_blocks.arena[block].iseq_ref_mut().split_off(pos);
+    for v in &tail {
+        f.inst_table[v.get_inst_id().unwrap()].parent = new;
+    }
+    *f.basic_blocks.arena[new].iseq_ref_mut() = tail;
+
+    let succ = ::std::mem::take(&mut f.basic_blocks.arena[block].succ);
+    for &s in &succ {
+        f.basic_blocks.arena[s].pred.remove(&block);
+        f.basic_blocks.arena[s].pred.insert(new);
+        let phis = f.basic_blocks.arena[s]
+            .iseq_ref()
+            .iter()
+            .map(|v| v.get_inst_id().unwrap())
+            .take_while(|&id| f.inst_table[id].opcode == Opcode::Phi)
+            .collect::<Vec<_>>();
+        for phi in phis {
+            for op in &mut f.inst_table[phi].operands {
+                if *op == Operand::BasicBlock(block) {
+                    *op = Operand::BasicBlock(new)
+                }
+            }
+        }
+    }
+    f.basic_blocks.arena[new].succ = succ;
+    new
+}
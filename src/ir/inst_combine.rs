@@ -4,6 +4,7 @@ use crate::ir::{
     opcode::{Instruction, Opcode, Operand},
     value::Value,
 };
+use crate::traits::pass::ModulePassTrait;
 use std::collections::VecDeque;
 
 pub struct InstructionCombine {}
@@ -12,6 +13,18 @@ pub struct InstructionCombineOnFunction<'a> {
     func: &'a mut Function,
 }
 
+impl ModulePassTrait for InstructionCombine {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "InstructionCombine"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl InstructionCombine {
     pub fn new() -> Self {
         Self {}
@@ -19,7 +32,7 @@ impl InstructionCombine {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
 
@@ -52,6 +65,8 @@ impl<'a> InstructionCombineOnFunction<'a> {
 
             if inst1.has_one_use() {
                 self.func.remove_inst(inst_id);
+            } else {
+                inst1.users.borrow_mut().retain(|&u| u != inst2_id);
             }
 
             let inst2 = &mut self.func.inst_table[inst2_id];
@@ -80,6 +95,7 @@ impl<'a> InstructionCombineOnFunction<'a> {
                     .unwrap(),
                 _ => unreachable!(),
             });
+            self.func.inst_table[inst2_id].set_users(&self.func.inst_table);
         }
     }
 
@@ -12,6 +12,7 @@ use crate::{
         simplify_loop::SimplifyLoop,
         value::*,
     },
+    traits::pass::ModulePassTrait,
 };
 use id_arena::Id;
 use rustc_hash::FxHashMap;
@@ -23,6 +24,18 @@ struct LoopInvariantCodeMotionOnFunction<'a> {
     func: &'a mut Function,
 }
 
+impl ModulePassTrait for LoopInvariantCodeMotion {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "LoopInvariantCodeMotion"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl LoopInvariantCodeMotion {
     pub fn new() -> Self {
         Self {}
@@ -32,7 +45,7 @@ impl LoopInvariantCodeMotion {
         SimplifyLoop::new().run_on_module(module);
 
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
             LoopInvariantCodeMotionOnFunction::new(func).run();
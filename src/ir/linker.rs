@@ -0,0 +1,381 @@
+// Linker that moves every function and global variable of one module into another.
+//
+// A function without basic blocks and a global variable with external linkage are declarations,
+// and are resolved against a definition of the same name in the other module. Common global
+// variables of the same name are merged unless both of them are initialized. Named structs are
+// matched by name and must have the same body in both modules unless one of them is opaque.
+
+use super::{
+    basic_block::BasicBlocks,
+    const_expr::ConstExprs,
+    function::{Function, FunctionId, Names},
+    global_val::{GlobalVariable, GlobalVariableId, Initializer, Linkage, Symbol},
+    module::Module,
+    opcode::{Instruction, Operand},
+    types::{NonPrimitiveType, Type, Types},
+    value::*,
+};
+use id_arena::Arena;
+use rustc_hash::FxHashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkError {
+    /// The symbol is defined in both modules
+    DuplicateSymbol(String),
+    /// The symbol has a different type in each module
+    SignatureMismatch {
+        name: String,
+        expected: String,
+        found: String,
+    },
+    /// The named struct has a different body in each module
+    StructMismatch {
+        name: String,
+        expected: String,
+        found: String,
+    },
+}
+
+/// How a symbol of the source module is resolved in the destination module
+enum Resolution<Id> {
+    /// Added to the destination as a new symbol
+    New,
+    /// Resolved to an existing symbol. If true, the source one is the definition and replaces it.
+    Existing(Id, bool),
+}
+
+type Resolutions = (
+    Vec<Resolution<GlobalVariableId>>,
+    Vec<Resolution<FunctionId>>,
+);
+
+struct Linker<'a> {
+    dst: &'a mut Module,
+    src_types: Types,
+    src_const_exprs: ConstExprs,
+    funcs: FxHashMap<FunctionId, FunctionId>,
+    globals: FxHashMap<GlobalVariableId, GlobalVariableId>,
+}
+
+impl Module {
+    /// Moves the contents of `other` into `self`, resolving declarations in either module against
+    /// definitions of the same name. Nothing is changed if an error is returned.
+    pub fn link_in(&mut self, other: Module) -> Result<(), LinkError> {
+        // Comparing the types of symbols imports types into `self`, so they are rolled back if
+        // resolving fails
+        let types = self.types.base.borrow().clone();
+        let mut linker = Linker {
+            src_types: other.types.clone(),
+            src_const_exprs: other.const_exprs.clone(),
+            dst: self,
+            funcs: FxHashMap::default(),
+            globals: FxHashMap::default(),
+        };
+        let (global_res, func_res) = match linker.resolve(&other) {
+            Ok(res) => res,
+            Err(e) => {
+                *linker.dst.types.base.borrow_mut() = types;
+                return Err(e);
+            }
+        };
+        let inits = linker.link_global_vars(other.global_vars.arena, global_res);
+        linker.link_functions(other.functions, func_res);
+        // Initializers may refer to any global variable or function
+        for id in inits {
+            let mut init = linker.dst.global_vars.arena[id].init.take();
+            if let Some(init) = &mut init {
+                linker.rewrite_initializer(init);
+            }
+            linker.dst.global_vars.arena[id].init = init;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Linker<'a> {
+    /// Decides how each symbol of `src` is linked without moving anything yet
+    fn resolve(&mut self, src: &Module) -> Result<Resolutions, LinkError> {
+        self.check_named_structs()?;
+        Ok((self.resolve_global_vars(src)?, self.resolve_functions(src)?))
+    }
+
+    /// Compares the bodies of the named structs that exist in both modules, so that importing
+    /// types can't fail afterwards
+    fn check_named_structs(&self) -> Result<(), LinkError> {
+        let shared = self
+            .src_types
+            .base
+            .borrow()
+            .non_primitive_types
+            .iter()
+            .filter_map(|(id, t)| match t {
+                NonPrimitiveType::Struct(s) => s.name().and_then(|name| {
+                    self.dst
+                        .types
+                        .find_named_struct_ty(name)
+                        .map(|_| Type::Struct(id))
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for ty in shared {
+            if let Err(name) = self.dst.types.import_ty(&self.src_types, ty) {
+                let body = |types: &Types| {
+                    let ty = types.find_named_struct_ty(&name).unwrap();
+                    let base = types.base.borrow();
+                    base.as_struct_ty(ty).unwrap().body_to_string(&base)
+                };
+                return Err(LinkError::StructMismatch {
+                    expected: body(&self.dst.types),
+                    found: body(&self.src_types),
+                    name,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_global_vars(
+        &mut self,
+        src: &Module,
+    ) -> Result<Vec<Resolution<GlobalVariableId>>, LinkError> {
+        let mut res = vec![];
+        for (_, g) in &src.global_vars.arena {
+            let existing = self
+                .dst
+                .global_vars
+                .arena
+                .iter()
+                .find(|(_, d)| d.name == g.name);
+            let (id, d) = match existing {
+                Some(existing) => existing,
+                None => {
+                    res.push(Resolution::New);
+                    continue;
+                }
+            };
+            self.check_type(&g.name, d.ty, g.ty)?;
+            let replace = match (d.linkage, g.linkage) {
+                (_, Linkage::External) => false,
+                (Linkage::External, _) => true,
+                (Linkage::Common, Linkage::Common) if d.init.is_some() && g.init.is_some() => {
+                    return Err(LinkError::DuplicateSymbol(g.name.clone()))
+                }
+                (Linkage::Common, Linkage::Common) => g.init.is_some(),
+            };
+            res.push(Resolution::Existing(id, replace));
+        }
+        Ok(res)
+    }
+
+    fn resolve_functions(
+        &mut self,
+        src: &Module,
+    ) -> Result<Vec<Resolution<FunctionId>>, LinkError> {
+        let is_decl = |f: &Function| f.is_prototype || f.basic_blocks.order.is_empty();
+        let mut res = vec![];
+        for (_, f) in &src.functions {
+            let id = match self.dst.find_function(f.name.as_str()) {
+                Some(id) => id,
+                None => {
+                    res.push(Resolution::New);
+                    continue;
+                }
+            };
+            let d = self.dst.function_ref(id);
+            self.check_type(&f.name, d.ty, f.ty)?;
+            if !is_decl(d) && !is_decl(f) {
+                return Err(LinkError::DuplicateSymbol(f.name.clone()));
+            }
+            res.push(Resolution::Existing(id, !is_decl(f)));
+        }
+        Ok(res)
+    }
+
+    fn check_type(&self, name: &str, dst_ty: Type, src_ty: Type) -> Result<(), LinkError> {
+        if dst_ty == self.import_ty(src_ty) {
+            return Ok(());
+        }
+        Err(LinkError::SignatureMismatch {
+            name: name.to_string(),
+            expected: self.dst.types.to_string(dst_ty),
+            found: self.src_types.to_string(src_ty),
+        })
+    }
+
+    /// Returns the global variables whose initializer comes from the source
+    fn link_global_vars(
+        &mut self,
+        src: Arena<GlobalVariable>,
+        res: Vec<Resolution<GlobalVariableId>>,
+    ) -> Vec<GlobalVariableId> {
+        let mut inits = vec![];
+        for ((src_id, mut g), res) in src.into_iter().zip(res) {
+            g.ty = self.import_ty(g.ty);
+            let id = match res {
+                Resolution::New => self.dst.global_vars.arena.alloc(g),
+                Resolution::Existing(id, true) => {
+                    self.dst.global_vars.arena[id] = g;
+                    id
+                }
+                Resolution::Existing(id, false) => {
+                    self.globals.insert(src_id, id);
+                    continue;
+                }
+            };
+            self.globals.insert(src_id, id);
+            inits.push(id);
+        }
+        inits
+    }
+
+    fn link_functions(&mut self, src: Arena<Function>, res: Vec<Resolution<FunctionId>>) {
+        // Every function needs an id in the destination before any body can be rewritten
+        let mut bodies = vec![];
+        for ((src_id, f), res) in src.into_iter().zip(res) {
+            let id = match res {
+                Resolution::New => self.dst.add_function(Function {
+                    name: f.name.clone(),
+                    ty: self.import_ty(f.ty),
+                    basic_blocks: BasicBlocks::new(),
+                    inst_table: Arena::new(),
+                    id: None,
+                    analyses: vec![],
+                    types: self.dst.types.clone(),
+                    is_internal: f.is_internal,
+                    is_prototype: f.is_prototype,
+                    names: Names::default(),
+                }),
+                Resolution::Existing(id, _) => id,
+            };
+            self.funcs.insert(src_id, id);
+            if matches!(res, Resolution::New | Resolution::Existing(_, true)) {
+                bodies.push((id, f));
+            }
+        }
+
+        for (id, f) in bodies {
+            let is_prototype = f.is_prototype;
+            let names = f.names;
+            let mut inst_table = f.inst_table;
+            for (_, inst) in &mut inst_table {
+                self.rewrite_inst(id, inst);
+            }
+            let mut basic_blocks = f.basic_blocks;
+            for (_, block) in &mut basic_blocks.arena {
+                for val in &mut *block.iseq.borrow_mut() {
+                    *val = self.rewrite_value(id, *val);
+                }
+            }
+            let dst = self.dst.function_ref_mut(id);
+            dst.inst_table = inst_table;
+            dst.basic_blocks = basic_blocks;
+            dst.is_prototype = is_prototype;
+            dst.names = names;
+            dst.analyses.clear();
+        }
+    }
+
+    fn rewrite_inst(&mut self, func_id: FunctionId, inst: &mut Instruction) {
+        inst.ty = self.import_ty(inst.ty);
+        for operand in &mut inst.operands {
+            match operand {
+                Operand::Type(ty) => *ty = self.import_ty(*ty),
+                Operand::Value(v) => *v = self.rewrite_value(func_id, *v),
+                Operand::BasicBlock(_) | Operand::ICmpKind(_) | Operand::FCmpKind(_) => {}
+            }
+        }
+    }
+
+    fn rewrite_value(&mut self, func_id: FunctionId, v: Value) -> Value {
+        match v {
+            Value::Argument(ArgumentValue { index, ty, .. }) => Value::Argument(ArgumentValue {
+                func_id,
+                index,
+                ty: self.import_ty(ty),
+            }),
+            Value::Instruction(InstructionValue { id, ty, .. }) => {
+                Value::Instruction(InstructionValue {
+                    func_id,
+                    id,
+                    ty: self.import_ty(ty),
+                })
+            }
+            _ => self.rewrite_const(v),
+        }
+    }
+
+    fn rewrite_const(&mut self, v: Value) -> Value {
+        match v {
+            Value::Function(FunctionValue { func_id, ty }) => Value::Function(FunctionValue {
+                func_id: self.funcs[&func_id],
+                ty: self.import_ty(ty),
+            }),
+            Value::Global(GlobalValue { id, ty }) => Value::Global(GlobalValue {
+                id: self.globals[&id],
+                ty: self.import_ty(ty),
+            }),
+            Value::ConstExpr(ConstExprValue { id, ty }) => {
+                let expr = self.src_const_exprs.arena[id].clone();
+                let operands = expr
+                    .operands
+                    .iter()
+                    .map(|&op| self.rewrite_const(op))
+                    .collect();
+                let ty = self.import_ty(ty);
+                self.dst.const_expr(expr.opcode, operands, ty)
+            }
+            Value::Argument(_) | Value::Instruction(_) | Value::Immediate(_) | Value::None => v,
+        }
+    }
+
+    fn rewrite_initializer(&mut self, init: &mut Initializer) {
+        match init {
+            Initializer::Expr(v) => *v = self.rewrite_const(*v),
+            Initializer::Array(elems) | Initializer::Struct(elems) => {
+                for e in elems {
+                    self.rewrite_initializer(e)
+                }
+            }
+            Initializer::Address(Symbol::Global(id), _) => *id = self.globals[id],
+            // Functions are referred to by name, which linking keeps
+            Initializer::Zero
+            | Initializer::Immediate(_)
+            | Initializer::Address(Symbol::Function(_), _) => {}
+        }
+    }
+
+    fn import_ty(&self, ty: Type) -> Type {
+        self.dst
+            .types
+            .import_ty(&self.src_types, ty)
+            .expect("named structs are checked before importing")
+    }
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateSymbol(name) => write!(f, "duplicate symbol '{}'", name),
+            Self::SignatureMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "mismatched types for '{}': {} and {}",
+                name, expected, found
+            ),
+            Self::StructMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "mismatched bodies for 'struct {}': {} and {}",
+                name, expected, found
+            ),
+        }
+    }
+}
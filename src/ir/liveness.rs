@@ -15,7 +15,7 @@ impl<'a> IRLivenessAnalyzer<'a> {
 
     pub fn analyze(&mut self) {
         for (_, f) in &self.module.functions {
-            if f.is_internal {
+            if f.is_internal || f.is_prototype {
                 continue;
             }
 
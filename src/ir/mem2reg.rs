@@ -8,7 +8,7 @@ use crate::{
         opcode::{Instruction, InstructionId, Opcode, Operand},
         value::{InstructionValue, Value},
     },
-    traits::function::FunctionTrait,
+    traits::{function::FunctionTrait, pass::ModulePassTrait},
 };
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::cmp::Ordering;
@@ -31,6 +31,18 @@ struct InstructionIndexes {
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 struct BBWithLevel(usize, BasicBlockId);
 
+impl ModulePassTrait for Mem2Reg {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "Mem2Reg"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl Mem2Reg {
     pub fn new() -> Self {
         Self {}
@@ -38,7 +50,7 @@ impl Mem2Reg {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
 
@@ -325,12 +337,8 @@ impl<'a> Mem2RegOnFunction<'a> {
                     if let Some(val) = added_phi.get(&(cur, *alloca_id)) {
                         let incoming_val = incoming.get_mut(alloca_id).unwrap();
 
-                        // skip if incoming is not updated
-                        if *val == *incoming_val {
-                            continue;
-                        }
-
-                        // append new incoming to phi
+                        // append new incoming to phi. it may be the phi itself if `pred` doesn't
+                        // store to the alloca, but every predecessor still needs an incoming value
                         let phi_id = val.as_value().as_instruction().id;
                         Instruction::add_operand(
                             &mut self.cur_func.inst_table,
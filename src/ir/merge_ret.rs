@@ -6,6 +6,7 @@ use crate::ir::{
     types::Type,
     value::Value,
 };
+use crate::traits::pass::ModulePassTrait;
 
 pub struct MergeReturns {}
 
@@ -13,6 +14,18 @@ struct MergeReturnsOnFunction<'a> {
     func: &'a mut Function,
 }
 
+impl ModulePassTrait for MergeReturns {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "MergeReturns"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl MergeReturns {
     pub fn new() -> Self {
         Self {}
@@ -20,7 +33,7 @@ impl MergeReturns {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
 
@@ -58,6 +71,11 @@ impl<'a> MergeReturnsOnFunction<'a> {
             }
         }
 
+        // Every path ends in unreachable
+        if returns.is_empty() {
+            return;
+        }
+
         if returns.len() == 1 && return_at_last_block {
             return;
         }
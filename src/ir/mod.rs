@@ -1,20 +1,31 @@
 pub mod basic_block;
+pub mod bitcode;
 pub mod builder;
+pub mod clone;
 pub mod codegen_prepare;
+pub mod const_expr;
 pub mod const_folding;
 pub mod cse;
+pub mod dce;
 pub mod function;
 pub mod global_val;
+pub mod gvn;
+pub mod inline;
 pub mod inst_combine;
 pub mod licm;
+pub mod linker;
 pub mod liveness;
 pub mod mem2reg;
 pub mod merge_ret;
 pub mod module;
 pub mod opcode;
+pub mod parser;
+pub mod sccp;
+pub mod simplify_cfg;
 pub mod simplify_loop;
 pub mod types;
 pub mod value;
+pub mod verify;
 
 pub trait DumpToString {
     fn dump(&self, module: &module::Module) -> String;
@@ -1,4 +1,11 @@
-use super::{function::*, global_val::*, types::*, DumpToString};
+use super::{
+    const_expr::ConstExprs,
+    function::*,
+    global_val::*,
+    types::*,
+    value::{FunctionValue, Value},
+    DumpToString,
+};
 use id_arena::*;
 use std::fmt;
 
@@ -7,6 +14,7 @@ pub struct Module {
     pub name: String,
     pub functions: Arena<Function>,
     pub global_vars: GlobalVariables,
+    pub const_exprs: ConstExprs,
     pub types: Types,
 }
 
@@ -17,6 +25,7 @@ impl Module {
             name: name.to_string(),
             functions: Arena::new(),
             global_vars: GlobalVariables::new(types.clone()),
+            const_exprs: ConstExprs::default(),
             types,
         }
     }
@@ -30,6 +39,23 @@ impl Module {
         Function::new(self, name, ret_ty, params_ty)
     }
 
+    /// Declares a function defined outside of the module, such as `printf` in libc, so that it
+    /// can be called. If `is_var_arg` is true, any number of arguments may follow `params_ty`.
+    pub fn declare_function(
+        &mut self,
+        name: &str,
+        ret_ty: Type,
+        params_ty: Vec<Type>,
+        is_var_arg: bool,
+    ) -> FunctionId {
+        let id = Function::new(self, name, ret_ty, params_ty.clone());
+        if is_var_arg {
+            self.functions[id].ty = self.types.new_var_arg_function_ty(ret_ty, params_ty);
+        }
+        self.functions[id].is_prototype = true;
+        id
+    }
+
     pub fn add_function(&mut self, f: Function) -> FunctionId {
         let id = self.functions.alloc(f);
         self.function_ref_mut(id).id = Some(id);
@@ -54,6 +80,13 @@ impl Module {
             .find_map(|(id, f)| if f.name == name { Some(id) } else { None })
     }
 
+    /// Returns the address of a function as a value of pointer-to-function type, which can be
+    /// stored, passed around and called indirectly
+    pub fn function_address(&self, id: FunctionId) -> Value {
+        let ty = self.types.new_pointer_ty(self.functions[id].ty);
+        Value::Function(FunctionValue { func_id: id, ty })
+    }
+
     pub fn dump<T: DumpToString>(&self, obj: T) -> String {
         obj.dump(self)
     }
@@ -62,7 +95,8 @@ impl Module {
 impl fmt::Debug for Module {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Module (name: {})", self.name)?;
-        writeln!(f, "{:?}", self.global_vars)?;
+        write!(f, "{}", self.types.base.borrow().named_structs_to_string())?;
+        writeln!(f, "{}", self.global_vars.to_string(self))?;
         for (_, func) in &self.functions {
             writeln!(f, "{}", self.dump(func))?;
         }
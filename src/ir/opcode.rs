@@ -1,4 +1,10 @@
-use super::{basic_block::BasicBlockId, function::FunctionId, module::Module, types::*, value::*};
+use super::{
+    basic_block::BasicBlockId,
+    function::{FunctionId, Names},
+    module::Module,
+    types::*,
+    value::*,
+};
 use id_arena::{Arena, Id};
 use std::cell::RefCell;
 
@@ -29,17 +35,34 @@ pub enum Opcode {
     Mul,
     Div,
     Rem,
+    UDiv,
+    URem,
     Shl,
+    LShr,
+    AShr,
+    And,
+    Or,
+    Xor,
     SIToFP,
     FPToSI,
     Sext,
+    ZExt,
+    Trunc,
+    FPExt,
+    FPTrunc,
+    Bitcast,
+    PtrToInt,
+    IntToPtr,
     ICmp,
     FCmp,
+    Select, // cond, true value, false value
     Br,
     CondBr,
+    Switch, // cond, default block, [case value, block]*
     Phi,
     Call,
     Ret,
+    Unreachable,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy, Hash, Eq)]
@@ -59,7 +82,10 @@ pub enum ICmpKind {
     Lt,
     Ge,
     Gt,
-    // Ne,
+    Ult,
+    Ule,
+    Ugt,
+    Uge,
 }
 
 #[derive(Clone, Debug, Copy, PartialEq, Hash, Eq)]
@@ -192,32 +218,49 @@ impl Instruction {
     }
 
     pub fn fold_const(&self) -> Option<Value> {
-        let operands = &self.operands;
+        // The kind of a compare is not a value
         match self.opcode {
-            Opcode::Add => operands[0].as_value().const_add(&operands[1].as_value()),
-            Opcode::Sub => operands[0].as_value().const_sub(&operands[1].as_value()),
-            Opcode::Mul => operands[0].as_value().const_mul(&operands[1].as_value()),
-            Opcode::Div => operands[0].as_value().const_div(&operands[1].as_value()),
-            Opcode::Rem => operands[0].as_value().const_rem(&operands[1].as_value()),
-            _ => None,
+            Opcode::ICmp => {
+                return self.operands[1].get_value()?.const_icmp(
+                    *self.operands[0].as_icmp_kind(),
+                    self.operands[2].get_value()?,
+                )
+            }
+            Opcode::FCmp => {
+                return self.operands[1].get_value()?.const_fcmp(
+                    *self.operands[0].as_fcmp_kind(),
+                    self.operands[2].get_value()?,
+                )
+            }
+            _ => {}
         }
+        let operands = self
+            .operands
+            .iter()
+            .map(|op| op.get_value().copied())
+            .collect::<Option<Vec<_>>>()?;
+        self.opcode.fold_const(&operands, self.ty)
     }
 
     pub fn has_one_use(&self) -> bool {
         self.users.borrow().len() == 1
     }
 
-    pub fn to_string(&self, parent: &Module) -> String {
+    pub fn to_string(&self, parent: &Module, names: &Names) -> String {
         let mut output = self.opcode.to_string().to_owned();
         for (i, operand) in self.operands.iter().enumerate() {
             output = format!(
                 "{}{}{}",
                 output,
                 if i == 0 { " " } else { ", " },
-                operand.to_string(parent)
+                operand.to_string(parent, names)
             );
         }
 
+        if self.opcode.is_cast() {
+            output = format!("{} to {}", output, parent.types.to_string(self.ty));
+        }
+
         format!(
             "{} ",
             output,
@@ -230,14 +273,68 @@ impl Instruction {
 impl Opcode {
     pub fn returns_value(&self) -> bool {
         match self {
-            Opcode::Br | Opcode::CondBr | Opcode::Ret | Opcode::Store | Opcode::Call|
+            Opcode::Br | Opcode::CondBr | Opcode::Switch | Opcode::Ret | Opcode::Unreachable |
+            Opcode::Store | Opcode::Call |
                 /* alloca doesn't return value = */ Opcode::Alloca => false,
             _ => true,
         }
     }
 
     pub fn is_terminator(&self) -> bool {
-        matches!(self, Opcode::Br | Opcode::CondBr | Opcode::Ret)
+        matches!(
+            self,
+            Opcode::Br | Opcode::CondBr | Opcode::Switch | Opcode::Ret | Opcode::Unreachable
+        )
+    }
+
+    pub fn is_cast(&self) -> bool {
+        matches!(
+            self,
+            Opcode::SIToFP
+                | Opcode::FPToSI
+                | Opcode::Sext
+                | Opcode::ZExt
+                | Opcode::Trunc
+                | Opcode::FPExt
+                | Opcode::FPTrunc
+                | Opcode::Bitcast
+                | Opcode::PtrToInt
+                | Opcode::IntToPtr
+        )
+    }
+
+    /// Folds the operation on constant `operands` whose result is of type `ty`
+    pub fn fold_const(&self, operands: &[Value], ty: Type) -> Option<Value> {
+        match self {
+            Opcode::Add => operands[0].const_add(&operands[1]),
+            Opcode::Sub => operands[0].const_sub(&operands[1]),
+            Opcode::Mul => operands[0].const_mul(&operands[1]),
+            Opcode::Div => operands[0].const_div(&operands[1]),
+            Opcode::Rem => operands[0].const_rem(&operands[1]),
+            Opcode::UDiv => operands[0].const_udiv(&operands[1]),
+            Opcode::URem => operands[0].const_urem(&operands[1]),
+            Opcode::Shl => operands[0].const_shl(&operands[1]),
+            Opcode::LShr => operands[0].const_lshr(&operands[1]),
+            Opcode::AShr => operands[0].const_ashr(&operands[1]),
+            Opcode::And => operands[0].const_and(&operands[1]),
+            Opcode::Or => operands[0].const_or(&operands[1]),
+            Opcode::Xor => operands[0].const_xor(&operands[1]),
+            Opcode::SIToFP => operands[0].const_sitofp(ty),
+            Opcode::FPToSI => operands[0].const_fptosi(ty),
+            Opcode::Sext => operands[0].const_sext(ty),
+            Opcode::ZExt => operands[0].const_zext(ty),
+            Opcode::Trunc => operands[0].const_trunc(ty),
+            Opcode::FPExt | Opcode::FPTrunc => operands[0].const_fpcast(ty),
+            Opcode::Bitcast => operands[0].const_bitcast(ty),
+            Opcode::Select => operands[0].const_select(&operands[1], &operands[2]),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the instruction does more than computing its result, so it must be kept
+    /// even if the result is unused
+    pub fn has_side_effects(&self) -> bool {
+        self.is_terminator() || matches!(self, Opcode::Store | Opcode::Call)
     }
 
     pub fn access_memory(&self) -> bool {
@@ -255,17 +352,34 @@ impl Opcode {
             Opcode::Mul => "mul",
             Opcode::Div => "div",
             Opcode::Rem => "rem",
+            Opcode::UDiv => "udiv",
+            Opcode::URem => "urem",
             Opcode::Shl => "shl",
+            Opcode::LShr => "lshr",
+            Opcode::AShr => "ashr",
+            Opcode::And => "and",
+            Opcode::Or => "or",
+            Opcode::Xor => "xor",
             Opcode::SIToFP => "sitofp",
             Opcode::FPToSI => "fptosi",
             Opcode::Sext => "sext",
+            Opcode::ZExt => "zext",
+            Opcode::Trunc => "trunc",
+            Opcode::FPExt => "fpext",
+            Opcode::FPTrunc => "fptrunc",
+            Opcode::Bitcast => "bitcast",
+            Opcode::PtrToInt => "ptrtoint",
+            Opcode::IntToPtr => "inttoptr",
             Opcode::ICmp => "icmp",
             Opcode::FCmp => "fcmp",
+            Opcode::Select => "select",
             Opcode::Br => "br",
             Opcode::CondBr => "br",
+            Opcode::Switch => "switch",
             Opcode::Phi => "phi",
             Opcode::Call => "call",
             Opcode::Ret => "ret",
+            Opcode::Unreachable => "unreachable",
         }
     }
 }
@@ -276,9 +390,9 @@ impl Operand {
     }
 
     // TODO: should return cow?
-    pub fn to_string(&self, parent: &Module) -> String {
+    pub fn to_string(&self, parent: &Module, names: &Names) -> String {
         match self {
-            Self::BasicBlock(id) => format!("%label.{}", id.index()),
+            Self::BasicBlock(id) => format!("%{}", names.block_name(*id)),
             Self::ICmpKind(kind) => kind.as_str().to_owned(),
             Self::FCmpKind(kind) => kind.as_str().to_owned(),
             Self::Type(ty) => parent.types.to_string(*ty),
@@ -349,6 +463,10 @@ impl ICmpKind {
             ICmpKind::Le => "le",
             ICmpKind::Gt => "gt",
             ICmpKind::Ge => "ge",
+            ICmpKind::Ult => "ult",
+            ICmpKind::Ule => "ule",
+            ICmpKind::Ugt => "ugt",
+            ICmpKind::Uge => "uge",
         }
     }
 }
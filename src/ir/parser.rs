@@ -0,0 +1,1259 @@
+// Parser for the textual form printed by `DumpToString` and `fmt::Debug for Module`.
+//
+// Instruction and block numbers are kept as they are written, so printing a parsed module gives
+// back the same text. Named instructions and blocks (`%sum` and `loop:`) are numbered after the
+// numbered ones.
+
+use super::{
+    basic_block::*, function::*, global_val::*, module::Module, opcode::*, types::*, value::*,
+    verify,
+};
+use id_arena::{ArenaBehavior, DefaultArenaBehavior};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{fmt, mem};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub msg: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Punct(char),
+    Comment(String),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    module: Module,
+    /// Numbers given to the named instructions and blocks of the function being parsed
+    inst_names: FxHashMap<String, usize>,
+    block_names: FxHashMap<String, usize>,
+    /// Global variables whose initializer is yet to be parsed, and where it starts
+    inits: Vec<(GlobalVariableId, usize)>,
+}
+
+struct ParsedBlock {
+    index: usize,
+    liveness: Option<String>,
+    insts: Vec<ParsedInst>,
+}
+
+struct ParsedInst {
+    result: Option<usize>,
+    inst: Instruction,
+    line: usize,
+}
+
+type PResult<T> = Result<T, ParseError>;
+
+/// Types and names of parameters, and whether they end with `...`
+type Params = (Vec<Type>, Vec<(usize, String)>, bool);
+
+/// Parses a module printed by `fmt::Debug for Module`, or a sequence of functions printed by
+/// `Module::dump`.
+pub fn parse_module(src: &str) -> Result<Module, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(src),
+        pos: 0,
+        module: Module::new("cilk"),
+        inst_names: FxHashMap::default(),
+        block_names: FxHashMap::default(),
+        inits: vec![],
+    };
+    parser.parse()?;
+    Ok(parser.module)
+}
+
+fn tokenize(src: &str) -> Vec<(Token, usize)> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '$');
+    let mut tokens = vec![];
+    for (line, text) in src.lines().enumerate() {
+        let line = line + 1;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            if text[i..].starts_with("//") {
+                tokens.push((Token::Comment(text[i + 2..].trim().to_string()), line));
+                break;
+            }
+            if !is_word_char(c) {
+                tokens.push((Token::Punct(c), line));
+                continue;
+            }
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, c)) = chars.peek() {
+                if !is_word_char(c) {
+                    break;
+                }
+                end = j + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((Token::Word(text[i..end].to_string()), line));
+        }
+    }
+    tokens
+}
+
+impl Parser {
+    fn parse(&mut self) -> PResult<()> {
+        let mut bodies = vec![];
+
+        // Create named structs in the order they are defined, since types can refer to the ones
+        // defined later
+        for w in self.tokens.windows(3) {
+            if let [(Token::Word(s), _), (Token::Word(name), _), (Token::Punct('='), _)] = w {
+                if s == "struct" {
+                    self.module.types.new_named_struct_ty(name);
+                }
+            }
+        }
+
+        // Declare globals and functions first so that bodies can refer to any of them.
+        loop {
+            self.skip_comments();
+            match self.peek() {
+                None => break,
+                Some(Token::Word(w)) if w == "Module" => self.parse_module_header()?,
+                Some(Token::Punct('@')) => self.parse_global()?,
+                Some(Token::Word(w)) if w == "struct" => self.parse_struct_def()?,
+                Some(Token::Word(w)) if w == "declare" => {
+                    self.parse_function_declaration()?;
+                }
+                Some(Token::Word(w)) if w == "define" => {
+                    let func_id = self.parse_function_header()?;
+                    if self.eat_word("internal") {
+                        self.expect_punct(';')?;
+                        continue;
+                    }
+                    bodies.push((func_id, self.pos));
+                    self.skip_braces()?;
+                }
+                Some(_) => {
+                    return Err(
+                        self.error("expected 'define', 'declare', 'struct' or global variable")
+                    )
+                }
+            }
+        }
+
+        for (id, pos) in mem::take(&mut self.inits) {
+            self.pos = pos;
+            let ty = self.module.global_vars.arena[id].ty;
+            let init = self.parse_initializer(ty)?;
+            self.module.global_vars.arena[id].init = Some(init);
+        }
+
+        for (func_id, pos) in bodies {
+            self.pos = pos;
+            self.parse_function_body(func_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_module_header(&mut self) -> PResult<()> {
+        self.expect_word("Module")?;
+        self.expect_punct('(')?;
+        self.expect_word("name")?;
+        self.expect_punct(':')?;
+        self.module.name = self.word()?;
+        self.expect_punct(')')
+    }
+
+    /// Parses `struct Name = {ty, ...}` or `struct Name = opaque`.
+    fn parse_struct_def(&mut self) -> PResult<()> {
+        self.expect_word("struct")?;
+        let name = self.word()?;
+        self.expect_punct('=')?;
+        if self.eat_word("opaque") {
+            return Ok(());
+        }
+        let types = &self.module.types;
+        if let Some(ty) = types.find_named_struct_ty(name.as_str()) {
+            if !types.base.borrow().as_struct_ty(ty).unwrap().is_opaque() {
+                return Err(self.error_prev(&format!("struct {} is already defined", name)));
+            }
+        }
+        let fields_ty = self.parse_struct_fields()?;
+        let ty = self.module.types.new_named_struct_ty(name.as_str());
+        self.module.types.set_struct_body(ty, fields_ty);
+        Ok(())
+    }
+
+    fn parse_global(&mut self) -> PResult<()> {
+        self.expect_punct('@')?;
+        let name = self.word()?;
+        self.expect_punct('=')?;
+        let linkage = match self.word()?.as_str() {
+            "common" => Linkage::Common,
+            "external" => Linkage::External,
+            l => return Err(self.error(&format!("unknown linkage '{}'", l))),
+        };
+        let line = self.line();
+        let is_constant = match self.word()?.as_str() {
+            "global" => false,
+            "constant" => true,
+            w => {
+                return Err(
+                    self.error_prev(&format!("expected 'global' or 'constant', found '{}'", w))
+                )
+            }
+        };
+        let ty = self.parse_type()?;
+        if self.at_line_end(line) {
+            if is_constant {
+                return Err(self.error("constant global variable needs an initializer"));
+            }
+            self.module
+                .global_vars
+                .new_global_var_with_name(ty, linkage, name.as_str());
+            return Ok(());
+        }
+        // The initializer is parsed later since it may refer to any global variable or function
+        let id = self.module.global_vars.new_global_var_with_init(
+            ty,
+            linkage,
+            name.as_str(),
+            Initializer::Zero,
+            is_constant,
+        );
+        self.inits.push((id, self.pos));
+        let mut depth = 0;
+        while depth > 0 || !self.at_line_end(line) {
+            match self.peek() {
+                Some(Token::Punct('[')) | Some(Token::Punct('{')) | Some(Token::Punct('(')) => {
+                    depth += 1
+                }
+                Some(Token::Punct(']')) | Some(Token::Punct('}')) | Some(Token::Punct(')')) => {
+                    depth -= 1
+                }
+                None => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    /// Parses `zeroinitializer`, a scalar, an address, `[init, ...]` or `{init, ...}` of type `ty`.
+    fn parse_initializer(&mut self, ty: Type) -> PResult<Initializer> {
+        if self.eat_word("zeroinitializer") {
+            return Ok(Initializer::Zero);
+        }
+        let (open, close) = match ty {
+            Type::Array(_) => ('[', ']'),
+            Type::Struct(_) => ('{', '}'),
+            _ if matches!(ty, Type::Pointer(_))
+                || self.peek() == Some(&Token::Punct('@'))
+                || self.tokens.get(self.pos + 1).map(|(t, _)| t) == Some(&Token::Punct('(')) =>
+            {
+                let init = Initializer::Expr(self.parse_constant(ty)?);
+                if !init.is_valid_for(ty, &self.module.types) {
+                    return Err(self.error_prev(&format!(
+                        "invalid {} initializer",
+                        self.module.types.to_string(ty)
+                    )));
+                }
+                return Ok(init);
+            }
+            _ => {
+                let word = self.word()?;
+                let invalid = |p: &Self| {
+                    p.error_prev(&format!(
+                        "invalid {} constant '{}'",
+                        p.module.types.to_string(ty),
+                        word
+                    ))
+                };
+                let imm = match ty {
+                    Type::i1 => ImmediateValue::Int1(word.parse().map_err(|_| invalid(self))?),
+                    Type::i8 => ImmediateValue::Int8(word.parse().map_err(|_| invalid(self))?),
+                    Type::i16 => ImmediateValue::Int16(word.parse().map_err(|_| invalid(self))?),
+                    Type::i32 => ImmediateValue::Int32(word.parse().map_err(|_| invalid(self))?),
+                    Type::i64 => ImmediateValue::Int64(word.parse().map_err(|_| invalid(self))?),
+                    Type::f32 => ImmediateValue::F32(word.parse().map_err(|_| invalid(self))?),
+                    Type::f64 => ImmediateValue::F64(word.parse().map_err(|_| invalid(self))?),
+                    _ => return Err(invalid(self)),
+                };
+                return Ok(Initializer::Immediate(imm));
+            }
+        };
+        self.expect_punct(open)?;
+        let mut elems = vec![];
+        while !self.eat_punct(close) {
+            if !elems.is_empty() {
+                self.expect_punct(',')?;
+            }
+            if let Some(struct_ty) = self.module.types.base.borrow().as_struct_ty(ty) {
+                if struct_ty.get_elem_offset(elems.len()).is_none() {
+                    return Err(self.error("too many elements in initializer"));
+                }
+            }
+            let elem_ty = self
+                .module
+                .types
+                .get_element_ty(ty, Some(&Value::new_imm_int32(elems.len() as i32)));
+            elems.push(self.parse_initializer(elem_ty.unwrap())?);
+        }
+        let init = match ty {
+            Type::Array(_) => Initializer::Array(elems),
+            _ => Initializer::Struct(elems),
+        };
+        if !init.is_valid_for(ty, &self.module.types) {
+            return Err(self.error_prev("too many elements in initializer"));
+        }
+        Ok(init)
+    }
+
+    fn parse_function_header(&mut self) -> PResult<FunctionId> {
+        self.expect_word("define")?;
+        let ret_ty = self.parse_type()?;
+        let name = self.word()?;
+        if self.module.find_function(name.as_str()).is_some() {
+            return Err(self.error(&format!("function '{}' is defined twice", name)));
+        }
+        let (params_ty, param_names, is_var_arg) = self.parse_params()?;
+        if is_var_arg {
+            return Err(self.error_prev("only a declared function can take variable arguments"));
+        }
+        let func_id = self
+            .module
+            .create_function(name.as_str(), ret_ty, params_ty);
+        let names = &mut self.module.function_ref_mut(func_id).names;
+        for (idx, name) in param_names {
+            names.set_param_name(idx, name.as_str());
+        }
+        Ok(func_id)
+    }
+
+    fn parse_function_declaration(&mut self) -> PResult<()> {
+        self.expect_word("declare")?;
+        let ret_ty = self.parse_type()?;
+        let name = self.word()?;
+        if self.module.find_function(name.as_str()).is_some() {
+            return Err(self.error(&format!("function '{}' is defined twice", name)));
+        }
+        let (params_ty, _, is_var_arg) = self.parse_params()?;
+        self.module
+            .declare_function(name.as_str(), ret_ty, params_ty, is_var_arg);
+        Ok(())
+    }
+
+    /// Parses `(ty, ty byval %name, ...)` and returns the parameter types, their names and
+    /// whether the list ends with `...`. A byval parameter is printed as a pointer to the struct,
+    /// while `new_function_ty` expects the struct itself.
+    fn parse_params(&mut self) -> PResult<Params> {
+        let mut params_ty = vec![];
+        let mut names = vec![];
+        self.expect_punct('(')?;
+        if self.eat_punct(')') {
+            return Ok((params_ty, names, false));
+        }
+        loop {
+            if self.eat_word("...") {
+                self.expect_punct(')')?;
+                return Ok((params_ty, names, true));
+            }
+            let mut ty = self.parse_type()?;
+            if self.eat_word("byval") {
+                ty = match ty {
+                    Type::Pointer(_) => self.module.types.get_element_ty(ty, None).unwrap(),
+                    _ => return Err(self.error("byval parameter must be a pointer")),
+                };
+            }
+            if self.eat_punct('%') {
+                let name = self.word()?;
+                if names.iter().any(|(_, n)| *n == name) {
+                    return Err(self.error_prev(&format!("%{} is defined twice", name)));
+                }
+                names.push((params_ty.len(), name));
+            }
+            params_ty.push(ty);
+            if self.eat_punct(')') {
+                return Ok((params_ty, names, false));
+            }
+            self.expect_punct(',')?;
+        }
+    }
+
+    fn parse_type(&mut self) -> PResult<Type> {
+        let mut ty = if self.eat_punct('[') {
+            let len = self.integer::<usize>()?;
+            self.expect_word("x")?;
+            let elem_ty = self.parse_type()?;
+            self.expect_punct(']')?;
+            self.module.types.new_array_ty(elem_ty, len)
+        } else {
+            match self.word()?.as_str() {
+                "void" => Type::Void,
+                "i1" => Type::i1,
+                "i8" => Type::i8,
+                "i16" => Type::i16,
+                "i32" => Type::i32,
+                "i64" => Type::i64,
+                "f32" => Type::f32,
+                "f64" => Type::f64,
+                "struct" if self.peek() == Some(&Token::Punct('{')) => {
+                    let fields_ty = self.parse_struct_fields()?;
+                    self.module.types.new_struct_ty(fields_ty)
+                }
+                "struct" => {
+                    let name = self.word()?;
+                    self.module.types.new_named_struct_ty(name.as_str())
+                }
+                t => return Err(self.error_prev(&format!("unknown type '{}'", t))),
+            }
+        };
+
+        loop {
+            if self.eat_punct('*') {
+                ty = self.module.types.new_pointer_ty(ty);
+            } else if self.peek() == Some(&Token::Punct('(')) {
+                let (params_ty, _, is_var_arg) = self.parse_params()?;
+                ty = if is_var_arg {
+                    self.module.types.new_var_arg_function_ty(ty, params_ty)
+                } else {
+                    self.module.types.new_function_ty(ty, params_ty)
+                };
+            } else {
+                return Ok(ty);
+            }
+        }
+    }
+
+    /// Parses `{ty, ...}`.
+    fn parse_struct_fields(&mut self) -> PResult<Vec<Type>> {
+        let mut fields_ty = vec![];
+        self.expect_punct('{')?;
+        while !self.eat_punct('}') {
+            if !fields_ty.is_empty() {
+                self.expect_punct(',')?;
+            }
+            fields_ty.push(self.parse_type()?);
+        }
+        Ok(fields_ty)
+    }
+
+    fn parse_function_body(&mut self, func_id: FunctionId) -> PResult<()> {
+        let (inst_arena_id, block_arena_id) = {
+            let f = self.module.function_ref(func_id);
+            (
+                DefaultArenaBehavior::arena_id(f.inst_table.next_id()),
+                DefaultArenaBehavior::arena_id(f.basic_blocks.arena.next_id()),
+            )
+        };
+        let inst_id = |n: usize| DefaultArenaBehavior::<Instruction>::new_id(inst_arena_id, n);
+        let block_id = |n: usize| DefaultArenaBehavior::<BasicBlock>::new_id(block_arena_id, n);
+
+        self.number_names(func_id)?;
+        self.expect_punct('{')?;
+
+        let mut blocks: Vec<ParsedBlock> = vec![];
+        loop {
+            self.skip_comments();
+            if self.eat_punct('}') {
+                break;
+            }
+            if let Some(index) = self.peek_label() {
+                self.pos += 1;
+                self.expect_punct(':')?;
+                let liveness = match self.peek() {
+                    Some(Token::Comment(c)) => Some(c.clone()),
+                    _ => None,
+                };
+                blocks.push(ParsedBlock {
+                    index,
+                    liveness,
+                    insts: vec![],
+                });
+                continue;
+            }
+            let parent = match blocks.last() {
+                Some(block) => block_id(block.index),
+                None => return Err(self.error("instruction outside of a block")),
+            };
+            let parsed = self.parse_inst(func_id, parent, &inst_id, &block_id)?;
+            blocks.last_mut().unwrap().insts.push(parsed);
+        }
+
+        let mut named = FxHashSet::default();
+        let mut num_unnamed = 0;
+        let mut num_blocks = 0;
+        for (b, block) in blocks.iter().enumerate() {
+            if blocks[..b].iter().any(|bb| bb.index == block.index) {
+                return Err(self.error(&format!("label.{} is defined twice", block.index)));
+            }
+            num_blocks = num_blocks.max(block.index + 1);
+            for parsed in &block.insts {
+                match parsed.result {
+                    Some(n) if !named.insert(n) => {
+                        return Err(ParseError {
+                            line: parsed.line,
+                            msg: format!("%{} is defined twice", n),
+                        })
+                    }
+                    Some(_) => {}
+                    None => num_unnamed += 1,
+                }
+            }
+        }
+        let num_insts = named
+            .iter()
+            .map(|n| n + 1)
+            .max()
+            .unwrap_or(0)
+            .max(named.len() + num_unnamed);
+
+        // Make sure every operand refers to something that exists
+        for block in &blocks {
+            for parsed in &block.insts {
+                for operand in &parsed.inst.operands {
+                    let err = match operand {
+                        Operand::Value(Value::Instruction(iv))
+                            if !named.contains(&iv.id.index()) =>
+                        {
+                            format!("%{} is not defined", iv.id.index())
+                        }
+                        Operand::BasicBlock(bb)
+                            if !blocks.iter().any(|b| b.index == bb.index()) =>
+                        {
+                            format!("label.{} is not defined", bb.index())
+                        }
+                        _ => continue,
+                    };
+                    return Err(ParseError {
+                        line: parsed.line,
+                        msg: err,
+                    });
+                }
+            }
+        }
+
+        let entry = match blocks.first() {
+            Some(block) => block_id(block.index),
+            None => return Err(self.error("function has no blocks")),
+        };
+
+        // Decide where each instruction goes in `inst_table`. Named instructions keep their
+        // number and the others fill the gaps in order.
+        let mut free = (0..num_insts).filter(|n| !named.contains(n));
+        let mut table: Vec<Option<Instruction>> = (0..num_insts).map(|_| None).collect();
+        let mut iseqs = vec![];
+        for block in blocks.iter_mut() {
+            let mut iseq = vec![];
+            for parsed in block.insts.drain(..) {
+                let n = match parsed.result {
+                    Some(n) => n,
+                    None => free.next().unwrap(),
+                };
+                table[n] = Some(parsed.inst);
+                iseq.push(n);
+            }
+            iseqs.push(iseq);
+        }
+
+        let inst_names = &self.inst_names;
+        let func = self.module.function_ref_mut(func_id);
+
+        for _ in 0..num_blocks {
+            func.basic_blocks.arena.alloc(BasicBlock::new());
+        }
+        func.basic_blocks.order = blocks.iter().map(|b| block_id(b.index)).collect();
+
+        let mut in_block = vec![];
+        for inst in table {
+            in_block.push(inst.is_some());
+            // A hole left by a removed instruction
+            let inst = inst.unwrap_or_else(|| {
+                Instruction::new(
+                    Opcode::Ret,
+                    vec![Operand::Value(Value::None)],
+                    Type::Void,
+                    entry,
+                )
+            });
+            let id = func.inst_table.alloc(inst);
+            func.inst_table[id].set_id(id);
+        }
+        for (n, _) in in_block.into_iter().enumerate().filter(|(_, b)| *b) {
+            func.inst_table[inst_id(n)].set_users(&func.inst_table);
+        }
+
+        let mut names = inst_names.iter().collect::<Vec<_>>();
+        names.sort_by_key(|(_, &n)| n);
+        for (name, &n) in names {
+            func.names.set_inst_name(inst_id(n), name);
+        }
+        let mut names = self.block_names.iter().collect::<Vec<_>>();
+        names.sort_by_key(|(_, &n)| n);
+        for (name, &n) in names {
+            func.names.set_block_name(block_id(n), name);
+        }
+
+        for (block, iseq) in blocks.iter().zip(iseqs) {
+            let bb_id = block_id(block.index);
+            for n in iseq {
+                let id = inst_id(n);
+                let inst = &func.inst_table[id];
+                for operand in &inst.operands {
+                    if let Operand::BasicBlock(succ) = operand {
+                        if !inst.opcode.is_terminator() {
+                            continue;
+                        }
+                        func.basic_blocks.arena[bb_id].succ.insert(*succ);
+                        func.basic_blocks.arena[*succ].pred.insert(bb_id);
+                    }
+                }
+                let ty = inst.ty;
+                func.basic_blocks.arena[bb_id]
+                    .iseq_ref_mut()
+                    .push(Value::Instruction(InstructionValue { func_id, id, ty }));
+            }
+            if let Some(liveness) = &block.liveness {
+                let mut info = func.basic_blocks.arena[bb_id].liveness.borrow_mut();
+                for (key, ids) in parse_block_comment(liveness) {
+                    let set = match key {
+                        "def" => &mut info.def,
+                        "in" => &mut info.live_in,
+                        "out" => &mut info.live_out,
+                        _ => continue,
+                    };
+                    set.extend(
+                        ids.into_iter()
+                            .filter_map(|w| w.parse().ok().or_else(|| inst_names.get(w).copied()))
+                            .filter(|&n| n < num_insts)
+                            .map(inst_id),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Numbers the named instructions and blocks of the function body that starts here, after
+    /// the largest numbers used by the unnamed ones.
+    fn number_names(&mut self, func_id: FunctionId) -> PResult<()> {
+        self.inst_names.clear();
+        self.block_names.clear();
+        let mut used: FxHashSet<&String> = self
+            .module
+            .function_ref(func_id)
+            .names
+            .params
+            .values()
+            .collect();
+        let mut num_insts = 0;
+        let mut num_blocks = 0;
+        let mut insts = vec![];
+        let mut blocks = vec![];
+        let mut depth = 0;
+        let token = |i: usize| self.tokens.get(i).map(|(t, _)| t);
+        for (i, (tok, line)) in self.tokens.iter().enumerate().skip(self.pos) {
+            let name = match (tok, token(i + 1), token(i + 2)) {
+                (Token::Punct('{'), _, _) => {
+                    depth += 1;
+                    continue;
+                }
+                (Token::Punct('}'), _, _) if depth == 1 => break,
+                (Token::Punct('}'), _, _) => {
+                    depth -= 1;
+                    continue;
+                }
+                (Token::Punct('%'), Some(Token::Word(w)), Some(Token::Punct('='))) => {
+                    match w.parse::<usize>() {
+                        Ok(n) => num_insts = num_insts.max(n + 1),
+                        Err(_) => insts.push(w),
+                    }
+                    w
+                }
+                // A label is the first token of its line
+                (Token::Word(w), Some(Token::Punct(':')), _)
+                    if i == 0 || self.tokens[i - 1].1 != *line =>
+                {
+                    match w
+                        .strip_prefix("label.")
+                        .and_then(|n| n.parse::<usize>().ok())
+                    {
+                        Some(n) => num_blocks = num_blocks.max(n + 1),
+                        None => blocks.push(w),
+                    }
+                    w
+                }
+                _ => continue,
+            };
+            if name.parse::<usize>().is_err() && !name.starts_with("label.") && !used.insert(name) {
+                return Err(ParseError {
+                    line: *line,
+                    msg: format!("%{} is defined twice", name),
+                });
+            }
+        }
+        self.inst_names = (num_insts..)
+            .zip(insts)
+            .map(|(n, name)| (name.clone(), n))
+            .collect();
+        self.block_names = (num_blocks..)
+            .zip(blocks)
+            .map(|(n, name)| (name.clone(), n))
+            .collect();
+        Ok(())
+    }
+
+    fn parse_inst(
+        &mut self,
+        func_id: FunctionId,
+        parent: BasicBlockId,
+        inst_id: &dyn Fn(usize) -> InstructionId,
+        block_id: &dyn Fn(usize) -> BasicBlockId,
+    ) -> PResult<ParsedInst> {
+        let line = self.line();
+        let result = if self.eat_punct('%') {
+            let name = self.word()?;
+            let n = match name.parse::<usize>() {
+                Ok(n) => n,
+                Err(_) => match self.inst_names.get(&name) {
+                    Some(&n) => n,
+                    None => return Err(self.error_prev(&format!("%{} is not defined", name))),
+                },
+            };
+            self.expect_punct('=')?;
+            Some(n)
+        } else {
+            None
+        };
+
+        let opcode_name = self.word()?;
+        let mut operands = vec![];
+        let opcode = match opcode_name.as_str() {
+            "alloca" => Opcode::Alloca,
+            "load" => Opcode::Load,
+            "store" => Opcode::Store,
+            "getelementptr" => Opcode::GetElementPtr,
+            "add" => Opcode::Add,
+            "sub" => Opcode::Sub,
+            "mul" => Opcode::Mul,
+            "div" => Opcode::Div,
+            "rem" => Opcode::Rem,
+            "udiv" => Opcode::UDiv,
+            "urem" => Opcode::URem,
+            "shl" => Opcode::Shl,
+            "lshr" => Opcode::LShr,
+            "ashr" => Opcode::AShr,
+            "and" => Opcode::And,
+            "or" => Opcode::Or,
+            "xor" => Opcode::Xor,
+            "sitofp" => Opcode::SIToFP,
+            "fptosi" => Opcode::FPToSI,
+            "sext" => Opcode::Sext,
+            "zext" => Opcode::ZExt,
+            "trunc" => Opcode::Trunc,
+            "fpext" => Opcode::FPExt,
+            "fptrunc" => Opcode::FPTrunc,
+            "bitcast" => Opcode::Bitcast,
+            "ptrtoint" => Opcode::PtrToInt,
+            "inttoptr" => Opcode::IntToPtr,
+            "icmp" => Opcode::ICmp,
+            "fcmp" => Opcode::FCmp,
+            "select" => Opcode::Select,
+            "br" if self.peek_block_operand() => Opcode::Br,
+            "br" => Opcode::CondBr,
+            "switch" => Opcode::Switch,
+            "phi" => Opcode::Phi,
+            "call" => Opcode::Call,
+            "ret" => Opcode::Ret,
+            "unreachable" => Opcode::Unreachable,
+            o => return Err(self.error_prev(&format!("unknown opcode '{}'", o))),
+        };
+
+        match opcode {
+            Opcode::Alloca => operands.push(Operand::Type(self.parse_type()?)),
+            Opcode::ICmp => {
+                let kind = match self.word()?.as_str() {
+                    "eq" => ICmpKind::Eq,
+                    "ne" => ICmpKind::Ne,
+                    "lt" => ICmpKind::Lt,
+                    "le" => ICmpKind::Le,
+                    "gt" => ICmpKind::Gt,
+                    "ge" => ICmpKind::Ge,
+                    "ult" => ICmpKind::Ult,
+                    "ule" => ICmpKind::Ule,
+                    "ugt" => ICmpKind::Ugt,
+                    "uge" => ICmpKind::Uge,
+                    k => return Err(self.error_prev(&format!("unknown icmp kind '{}'", k))),
+                };
+                operands.push(Operand::ICmpKind(kind));
+                self.expect_punct(',')?;
+                self.parse_operand_list(&mut operands, func_id, inst_id, block_id)?;
+            }
+            Opcode::FCmp => {
+                let kind = match self.word()?.as_str() {
+                    "ueq" => FCmpKind::UEq,
+                    "une" => FCmpKind::UNe,
+                    "ult" => FCmpKind::ULt,
+                    "ule" => FCmpKind::ULe,
+                    "ugt" => FCmpKind::UGt,
+                    "uge" => FCmpKind::UGe,
+                    k => return Err(self.error_prev(&format!("unknown fcmp kind '{}'", k))),
+                };
+                operands.push(Operand::FCmpKind(kind));
+                self.expect_punct(',')?;
+                self.parse_operand_list(&mut operands, func_id, inst_id, block_id)?;
+            }
+            Opcode::Ret if self.at_line_end(line) => operands.push(Operand::Value(Value::None)),
+            Opcode::Unreachable => {}
+            _ => self.parse_operand_list(&mut operands, func_id, inst_id, block_id)?,
+        }
+
+        let cast_ty = if opcode.is_cast() {
+            self.expect_word("to")?;
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        let ty = self.result_type(opcode, &operands, cast_ty)?;
+        match result {
+            Some(_) if ty == Type::Void => {
+                return Err(ParseError {
+                    line,
+                    msg: format!("'{}' does not produce a value", opcode_name),
+                })
+            }
+            None if ty != Type::Void => {
+                return Err(ParseError {
+                    line,
+                    msg: format!("result of '{}' must be named", opcode_name),
+                })
+            }
+            _ => {}
+        }
+
+        Ok(ParsedInst {
+            result,
+            inst: Instruction::new(opcode, operands, ty, parent),
+            line,
+        })
+    }
+
+    /// Parses `operand, operand, ...`.
+    fn parse_operand_list(
+        &mut self,
+        operands: &mut Vec<Operand>,
+        func_id: FunctionId,
+        inst_id: &dyn Fn(usize) -> InstructionId,
+        block_id: &dyn Fn(usize) -> BasicBlockId,
+    ) -> PResult<()> {
+        operands.push(self.parse_operand(func_id, inst_id, block_id)?);
+        while self.eat_punct(',') {
+            operands.push(self.parse_operand(func_id, inst_id, block_id)?);
+        }
+        Ok(())
+    }
+
+    fn parse_operand(
+        &mut self,
+        func_id: FunctionId,
+        inst_id: &dyn Fn(usize) -> InstructionId,
+        block_id: &dyn Fn(usize) -> BasicBlockId,
+    ) -> PResult<Operand> {
+        if self.peek_block_operand() {
+            self.pos += 1;
+            let label = self.word()?;
+            return match self.label_index(&label) {
+                Some(index) => Ok(Operand::BasicBlock(block_id(index))),
+                None => Err(self.error_prev(&format!("unknown label %{}", label))),
+            };
+        }
+        if self.eat_punct('%') {
+            let name = self.word()?;
+            return Err(self.error_prev(&format!("%{} is not defined", name)));
+        }
+
+        let line = self.line();
+        let ty = self.parse_type()?;
+
+        if self.eat_punct('%') {
+            let name = self.word()?;
+            if let Some(index) = name.strip_prefix("arg.") {
+                let index = index
+                    .parse::<usize>()
+                    .map_err(|_| self.error_prev(&format!("invalid argument '%{}'", name)))?;
+                return self
+                    .module
+                    .function_ref(func_id)
+                    .get_param_value(index)
+                    .map(Operand::Value)
+                    .ok_or_else(|| self.error_prev(&format!("no such argument '%{}'", name)));
+            }
+            if let Some(n) = name
+                .parse::<usize>()
+                .ok()
+                .or_else(|| self.inst_names.get(&name).copied())
+            {
+                return Ok(Operand::new_inst(func_id, inst_id(n), ty));
+            }
+            let f = self.module.function_ref(func_id);
+            return f
+                .names
+                .params
+                .iter()
+                .find(|(_, n)| **n == name)
+                .and_then(|(&index, _)| f.get_param_value(index))
+                .map(Operand::Value)
+                .ok_or_else(|| self.error_prev(&format!("invalid value '%{}'", name)));
+        }
+
+        if self.at_line_end(line) || self.peek() == Some(&Token::Punct(',')) {
+            return match ty {
+                Type::Void => Ok(Operand::Value(Value::None)),
+                _ => Err(self.error("expected value")),
+            };
+        }
+
+        self.parse_constant(ty).map(Operand::Value)
+    }
+
+    /// Parses a constant of type `ty`: a global variable, a function, a constant expression or an
+    /// immediate.
+    fn parse_constant(&mut self, ty: Type) -> PResult<Value> {
+        if self.eat_punct('@') {
+            let name = self.word()?;
+            return self
+                .module
+                .global_vars
+                .arena
+                .iter()
+                .find(|(_, g)| g.name == name)
+                .map(|(id, _)| Value::Global(GlobalValue { id, ty }))
+                .ok_or_else(|| self.error_prev(&format!("unknown global variable '@{}'", name)));
+        }
+
+        let word = self.word()?;
+        if self.peek() == Some(&Token::Punct('(')) {
+            return self.parse_const_expr(&word, ty);
+        }
+        // A function is printed as its return type followed by its name, and the address of a
+        // function as its pointer type followed by its name
+        if let Some(id) = self.module.find_function(word.as_str()) {
+            let f_ty = self.module.function_ref(id).ty;
+            if matches!(ty, Type::Pointer(_))
+                && self.module.types.get_element_ty(ty, None) == Some(f_ty)
+            {
+                return Ok(self.module.function_address(id));
+            }
+            return Ok(Value::Function(FunctionValue {
+                func_id: id,
+                ty: f_ty,
+            }));
+        }
+        let invalid = |p: &Self| {
+            p.error_prev(&format!(
+                "invalid {} constant '{}'",
+                p.module.types.to_string(ty),
+                word
+            ))
+        };
+        let imm = match ty {
+            Type::i1 => ImmediateValue::Int1(word.parse().map_err(|_| invalid(self))?),
+            Type::i8 => ImmediateValue::Int8(word.parse().map_err(|_| invalid(self))?),
+            Type::i16 => ImmediateValue::Int16(word.parse().map_err(|_| invalid(self))?),
+            Type::i32 => ImmediateValue::Int32(word.parse().map_err(|_| invalid(self))?),
+            Type::i64 => ImmediateValue::Int64(word.parse().map_err(|_| invalid(self))?),
+            Type::f32 => ImmediateValue::F32(word.parse().map_err(|_| invalid(self))?),
+            Type::f64 => ImmediateValue::F64(word.parse().map_err(|_| invalid(self))?),
+            _ => return Err(self.error_prev(&format!("unknown function '{}'", word))),
+        };
+        Ok(Value::Immediate(imm))
+    }
+
+    /// Parses `(constant, ...)` of the constant expression `opcode_name` of type `ty`.
+    fn parse_const_expr(&mut self, opcode_name: &str, ty: Type) -> PResult<Value> {
+        let opcode = match opcode_name {
+            "getelementptr" => Opcode::GetElementPtr,
+            "add" => Opcode::Add,
+            "sub" => Opcode::Sub,
+            "mul" => Opcode::Mul,
+            "div" => Opcode::Div,
+            "rem" => Opcode::Rem,
+            "udiv" => Opcode::UDiv,
+            "urem" => Opcode::URem,
+            "shl" => Opcode::Shl,
+            "lshr" => Opcode::LShr,
+            "ashr" => Opcode::AShr,
+            "and" => Opcode::And,
+            "or" => Opcode::Or,
+            "xor" => Opcode::Xor,
+            "sext" => Opcode::Sext,
+            "zext" => Opcode::ZExt,
+            "trunc" => Opcode::Trunc,
+            "fpext" => Opcode::FPExt,
+            "fptrunc" => Opcode::FPTrunc,
+            "bitcast" => Opcode::Bitcast,
+            "ptrtoint" => Opcode::PtrToInt,
+            "inttoptr" => Opcode::IntToPtr,
+            o => return Err(self.error_prev(&format!("unknown constant expression '{}'", o))),
+        };
+        self.expect_punct('(')?;
+        let mut operands = vec![];
+        while !self.eat_punct(')') {
+            if !operands.is_empty() {
+                self.expect_punct(',')?;
+            }
+            let ty = self.parse_type()?;
+            operands.push(self.parse_constant(ty)?);
+        }
+        let is_valid = match opcode {
+            Opcode::GetElementPtr if !operands.is_empty() => {
+                self.module
+                    .const_gep(operands[0], operands[1..].to_vec())
+                    .map(|v| v.get_type())
+                    == Some(ty)
+            }
+            Opcode::GetElementPtr => false,
+            _ if opcode.is_cast() => operands.len() == 1,
+            _ => operands.len() == 2 && operands.iter().all(|v| v.get_type() == ty),
+        };
+        if !is_valid {
+            return Err(self.error_prev(&format!(
+                "invalid operands for constant expression '{}'",
+                opcode_name
+            )));
+        }
+        Ok(self.module.const_expr(opcode, operands, ty))
+    }
+
+    fn result_type(
+        &self,
+        opcode: Opcode,
+        operands: &[Operand],
+        cast_ty: Option<Type>,
+    ) -> PResult<Type> {
+        let types = &self.module.types;
+        let value_ty = |i: usize| {
+            operands
+                .get(i)
+                .and_then(|o| o.get_value())
+                .map(|v| v.get_type())
+        };
+        let ty = match opcode {
+            Opcode::Alloca => Some(types.new_pointer_ty(*operands[0].as_type())),
+            Opcode::Load => match value_ty(0) {
+                Some(ty @ Type::Pointer(_)) => types.get_element_ty(ty, None),
+                _ => None,
+            },
+            Opcode::GetElementPtr => {
+                let elem_ty = verify::gep_elem_ty(types, operands)
+                    .map_err(|msg| self.error_prev(&format!("invalid getelementptr: {}", msg)))?;
+                Some(types.new_pointer_ty(elem_ty))
+            }
+            Opcode::Add
+            | Opcode::Sub
+            | Opcode::Mul
+            | Opcode::Div
+            | Opcode::Rem
+            | Opcode::UDiv
+            | Opcode::URem
+            | Opcode::Shl
+            | Opcode::LShr
+            | Opcode::AShr
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Phi => value_ty(0),
+            Opcode::SIToFP
+            | Opcode::FPToSI
+            | Opcode::Sext
+            | Opcode::ZExt
+            | Opcode::Trunc
+            | Opcode::FPExt
+            | Opcode::FPTrunc
+            | Opcode::Bitcast
+            | Opcode::PtrToInt
+            | Opcode::IntToPtr => cast_ty,
+            Opcode::ICmp | Opcode::FCmp => Some(Type::i1),
+            Opcode::Select => value_ty(1),
+            Opcode::Store
+            | Opcode::Br
+            | Opcode::CondBr
+            | Opcode::Switch
+            | Opcode::Ret
+            | Opcode::Unreachable => Some(Type::Void),
+            Opcode::Call => {
+                value_ty(0).and_then(|ty| types.base.borrow().as_callee_ty(ty).map(|f| f.ret_ty))
+            }
+        };
+        ty.ok_or_else(|| self.error_prev("cannot determine the type of the result"))
+    }
+
+    // Utils
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    /// Returns the number of a block if the next tokens are `label.N` or `name:`.
+    fn peek_label(&self) -> Option<usize> {
+        let w = match self.peek() {
+            Some(Token::Word(w)) => w,
+            _ => return None,
+        };
+        if !w.starts_with("label.")
+            && self.tokens.get(self.pos + 1).map(|(t, _)| t) != Some(&Token::Punct(':'))
+        {
+            return None;
+        }
+        self.label_index(w)
+    }
+
+    /// Returns true if the next tokens are `%label.N` or `%name` of a block.
+    fn peek_block_operand(&self) -> bool {
+        if self.peek() != Some(&Token::Punct('%')) {
+            return false;
+        }
+        match self.tokens.get(self.pos + 1) {
+            Some((Token::Word(w), _)) => {
+                w.starts_with("label.") || self.block_names.contains_key(w)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the number of the block `label.N` or `name`.
+    fn label_index(&self, label: &str) -> Option<usize> {
+        match label.strip_prefix("label.") {
+            Some(n) => n.parse().ok(),
+            None => self.block_names.get(label).copied(),
+        }
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |(_, line)| *line)
+    }
+
+    fn at_line_end(&self, line: usize) -> bool {
+        match self.tokens.get(self.pos) {
+            None | Some((Token::Comment(_), _)) => true,
+            Some((_, l)) => *l != line,
+        }
+    }
+
+    fn skip_comments(&mut self) {
+        while let Some(Token::Comment(_)) = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips a `{ ... }` block, which may contain nested braces of struct types.
+    fn skip_braces(&mut self) -> PResult<()> {
+        self.expect_punct('{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Some(Token::Punct('{')) => depth += 1,
+                Some(Token::Punct('}')) => depth -= 1,
+                Some(_) => {}
+                None => return Err(self.error("unexpected end of input")),
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w == word => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> PResult<()> {
+        if self.eat_punct(c) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected '{}'", c)))
+    }
+
+    fn expect_word(&mut self, word: &str) -> PResult<()> {
+        if self.eat_word(word) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected '{}'", word)))
+    }
+
+    fn word(&mut self) -> PResult<String> {
+        match self.peek() {
+            Some(Token::Word(w)) => {
+                let w = w.clone();
+                self.pos += 1;
+                Ok(w)
+            }
+            _ => Err(self.error("expected identifier")),
+        }
+    }
+
+    fn integer<T: ::std::str::FromStr>(&mut self) -> PResult<T> {
+        let word = self.word()?;
+        word.parse()
+            .map_err(|_| self.error_prev(&format!("expected integer, found '{}'", word)))
+    }
+
+    fn error(&self, msg: &str) -> ParseError {
+        ParseError {
+            line: self.line(),
+            msg: msg.to_string(),
+        }
+    }
+
+    fn error_prev(&self, msg: &str) -> ParseError {
+        ParseError {
+            line: self.tokens[..self.pos].last().map_or(0, |(_, line)| *line),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+/// Parses the comment printed after a label, e.g. `pred(0), succ(2,3), def(1,sum), in(), out(1)`.
+fn parse_block_comment(comment: &str) -> Vec<(&str, Vec<&str>)> {
+    comment
+        .split(')')
+        .filter_map(|item| {
+            let (key, list) = item
+                .trim_start_matches(|c: char| c == ',' || c.is_whitespace())
+                .split_once('(')?;
+            Some((
+                key,
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .collect(),
+            ))
+        })
+        .collect()
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
@@ -0,0 +1,337 @@
+// Sparse conditional constant propagation.
+//
+// Every instruction starts as `Unknown` and only the entry block is executable. Visiting an
+// instruction lowers its lattice value using the values of its operands; a phi only looks at
+// incoming values from edges known to be executable, and a branch only makes the edges it can take
+// executable. Once nothing changes, instructions proven constant are replaced with the constant,
+// branches on a constant become unconditional and blocks that can never run are removed.
+//
+// Only immediates are tracked as constants, and the pass expects SSA form (e.g. after mem2reg).
+
+use crate::ir::{
+    basic_block::BasicBlockId,
+    function::Function,
+    module::Module,
+    opcode::{Instruction, InstructionId, Opcode, Operand},
+    types::Type,
+    value::{ImmediateValue, InstructionValue, Value},
+};
+use crate::traits::pass::ModulePassTrait;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+#[derive(Default)]
+pub struct SparseConditionalConstantPropagation {}
+
+struct SparseConditionalConstantPropagationOnFunction<'a> {
+    func: &'a mut Function,
+    lattice: FxHashMap<InstructionId, LatticeValue>,
+    executable_blocks: FxHashSet<BasicBlockId>,
+    executable_edges: FxHashSet<(BasicBlockId, BasicBlockId)>,
+    block_worklist: Vec<(BasicBlockId, BasicBlockId)>,
+    inst_worklist: Vec<InstructionId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LatticeValue {
+    /// Not known yet. It may still turn out to be a constant.
+    Unknown,
+    Const(ImmediateValue),
+    /// Not a constant
+    Overdefined,
+}
+
+impl ModulePassTrait for SparseConditionalConstantPropagation {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "SparseConditionalConstantPropagation"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
+impl SparseConditionalConstantPropagation {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn run_on_module(&mut self, module: &mut Module) {
+        for (_, func) in &mut module.functions {
+            if func.is_internal || func.is_prototype || func.basic_blocks.order.is_empty() {
+                continue;
+            }
+            SparseConditionalConstantPropagationOnFunction::new(func).run();
+        }
+    }
+}
+
+impl<'a> SparseConditionalConstantPropagationOnFunction<'a> {
+    pub fn new(func: &'a mut Function) -> Self {
+        Self {
+            func,
+            lattice: FxHashMap::default(),
+            executable_blocks: FxHashSet::default(),
+            executable_edges: FxHashSet::default(),
+            block_worklist: vec![],
+            inst_worklist: vec![],
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.solve();
+        self.replace_constants();
+        self.fold_branches();
+        self.func.remove_unreachable_blocks();
+    }
+
+    fn solve(&mut self) {
+        let entry = self.func.basic_blocks.order[0];
+        self.executable_blocks.insert(entry);
+        self.visit_block(entry);
+
+        loop {
+            if let Some((from, to)) = self.block_worklist.pop() {
+                if !self.executable_edges.insert((from, to)) {
+                    continue;
+                }
+                if self.executable_blocks.insert(to) {
+                    self.visit_block(to);
+                } else {
+                    // Only phis depend on which edges are executable
+                    for id in self.block_insts(to) {
+                        if self.func.inst_table[id].opcode != Opcode::Phi {
+                            break;
+                        }
+                        self.visit_inst(id);
+                    }
+                }
+            } else if let Some(id) = self.inst_worklist.pop() {
+                if self
+                    .executable_blocks
+                    .contains(&self.func.inst_table[id].parent)
+                {
+                    self.visit_inst(id);
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn visit_block(&mut self, block: BasicBlockId) {
+        for id in self.block_insts(block) {
+            self.visit_inst(id);
+        }
+    }
+
+    fn visit_inst(&mut self, id: InstructionId) {
+        let inst = &self.func.inst_table[id];
+        let parent = inst.parent;
+        let new = match inst.opcode {
+            Opcode::Br => {
+                let target = *inst.operands[0].as_basic_block();
+                self.block_worklist.push((parent, target));
+                return;
+            }
+            Opcode::CondBr => {
+                let targets = match self.value_of(&inst.operands[0]) {
+                    LatticeValue::Unknown => vec![],
+                    LatticeValue::Const(ImmediateValue::Int1(true)) => vec![&inst.operands[1]],
+                    LatticeValue::Const(ImmediateValue::Int1(false)) => vec![&inst.operands[2]],
+                    _ => vec![&inst.operands[1], &inst.operands[2]],
+                };
+                let edges = targets
+                    .into_iter()
+                    .map(|t| (parent, *t.as_basic_block()))
+                    .collect::<Vec<_>>();
+                self.block_worklist.extend(edges);
+                return;
+            }
+            Opcode::Switch => {
+                let edges = match self.value_of(&inst.operands[0]) {
+                    LatticeValue::Unknown => vec![],
+                    LatticeValue::Const(imm) => vec![switch_target(inst, &imm)],
+                    LatticeValue::Overdefined => inst
+                        .operands
+                        .iter()
+                        .filter_map(|op| match op {
+                            Operand::BasicBlock(b) => Some(*b),
+                            _ => None,
+                        })
+                        .collect(),
+                };
+                let edges = edges.into_iter().map(|t| (parent, t)).collect::<Vec<_>>();
+                self.block_worklist.extend(edges);
+                return;
+            }
+            _ if inst.ty == Type::Void => return,
+            Opcode::Phi => {
+                let mut val = LatticeValue::Unknown;
+                for pair in inst.operands.chunks(2) {
+                    let pred = *pair[1].as_basic_block();
+                    if self.executable_edges.contains(&(pred, parent)) {
+                        val = meet(val, self.value_of(&pair[0]));
+                    }
+                }
+                val
+            }
+            Opcode::Select => match self.value_of(&inst.operands[0]) {
+                LatticeValue::Unknown => LatticeValue::Unknown,
+                LatticeValue::Const(ImmediateValue::Int1(true)) => self.value_of(&inst.operands[1]),
+                LatticeValue::Const(ImmediateValue::Int1(false)) => {
+                    self.value_of(&inst.operands[2])
+                }
+                _ => meet(
+                    self.value_of(&inst.operands[1]),
+                    self.value_of(&inst.operands[2]),
+                ),
+            },
+            Opcode::Alloca | Opcode::Load | Opcode::GetElementPtr | Opcode::Call => {
+                LatticeValue::Overdefined
+            }
+            _ => self.fold(inst),
+        };
+
+        let old = self
+            .lattice
+            .get(&id)
+            .copied()
+            .unwrap_or(LatticeValue::Unknown);
+        // Values only go down the lattice
+        let new = meet(old, new);
+        if !same_value(new, old) {
+            self.lattice.insert(id, new);
+            let users = self.func.inst_table[id].users.borrow().clone();
+            self.inst_worklist.extend(users);
+        }
+    }
+
+    /// Evaluates `inst` with the lattice values of its operands
+    fn fold(&self, inst: &Instruction) -> LatticeValue {
+        let mut operands = vec![];
+        let mut unknown = false;
+        for op in &inst.operands {
+            operands.push(match op {
+                Operand::Value(_) => match self.value_of(op) {
+                    LatticeValue::Const(imm) => Operand::Value(Value::Immediate(imm)),
+                    LatticeValue::Unknown => {
+                        unknown = true;
+                        *op
+                    }
+                    LatticeValue::Overdefined => return LatticeValue::Overdefined,
+                },
+                op => *op,
+            })
+        }
+        if unknown {
+            return LatticeValue::Unknown;
+        }
+        let folded = Instruction::new(inst.opcode, operands, inst.ty, inst.parent).fold_const();
+        match folded {
+            Some(Value::Immediate(imm)) => LatticeValue::Const(imm),
+            _ => LatticeValue::Overdefined,
+        }
+    }
+
+    fn value_of(&self, op: &Operand) -> LatticeValue {
+        match op {
+            Operand::Value(Value::Immediate(imm)) => LatticeValue::Const(*imm),
+            Operand::Value(Value::Instruction(InstructionValue { id, .. })) => self
+                .lattice
+                .get(id)
+                .copied()
+                .unwrap_or(LatticeValue::Unknown),
+            _ => LatticeValue::Overdefined,
+        }
+    }
+
+    fn replace_constants(&mut self) {
+        let mut consts = self
+            .lattice
+            .iter()
+            .filter_map(|(&id, val)| match val {
+                LatticeValue::Const(imm) => Some((id, *imm)),
+                _ => None,
+            })
+            .filter(|(id, _)| {
+                self.executable_blocks
+                    .contains(&self.func.inst_table[*id].parent)
+            })
+            .collect::<Vec<_>>();
+        consts.sort_by_key(|(id, _)| id.index());
+        for (id, imm) in consts {
+            Instruction::replace_all_uses(
+                &mut self.func.inst_table,
+                id,
+                Operand::Value(Value::Immediate(imm)),
+            );
+            self.func.remove_inst(id);
+        }
+    }
+
+    /// Makes branches that can only take one of the successors unconditional
+    fn fold_branches(&mut self) {
+        for block in self.func.basic_blocks.order.clone() {
+            if !self.executable_blocks.contains(&block) {
+                continue;
+            }
+            let term = match self.block_insts(block).last() {
+                Some(term) => *term,
+                None => continue,
+            };
+            let inst = &self.func.inst_table[term];
+            let target = match (inst.opcode, inst.operands[0].get_value()) {
+                (Opcode::CondBr, Some(Value::Immediate(ImmediateValue::Int1(cond)))) => {
+                    *inst.operands[if *cond { 1 } else { 2 }].as_basic_block()
+                }
+                (Opcode::Switch, Some(Value::Immediate(imm))) => switch_target(inst, imm),
+                _ => continue,
+            };
+            self.func.replace_terminator_with_br(block, target);
+        }
+    }
+
+    fn block_insts(&self, block: BasicBlockId) -> Vec<InstructionId> {
+        self.func.basic_blocks.arena[block]
+            .iseq_ref()
+            .iter()
+            .map(|v| v.get_inst_id().unwrap())
+            .collect()
+    }
+}
+
+fn meet(x: LatticeValue, y: LatticeValue) -> LatticeValue {
+    match (x, y) {
+        (LatticeValue::Unknown, v) | (v, LatticeValue::Unknown) => v,
+        (LatticeValue::Const(a), LatticeValue::Const(b)) if same_imm(&a, &b) => x,
+        _ => LatticeValue::Overdefined,
+    }
+}
+
+fn same_value(x: LatticeValue, y: LatticeValue) -> bool {
+    match (x, y) {
+        (LatticeValue::Const(a), LatticeValue::Const(b)) => same_imm(&a, &b),
+        _ => x == y,
+    }
+}
+
+/// Compares floats by their bits so that 0.0 and -0.0 differ and NaN equals itself
+fn same_imm(x: &ImmediateValue, y: &ImmediateValue) -> bool {
+    match (x, y) {
+        (ImmediateValue::F32(a), ImmediateValue::F32(b)) => a.to_bits() == b.to_bits(),
+        (ImmediateValue::F64(a), ImmediateValue::F64(b)) => a.to_bits() == b.to_bits(),
+        _ => x == y,
+    }
+}
+
+/// Returns the block `switch` jumps to when the condition is `imm`
+fn switch_target(switch: &Instruction, imm: &ImmediateValue) -> BasicBlockId {
+    switch.operands[2..]
+        .chunks(2)
+        .find(|c| c[0].get_value().and_then(|v| v.get_imm()) == Some(imm))
+        .map_or(*switch.operands[1].as_basic_block(), |c| {
+            *c[1].as_basic_block()
+        })
+}
@@ -0,0 +1,256 @@
+// Cleans up the control flow graph. The following are repeated until nothing changes:
+//
+// - blocks unreachable from the entry block are removed
+// - `CondBr` whose targets are the same or whose condition is a constant becomes `Br`
+// - phis whose incoming values are all the same value are replaced with it
+// - a block that only jumps to another block is removed, and its predecessors jump there directly
+// - a block with a single successor is merged with it if it is the only predecessor of it
+
+use crate::ir::{
+    basic_block::BasicBlockId,
+    function::Function,
+    module::Module,
+    opcode::{Instruction, InstructionId, Opcode, Operand},
+    value::{ImmediateValue, InstructionValue, Value},
+};
+use crate::traits::pass::ModulePassTrait;
+
+#[derive(Default)]
+pub struct SimplifyCFG {}
+
+struct SimplifyCFGOnFunction<'a> {
+    func: &'a mut Function,
+}
+
+impl ModulePassTrait for SimplifyCFG {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "SimplifyCFG"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
+impl SimplifyCFG {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn run_on_module(&mut self, module: &mut Module) {
+        for (_, func) in &mut module.functions {
+            if func.is_internal || func.is_prototype || func.basic_blocks.order.is_empty() {
+                continue;
+            }
+            SimplifyCFGOnFunction { func }.run();
+        }
+    }
+}
+
+impl<'a> SimplifyCFGOnFunction<'a> {
+    pub fn run(&mut self) {
+        loop {
+            let mut changed = self.func.remove_unreachable_blocks();
+            changed |= self.fold_branches();
+            changed |= self.fold_trivial_phis();
+            changed |= self.remove_forwarding_blocks();
+            changed |= self.merge_blocks();
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn fold_branches(&mut self) -> bool {
+        let mut changed = false;
+        for block in self.func.basic_blocks.order.clone() {
+            let br = &self.func.inst_table[self.terminator(block)];
+            if br.opcode != Opcode::CondBr {
+                continue;
+            }
+            let target = match br.operands[0].get_value() {
+                Some(Value::Immediate(ImmediateValue::Int1(cond))) => {
+                    br.operands[if *cond { 1 } else { 2 }]
+                }
+                _ if br.operands[1] == br.operands[2] => br.operands[1],
+                _ => continue,
+            };
+            self.func
+                .replace_terminator_with_br(block, *target.as_basic_block());
+            changed = true;
+        }
+        changed
+    }
+
+    fn fold_trivial_phis(&mut self) -> bool {
+        let mut changed = false;
+        for block in self.func.basic_blocks.order.clone() {
+            for phi in self.phis(block) {
+                let value = match self.trivial_phi_value(phi) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                Instruction::replace_all_uses(
+                    &mut self.func.inst_table,
+                    phi,
+                    Operand::Value(value),
+                );
+                self.func.remove_inst(phi);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns the value `phi` always takes, ignoring incoming values that are `phi` itself
+    fn trivial_phi_value(&self, phi: InstructionId) -> Option<Value> {
+        let mut value = None;
+        for pair in self.func.inst_table[phi].operands.chunks(2) {
+            let v = *pair[0].as_value();
+            match v {
+                Value::Instruction(InstructionValue { id, .. }) if id == phi => {}
+                _ if value.is_none() => value = Some(v),
+                _ if value == Some(v) => {}
+                _ => return None,
+            }
+        }
+        value
+    }
+
+    /// Removes blocks that consist only of `br` by making their predecessors jump to the
+    /// destination directly
+    fn remove_forwarding_blocks(&mut self) -> bool {
+        let mut changed = false;
+        let entry = self.func.basic_blocks.order[0];
+        for block in self.func.basic_blocks.order.clone() {
+            if block == entry || self.func.basic_blocks.arena[block].iseq_ref().len() != 1 {
+                continue;
+            }
+            let br = &self.func.inst_table[self.terminator(block)];
+            if br.opcode != Opcode::Br {
+                continue;
+            }
+            let dest = *br.operands[0].as_basic_block();
+            if dest == block {
+                continue;
+            }
+            let preds = self.func.basic_blocks.arena[block].pred.clone();
+            // A phi in `dest` can't tell apart the two paths from a block that already jumps there
+            let phis = self.phis(dest);
+            if !phis.is_empty()
+                && preds
+                    .iter()
+                    .any(|p| self.func.basic_blocks.arena[dest].pred.contains(p))
+            {
+                continue;
+            }
+
+            for &pred in &preds {
+                let term = self.terminator(pred);
+                for op in &mut self.func.inst_table[term].operands {
+                    if *op == Operand::BasicBlock(block) {
+                        *op = Operand::BasicBlock(dest)
+                    }
+                }
+                let pred_block = &mut self.func.basic_blocks.arena[pred];
+                pred_block.succ.remove(&block);
+                pred_block.succ.insert(dest);
+                self.func.basic_blocks.arena[dest].pred.insert(pred);
+            }
+            // Values coming through `block` now come from each of its predecessors
+            for phi in phis {
+                let operands = &mut self.func.inst_table[phi].operands;
+                let pos = operands
+                    .iter()
+                    .position(|op| *op == Operand::BasicBlock(block))
+                    .unwrap();
+                let incoming = operands[pos - 1];
+                operands.drain(pos - 1..=pos);
+                for &pred in &preds {
+                    operands.push(incoming);
+                    operands.push(Operand::BasicBlock(pred));
+                }
+            }
+            self.func.basic_blocks.arena[dest].pred.remove(&block);
+            self.func.basic_blocks.arena[block].pred.clear();
+            self.func.basic_blocks.arena[block].succ.clear();
+            self.func.remove_inst(self.terminator(block));
+            self.func.basic_blocks.order.retain(|&b| b != block);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Merges a block into its predecessor if they are the only successor and predecessor of
+    /// each other
+    fn merge_blocks(&mut self) -> bool {
+        let mut changed = false;
+        let entry = self.func.basic_blocks.order[0];
+        let mut i = 0;
+        while i < self.func.basic_blocks.order.len() {
+            let block = self.func.basic_blocks.order[i];
+            let br = &self.func.inst_table[self.terminator(block)];
+            let succ = match br.opcode {
+                Opcode::Br => *br.operands[0].as_basic_block(),
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            if succ == block || succ == entry || self.func.basic_blocks.arena[succ].pred.len() != 1
+            {
+                i += 1;
+                continue;
+            }
+
+            // Phis of a block with a single predecessor are trivial
+            for phi in self.phis(succ) {
+                let value = *self.func.inst_table[phi].operands[0].as_value();
+                Instruction::replace_all_uses(
+                    &mut self.func.inst_table,
+                    phi,
+                    Operand::Value(value),
+                );
+                self.func.remove_inst(phi);
+            }
+            self.func.remove_inst(self.terminator(block));
+            for v in self.func.basic_blocks.arena[succ].iseq_ref().iter() {
+                self.func.inst_table[v.get_inst_id().unwrap()].parent = block;
+            }
+            let succ_succ = self.func.basic_blocks.arena[succ].succ.clone();
+            for s in succ_succ {
+                for phi in self.phis(s) {
+                    for op in &mut self.func.inst_table[phi].operands {
+                        if *op == Operand::BasicBlock(succ) {
+                            *op = Operand::BasicBlock(block)
+                        }
+                    }
+                }
+            }
+            self.func.basic_blocks.merge(&block, &succ);
+            // `block` may be merged with its new successor as well
+            changed = true;
+        }
+        changed
+    }
+
+    fn terminator(&self, block: BasicBlockId) -> InstructionId {
+        self.func.basic_blocks.arena[block]
+            .iseq_ref()
+            .last()
+            .unwrap()
+            .get_inst_id()
+            .unwrap()
+    }
+
+    fn phis(&self, block: BasicBlockId) -> Vec<InstructionId> {
+        self.func.basic_blocks.arena[block]
+            .iseq_ref()
+            .iter()
+            .map(|v| v.get_inst_id().unwrap())
+            .take_while(|&id| self.func.inst_table[id].opcode == Opcode::Phi)
+            .collect()
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
         module::Module,
         opcode::{Instruction, Opcode, Operand},
     },
+    traits::pass::ModulePassTrait,
 };
 
 // Make sure a natural loop has a single backedge.
@@ -22,6 +23,18 @@ struct BackEdges {
     dest: BasicBlockId,
 }
 
+impl ModulePassTrait for SimplifyLoop {
+    type M = Module;
+
+    fn name(&self) -> &'static str {
+        "SimplifyLoop"
+    }
+
+    fn run_on_module(&mut self, module: &mut Self::M) {
+        self.run_on_module(module);
+    }
+}
+
 impl SimplifyLoop {
     pub fn new() -> Self {
         Self {}
@@ -29,7 +42,7 @@ impl SimplifyLoop {
 
     pub fn run_on_module(&mut self, module: &mut Module) {
         for (_, func) in &mut module.functions {
-            if func.is_internal {
+            if func.is_internal || func.is_prototype {
                 continue;
             }
             SimplifyLoopOnFunction::new(func).run();
@@ -147,6 +160,12 @@ impl<'a> SimplifyLoopOnFunction<'a> {
                 inst.operands.remove(i); // remove block
                 inst.operands.remove(i - 1); // remove val
             }
+            for (val, _) in &phi_incomings {
+                let val = Operand::Value(*val);
+                if !self.func.inst_table[id].operands.contains(&val) {
+                    val.remove_from_users(&self.func.inst_table, id);
+                }
+            }
             if phi_incomings.len() > 0 {
                 new_phi_incomings.push((id, phi_incomings));
             }
@@ -157,9 +176,9 @@ impl<'a> SimplifyLoopOnFunction<'a> {
 
         for (phi, incomings) in new_phi_incomings {
             let new_phi = builder.build_phi(incomings);
-            let phi = &mut builder.func.func_ref_mut().inst_table[phi];
-            phi.operands.push(Operand::Value(new_phi));
-            phi.operands.push(Operand::BasicBlock(merge));
+            let inst_table = &mut builder.func.func_ref_mut().inst_table;
+            Instruction::add_operand(inst_table, phi, Operand::Value(new_phi));
+            Instruction::add_operand(inst_table, phi, Operand::BasicBlock(merge));
         }
     }
 }
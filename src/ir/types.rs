@@ -31,8 +31,10 @@ pub enum Type {
     Void,
     i1,
     i8,
+    i16,
     i32,
     i64,
+    f32,
     f64,
     Pointer(NonPrimitiveTypeId),
     Array(NonPrimitiveTypeId),
@@ -51,6 +53,8 @@ pub struct FunctionType {
     pub ret_ty: Type,
     pub params_ty: Vec<Type>,
     pub params_attr: FxHashMap<usize, ParamAttribute>,
+    /// True if the function takes a variable number of arguments after `params_ty`
+    pub is_var_arg: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -66,6 +70,11 @@ pub struct ArrayType {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StructType {
+    /// Name of a named struct, printed as `struct Name`. Unlike unnamed structs, named ones are
+    /// told apart by their names, so they can refer to themselves through pointers.
+    name: Option<String>,
+    /// True while the body of a named struct is not set yet
+    is_opaque: bool,
     fields_ty: Vec<Type>,
     fields_offset: Vec<usize>,
     align: usize,
@@ -100,7 +109,22 @@ impl Types {
         Type::Array(id)
     }
 
-    pub fn new_function_ty(&self, ret_ty: Type, mut params_ty: Vec<Type>) -> Type {
+    pub fn new_function_ty(&self, ret_ty: Type, params_ty: Vec<Type>) -> Type {
+        self.new_function_ty_with_var_arg(ret_ty, params_ty, false)
+    }
+
+    /// Returns the type of a function like `printf` that accepts any number of arguments after
+    /// `params_ty`
+    pub fn new_var_arg_function_ty(&self, ret_ty: Type, params_ty: Vec<Type>) -> Type {
+        self.new_function_ty_with_var_arg(ret_ty, params_ty, true)
+    }
+
+    fn new_function_ty_with_var_arg(
+        &self,
+        ret_ty: Type,
+        mut params_ty: Vec<Type>,
+        is_var_arg: bool,
+    ) -> Type {
         let mut params_attr = FxHashMap::default();
         for (i, ty) in params_ty.iter_mut().enumerate() {
             match ty {
@@ -112,11 +136,9 @@ impl Types {
                 _ => {}
             }
         }
-        let id = self.new_non_primitive_ty(NonPrimitiveType::Function(FunctionType::new(
-            ret_ty,
-            params_ty,
-            params_attr,
-        )));
+        let mut ty = FunctionType::new(ret_ty, params_ty, params_attr);
+        ty.is_var_arg = is_var_arg;
+        let id = self.new_non_primitive_ty(NonPrimitiveType::Function(ty));
         Type::Function(id)
     }
 
@@ -126,6 +148,135 @@ impl Types {
         Type::Struct(id)
     }
 
+    /// Returns the struct named `name`. If there's no such struct yet, creates an opaque one
+    /// whose body is given later by `set_struct_body`.
+    pub fn new_named_struct_ty(&self, name: &str) -> Type {
+        if let Some(ty) = self.find_named_struct_ty(name) {
+            return ty;
+        }
+        let id = self
+            .base
+            .borrow_mut()
+            .non_primitive_types
+            .alloc(NonPrimitiveType::Struct(StructType::opaque(name)));
+        Type::Struct(id)
+    }
+
+    pub fn find_named_struct_ty(&self, name: &str) -> Option<Type> {
+        self.base
+            .borrow()
+            .non_primitive_types
+            .iter()
+            .find(|(_, t)| match t {
+                NonPrimitiveType::Struct(s) => s.name() == Some(name),
+                _ => false,
+            })
+            .map(|(id, _)| Type::Struct(id))
+    }
+
+    /// Sets the fields of the opaque named struct `ty` and computes its layout. The fields may
+    /// refer to `ty` itself through pointers, but structs contained by value must already have a
+    /// body. Panics if `ty` already has a body.
+    pub fn set_struct_body(&self, ty: Type, fields_ty: Vec<Type>) {
+        let id = match ty {
+            Type::Struct(id) => id,
+            _ => panic!("{} is not a struct", self.to_string(ty)),
+        };
+        let body = StructType::new(self, fields_ty);
+        let mut base = self.base.borrow_mut();
+        match &mut base.non_primitive_types[id] {
+            NonPrimitiveType::Struct(s) if s.name.is_some() && s.is_opaque => {
+                *s = StructType {
+                    name: s.name.take(),
+                    ..body
+                }
+            }
+            NonPrimitiveType::Struct(s) if s.name.is_some() => {
+                panic!("body of struct {} is already set", s.name.as_ref().unwrap())
+            }
+            _ => panic!("body of an unnamed struct cannot be changed"),
+        }
+    }
+
+    /// Copies `ty`, which belongs to `from`, into `self` and returns the type equal to it in
+    /// `self`. Named structs are matched by name: an opaque struct in `self` takes the body from
+    /// `from`, and an opaque one in `from` matches any body. If a named struct has different
+    /// bodies, returns its name.
+    pub fn import_ty(&self, from: &Types, ty: Type) -> Result<Type, String> {
+        self.import_ty_in(from, ty, &mut vec![])
+    }
+
+    /// `importing` holds the names of the structs whose fields are being imported. They are
+    /// taken as equal when they are reached again through pointers in their fields.
+    fn import_ty_in(
+        &self,
+        from: &Types,
+        ty: Type,
+        importing: &mut Vec<String>,
+    ) -> Result<Type, String> {
+        if Rc::ptr_eq(&self.base, &from.base) {
+            return Ok(ty);
+        }
+        let t = match ty {
+            Type::Pointer(id) | Type::Array(id) | Type::Function(id) | Type::Struct(id) => {
+                from.base.borrow().non_primitive_types[id].clone()
+            }
+            _ => return Ok(ty),
+        };
+        Ok(match t {
+            NonPrimitiveType::Pointer(elem_ty) => {
+                let elem_ty = self.import_ty_in(from, elem_ty, importing)?;
+                self.new_pointer_ty(elem_ty)
+            }
+            NonPrimitiveType::Array(ArrayType { elem_ty, len }) => {
+                let elem_ty = self.import_ty_in(from, elem_ty, importing)?;
+                self.new_array_ty(elem_ty, len)
+            }
+            // Parameters passed byval are already pointers, so build the type as it is
+            NonPrimitiveType::Function(f) => {
+                let f = FunctionType {
+                    ret_ty: self.import_ty_in(from, f.ret_ty, importing)?,
+                    params_ty: f
+                        .params_ty
+                        .iter()
+                        .map(|&ty| self.import_ty_in(from, ty, importing))
+                        .collect::<Result<_, _>>()?,
+                    ..f
+                };
+                Type::Function(self.new_non_primitive_ty(NonPrimitiveType::Function(f)))
+            }
+            NonPrimitiveType::Struct(s) => {
+                let name = s.name().map(|name| name.to_string());
+                let named_ty = name.as_ref().map(|name| self.new_named_struct_ty(name));
+                if let (Some(name), Some(ty)) = (&name, named_ty) {
+                    if s.is_opaque() || importing.contains(name) {
+                        return Ok(ty);
+                    }
+                    importing.push(name.clone());
+                }
+                let fields_ty = s
+                    .fields_ty
+                    .iter()
+                    .map(|&ty| self.import_ty_in(from, ty, importing))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ty = match named_ty {
+                    Some(ty) => ty,
+                    None => return Ok(self.new_struct_ty(fields_ty)),
+                };
+                importing.pop();
+                let base = self.base.borrow();
+                let dst = base.as_struct_ty(ty).unwrap();
+                if dst.is_opaque() {
+                    drop(base);
+                    self.set_struct_body(ty, fields_ty);
+                } else if dst.fields_ty != fields_ty {
+                    return Err(name.unwrap());
+                }
+                ty
+            }
+        })
+    }
+
     // pub fn as_function_ty(&self, ty: Type) -> Option<&FunctionType> {
     //     match ty {
     //         Type::Function(id) => Some(self.non_primitive_types[id].as_function()),
@@ -149,8 +300,10 @@ impl Types {
             Type::Void
             | Type::i1
             | Type::i8
+            | Type::i16
             | Type::i32
             | Type::i64
+            | Type::f32
             | Type::f64
             | Type::Function(_) => Some(ty),
         }
@@ -165,8 +318,10 @@ impl Types {
             Type::Void
             | Type::i1
             | Type::i8
+            | Type::i16
             | Type::i32
             | Type::i64
+            | Type::f32
             | Type::f64
             | Type::Function(_) => None,
             Type::Pointer(id) => match indices.len() {
@@ -272,6 +427,15 @@ impl TypesBase {
         }
     }
 
+    /// Returns the type of a function called through `ty`, which is either a function type or a
+    /// pointer to one
+    pub fn as_callee_ty(&self, ty: Type) -> Option<&FunctionType> {
+        match ty {
+            Type::Pointer(id) => self.as_function_ty(*self.non_primitive_types[id].as_pointer()),
+            _ => self.as_function_ty(ty),
+        }
+    }
+
     pub fn as_struct_ty(&self, ty: Type) -> Option<&StructType> {
         match ty {
             Type::Struct(id) => Some(self.non_primitive_types[id].as_struct()),
@@ -279,6 +443,24 @@ impl TypesBase {
         }
     }
 
+    /// Returns the definitions of named structs in the order they were created, e.g.
+    /// `struct Node = {i32, struct Node*}` or `struct Handle = opaque`, one per line
+    pub fn named_structs_to_string(&self) -> String {
+        self.non_primitive_types
+            .iter()
+            .filter_map(|(_, t)| match t {
+                NonPrimitiveType::Struct(s) => s.name().map(|name| {
+                    if s.is_opaque() {
+                        format!("struct {} = opaque\n", name)
+                    } else {
+                        format!("struct {} = {}\n", name, s.body_to_string(self))
+                    }
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn get_element_ty(&self, ty: Type, index: Option<&Value>) -> Option<Type> {
         match ty {
             Type::Pointer(id) => Some(*self.non_primitive_types[id].as_pointer()),
@@ -290,8 +472,10 @@ impl TypesBase {
             Type::Void
             | Type::i1
             | Type::i8
+            | Type::i16
             | Type::i32
             | Type::i64
+            | Type::f32
             | Type::f64
             | Type::Function(_) => Some(ty),
         }
@@ -306,8 +490,10 @@ impl TypesBase {
             Type::Void
             | Type::i1
             | Type::i8
+            | Type::i16
             | Type::i32
             | Type::i64
+            | Type::f32
             | Type::f64
             | Type::Function(_) => None,
             Type::Pointer(id) => match indices.len() {
@@ -343,8 +529,10 @@ impl TypesBase {
             Type::Void => "void".to_string(),
             Type::i1 => "i1".to_string(),
             Type::i8 => "i8".to_string(),
+            Type::i16 => "i16".to_string(),
             Type::i32 => "i32".to_string(),
             Type::i64 => "i64".to_string(),
+            Type::f32 => "f32".to_string(),
             Type::f64 => "f64".to_string(),
             Type::Pointer(id) => {
                 let elem_ty = self.non_primitive_types[id].as_pointer();
@@ -374,16 +562,30 @@ impl Type {
     pub fn is_atomic(&self) -> bool {
         matches!(
             self,
-            Self::Void | Self::i1 | Self::i8 | Self::i32 | Self::i64 | Self::f64
+            Self::Void
+                | Self::i1
+                | Self::i8
+                | Self::i16
+                | Self::i32
+                | Self::i64
+                | Self::f32
+                | Self::f64
         )
     }
 
     pub fn is_integer(&self) -> bool {
-        matches!(self, Self::i1 | Self::i8 | Self::i32 | Self::i64)
+        matches!(
+            self,
+            Self::i1 | Self::i8 | Self::i16 | Self::i32 | Self::i64
+        )
     }
 
     pub fn is_float(&self) -> bool {
-        matches!(self, Self::f64)
+        matches!(self, Self::f32 | Self::f64)
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, Self::Pointer(_))
     }
 
     pub fn to_string(&self) -> String {
@@ -391,8 +593,10 @@ impl Type {
             Type::Void => "void".to_string(),
             Type::i1 => "i1".to_string(),
             Type::i8 => "i8".to_string(),
+            Type::i16 => "i16".to_string(),
             Type::i32 => "i32".to_string(),
             Type::i64 => "i64".to_string(),
+            Type::f32 => "f32".to_string(),
             Type::f64 => "f64".to_string(),
             Type::Pointer(id) => format!("(ty:{})*", id.index()),
             Type::Array(id) => format!("arrty:{}", id.index()),
@@ -412,6 +616,7 @@ impl FunctionType {
             ret_ty,
             params_ty,
             params_attr,
+            is_var_arg: false,
         }
     }
 
@@ -419,21 +624,40 @@ impl FunctionType {
         format!(
             "{} ({})",
             tys.to_string(self.ret_ty),
-            self.params_ty
-                .iter()
-                .enumerate()
-                .fold("".to_string(), |mut s, (i, p)| {
-                    s += &(tys.to_string(*p)
-                        + self
-                            .params_attr
-                            .get(&i)
-                            .map_or("", |a| if a.byval { " byval" } else { "" })
-                        + ", ");
-                    s
-                })
-                .trim_matches(&[',', ' '][0..]),
+            self.params_to_string(tys)
         )
     }
+
+    /// Returns the parameter list without parentheses, e.g. `i8*, ...`
+    pub fn params_to_string(&self, tys: &TypesBase) -> String {
+        self.params_to_string_with_names(tys, &FxHashMap::default())
+    }
+
+    /// Like `params_to_string`, but prints `%name` after each parameter that has a name
+    pub fn params_to_string_with_names(
+        &self,
+        tys: &TypesBase,
+        names: &FxHashMap<usize, String>,
+    ) -> String {
+        let mut params = self
+            .params_ty
+            .iter()
+            .enumerate()
+            .fold("".to_string(), |mut s, (i, p)| {
+                s += &(tys.to_string(*p)
+                    + self
+                        .params_attr
+                        .get(&i)
+                        .map_or("", |a| if a.byval { " byval" } else { "" })
+                    + &names.get(&i).map_or("".to_string(), |n| format!(" %{}", n))
+                    + ", ");
+                s
+            });
+        if self.is_var_arg {
+            params += "...";
+        }
+        params.trim_matches(&[',', ' '][0..]).to_string()
+    }
 }
 
 impl ArrayType {
@@ -449,6 +673,8 @@ impl ArrayType {
 impl StructType {
     pub fn new(tys: &Types, fields_ty: Vec<Type>) -> Self {
         let mut self_ = Self {
+            name: None,
+            is_opaque: false,
             fields_ty,
             fields_offset: vec![],
             align: 0,
@@ -458,6 +684,52 @@ impl StructType {
         self_
     }
 
+    /// Builds a named struct without a body
+    pub fn opaque(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            is_opaque: true,
+            fields_ty: vec![],
+            fields_offset: vec![],
+            align: 1,
+            size: 0,
+        }
+    }
+
+    /// Builds a struct type whose layout is already known, e.g. one read back from bitcode
+    pub fn with_layout(
+        name: Option<String>,
+        fields_ty: Vec<Type>,
+        fields_offset: Vec<usize>,
+        align: usize,
+        size: usize,
+    ) -> Self {
+        Self {
+            name,
+            is_opaque: false,
+            fields_ty,
+            fields_offset,
+            align,
+            size,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.is_opaque
+    }
+
+    pub fn fields_ty(&self) -> &[Type] {
+        &self.fields_ty
+    }
+
+    pub fn fields_offset(&self) -> &[usize] {
+        &self.fields_offset
+    }
+
     pub fn compute_elem_offsets(&mut self, tys: &Types) {
         let mut align = 1;
         let mut offset = 0;
@@ -494,8 +766,16 @@ impl StructType {
     }
 
     pub fn to_string(&self, tys: &TypesBase) -> String {
+        match &self.name {
+            Some(name) => format!("struct {}", name),
+            None => format!("struct {}", self.body_to_string(tys)),
+        }
+    }
+
+    /// Returns the fields in braces, e.g. `{i32, f64}`
+    pub fn body_to_string(&self, tys: &TypesBase) -> String {
         format!(
-            "struct {{{}}}",
+            "{{{}}}",
             self.fields_ty
                 .iter()
                 .fold("".to_string(), |mut s, t| {
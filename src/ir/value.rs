@@ -1,28 +1,96 @@
 use super::{
-    function::*, global_val::GlobalVariableId, module::*, opcode::*, types::*, DumpToString,
+    const_expr::ConstExprId, function::*, global_val::GlobalVariableId, module::*, opcode::*,
+    types::*, DumpToString,
 };
 use std::hash;
 
+// Integer operations are done on the sign-extended operands and truncated back to the width of
+// the type, so they wrap around on overflow as they do at runtime. `$int` returns None if the
+// operation would trap (e.g. division by zero), which is then left to the runtime. `$min` is the
+// minimum value of the type.
 macro_rules! const_op {
-    ($name:ident, $op:tt) => {
+    ($name:ident, $fop:tt, |$x:ident, $y:ident, $min:ident| $int:expr) => {
     pub fn $name(&self, v: &Value) -> Option<Value> {
         use ImmediateValue::*;
         match (self, v) {
-            (Value::Immediate(Int8(x)), Value::Immediate(Int8(y))) => Some(Value::Immediate(Int8(x $op y))),
-            (Value::Immediate(Int32(x)), Value::Immediate(Int32(y))) => Some(Value::Immediate(Int32(x $op y))),
-            (Value::Immediate(F64(x)), Value::Immediate(F64(y))) => Some(Value::Immediate(F64(x $op y))),
-            _ => None,
+            (Value::Immediate(F32(x)), Value::Immediate(F32(y))) => Some(Value::Immediate(F32(x $fop y))),
+            (Value::Immediate(F64(x)), Value::Immediate(F64(y))) => Some(Value::Immediate(F64(x $fop y))),
+            _ => self.const_int_op(v, |$x, $y, $min| $int),
         }
     } };
-    (int_only $name:ident, $op:tt) => {
+    ($name:ident, $fop:tt, |$x:ident, $y:ident| $int:expr) => {
+        const_op!($name, $fop, |$x, $y, _min| $int);
+    };
+    (int_only $name:ident, |$x:ident, $y:ident, $min:ident| $int:expr) => {
     pub fn $name(&self, v: &Value) -> Option<Value> {
-        use ImmediateValue::*;
-        match (self, v) {
-            (Value::Immediate(Int8(x)), Value::Immediate(Int8(y))) => Some(Value::Immediate(Int8(x $op y))),
-            (Value::Immediate(Int32(x)), Value::Immediate(Int32(y))) => Some(Value::Immediate(Int32(x $op y))),
-            _ => None,
+        self.const_int_op(v, |$x, $y, $min| $int)
+    } };
+    (int_only $name:ident, |$x:ident, $y:ident| $int:expr) => {
+        const_op!(int_only $name, |$x, $y, _min| $int);
+    };
+}
+
+// The shift amount may have a different type from the shifted value.
+// Logical shifts are done on unsigned integers and arithmetic ones on signed integers.
+// Shifting by a negative amount or by the bit width or more is left to the runtime, since x86
+// masks the amount by 31 (63 for i64) rather than by the bit width.
+macro_rules! const_shift_op {
+    ($name:ident, $shift:ident, $t8:ty, $t16:ty, $t32:ty, $t64:ty) => {
+        pub fn $name(&self, v: &Value) -> Option<Value> {
+            use ImmediateValue::*;
+            let amount = match v {
+                Value::Immediate(Int8(y)) => *y as i64,
+                Value::Immediate(Int16(y)) => *y as i64,
+                Value::Immediate(Int32(y)) => *y as i64,
+                Value::Immediate(Int64(y)) => *y,
+                _ => return None,
+            };
+            if !(0..64).contains(&amount) {
+                return None;
+            }
+            let amount = amount as u32;
+            match self {
+                Value::Immediate(Int8(x)) => {
+                    Some(Value::Immediate(Int8((*x as $t8).$shift(amount)? as i8)))
+                }
+                Value::Immediate(Int16(x)) => {
+                    Some(Value::Immediate(Int16((*x as $t16).$shift(amount)? as i16)))
+                }
+                Value::Immediate(Int32(x)) => {
+                    Some(Value::Immediate(Int32((*x as $t32).$shift(amount)? as i32)))
+                }
+                Value::Immediate(Int64(x)) => {
+                    Some(Value::Immediate(Int64((*x as $t64).$shift(amount)? as i64)))
+                }
+                _ => None,
+            }
         }
-    } }
+    };
+}
+
+// Unsigned operations reinterpret both operands as unsigned integers of the same width.
+// Division by zero is left to the runtime.
+macro_rules! const_unsigned_op {
+    ($name:ident, $op:ident) => {
+        pub fn $name(&self, v: &Value) -> Option<Value> {
+            use ImmediateValue::*;
+            match (self, v) {
+                (Value::Immediate(Int8(x)), Value::Immediate(Int8(y))) => {
+                    Some(Value::Immediate(Int8((*x as u8).$op(*y as u8)? as i8)))
+                }
+                (Value::Immediate(Int16(x)), Value::Immediate(Int16(y))) => {
+                    Some(Value::Immediate(Int16((*x as u16).$op(*y as u16)? as i16)))
+                }
+                (Value::Immediate(Int32(x)), Value::Immediate(Int32(y))) => {
+                    Some(Value::Immediate(Int32((*x as u32).$op(*y as u32)? as i32)))
+                }
+                (Value::Immediate(Int64(x)), Value::Immediate(Int64(y))) => {
+                    Some(Value::Immediate(Int64((*x as u64).$op(*y as u64)? as i64)))
+                }
+                _ => None,
+            }
+        }
+    };
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
@@ -32,6 +100,7 @@ pub enum Value {
     Instruction(InstructionValue),
     Function(FunctionValue),
     Global(GlobalValue),
+    ConstExpr(ConstExprValue),
     None,
 }
 
@@ -61,20 +130,32 @@ pub struct GlobalValue {
     pub ty: Type,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
+pub struct ConstExprValue {
+    pub id: ConstExprId,
+    pub ty: Type,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ImmediateValue {
+    Int1(bool),
     Int8(i8),
+    Int16(i16),
     Int32(i32),
     Int64(i64),
+    F32(f32),
     F64(f64),
 }
 
 impl hash::Hash for ImmediateValue {
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
         match self {
+            Self::Int1(b) => b.hash(state),
             Self::Int8(i) => i.hash(state),
+            Self::Int16(i) => i.hash(state),
             Self::Int32(i) => i.hash(state),
             Self::Int64(i) => i.hash(state),
+            Self::F32(f) => unsafe { ::std::mem::transmute::<f32, u32>(*f) }.hash(state),
             Self::F64(f) => unsafe { ::std::mem::transmute::<f64, u64>(*f) }.hash(state),
         }
     }
@@ -87,10 +168,18 @@ impl Value {
         Self::Immediate(ImmediateValue::Int8(i))
     }
 
+    pub fn new_imm_int16(i: i16) -> Self {
+        Self::Immediate(ImmediateValue::Int16(i))
+    }
+
     pub fn new_imm_int32(i: i32) -> Self {
         Self::Immediate(ImmediateValue::Int32(i))
     }
 
+    pub fn new_imm_f32(f: f32) -> Self {
+        Self::Immediate(ImmediateValue::F32(f))
+    }
+
     pub fn new_imm_f64(f: f64) -> Self {
         Self::Immediate(ImmediateValue::F64(f))
     }
@@ -105,6 +194,7 @@ impl Value {
             Value::Instruction(InstructionValue { ty, .. }) => *ty,
             Value::Function(FunctionValue { ty, .. }) => *ty,
             Value::Global(GlobalValue { ty, .. }) => *ty,
+            Value::ConstExpr(ConstExprValue { ty, .. }) => *ty,
             Value::Immediate(ref im) => *im.get_type(),
             Value::None => Type::Void,
         }
@@ -119,11 +209,181 @@ impl Value {
 
     // Constant folding
 
-    const_op!(const_add, +);
-    const_op!(const_sub, -);
-    const_op!(const_mul, *);
-    const_op!(const_div, /);
-    const_op!(int_only const_rem, %);
+    const_op!(const_add, +, |x, y| Some(x.wrapping_add(y)));
+    const_op!(const_sub, -, |x, y| Some(x.wrapping_sub(y)));
+    const_op!(const_mul, *, |x, y| Some(x.wrapping_mul(y)));
+    // The minimum value divided by -1 overflows, which traps on x86
+    const_op!(const_div, /, |x, y, min| if x == min && y == -1 { None } else { x.checked_div(y) });
+    const_op!(int_only const_rem, |x, y, min| if x == min && y == -1 { None } else { x.checked_rem(y) });
+    const_op!(int_only const_and, |x, y| Some(x & y));
+    const_op!(int_only const_or, |x, y| Some(x | y));
+    const_op!(int_only const_xor, |x, y| Some(x ^ y));
+    const_shift_op!(const_shl, checked_shl, i8, i16, i32, i64);
+    const_shift_op!(const_lshr, checked_shr, u8, u16, u32, u64);
+    const_shift_op!(const_ashr, checked_shr, i8, i16, i32, i64);
+    const_unsigned_op!(const_udiv, checked_div);
+    const_unsigned_op!(const_urem, checked_rem);
+
+    fn const_int_op(
+        &self,
+        v: &Value,
+        op: impl FnOnce(i64, i64, i64) -> Option<i64>,
+    ) -> Option<Value> {
+        match (self, v) {
+            (Value::Immediate(x), Value::Immediate(y)) if x.get_type() == y.get_type() => {
+                let ty = *x.get_type();
+                let min = match ty {
+                    Type::i1 => -1,
+                    Type::i8 => i8::MIN as i64,
+                    Type::i16 => i16::MIN as i64,
+                    Type::i32 => i32::MIN as i64,
+                    _ => i64::MIN,
+                };
+                ImmediateValue::from_bits(op(x.as_i64()?, y.as_i64()?, min)?, ty)
+                    .map(Value::Immediate)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn const_icmp(&self, kind: ICmpKind, v: &Value) -> Option<Value> {
+        let (x, y) = match (self, v) {
+            (Value::Immediate(x), Value::Immediate(y)) if x.get_type() == y.get_type() => (x, y),
+            _ => return None,
+        };
+        let (sx, sy, ux, uy) = (x.as_i64()?, y.as_i64()?, x.as_u64()?, y.as_u64()?);
+        let b = match kind {
+            ICmpKind::Eq => sx == sy,
+            ICmpKind::Ne => sx != sy,
+            ICmpKind::Lt => sx < sy,
+            ICmpKind::Le => sx <= sy,
+            ICmpKind::Gt => sx > sy,
+            ICmpKind::Ge => sx >= sy,
+            ICmpKind::Ult => ux < uy,
+            ICmpKind::Ule => ux <= uy,
+            ICmpKind::Ugt => ux > uy,
+            ICmpKind::Uge => ux >= uy,
+        };
+        Some(Value::Immediate(ImmediateValue::Int1(b)))
+    }
+
+    /// Every kind of fcmp is unordered, so it is true if either operand is NaN
+    pub fn const_fcmp(&self, kind: FCmpKind, v: &Value) -> Option<Value> {
+        let (x, y) = match (self, v) {
+            (
+                Value::Immediate(ImmediateValue::F32(x)),
+                Value::Immediate(ImmediateValue::F32(y)),
+            ) => (*x as f64, *y as f64),
+            (
+                Value::Immediate(ImmediateValue::F64(x)),
+                Value::Immediate(ImmediateValue::F64(y)),
+            ) => (*x, *y),
+            _ => return None,
+        };
+        let b = x.is_nan()
+            || y.is_nan()
+            || match kind {
+                FCmpKind::UEq => x == y,
+                FCmpKind::UNe => x != y,
+                FCmpKind::ULt => x < y,
+                FCmpKind::ULe => x <= y,
+                FCmpKind::UGt => x > y,
+                FCmpKind::UGe => x >= y,
+            };
+        Some(Value::Immediate(ImmediateValue::Int1(b)))
+    }
+
+    pub fn const_sext(&self, ty: Type) -> Option<Value> {
+        let x = self.get_imm()?.as_i64()?;
+        ImmediateValue::from_bits(x, ty).map(Value::Immediate)
+    }
+
+    pub fn const_zext(&self, ty: Type) -> Option<Value> {
+        let x = self.get_imm()?.as_u64()? as i64;
+        ImmediateValue::from_bits(x, ty).map(Value::Immediate)
+    }
+
+    pub fn const_trunc(&self, ty: Type) -> Option<Value> {
+        // Truncation keeps the low bits, so it doesn't matter how the source is extended
+        self.const_sext(ty)
+    }
+
+    pub fn const_fpcast(&self, ty: Type) -> Option<Value> {
+        let x = match self {
+            Value::Immediate(ImmediateValue::F32(x)) => *x as f64,
+            Value::Immediate(ImmediateValue::F64(x)) => *x,
+            _ => return None,
+        };
+        match ty {
+            Type::f32 => Some(Value::Immediate(ImmediateValue::F32(x as f32))),
+            Type::f64 => Some(Value::Immediate(ImmediateValue::F64(x))),
+            _ => None,
+        }
+    }
+
+    pub fn const_sitofp(&self, ty: Type) -> Option<Value> {
+        let x = self.get_imm()?.as_i64()?;
+        match ty {
+            Type::f32 => Some(Value::Immediate(ImmediateValue::F32(x as f32))),
+            Type::f64 => Some(Value::Immediate(ImmediateValue::F64(x as f64))),
+            _ => None,
+        }
+    }
+
+    /// Folds `fptosi self to ty`. NaN and values out of the range of `ty` are not folded since
+    /// the result is undefined.
+    pub fn const_fptosi(&self, ty: Type) -> Option<Value> {
+        let x = match self {
+            Value::Immediate(ImmediateValue::F32(x)) => *x as f64,
+            Value::Immediate(ImmediateValue::F64(x)) => *x,
+            _ => return None,
+        };
+        let bits = match ty {
+            Type::i1 => 1,
+            Type::i8 => 8,
+            Type::i16 => 16,
+            Type::i32 => 32,
+            Type::i64 => 64,
+            _ => return None,
+        };
+        // The range is [-2^(bits-1), 2^(bits-1)). NaN fails both comparisons.
+        let (x, bound) = (x.trunc(), 2f64.powi(bits - 1));
+        if x >= -bound && x < bound {
+            ImmediateValue::from_bits(x as i64, ty).map(Value::Immediate)
+        } else {
+            None
+        }
+    }
+
+    /// Folds `bitcast self to ty`. A global's address is simply retyped.
+    pub fn const_bitcast(&self, ty: Type) -> Option<Value> {
+        match self {
+            _ if self.get_type() == ty => Some(*self),
+            Value::Global(GlobalValue { id, .. }) => {
+                Some(Value::Global(GlobalValue { id: *id, ty }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Folds `select self, t, f` when the condition is known or both arms are the same value.
+    pub fn const_select(&self, t: &Value, f: &Value) -> Option<Value> {
+        if t == f {
+            return Some(*t);
+        }
+        match self {
+            Value::Immediate(imm) => match imm {
+                ImmediateValue::Int1(false)
+                | ImmediateValue::Int8(0)
+                | ImmediateValue::Int16(0)
+                | ImmediateValue::Int32(0)
+                | ImmediateValue::Int64(0) => Some(*f),
+                ImmediateValue::F32(_) | ImmediateValue::F64(_) => None,
+                _ => Some(*t),
+            },
+            _ => None,
+        }
+    }
 
     // Utils
 
@@ -132,21 +392,32 @@ impl Value {
             Value::Argument(ArgumentValue { index, func_id, .. }) => {
                 let f = parent.function_ref(*func_id);
                 let ty = f.get_param_type(*index).unwrap();
-                format!("{} %arg.{}", ty.to_string(), index)
+                format!(
+                    "{} %{}",
+                    parent.types.to_string(ty),
+                    f.names.param_name(*index)
+                )
             }
             Value::Immediate(iv) => match iv {
+                ImmediateValue::Int1(b) => format!("i1 {}", b),
                 ImmediateValue::Int8(i) => format!("i8 {}", i),
+                ImmediateValue::Int16(i) => format!("i16 {}", i),
                 ImmediateValue::Int32(i) => format!("i32 {}", i),
                 ImmediateValue::Int64(i) => format!("i64 {}", i),
+                ImmediateValue::F32(f) => format!("f32 {}", f),
                 ImmediateValue::F64(f) => format!("f64 {}", f),
             },
             Value::Instruction(InstructionValue { func_id, id, .. }) if inst => {
                 let f = parent.function_ref(*func_id);
                 let inst = &f.inst_table[*id];
                 if inst.ty == Type::Void {
-                    format!("    {}", inst.to_string(parent))
+                    format!("    {}", inst.to_string(parent, &f.names))
                 } else {
-                    format!("    %{} = {}", id.index(), inst.to_string(parent))
+                    format!(
+                        "    %{} = {}",
+                        f.names.inst_name(*id),
+                        inst.to_string(parent, &f.names)
+                    )
                 }
             }
             Value::Instruction(InstructionValue { func_id, id, .. }) => {
@@ -154,13 +425,17 @@ impl Value {
                 format!(
                     "{} %{}",
                     parent.types.to_string(f.inst_table[*id].ty),
-                    id.index()
+                    f.names.inst_name(*id)
                 )
             }
             Value::Function(FunctionValue { func_id, .. }) if inst => {
                 let f = parent.function_ref(*func_id);
                 f.dump(parent)
             }
+            Value::Function(FunctionValue { func_id, ty }) if matches!(ty, Type::Pointer(_)) => {
+                let f = parent.function_ref(*func_id);
+                format!("{} {}", parent.types.to_string(*ty), f.name)
+            }
             Value::Function(FunctionValue { func_id, .. }) => {
                 let f = parent.function_ref(*func_id);
                 let ret_ty = parent
@@ -176,6 +451,11 @@ impl Value {
                 let g = &parent.global_vars.arena[*id];
                 format!("{} @{}", parent.types.to_string(*ty), g.name)
             }
+            Value::ConstExpr(ConstExprValue { id, ty }) => format!(
+                "{} {}",
+                parent.types.to_string(*ty),
+                parent.const_exprs.to_string(parent, *id)
+            ),
             Value::None => "".to_string(),
         }
     }
@@ -203,20 +483,42 @@ impl Value {
 }
 
 impl ImmediateValue {
+    /// Makes an integer immediate of `ty` from the low bits of `x`.
+    pub fn from_bits(x: i64, ty: Type) -> Option<Self> {
+        match ty {
+            Type::i1 => Some(ImmediateValue::Int1(x & 1 != 0)),
+            Type::i8 => Some(ImmediateValue::Int8(x as i8)),
+            Type::i16 => Some(ImmediateValue::Int16(x as i16)),
+            Type::i32 => Some(ImmediateValue::Int32(x as i32)),
+            Type::i64 => Some(ImmediateValue::Int64(x)),
+            _ => None,
+        }
+    }
+
     pub fn is_power_of_two(&self) -> Option<u32> {
         match self {
             Self::Int8(x) if (*x as usize).is_power_of_two() => Some(x.trailing_zeros()),
+            Self::Int16(x) if (*x as usize).is_power_of_two() => Some(x.trailing_zeros()),
             Self::Int32(x) if (*x as usize).is_power_of_two() => Some(x.trailing_zeros()),
             Self::Int64(x) if (*x as usize).is_power_of_two() => Some(x.trailing_zeros()),
-            Self::Int64(_) | Self::Int32(_) | Self::Int8(_) | Self::F64(_) => None,
+            Self::Int64(_)
+            | Self::Int32(_)
+            | Self::Int16(_)
+            | Self::Int8(_)
+            | Self::Int1(_)
+            | Self::F32(_)
+            | Self::F64(_) => None,
         }
     }
 
     pub fn get_type(&self) -> &Type {
         match self {
+            ImmediateValue::Int1(_) => &Type::i1,
             ImmediateValue::Int8(_) => &Type::i8,
+            ImmediateValue::Int16(_) => &Type::i16,
             ImmediateValue::Int32(_) => &Type::i32,
             ImmediateValue::Int64(_) => &Type::i64,
+            ImmediateValue::F32(_) => &Type::f32,
             ImmediateValue::F64(_) => &Type::f64,
         }
     }
@@ -234,4 +536,28 @@ impl ImmediateValue {
             _ => panic!(),
         }
     }
+
+    /// Returns the sign-extended value of an integer immediate. `true` is -1.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ImmediateValue::Int1(b) => Some(-(*b as i64)),
+            ImmediateValue::Int8(i) => Some(*i as i64),
+            ImmediateValue::Int16(i) => Some(*i as i64),
+            ImmediateValue::Int32(i) => Some(*i as i64),
+            ImmediateValue::Int64(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the zero-extended value of an integer immediate
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            ImmediateValue::Int1(b) => Some(*b as u64),
+            ImmediateValue::Int8(i) => Some(*i as u8 as u64),
+            ImmediateValue::Int16(i) => Some(*i as u16 as u64),
+            ImmediateValue::Int32(i) => Some(*i as u32 as u64),
+            ImmediateValue::Int64(i) => Some(*i as u64),
+            _ => None,
+        }
+    }
 }
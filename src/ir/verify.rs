@@ -0,0 +1,627 @@
+use crate::{
+    analysis::dom_tree::{DominatorTree, DominatorTreeConstructor},
+    ir::{
+        basic_block::{BasicBlock, BasicBlockId},
+        function::{Function, FunctionId},
+        module::Module,
+        opcode::{Instruction, InstructionId, Opcode, Operand},
+        types::{Type, TypeSize, Types},
+        value::{ImmediateValue, InstructionValue, Value},
+    },
+    traits::pass::ModulePassManager,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifierError {
+    pub func: FunctionId,
+    pub func_name: String,
+    pub block: Option<BasicBlockId>,
+    pub inst: Option<InstructionId>,
+    pub msg: String,
+}
+
+struct VerifierOnFunction<'a> {
+    module: &'a Module,
+    func: &'a Function,
+    dom_tree: DominatorTree<BasicBlock>,
+    /// Position (block, index) of every instruction placed in a block
+    positions: FxHashMap<InstructionId, (BasicBlockId, usize)>,
+    errors: Vec<VerifierError>,
+}
+
+/// Checks the structural invariants of every function in `module`.
+pub fn verify_module(module: &Module) -> Result<(), Vec<VerifierError>> {
+    let mut errors = vec![];
+    for (id, _) in &module.functions {
+        if let Err(mut e) = verify_function(module, id) {
+            errors.append(&mut e)
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn verify_function(module: &Module, func_id: FunctionId) -> Result<(), Vec<VerifierError>> {
+    let func = module.function_ref(func_id);
+    if func.is_prototype && !func.basic_blocks.order.is_empty() {
+        return Err(vec![VerifierError {
+            func: func_id,
+            func_name: func.name.clone(),
+            block: None,
+            inst: None,
+            msg: "declared function has a body".to_string(),
+        }]);
+    }
+    if func.is_internal || func.basic_blocks.order.is_empty() {
+        return Ok(());
+    }
+
+    let mut verifier = VerifierOnFunction {
+        module,
+        func,
+        dom_tree: DominatorTreeConstructor::new(&func.basic_blocks).construct(),
+        positions: FxHashMap::default(),
+        errors: vec![],
+    };
+    verifier.run();
+
+    if verifier.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(verifier.errors)
+    }
+}
+
+impl ModulePassManager<Module> {
+    /// Runs `verify_module` after every pass. `run_on_module` panics as soon as a pass breaks the
+    /// module, and `try_run_on_module` returns the errors.
+    pub fn verify_each_pass(&mut self) {
+        self.verifier = Some(verify_module)
+    }
+}
+
+impl<'a> VerifierOnFunction<'a> {
+    fn run(&mut self) {
+        let func = self.func;
+        for &bb_id in &func.basic_blocks.order {
+            for (i, val) in func.basic_blocks.arena[bb_id].iseq_ref().iter().enumerate() {
+                match val {
+                    Value::Instruction(InstructionValue { id, .. }) => {
+                        if self.positions.insert(*id, (bb_id, i)).is_some() {
+                            self.error(Some(bb_id), Some(*id), "placed more than once");
+                        }
+                    }
+                    _ => self.error(Some(bb_id), None, "non-instruction value in block"),
+                }
+            }
+        }
+
+        for &bb_id in &func.basic_blocks.order {
+            self.verify_block(bb_id);
+        }
+    }
+
+    fn verify_block(&mut self, bb_id: BasicBlockId) {
+        let func = self.func;
+        let block = &func.basic_blocks.arena[bb_id];
+        let iseq: Vec<InstructionId> = block
+            .iseq_ref()
+            .iter()
+            .filter_map(|v| v.get_inst_id())
+            .collect();
+
+        // Every block ends in exactly one terminator
+        match iseq.last() {
+            Some(&last) if self.func.inst_table[last].opcode.is_terminator() => {}
+            Some(&last) => self.error(
+                Some(bb_id),
+                Some(last),
+                "block does not end in a terminator",
+            ),
+            None => self.error(Some(bb_id), None, "empty block"),
+        }
+        for &id in iseq.iter().rev().skip(1) {
+            if self.func.inst_table[id].opcode.is_terminator() {
+                self.error(Some(bb_id), Some(id), "terminator in the middle of a block");
+            }
+        }
+
+        // Successors must agree with the terminator
+        if let Some(&last) = iseq.last() {
+            let targets: FxHashSet<BasicBlockId> = self.func.inst_table[last]
+                .operands
+                .iter()
+                .filter_map(|op| match op {
+                    Operand::BasicBlock(bb) => Some(*bb),
+                    _ => None,
+                })
+                .collect();
+            if targets != block.succ {
+                self.error(
+                    Some(bb_id),
+                    Some(last),
+                    "successors do not match the terminator",
+                );
+            }
+        }
+        for succ in &block.succ {
+            if !self.func.basic_blocks.arena[*succ].pred.contains(&bb_id) {
+                self.error(
+                    Some(bb_id),
+                    None,
+                    "successor does not list this block as predecessor",
+                );
+            }
+        }
+
+        let mut phis_done = false;
+        for &id in &iseq {
+            let inst = &func.inst_table[id];
+            if inst.parent != bb_id {
+                self.error(
+                    Some(bb_id),
+                    Some(id),
+                    "parent does not match the containing block",
+                );
+            }
+            if inst.opcode == Opcode::Phi {
+                if phis_done {
+                    self.error(Some(bb_id), Some(id), "phi after a non-phi instruction");
+                }
+                self.verify_phi(bb_id, inst);
+            } else {
+                phis_done = true;
+            }
+            self.verify_types(bb_id, inst);
+            self.verify_operands(bb_id, inst);
+            self.verify_users(bb_id, inst);
+        }
+    }
+
+    fn verify_phi(&mut self, bb_id: BasicBlockId, inst: &Instruction) {
+        let func = self.func;
+        let preds = &func.basic_blocks.arena[bb_id].pred;
+        let mut incoming = FxHashSet::default();
+        for pair in inst.operands.chunks(2) {
+            let bb = match pair.get(1) {
+                Some(Operand::BasicBlock(bb)) => *bb,
+                _ => {
+                    self.error(
+                        Some(bb_id),
+                        inst.id,
+                        "phi operands must be (value, block) pairs",
+                    );
+                    return;
+                }
+            };
+            if !incoming.insert(bb) {
+                let msg = format!("label.{} is listed more than once", bb.index());
+                self.error(Some(bb_id), inst.id, &msg);
+            }
+            if !preds.contains(&bb) {
+                let msg = format!("label.{} is not a predecessor", bb.index());
+                self.error(Some(bb_id), inst.id, &msg);
+            }
+        }
+        for pred in preds {
+            if !incoming.contains(pred) {
+                let msg = format!("no incoming value for label.{}", pred.index());
+                self.error(Some(bb_id), inst.id, &msg);
+            }
+        }
+    }
+
+    /// Checks that an operand defined by an instruction is placed in this function and that its
+    /// definition dominates the use.
+    fn verify_operands(&mut self, bb_id: BasicBlockId, inst: &Instruction) {
+        let use_pos = self.positions[&inst.id.unwrap()];
+        for (i, operand) in inst.operands.iter().enumerate() {
+            let iv = match operand {
+                Operand::Value(Value::Instruction(iv)) => iv,
+                Operand::Value(Value::Argument(arg)) => {
+                    if Some(arg.func_id) != self.func.id {
+                        self.error(Some(bb_id), inst.id, "argument of another function");
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if Some(iv.func_id) != self.func.id {
+                self.error(Some(bb_id), inst.id, "operand from another function");
+                continue;
+            }
+            let def_pos = match self.positions.get(&iv.id) {
+                Some(pos) => *pos,
+                None => {
+                    let msg = format!("%{} is not in any block", iv.id.index());
+                    self.error(Some(bb_id), inst.id, &msg);
+                    continue;
+                }
+            };
+            if iv.ty != self.func.inst_table[iv.id].ty {
+                let msg = format!("%{} is used with a wrong type", iv.id.index());
+                self.error(Some(bb_id), inst.id, &msg);
+            }
+
+            // A phi uses its operand at the end of the incoming block
+            let use_pos = if inst.opcode == Opcode::Phi {
+                let incoming = *inst.operands[i + 1].as_basic_block();
+                let len = self.func.basic_blocks.arena[incoming].iseq_ref().len();
+                (incoming, len)
+            } else {
+                use_pos
+            };
+
+            // Uses in unreachable blocks are not checked
+            if !self.dom_tree.level.contains_key(&use_pos.0) {
+                continue;
+            }
+            let dominates = if def_pos.0 == use_pos.0 {
+                def_pos.1 < use_pos.1
+            } else {
+                self.dom_tree.dominate_bb(def_pos.0, use_pos.0)
+            };
+            if !dominates {
+                let msg = format!("%{} does not dominate this use", iv.id.index());
+                self.error(Some(bb_id), inst.id, &msg);
+            }
+        }
+    }
+
+    /// Checks that `users` of `inst` lists exactly the placed instructions that use it.
+    fn verify_users(&mut self, bb_id: BasicBlockId, inst: &Instruction) {
+        let id = inst.id.unwrap();
+        let users: FxHashSet<InstructionId> = inst.users.borrow().iter().copied().collect();
+        for &user in &users {
+            if !self.positions.contains_key(&user) {
+                let msg = format!("user %{} is not in any block", user.index());
+                self.error(Some(bb_id), Some(id), &msg);
+            } else if !uses(&self.func.inst_table[user], id) {
+                let msg = format!("user %{} does not use this instruction", user.index());
+                self.error(Some(bb_id), Some(id), &msg);
+            }
+        }
+        for operand in &inst.operands {
+            if let Operand::Value(Value::Instruction(iv)) = operand {
+                if self.func.inst_table.get(iv.id).is_none() {
+                    continue;
+                }
+                if !self.func.inst_table[iv.id].users.borrow().contains(&id) {
+                    let msg = format!("missing from the users of %{}", iv.id.index());
+                    self.error(Some(bb_id), Some(id), &msg);
+                }
+            }
+        }
+    }
+
+    fn verify_types(&mut self, bb_id: BasicBlockId, inst: &Instruction) {
+        if let Err(msg) = check_inst_types(&self.module.types, self.func.get_return_type(), inst) {
+            let msg = format!("{}: {}", inst.opcode.to_string(), msg);
+            self.error(Some(bb_id), inst.id, &msg);
+        }
+    }
+
+    fn error(&mut self, block: Option<BasicBlockId>, inst: Option<InstructionId>, msg: &str) {
+        self.errors.push(VerifierError {
+            func: self.func.id.unwrap(),
+            func_name: self.func.name.clone(),
+            block,
+            inst,
+            msg: msg.to_string(),
+        })
+    }
+}
+
+fn uses(inst: &Instruction, id: InstructionId) -> bool {
+    inst.operands.iter().any(|op| match op {
+        Operand::Value(Value::Instruction(iv)) => iv.id == id,
+        _ => false,
+    })
+}
+
+/// Checks that the operands of `inst` have the types its opcode needs and that its own type is
+/// the one they give. `ret_ty` is the return type of the function containing `inst`.
+pub fn check_inst_types(types: &Types, ret_ty: Type, inst: &Instruction) -> Result<(), String> {
+    let ty_str = |ty: Type| types.to_string(ty);
+    let operands = &inst.operands;
+    let value = |i: usize| -> Result<&Value, String> {
+        operands
+            .get(i)
+            .and_then(|op| op.get_value())
+            .ok_or_else(|| format!("operand {} must be a value", i))
+    };
+    let block = |i: usize| -> Result<(), String> {
+        match operands.get(i) {
+            Some(Operand::BasicBlock(_)) => Ok(()),
+            _ => Err(format!("operand {} must be a block", i)),
+        }
+    };
+    let num_operands = |n: usize| -> Result<(), String> {
+        if operands.len() == n {
+            Ok(())
+        } else {
+            Err(format!("expected {} operands, found {}", n, operands.len()))
+        }
+    };
+    let expect = |what: &str, expected: Type, found: Type| -> Result<(), String> {
+        if expected == found {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} must be {}, found {}",
+                what,
+                ty_str(expected),
+                ty_str(found)
+            ))
+        }
+    };
+    let pointee = |ty: Type| -> Result<Type, String> {
+        match ty {
+            Type::Pointer(_) => Ok(types.get_element_ty(ty, None).unwrap()),
+            _ => Err(format!("{} is not a pointer", ty_str(ty))),
+        }
+    };
+
+    match inst.opcode {
+        Opcode::Alloca => {
+            num_operands(1)?;
+            match operands[0] {
+                Operand::Type(ty) => expect("result", types.new_pointer_ty(ty), inst.ty),
+                _ => Err("operand 0 must be a type".to_string()),
+            }
+        }
+        Opcode::Load => {
+            num_operands(1)?;
+            let elem_ty = pointee(value(0)?.get_type())?;
+            expect("result", elem_ty, inst.ty)
+        }
+        Opcode::Store => {
+            num_operands(2)?;
+            let elem_ty = pointee(value(1)?.get_type())?;
+            expect("stored value", elem_ty, value(0)?.get_type())
+        }
+        Opcode::GetElementPtr => {
+            let elem_ty = gep_elem_ty(types, operands)?;
+            expect("result", types.new_pointer_ty(elem_ty), inst.ty)
+        }
+        Opcode::Add
+        | Opcode::Sub
+        | Opcode::Mul
+        | Opcode::Div
+        | Opcode::Rem
+        | Opcode::UDiv
+        | Opcode::URem
+        | Opcode::Shl
+        | Opcode::LShr
+        | Opcode::AShr
+        | Opcode::And
+        | Opcode::Or
+        | Opcode::Xor => {
+            num_operands(2)?;
+            let (lhs, rhs) = (value(0)?.get_type(), value(1)?.get_type());
+            if !lhs.is_integer() && !lhs.is_float() {
+                return Err(format!("{} is not an arithmetic type", ty_str(lhs)));
+            }
+            if !matches!(
+                inst.opcode,
+                Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div
+            ) && !lhs.is_integer()
+            {
+                return Err(format!("{} is not an integer type", ty_str(lhs)));
+            }
+            // The shift amount may be narrower than the shifted value (e.g. i8)
+            if matches!(inst.opcode, Opcode::Shl | Opcode::LShr | Opcode::AShr) {
+                if !rhs.is_integer() {
+                    return Err(format!("{} is not an integer type", ty_str(rhs)));
+                }
+            } else {
+                expect("rhs", lhs, rhs)?;
+            }
+            expect("result", lhs, inst.ty)
+        }
+        Opcode::SIToFP
+        | Opcode::FPToSI
+        | Opcode::Sext
+        | Opcode::ZExt
+        | Opcode::Trunc
+        | Opcode::FPExt
+        | Opcode::FPTrunc
+        | Opcode::Bitcast
+        | Opcode::PtrToInt
+        | Opcode::IntToPtr => {
+            num_operands(1)?;
+            let from = value(0)?.get_type();
+            let ok = match inst.opcode {
+                Opcode::SIToFP => from.is_integer() && inst.ty.is_float(),
+                Opcode::FPToSI => from.is_float() && inst.ty.is_integer(),
+                Opcode::FPExt => from == Type::f32 && inst.ty == Type::f64,
+                Opcode::FPTrunc => from == Type::f64 && inst.ty == Type::f32,
+                Opcode::Bitcast => from.is_pointer() && inst.ty.is_pointer(),
+                // Pointers are 64 bits wide on every supported target
+                Opcode::PtrToInt => from.is_pointer() && inst.ty == Type::i64,
+                Opcode::IntToPtr => from == Type::i64 && inst.ty.is_pointer(),
+                Opcode::Trunc => {
+                    from.is_integer()
+                        && inst.ty.is_integer()
+                        && from.size_in_bits(types) > inst.ty.size_in_bits(types)
+                }
+                _ => {
+                    from.is_integer()
+                        && inst.ty.is_integer()
+                        && from.size_in_bits(types) < inst.ty.size_in_bits(types)
+                }
+            };
+            if ok {
+                Ok(())
+            } else {
+                Err(format!(
+                    "cannot cast {} to {}",
+                    ty_str(from),
+                    ty_str(inst.ty)
+                ))
+            }
+        }
+        Opcode::ICmp | Opcode::FCmp => {
+            num_operands(3)?;
+            let (lhs, rhs) = (value(1)?.get_type(), value(2)?.get_type());
+            match (inst.opcode, &operands[0]) {
+                (Opcode::ICmp, Operand::ICmpKind(_)) if lhs.is_integer() => {}
+                (Opcode::ICmp, Operand::ICmpKind(_)) if matches!(lhs, Type::Pointer(_)) => {}
+                (Opcode::FCmp, Operand::FCmpKind(_)) if lhs.is_float() => {}
+                _ => return Err(format!("cannot compare {}", ty_str(lhs))),
+            }
+            expect("rhs", lhs, rhs)?;
+            expect("result", Type::i1, inst.ty)
+        }
+        Opcode::Select => {
+            num_operands(3)?;
+            expect("condition", Type::i1, value(0)?.get_type())?;
+            let ty = value(1)?.get_type();
+            expect("false value", ty, value(2)?.get_type())?;
+            expect("result", ty, inst.ty)
+        }
+        Opcode::Br => {
+            num_operands(1)?;
+            block(0)
+        }
+        Opcode::CondBr => {
+            num_operands(3)?;
+            expect("condition", Type::i1, value(0)?.get_type())?;
+            block(1)?;
+            block(2)
+        }
+        Opcode::Switch => {
+            if operands.len() < 2 || operands.len() % 2 == 1 {
+                return Err(
+                    "operands must be a condition, a default and (value, block) pairs".to_string(),
+                );
+            }
+            let cond_ty = value(0)?.get_type();
+            if !cond_ty.is_integer() {
+                return Err(format!("{} is not an integer type", ty_str(cond_ty)));
+            }
+            block(1)?;
+            let mut cases = vec![];
+            for i in (2..operands.len()).step_by(2) {
+                let case = value(i)?;
+                expect("case value", cond_ty, case.get_type())?;
+                match case.get_imm() {
+                    Some(imm) if !cases.contains(imm) => cases.push(*imm),
+                    Some(_) => return Err(format!("duplicate case value {}", i / 2)),
+                    None => return Err(format!("case value {} is not a constant", i / 2)),
+                }
+                block(i + 1)?;
+            }
+            Ok(())
+        }
+        Opcode::Phi => {
+            if operands.is_empty() {
+                return Err("no incoming values".to_string());
+            }
+            for i in (0..operands.len()).step_by(2) {
+                expect("incoming value", inst.ty, value(i)?.get_type())?;
+                block(i + 1)?;
+            }
+            Ok(())
+        }
+        Opcode::Call => {
+            let base = types.base.borrow();
+            let f_ty = base
+                .as_callee_ty(value(0)?.get_type())
+                .ok_or_else(|| "callee is not a function or a function pointer".to_string())?;
+            if f_ty.is_var_arg && f_ty.params_ty.len() > operands.len() - 1 {
+                return Err(format!(
+                    "expected at least {} arguments, found {}",
+                    f_ty.params_ty.len(),
+                    operands.len() - 1
+                ));
+            }
+            if !f_ty.is_var_arg && f_ty.params_ty.len() != operands.len() - 1 {
+                return Err(format!(
+                    "expected {} arguments, found {}",
+                    f_ty.params_ty.len(),
+                    operands.len() - 1
+                ));
+            }
+            for (i, &param_ty) in f_ty.params_ty.iter().enumerate() {
+                let arg_ty = value(i + 1)?.get_type();
+                // A pointer to an array may be passed where a pointer to its element is expected
+                let decayed = match (param_ty, arg_ty) {
+                    (Type::Pointer(_), Type::Pointer(_)) => {
+                        let arg_elem_ty = pointee(arg_ty)?;
+                        matches!(arg_elem_ty, Type::Array(_))
+                            && types.get_element_ty(arg_elem_ty, None) == Some(pointee(param_ty)?)
+                    }
+                    _ => false,
+                };
+                if !decayed {
+                    expect("argument", param_ty, arg_ty)?;
+                }
+            }
+            expect("result", f_ty.ret_ty, inst.ty)
+        }
+        Opcode::Ret => {
+            num_operands(1)?;
+            expect("returned value", ret_ty, value(0)?.get_type())
+        }
+        Opcode::Unreachable => num_operands(0),
+    }
+}
+
+/// Returns the type that a getelementptr with `operands` (the pointer and the indices) points to.
+pub fn gep_elem_ty(types: &Types, operands: &[Operand]) -> Result<Type, String> {
+    let ty_str = |ty: Type| types.to_string(ty);
+    let value = |i: usize| -> Result<&Value, String> {
+        operands
+            .get(i)
+            .and_then(|op| op.get_value())
+            .ok_or_else(|| format!("operand {} must be a value", i))
+    };
+    let ptr_ty = value(0)?.get_type();
+    if !ptr_ty.is_pointer() {
+        return Err(format!("{} is not a pointer", ty_str(ptr_ty)));
+    }
+    let mut elem_ty = types.get_element_ty(ptr_ty, None).unwrap();
+    for i in 1..operands.len() {
+        let idx = value(i)?;
+        if !idx.get_type().is_integer() {
+            return Err(format!("index {} must be an integer", i));
+        }
+        if i == 1 {
+            continue;
+        }
+        match elem_ty {
+            Type::Struct(_) => {
+                let base = types.base.borrow();
+                let struct_ty = base.as_struct_ty(elem_ty).unwrap();
+                match idx.get_imm() {
+                    Some(ImmediateValue::Int32(n))
+                        if struct_ty.get_elem_offset(*n as usize).is_some() => {}
+                    _ => return Err(format!("invalid struct index {}", i)),
+                }
+            }
+            Type::Array(_) | Type::Pointer(_) => {}
+            _ => return Err(format!("cannot index into {}", ty_str(elem_ty))),
+        }
+        elem_ty = types.get_element_ty(elem_ty, Some(idx)).unwrap();
+    }
+    Ok(elem_ty)
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.func_name)?;
+        if let Some(block) = self.block {
+            write!(f, ": label.{}", block.index())?;
+        }
+        if let Some(inst) = self.inst {
+            write!(f, ": %{}", inst.index())?;
+        }
+        write!(f, ": {}", self.msg)
+    }
+}
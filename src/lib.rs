@@ -2,7 +2,7 @@
 #![feature(stmt_expr_attributes)]
 #![feature(drain_filter)]
 #![feature(vec_remove_item)]
-#![recursion_limit = "256"]
+#![recursion_limit = "512"]
 
 #[macro_use]
 pub mod macros;
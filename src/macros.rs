@@ -40,18 +40,29 @@ macro_rules! cilk_parse_ty {
     ($_:expr, i8) => {
         types::Type::i8
     };
+    ($_:expr, i16) => {
+        types::Type::i16
+    };
     ($_:expr, i32) => {
         types::Type::i32
     };
     ($_:expr, i64) => {
         types::Type::i64
     };
+    ($_:expr, f32) => {
+        types::Type::f32
+    };
     ($_:expr, f64) => {
         types::Type::f64
     };
     ($_:expr, void) => {
         types::Type::Void
     };
+    ($tys:expr, fn [$($ret:tt)*] [$( ( $($param:tt)* ) ),*]) => {{
+        let ret = cilk_parse_ty!($tys, $($ret)*);
+        let params = vec![$( cilk_parse_ty!($tys, $($param)*) ),*];
+        $tys.new_function_ty(ret, params)
+    }};
     ($tys:expr, ptr $($elem:tt)*) => {{
         let e = cilk_parse_ty!($tys, $($elem)*);
         $tys.new_pointer_ty(e)
@@ -77,15 +88,25 @@ macro_rules! cilk_value {
     ($builder:expr; i8 $n:expr) => {{
         value::Value::Immediate(value::ImmediateValue::Int8($n))
     }};
+    ($builder:expr; i16 $n:expr) => {{
+        value::Value::Immediate(value::ImmediateValue::Int16($n))
+    }};
     ($builder:expr; i32 $n:expr) => {{
         value::Value::Immediate(value::ImmediateValue::Int32($n))
     }};
     ($builder:expr; i64 $n:expr) => {{
         value::Value::Immediate(value::ImmediateValue::Int64($n))
     }};
+    ($builder:expr; f32 $n:expr) => {{
+        value::Value::Immediate(value::ImmediateValue::F32($n))
+    }};
     ($builder:expr; f64 $n:expr) => {{
         value::Value::Immediate(value::ImmediateValue::F64($n))
     }};
+    ($builder:expr; addr $name:ident) => {{
+        let id = $builder.func.module.find_function(stringify!($name)).unwrap();
+        $builder.func.module.function_address(id)
+    }};
     ($builder:expr; % $n:expr) => {{
         $n
     }};
@@ -99,9 +120,30 @@ macro_rules! icmp_kind {
     (eq) => {
         opcode::ICmpKind::Eq
     };
+    (ne) => {
+        opcode::ICmpKind::Ne
+    };
     (lt) => {
         opcode::ICmpKind::Lt
     };
+    (gt) => {
+        opcode::ICmpKind::Gt
+    };
+    (ge) => {
+        opcode::ICmpKind::Ge
+    };
+    (ult) => {
+        opcode::ICmpKind::Ult
+    };
+    (ule) => {
+        opcode::ICmpKind::Ule
+    };
+    (ugt) => {
+        opcode::ICmpKind::Ugt
+    };
+    (uge) => {
+        opcode::ICmpKind::Uge
+    };
 }
 
 #[macro_export]
@@ -115,6 +157,15 @@ macro_rules! fcmp_kind {
     (ult) => {
         opcode::FCmpKind::ULt
     };
+    (une) => {
+        opcode::FCmpKind::UNe
+    };
+    (ugt) => {
+        opcode::FCmpKind::UGt
+    };
+    (uge) => {
+        opcode::FCmpKind::UGe
+    };
 }
 
 #[macro_export]
@@ -177,12 +228,102 @@ macro_rules! cilk_expr {
     let $x = $builder.build_rem(val1, val2);
     cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; $x:ident = udiv ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_udiv(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = urem ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_urem(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = shl ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_shl(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = lshr ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_lshr(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = ashr ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_ashr(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = and ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_and(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = or ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_or(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = xor ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_xor(val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; $x:ident = sext [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
     let val = cilk_value!($builder; $( $val )*);
     let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
     let $x = $builder.build_sext(val, ty);
     cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; $x:ident = zext [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_zext(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = trunc [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_trunc(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = fpext [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_fpext(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = fptrunc [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_fptrunc(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = bitcast [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_bitcast(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = ptrtoint [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_ptrtoint(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
+($builder:expr; $bb_map:expr; $x:ident = inttoptr [$($ty:tt)*] ($($val:tt)*); $($remain:tt)*) => {
+    let val = cilk_value!($builder; $( $val )*);
+    let ty = cilk_parse_ty!($builder.func.module.types, $($ty)*);
+    let $x = $builder.build_inttoptr(val, ty);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; $x:ident = gep ($($val:tt)*), [$( ( $($idx:tt)* ) ),*] ; $($remain:tt)*) => {
     let val = cilk_value!($builder; $( $val )*);
     let indices = vec![$( cilk_value!($builder; $( $idx )*) ),*];
@@ -215,6 +356,12 @@ macro_rules! cilk_expr {
         }), args);
         cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; $x:ident = call ($($callee:tt)*) [$( ( $($arg:tt)* ) ),*] ; $($remain:tt)*) => {
+    let callee = cilk_value!($builder; $( $callee )*);
+    let args = vec![ $( cilk_value!($builder; $( $arg )*) ),* ];
+    let $x = $builder.build_call(callee, args);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; $x:ident = icmp $kind:ident ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
     let val1 = cilk_value!($builder; $( $val1 )*);
     let val2 = cilk_value!($builder; $( $val2 )*);
@@ -227,6 +374,13 @@ macro_rules! cilk_expr {
     let $x = $builder.build_fcmp(fcmp_kind!($kind), val1, val2);
     cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; $x:ident = select ($($cond:tt)*), ($($val1:tt)*), ($($val2:tt)*); $($remain:tt)*) => {
+    let cond = cilk_value!($builder; $( $cond )*);
+    let val1 = cilk_value!($builder; $( $val1 )*);
+    let val2 = cilk_value!($builder; $( $val2 )*);
+    let $x = $builder.build_select(cond, val1, val2);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; br ($($cond:tt)*) $l1:ident, $l2:ident; $($remain:tt)*) => {
     let bb1 = *$bb_map.entry(stringify!($l1)).or_insert_with(|| $builder.append_basic_block());
     let bb2 = *$bb_map.entry(stringify!($l2)).or_insert_with(|| $builder.append_basic_block());
@@ -234,11 +388,25 @@ macro_rules! cilk_expr {
     $builder.build_cond_br(cond, bb1, bb2);
     cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; switch ($($cond:tt)*), $default:ident, [$( [ ($($case:tt)*), $bb:ident ] ),*] ; $($remain:tt)*) => {
+    let default = *$bb_map.entry(stringify!($default)).or_insert_with(|| $builder.append_basic_block());
+    let cases = vec![$(
+                        (cilk_value!($builder; $( $case )*),
+                         *$bb_map.entry(stringify!($bb)).or_insert_with(|| $builder.append_basic_block()))
+                    ),*];
+    let cond = cilk_value!($builder; $( $cond )*);
+    $builder.build_switch(cond, default, cases);
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; br $label:ident; $($remain:tt)*) => {
     let bb = *$bb_map.entry(stringify!($label)).or_insert_with(|| $builder.append_basic_block());
     $builder.build_br(bb);
     cilk_expr!($builder; $bb_map; $( $remain )*);
 };
+($builder:expr; $bb_map:expr; unreachable ; $($remain:tt)*) => {
+    $builder.build_unreachable();
+    cilk_expr!($builder; $bb_map; $( $remain )*);
+};
 ($builder:expr; $bb_map:expr; ret ($($val:tt)*) ; $($remain:tt)*) => {
     let val = cilk_value!($builder; $( $val )*);
     $builder.build_ret(val);
@@ -261,6 +429,16 @@ macro_rules! cilk_ir {
         cilk_expr!(builder; bb_map; $( $exp )*);
         f_id
     }};
+    ($m:expr; declare [$($ret_ty:tt)*] $name:ident [$(($($arg:tt)*)),*]) => {{
+        let ret_ty = cilk_parse_ty!($m.types, $($ret_ty)*);
+        let args_ty = vec![$( cilk_parse_ty!($m.types, $($arg)*) ),*];
+        $m.declare_function(stringify!($name), ret_ty, args_ty, false)
+    }};
+    ($m:expr; declare [$($ret_ty:tt)*] $name:ident [$(($($arg:tt)*),)* ...]) => {{
+        let ret_ty = cilk_parse_ty!($m.types, $($ret_ty)*);
+        let args_ty = vec![$( cilk_parse_ty!($m.types, $($arg)*) ),*];
+        $m.declare_function(stringify!($name), ret_ty, args_ty, true)
+    }};
     (($builder:expr) { $($exp:tt)* }) => {{
         let mut bb_map: FxHashMap<&str, basic_block::BasicBlockId> = FxHashMap::default();
         cilk_expr!($builder; bb_map; $( $exp )*);
@@ -1,3 +1,4 @@
+use crate::ir::verify::VerifierError;
 use std::fmt::Debug;
 
 pub trait ModulePassTrait {
@@ -6,16 +7,51 @@ pub trait ModulePassTrait {
     fn run_on_module(&mut self, module: &mut Self::M);
 }
 
+pub type ModuleVerifier<M> = fn(&M) -> Result<(), Vec<VerifierError>>;
+
+/// The pass that broke the module and what the verifier found after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassError {
+    pub pass: &'static str,
+    pub errors: Vec<VerifierError>,
+}
+
 pub struct ModulePassManager<M: Debug> {
     pub list: Vec<Box<dyn ModulePassTrait<M = M>>>,
+
+    /// If set, checks the module after every pass
+    pub verifier: Option<ModuleVerifier<M>>,
 }
 
 impl<M: Debug> ModulePassManager<M> {
     pub fn new() -> Self {
-        Self { list: vec![] }
+        Self {
+            list: vec![],
+            verifier: None,
+        }
     }
 
+    /// Runs the passes in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a verifier is set and a pass breaks the module. Use `try_run_on_module` to get
+    /// the error instead.
     pub fn run_on_module(&mut self, module: &mut M) {
+        if let Err(e) = self.try_run_on_module(module) {
+            panic!(
+                "module is broken after pass '{}':\n{}",
+                e.pass,
+                e.errors
+                    .iter()
+                    .fold("".to_string(), |s, e| format!("{}{}\n", s, e))
+            )
+        }
+    }
+
+    /// Runs the passes in order. If a verifier is set and a pass breaks the module, stops there
+    /// and returns what is broken.
+    pub fn try_run_on_module(&mut self, module: &mut M) -> Result<(), PassError> {
         for pass in &mut self.list {
             let now = ::std::time::Instant::now();
             pass.run_on_module(module);
@@ -24,7 +60,16 @@ impl<M: Debug> ModulePassManager<M> {
                 pass.name(),
                 ::std::time::Instant::now().duration_since(now)
             ));
+            if let Some(verify) = self.verifier {
+                if let Err(errors) = verify(module) {
+                    return Err(PassError {
+                        pass: pass.name(),
+                        errors,
+                    });
+                }
+            }
         }
+        Ok(())
     }
 
     pub fn add_pass<A: 'static + ModulePassTrait<M = M>>(&mut self, pass: A) {
@@ -7,7 +7,7 @@ mod x86_64 {
             exec::executor::{Executor, GenericValue},
             standard_conversion_into_machine_module,
         },
-        ir::{builder, /*global_val,*/ types, value},
+        ir::{builder, global_val, parser, types, value},
         module::Module,
         *, // for macro
     };
@@ -94,6 +94,35 @@ mod x86_64 {
         );
     }
 
+    #[test]
+    fn asmer_jit_indirect_call() {
+        let mut m = Module::new("cilk");
+        cilk_ir!(m; define [i32] add [(i32)] {
+            entry: x = add (%arg.0), (i32 2);
+                   ret (%x);
+        });
+
+        cilk_ir!(m; define [i32] apply [(ptr fn [i32] [(i32)]), (i32)] {
+            entry: x = call (%arg.0) [(%arg.1)];
+                   ret (%x);
+        });
+
+        cilk_ir!(m; define [i32] main [(i32)] {
+            entry: x = call apply [(addr add), (%arg.0)];
+                   ret (%x);
+        });
+
+        let machine_module = standard_conversion_into_machine_module(&mut m);
+
+        let mut exec = Executor::new(machine_module);
+        exec.compile();
+        let f = exec.find_function_by_name("main").unwrap();
+        assert_eq!(
+            GenericValue::i32(5),
+            exec.execute(f, vec![GenericValue::i32(3)])
+        );
+    }
+
     #[test]
     fn asmer_jit_local_var() {
         let mut m = Module::new("cilk");
@@ -128,6 +157,54 @@ mod x86_64 {
         );
     }
 
+    #[test]
+    fn asmer_global_var_init() {
+        let mut m = Module::new("cilk");
+        let ty = m
+            .types
+            .new_struct_ty(vec![types::Type::i8, types::Type::f64]);
+        m.global_vars.new_global_var_with_init(
+            ty,
+            global_val::Linkage::Common,
+            "pt",
+            global_val::Initializer::Struct(vec![
+                global_val::Initializer::Immediate(value::ImmediateValue::Int8(7)),
+                global_val::Initializer::Immediate(value::ImmediateValue::F64(2.5)),
+            ]),
+            false,
+        );
+        cilk_ir!(m; define [i32] test [] {
+            entry:
+                ret (i32 42);
+        });
+        compile(
+            "#include <assert.h>
+                 extern struct { char a; double b; } pt;
+                 int main() { assert(pt.a == 7 && pt.b == 2.5); pt.b = 1.0; return 0; }",
+            &mut m,
+        );
+    }
+
+    #[test]
+    fn asmer_global_var_address() {
+        let mut m = parser::parse_module(
+            "@tbl = common global [3 x i32] [1, 2, 3]
+@p = common global i32* getelementptr ([3 x i32]* @tbl, i32 0, i32 2)
+@fp = common constant i32 ()* test
+define i32 test() {
+label.0:
+    ret i32 42
+}",
+        )
+        .unwrap();
+        compile(
+            "#include <assert.h>
+                 extern int tbl[3], *p; extern int (*fp)(void);
+                 int main() { assert(p == &tbl[2] && *p == 3 && fp() == 42); return 0; }",
+            &mut m,
+        );
+    }
+
     #[test]
     fn asmer_local() {
         let mut m = Module::new("cilk");
@@ -194,6 +271,29 @@ mod x86_64 {
         )
     }
 
+    #[test]
+    fn asmer_var_arg_call() {
+        let mut m = Module::new("cilk");
+        cilk_ir!(m; declare [i32] sprintf [(ptr i8), (ptr i8), ...]);
+        cilk_ir!(m; define [i32] format [(ptr i8), (ptr i8), (i32), (f64)] {
+            entry: x = call sprintf [(%arg.0), (%arg.1), (%arg.2), (%arg.3), (%arg.2)];
+                   ret (%x);
+        });
+
+        compile(
+            "#include <assert.h>
+             #include <string.h>
+             extern int format(char *, const char *, int, double);
+             int main() {
+                char buf[32];
+                assert(format(buf, \"%d %.2f %d\", 42, 1.5) == 10);
+                assert(strcmp(buf, \"42 1.50 42\") == 0);
+                return 0;
+             }",
+            &mut m,
+        )
+    }
+
     #[test]
     fn asmer_arith_ri() {
         let mut m = Module::new("cilk");
@@ -0,0 +1,176 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{parser, types, verify},
+        *,
+    };
+    use std::io;
+
+    const TEXT: &str = "@pair = common constant struct {i8, f64} {-3, 2.5}
+@tbl = common global [3 x i16] [1, -2, 3]
+@zero = common global [2 x f32] zeroinitializer
+@dbl = common global [2 x f64] [0.5, -2]
+@ext = external global i64
+declare i32 printf(i8*, ...)
+define f64 second(struct {i8, f64}* byval) {
+label.0:
+    %0 = getelementptr struct {i8, f64}* %arg.0, i32 0, i32 1
+    %1 = load f64* %0
+    ret f64 %1
+}
+define f64 main(i32) {
+label.0:
+    %0 = alloca struct {i8, f64}
+    %1 = icmp lt, i32 %arg.0, i32 0
+    br i1 %1, %label.1, %label.2
+label.1:
+    %2 = getelementptr [2 x f64]* @dbl, i32 0, i32 1
+    %5 = load f64* %2
+    br %label.3
+label.2:
+    %6 = getelementptr struct {i8, f64}* @pair, i32 0, i32 1
+    %7 = load f64* %6
+    %8 = getelementptr struct {i8, f64}* %0, i32 0, i32 1
+    store f64 %7, f64* %8
+    %9 = call f64 second, struct {i8, f64}* %0
+    br %label.3
+label.3:
+    %10 = phi f64 %5, %label.1, f64 %9, %label.2
+    ret f64 %10
+}";
+
+    fn write(m: &module::Module) -> Vec<u8> {
+        let mut buf = vec![];
+        m.write_bitcode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn bitcode_roundtrip() {
+        let m = parser::parse_module(TEXT).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let buf = write(&m);
+        let mut m2 = module::Module::read_bitcode(buf.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", m2), format!("{:?}", m));
+        // Writing it again gives exactly the same bytes
+        assert_eq!(write(&m2), buf);
+
+        let second = m2.find_function("second").unwrap();
+        assert_eq!(
+            m2.function_ref(second).get_param_attr(0),
+            Some(types::ParamAttribute { byval: true })
+        );
+        let printf = m2.find_function("printf").unwrap();
+        let base = m2.types.base.borrow();
+        assert!(
+            base.as_function_ty(m2.function_ref(printf).ty)
+                .unwrap()
+                .is_var_arg
+        );
+        let pair_ty = m2.global_vars.arena.iter().next().unwrap().1.ty;
+        let pair_ty = base.as_struct_ty(pair_ty).unwrap();
+        assert_eq!(pair_ty.fields_offset(), &[0, 8]);
+        assert_eq!((pair_ty.size(), pair_ty.align()), (16, 8));
+        drop(base);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m2);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(-1)]),
+            exec::jit::GenericValue::F64(-2.0)
+        );
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(1)]),
+            exec::jit::GenericValue::F64(2.5)
+        );
+    }
+
+    #[test]
+    fn bitcode_roundtrip_printed_ir() {
+        // Named values, a recursive struct, indirect calls and most kinds of instructions
+        let m = parser::parse_module(
+            "struct Node = {i32, struct Node*}
+@head = common global struct Node* zeroinitializer
+@handler = common global i32 (i32)* twice
+@off = common global i64 add (i64 ptrtoint (struct Node** @head), i64 8)
+declare void abort()
+define i32 twice(i32 %x) {
+entry:
+    %y = shl i32 %x, i32 1
+    ret i32 %y
+}
+define i32 sum(struct Node* %n) {
+entry:
+    br %loop
+loop:
+    %p = phi struct Node* %n, %entry, struct Node* %next, %body
+    %acc = phi i32 0, %entry, i32 %acc.next, %body
+    %pi = ptrtoint struct Node* %p to i64
+    %c = icmp eq, i64 %pi, i64 0
+    br i1 %c, %exit, %body
+body:
+    %vp = getelementptr struct Node* %p, i32 0, i32 0
+    %v = load i32* %vp
+    %acc.next = add i32 %acc, i32 %v
+    %np = getelementptr struct Node* %p, i32 0, i32 1
+    %next = load struct Node** %np
+    br %loop
+exit:
+    ret i32 %acc
+}
+define f32 misc(i16 %a, i8 %b, f32 %f) {
+entry:
+    %h = load i32 (i32)** @handler
+    %a32 = sext i16 %a to i32
+    %r = call i32 (i32)* %h, i32 %a32
+    %u = udiv i32 %r, i32 3
+    %m = urem i32 %u, i32 7
+    %z = zext i8 %b to i32
+    %t = trunc i32 %m to i16
+    %s = select i1 true, i32 %z, i32 %m
+    %fc = fcmp ult, f32 %f, f32 1.5
+    br i1 %fc, %ok, %bad
+ok:
+    %g = sitofp i32 %s to f32
+    %g2 = add f32 %g, f32 %f
+    switch i16 %t, %done, i16 1, %done, i16 2, %bad
+done:
+    ret f32 %g2
+bad:
+    call void abort
+    unreachable
+}",
+        )
+        .unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let buf = write(&m);
+        let m2 = module::Module::read_bitcode(buf.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", m2), format!("{:?}", m));
+        assert_eq!(verify::verify_module(&m2), Ok(()));
+        assert_eq!(write(&m2), buf);
+    }
+
+    #[test]
+    fn bitcode_errors() {
+        let buf = write(&parser::parse_module(TEXT).unwrap());
+        let read = |buf: &[u8]| module::Module::read_bitcode(buf).unwrap_err();
+
+        let mut bad_magic = buf.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(read(&bad_magic).kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_version = buf.clone();
+        bad_version[4] = 0x7f;
+        let e = read(&bad_version);
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.to_string(),
+            "unsupported bitcode version 127 (expected 5)"
+        );
+
+        for len in (0..buf.len()).step_by(7) {
+            assert_eq!(read(&buf[..len]).kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
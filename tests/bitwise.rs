@@ -0,0 +1,157 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{builder, types, value},
+        *,
+    };
+
+    #[test]
+    fn bitwise_i32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32), (i32)] {
+        entry:
+            x = and (%arg.0), (%arg.1);
+            y = or (%arg.0), (i32 240);
+            z = xor (%x), (%y);
+            s = shl (%z), (%arg.1);
+            l = lshr (%arg.0), (i32 3);
+            r = ashr (%arg.0), (%arg.1);
+            t = xor (%s), (%l);
+            t = and (%t), (i32 65535);
+            t = xor (%t), (%r);
+            ret (%t);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[
+            (123456789i32, 3i32),
+            (-98765, 7),
+            (0x7f00_ff00, 1),
+            (-1, 31),
+        ] {
+            let x = a & b;
+            let y = a | 240;
+            let s = (x ^ y).wrapping_shl(b as u32);
+            let l = ((a as u32) >> 3) as i32;
+            let r = a >> b;
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(((s ^ l) & 65535) ^ r)
+            );
+        }
+    }
+
+    #[test]
+    fn bitwise_i8() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i8] func [(i8), (i8)] {
+        entry:
+            x = and (%arg.0), (i8 60);
+            y = or (%arg.0), (%arg.1);
+            z = xor (%x), (%y);
+            s = shl (%z), (%arg.1);
+            l = lshr (%arg.0), (%arg.1);
+            r = ashr (%arg.0), (i8 2);
+            t = xor (%s), (%l);
+            t = or (%t), (i8 1);
+            t = xor (%t), (%r);
+            ret (%t);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(100i8, 1i8), (-77, 3), (-128, 7), (5, 0)] {
+            let z = (a & 60) ^ (a | b);
+            let s = z.wrapping_shl(b as u32);
+            let l = ((a as u8) >> b) as i8;
+            let r = a >> 2;
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int8(a),
+                        exec::jit::GenericValue::Int8(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int8(((s ^ l) | 1) ^ r)
+            );
+        }
+    }
+
+    #[test]
+    fn bitwise_i64() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i64] func [(i64), (i64)] {
+        entry:
+            x = and (%arg.0), (i64 1085102592571150095);
+            y = or (%arg.0), (i64 255);
+            z = xor (%x), (%y);
+            z = xor (%z), (%arg.1);
+            s = shl (%z), (i64 4);
+            l = lshr (%arg.0), (%arg.1);
+            r = ashr (%arg.0), (i64 40);
+            t = and (%s), (%l);
+            t = or (%t), (%r);
+            ret (%t);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[
+            (0x1234_5678_9abc_def0i64, 8i64),
+            (-0x0fed_cba9_8765_4321, 63),
+            (-1, 1),
+        ] {
+            let z = (a & 0x0f0f_0f0f_0f0f_0f0f) ^ (a | 255) ^ b;
+            let s = z << 4;
+            let l = ((a as u64) >> b) as i64;
+            let r = a >> 40;
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int64(a),
+                        exec::jit::GenericValue::Int64(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int64((s & l) | r)
+            );
+        }
+    }
+
+    #[test]
+    fn bitwise_const_folding() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [] {
+        entry:
+            x = and (i32 12), (i32 10);
+            y = or (%x), (i32 3);
+            z = xor (%y), (i32 -1);
+            l = lshr (%z), (i32 28);
+            r = ashr (%z), (i32 28);
+            t = shl (%l), (i32 1);
+            t = xor (%t), (%r);
+            ret (%t);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        let z = !((12 & 10) | 3i32);
+        assert_eq!(
+            jit.run(func, vec![]),
+            exec::jit::GenericValue::Int32(((((z as u32) >> 28) << 1) as i32) ^ (z >> 28))
+        );
+    }
+}
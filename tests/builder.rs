@@ -0,0 +1,122 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::builder::{BuildError, FuncRef},
+        ir::{builder, opcode, types, value, verify},
+        *,
+    };
+
+    #[test]
+    fn try_build_valid() {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function(
+            "f",
+            types::Type::i32,
+            vec![types::Type::i32, types::Type::i32],
+        );
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        builder.set_insert_point(entry);
+        let x = builder.try_build_alloca(types::Type::i32).unwrap();
+        let a = builder.get_param(0).unwrap();
+        let b = builder.get_param(1).unwrap();
+        builder.try_build_store(a, x).unwrap();
+        let y = builder.try_build_load(x).unwrap();
+        let y = builder.try_build_mul(y, b).unwrap();
+        let c = builder
+            .try_build_icmp(opcode::ICmpKind::Lt, y, value::Value::new_imm_int32(0))
+            .unwrap();
+        let neg = builder
+            .try_build_sub(value::Value::new_imm_int32(0), y)
+            .unwrap();
+        let y = builder.try_build_select(c, neg, y).unwrap();
+        builder.try_build_ret(y).unwrap();
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("f").unwrap();
+        assert_eq!(
+            jit.run(
+                func,
+                vec![
+                    exec::jit::GenericValue::Int32(-6),
+                    exec::jit::GenericValue::Int32(7)
+                ]
+            ),
+            exec::jit::GenericValue::Int32(42)
+        );
+    }
+
+    #[test]
+    fn try_build_errors() {
+        let mut m = module::Module::new("cilk");
+        let g = m.create_function(
+            "g",
+            types::Type::i32,
+            vec![types::Type::i32, types::Type::f64],
+        );
+        let g_ty = m.function_ref(g).ty;
+        let g = value::Value::Function(value::FunctionValue {
+            func_id: g,
+            ty: g_ty,
+        });
+        let f = m.create_function("f", types::Type::i32, vec![types::Type::i64]);
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        let arg = builder.get_param(0).unwrap();
+        let one = value::Value::new_imm_int32(1);
+
+        assert_eq!(
+            builder.try_build_add(one, one),
+            Err(BuildError::NoInsertPoint)
+        );
+        builder.set_insert_point(entry);
+
+        let check = |r: Result<value::Value, BuildError>, msg: &str| {
+            assert!(matches!(r, Err(BuildError::InvalidOperands { .. })));
+            assert_eq!(r.unwrap_err().to_string(), msg);
+        };
+        check(
+            builder.try_build_add(one, arg),
+            "add: rhs must be i32, found i64",
+        );
+        check(
+            builder.try_build_store(one, arg),
+            "store: i64 is not a pointer",
+        );
+        check(builder.try_build_load(arg), "load: i64 is not a pointer");
+        check(
+            builder.try_build_gep(arg, vec![one]),
+            "getelementptr: i64 is not a pointer",
+        );
+        check(
+            builder.try_build_call(g, vec![one]),
+            "call: expected 2 arguments, found 1",
+        );
+        check(
+            builder.try_build_call(g, vec![one, one]),
+            "call: argument must be f64, found i32",
+        );
+        check(
+            builder.try_build_trunc(one, types::Type::i64),
+            "trunc: cannot cast i32 to i64",
+        );
+        check(
+            builder.try_build_ret(arg),
+            "ret: returned value must be i32, found i64",
+        );
+        // Nothing is built on errors
+        assert_eq!(builder.func.func_ref().inst_table.len(), 0);
+
+        let x = builder.try_build_trunc(arg, types::Type::i32).unwrap();
+        builder.try_build_ret(x).unwrap();
+        let e = builder.try_build_add(x, one).unwrap_err();
+        assert_eq!(e, BuildError::AfterTerminator(entry));
+        assert_eq!(
+            e.to_string(),
+            "cannot insert after the terminator of label.0"
+        );
+        assert_eq!(verify::verify_module(&m), Ok(()));
+    }
+}
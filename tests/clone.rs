@@ -0,0 +1,132 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{builder, clone, parser, types, value, verify},
+        *,
+    };
+    use rustc_hash::FxHashMap;
+
+    const TEXT: &str = "define i32 sum(i32) {
+label.0:
+    br %label.1
+label.1:
+    %0 = phi i32 0, %label.0, i32 %2, %label.2
+    %1 = phi i32 0, %label.0, i32 %3, %label.2
+    %4 = icmp lt, i32 %1, i32 %arg.0
+    br i1 %4, %label.2, %label.3
+label.2:
+    %2 = add i32 %0, i32 %1
+    %3 = add i32 %1, i32 1
+    br %label.1
+label.3:
+    ret i32 %0
+}
+define i32 fibo(i32) {
+label.0:
+    %0 = icmp le, i32 %arg.0, i32 2
+    br i1 %0, %label.1, %label.2
+label.1:
+    ret i32 1
+label.2:
+    %1 = sub i32 %arg.0, i32 1
+    %2 = call i32 fibo, i32 %1
+    %3 = sub i32 %arg.0, i32 2
+    %4 = call i32 fibo, i32 %3
+    %5 = add i32 %2, i32 %4
+    ret i32 %5
+}";
+
+    fn run(m: &mut module::Module, name: &str, args: Vec<i32>) -> exec::jit::GenericValue {
+        let mut jit = exec::jit::JITExecutor::new(m);
+        let func = jit.find_function_by_name(name).unwrap();
+        jit.run(
+            func,
+            args.into_iter()
+                .map(exec::jit::GenericValue::Int32)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn clone_function() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        let sum = m.find_function("sum").unwrap();
+        let fibo = m.find_function("fibo").unwrap();
+
+        // A plain copy
+        let mut value_map = FxHashMap::default();
+        let sum2 = clone::clone_function(&mut m, sum, "sum2", &mut value_map);
+        let arg = m.function_ref(sum).get_param_value(0).unwrap();
+        assert_eq!(
+            value_map[&arg],
+            m.function_ref(sum2).get_param_value(0).unwrap()
+        );
+
+        // Specialized for an argument
+        let mut value_map = FxHashMap::default();
+        value_map.insert(arg, value::Value::new_imm_int32(10));
+        clone::clone_function(&mut m, sum, "sum10", &mut value_map);
+
+        // Recursive calls still go to the original
+        clone::clone_function(&mut m, fibo, "fibo2", &mut FxHashMap::default());
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("define i32 sum2(i32)"));
+        assert!(printed.contains("icmp lt, i32 %2, i32 10"));
+        assert_eq!(printed.matches("call i32 fibo,").count(), 4);
+
+        assert_eq!(
+            run(&mut m, "sum", vec![5]),
+            exec::jit::GenericValue::Int32(10)
+        );
+        assert_eq!(
+            run(&mut m, "sum2", vec![6]),
+            exec::jit::GenericValue::Int32(15)
+        );
+        assert_eq!(
+            run(&mut m, "sum10", vec![0]),
+            exec::jit::GenericValue::Int32(45)
+        );
+        assert_eq!(
+            run(&mut m, "fibo2", vec![10]),
+            exec::jit::GenericValue::Int32(55)
+        );
+    }
+
+    #[test]
+    fn clone_blocks_into_another_function() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        let sum = m.find_function("sum").unwrap();
+        let f = m.create_function(
+            "f",
+            types::Type::i32,
+            vec![types::Type::i32, types::Type::i32],
+        );
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        drop(builder);
+
+        // Runs the body of `sum` on the second argument of `f`
+        let mut value_map = FxHashMap::default();
+        value_map.insert(
+            m.function_ref(sum).get_param_value(0).unwrap(),
+            m.function_ref(f).get_param_value(1).unwrap(),
+        );
+        let blocks = m.function_ref(sum).basic_blocks.order.clone();
+        let new_blocks = clone::clone_blocks(&mut m, sum, &blocks, f, &mut value_map);
+        assert_eq!(new_blocks.len(), 4);
+        assert_eq!(m.function_ref(f).basic_blocks.order[1..], new_blocks[..]);
+
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        builder.set_insert_point(entry);
+        builder.build_br(new_blocks[0]);
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        assert_eq!(
+            run(&mut m, "f", vec![100, 4]),
+            exec::jit::GenericValue::Int32(6)
+        );
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::{
+            asm::print::MachineAsmPrinter, exec, standard_conversion_into_machine_module,
+        },
+        ir::{const_folding, global_val, opcode, parser, types, value, verify},
+        *,
+    };
+
+    const TEXT: &str = "@tbl = common global [3 x i32] [10, 20, 30]
+@p = common global i32* getelementptr ([3 x i32]* @tbl, i32 0, i32 2)
+@fp = common constant i32 (i32)* twice
+@pair = common global struct {i32*, i64} {@tbl, add (i64 ptrtoint ([3 x i32]* @tbl), i64 4)}
+define i32 twice(i32) {
+label.0:
+    %0 = add i32 %arg.0, i32 %arg.0
+    ret i32 %0
+}
+define i32 main(i32) {
+label.0:
+    %0 = load i32** @p
+    %1 = load i32* %0
+    %2 = load i32* getelementptr ([3 x i32]* @tbl, i32 0, i32 1)
+    %3 = add i32 %1, i32 %2
+    %4 = load i32 (i32)** @fp
+    %5 = call i32 (i32)* %4, i32 %3
+    %6 = getelementptr struct {i32*, i64}* @pair, i32 0, i32 1
+    %7 = load i64* %6
+    %8 = inttoptr i64 %7 to i32*
+    %9 = load i32* %8
+    %10 = add i32 %5, i32 %9
+    %11 = add i32 %10, i32 %arg.0
+    ret i32 %11
+}";
+
+    #[test]
+    fn const_expr_roundtrip() {
+        let m = parser::parse_module(TEXT).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed
+            .contains("@p = common global i32* getelementptr ([3 x i32]* @tbl, i32 0, i32 2)\n"));
+        assert!(printed.contains("@fp = common constant i32 (i32)* twice\n"));
+        assert!(printed.contains("{@tbl, add (i64 ptrtoint ([3 x i32]* @tbl), i64 4)}\n"));
+        assert!(printed.contains("load i32* getelementptr ([3 x i32]* @tbl, i32 0, i32 1)"));
+
+        let m2 = parser::parse_module(&printed).unwrap();
+        assert_eq!(format!("{:?}", m2), printed);
+        let mut buf = vec![];
+        m.write_bitcode(&mut buf).unwrap();
+        let mut m3 = module::Module::read_bitcode(buf.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", m3), printed);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m3);
+        let func = jit.find_function_by_name("main").unwrap();
+        // (30 + 20) * 2 + 20 + 1
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(1)]),
+            exec::jit::GenericValue::Int32(121)
+        );
+    }
+
+    #[test]
+    fn const_expr_relocations() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        let machine_module = standard_conversion_into_machine_module(&mut m);
+        let mut printer = MachineAsmPrinter::new();
+        printer.run_on_module(&machine_module);
+        assert!(printer.output.contains("p:\n  .quad tbl+8\n"));
+        assert!(printer.output.contains("fp:\n  .quad twice\n"));
+        assert!(printer
+            .output
+            .contains("pair:\n  .quad tbl\n  .quad tbl+4\n"));
+    }
+
+    #[test]
+    fn const_expr_folding() {
+        let mut m = parser::parse_module(
+            "@g = common global i32 5
+@n = common global i64 sub (i64 ptrtoint (i32* @g), i64 0)
+@q = common global i32* getelementptr (i32* @g, i32 0)
+define i32 main() {
+label.0:
+    %0 = load i32* bitcast (i32* @g)
+    %1 = add i32 %0, i32 mul (i32 2, i32 3)
+    ret i32 %1
+}",
+        )
+        .unwrap();
+        const_folding::ConstantFolding::new().run_on_module(&mut m);
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("@n = common global i64 ptrtoint (i32* @g)\n"));
+        assert!(printed.contains("@q = common global i32* @g\n"));
+        assert!(printed.contains("%0 = load i32* @g"));
+        assert!(printed.contains("%1 = add i32 %0, i32 6"));
+
+        // Expressions built by hand are folded the same way
+        let g_id = m.global_vars.arena.iter().next().unwrap().0;
+        let i32_ptr = m.types.new_pointer_ty(types::Type::i32);
+        let g = value::Value::Global(value::GlobalValue {
+            id: g_id,
+            ty: i32_ptr,
+        });
+        let gep = m
+            .const_gep(g, vec![value::Value::new_imm_int32(1)])
+            .unwrap();
+        assert_eq!(m.const_exprs.fold(&gep), None);
+        assert_eq!(m.const_exprs.resolve_address(&m.types, &gep), Some((g, 4)));
+        let i8_ptr = m.types.new_pointer_ty(types::Type::i8);
+        let cast = m.const_expr(opcode::Opcode::Bitcast, vec![g], i8_ptr);
+        assert_eq!(
+            m.const_exprs.fold(&cast),
+            Some(value::Value::Global(value::GlobalValue {
+                id: g_id,
+                ty: i8_ptr
+            }))
+        );
+        assert!(!global_val::Initializer::Expr(gep).is_valid_for(types::Type::i32, &m.types));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(jit.run(func, vec![]), exec::jit::GenericValue::Int32(11));
+    }
+
+    #[test]
+    fn const_expr_parse_errors() {
+        let e = parser::parse_module("@p = common global i32* getelementptr (i32 @q)").unwrap_err();
+        assert_eq!(e.to_string(), "line 1: unknown global variable '@q'");
+        let e = parser::parse_module(
+            "@g = common global i32 0
+@p = common global i64* getelementptr (i32* @g, i32 0)",
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2: invalid operands for constant expression 'getelementptr'"
+        );
+        let e = parser::parse_module(
+            "@g = common global i32 0
+@p = common global i32 bitcast (i32* @g)",
+        )
+        .unwrap_err();
+        assert_eq!(e.to_string(), "line 2: invalid i32 initializer");
+    }
+}
@@ -0,0 +1,240 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{const_folding, parser, verify},
+        *,
+    };
+
+    fn fold(src: &str) -> module::Module {
+        let mut m = parser::parse_module(src).unwrap();
+        const_folding::ConstantFolding::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        m
+    }
+
+    #[test]
+    fn const_folding_compares_and_casts() {
+        let mut m = fold(
+            "define i32 main(i32 %x) {
+entry:
+    %lt = icmp lt, i32 -1, i32 2
+    %ult = icmp ult, i32 -1, i32 2
+    %b = icmp eq, i1 true, i1 false
+    %nan = div f64 0, f64 0
+    %uno = fcmp ueq, f64 %nan, f64 1
+    %ole = fcmp ule, f32 2.5, f32 2.5
+    %big = mul i64 4294967296, i64 3
+    %q = div i64 %big, i64 4294967296
+    %t = trunc i64 %q to i32
+    %s = sext i1 %lt to i32
+    %z = zext i1 %ult to i32
+    %fp = sitofp i32 %t to f64
+    %fp2 = add f64 %fp, f64 0.75
+    %i = fptosi f64 %fp2 to i32
+    %sum = add i32 %i, i32 %s
+    %sum2 = add i32 %sum, i32 %z
+    %r = add i32 %sum2, i32 %x
+    %w = add i32 2147483647, i32 1
+    ret i32 %r
+}",
+        );
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("%r = add i32 2, i32 %x"));
+        for name in &[
+            "%lt", "%ult", "%b", "%nan", "%uno", "%ole", "%big", "%q", "%t", "%s", "%z", "%fp",
+            "%i", "%sum", "%w",
+        ] {
+            assert!(!printed.contains(&format!("{} =", name)));
+        }
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(40)]),
+            exec::jit::GenericValue::Int32(42)
+        );
+    }
+
+    #[test]
+    fn const_folding_immediates() {
+        use value::{ImmediateValue, Value};
+        let imm = |i| Value::Immediate(i);
+        assert_eq!(
+            Value::new_imm_int32(i32::MAX).const_add(&Value::new_imm_int32(1)),
+            Some(Value::new_imm_int32(i32::MIN))
+        );
+        assert_eq!(
+            imm(ImmediateValue::Int64(i64::MIN)).const_div(&imm(ImmediateValue::Int64(-1))),
+            None
+        );
+        assert_eq!(
+            Value::new_imm_int8(7).const_rem(&Value::new_imm_int8(0)),
+            None
+        );
+        assert_eq!(
+            imm(ImmediateValue::Int1(true)).const_sext(types::Type::i64),
+            Some(imm(ImmediateValue::Int64(-1)))
+        );
+        assert_eq!(
+            Value::new_imm_int8(-1).const_icmp(opcode::ICmpKind::Ugt, &Value::new_imm_int8(1)),
+            Some(imm(ImmediateValue::Int1(true)))
+        );
+        assert_eq!(
+            Value::new_imm_f64(1e20).const_fptosi(types::Type::i32),
+            None
+        );
+        assert_eq!(
+            Value::new_imm_f64(-2.9).const_fptosi(types::Type::i32),
+            Some(Value::new_imm_int32(-2))
+        );
+    }
+
+    #[test]
+    fn const_folding_overflowing_traps() {
+        use value::{ImmediateValue, Value};
+        let imm = |i| Value::Immediate(i);
+        // x86 `idiv` traps when the quotient doesn't fit, for the remainder as well
+        for &(x, y) in &[
+            (imm(ImmediateValue::Int8(i8::MIN)), Value::new_imm_int8(-1)),
+            (
+                imm(ImmediateValue::Int16(i16::MIN)),
+                Value::new_imm_int16(-1),
+            ),
+            (Value::new_imm_int32(i32::MIN), Value::new_imm_int32(-1)),
+            (
+                imm(ImmediateValue::Int64(i64::MIN)),
+                imm(ImmediateValue::Int64(-1)),
+            ),
+        ] {
+            assert_eq!(x.const_div(&y), None);
+            assert_eq!(x.const_rem(&y), None);
+        }
+        assert_eq!(
+            Value::new_imm_int32(i8::MIN as i32).const_div(&Value::new_imm_int32(-1)),
+            Some(Value::new_imm_int32(128))
+        );
+        assert_eq!(
+            Value::new_imm_int32(i32::MIN).const_rem(&Value::new_imm_int32(3)),
+            Some(Value::new_imm_int32(i32::MIN % 3))
+        );
+
+        let fptosi = |x: f64, ty| Value::new_imm_f64(x).const_fptosi(ty);
+        let two = 2f64;
+        assert_eq!(fptosi(two.powi(63), types::Type::i64), None);
+        assert_eq!(
+            fptosi(-two.powi(63), types::Type::i64),
+            Some(imm(ImmediateValue::Int64(i64::MIN)))
+        );
+        assert_eq!(fptosi(two.powi(31), types::Type::i32), None);
+        assert_eq!(fptosi(-two.powi(31) - 1.0, types::Type::i32), None);
+        assert_eq!(
+            fptosi(two.powi(31) - 0.5, types::Type::i32),
+            Some(Value::new_imm_int32(i32::MAX))
+        );
+        assert_eq!(
+            fptosi(-two.powi(31) - 0.5, types::Type::i32),
+            Some(Value::new_imm_int32(i32::MIN))
+        );
+        assert_eq!(fptosi(128.0, types::Type::i8), None);
+        assert_eq!(
+            fptosi(-128.7, types::Type::i8),
+            Some(imm(ImmediateValue::Int8(-128)))
+        );
+        assert_eq!(fptosi(f64::NAN, types::Type::i64), None);
+        assert_eq!(fptosi(f64::INFINITY, types::Type::i16), None);
+    }
+
+    #[test]
+    fn const_folding_oversized_shifts() {
+        use value::{ImmediateValue, Value};
+        let imm = |i| Value::Immediate(i);
+        // x86 masks the shift amount by 31 (63 for i64), not by the bit width of i8 and i16
+        let one8 = Value::new_imm_int8(1);
+        assert_eq!(one8.const_shl(&Value::new_imm_int8(8)), None);
+        assert_eq!(
+            one8.const_shl(&Value::new_imm_int8(7)),
+            Some(imm(ImmediateValue::Int8(-128)))
+        );
+        assert_eq!(
+            Value::new_imm_int16(-1).const_lshr(&Value::new_imm_int32(16)),
+            None
+        );
+        assert_eq!(
+            Value::new_imm_int16(-1).const_lshr(&Value::new_imm_int32(15)),
+            Some(Value::new_imm_int16(1))
+        );
+        assert_eq!(
+            Value::new_imm_int32(-8).const_ashr(&Value::new_imm_int32(32)),
+            None
+        );
+        assert_eq!(
+            Value::new_imm_int32(1).const_shl(&Value::new_imm_int32(-1)),
+            None
+        );
+        assert_eq!(
+            imm(ImmediateValue::Int64(1)).const_shl(&imm(ImmediateValue::Int64(1 << 32))),
+            None
+        );
+        assert_eq!(
+            imm(ImmediateValue::Int64(i64::MIN)).const_ashr(&Value::new_imm_int8(63)),
+            Some(imm(ImmediateValue::Int64(-1)))
+        );
+    }
+
+    #[test]
+    fn const_folding_traps_are_kept() {
+        let m = fold(
+            "define i32 main(i32 %x) {
+entry:
+    %d = div i32 1, i32 0
+    %r = rem i64 -9223372036854775808, i64 -1
+    %q = div i32 -2147483648, i32 -1
+    %g = fptosi f64 9223372036854776000 to i64
+    %f = fptosi f64 1e20 to i32
+    %s = add i32 %d, i32 %f
+    ret i32 %s
+}",
+        );
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("%d = div i32 1, i32 0"));
+        assert!(printed.contains("%r = rem i64 -9223372036854775808, i64 -1"));
+        assert!(printed.contains("%f = fptosi f64 100000000000000000000 to i32"));
+        assert!(printed.contains("%q = div i32 -2147483648, i32 -1"));
+        assert!(printed.contains("%g = fptosi f64 9223372036854776000 to i64"));
+    }
+
+    #[test]
+    fn const_folding_cond_br() {
+        let mut m = fold(
+            "define i32 main(i32 %x) {
+entry:
+    %c = icmp eq, i32 1, i32 1
+    br i1 %c, %then, %merge
+then:
+    %n = fcmp ult, f64 1, f64 0
+    br i1 %n, %dead, %merge
+dead:
+    %y = add i32 %x, i32 1
+    br %merge
+merge:
+    %p = phi i32 %x, %then, i32 7, %entry, i32 %y, %dead
+    ret i32 %p
+}",
+        );
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("entry:\t// pred(), succ(then),"));
+        assert!(printed.contains("    br %then"));
+        assert!(printed.contains("then:\t// pred(entry), succ(merge),"));
+        assert!(printed.contains("merge:\t// pred(then),"));
+        assert!(printed.contains("%p = phi i32 %x, %then \n"));
+        assert!(!printed.contains("dead"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(3)]),
+            exec::jit::GenericValue::Int32(3)
+        );
+    }
+}
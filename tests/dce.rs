@@ -0,0 +1,72 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{dce, parser, verify},
+        *,
+    };
+
+    const TEXT: &str = "define i32 main(i32 %n) {
+entry:
+    %a = alloca i32
+    %unused = alloca i32
+    store i32 5, i32* %a
+    %l = load i32* %a
+    %d1 = add i32 %n, i32 %l
+    %d2 = mul i32 %d1, i32 3
+    br %loop
+loop:
+    %i = phi i32 0, %entry, i32 %i.next, %loop
+    %j = phi i32 0, %entry, i32 %j.next, %loop
+    %i.next = add i32 %i, i32 1
+    %j.next = add i32 %j, i32 2
+    %c = icmp lt, i32 %i.next, i32 %n
+    br i1 %c, %loop, %exit
+exit:
+    %r = load i32* %a
+    %s = add i32 %r, i32 %i.next
+    ret i32 %s
+orphan:
+    %o = add i32 %n, i32 4
+    br %exit
+}";
+
+    fn run(m: &mut module::Module) -> exec::jit::GenericValue {
+        let mut jit = exec::jit::JITExecutor::new(m);
+        let func = jit.find_function_by_name("main").unwrap();
+        jit.run(func, vec![exec::jit::GenericValue::Int32(4)])
+    }
+
+    #[test]
+    fn dce_removes_unused_values() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        dce::DeadCodeElimination::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        for name in &["%unused", "%l", "%d1", "%d2", "%o"] {
+            assert!(!printed.contains(&format!("{} =", name)));
+        }
+        assert!(printed.contains("store i32 5, i32* %a"));
+        assert!(printed.contains("%r = load i32* %a"));
+        // A cycle keeps itself alive
+        assert!(printed.contains("%j = phi"));
+        assert!(printed.contains("%j.next = add"));
+        assert!(printed.contains("orphan:"));
+        assert_eq!(run(&mut m), exec::jit::GenericValue::Int32(9));
+    }
+
+    #[test]
+    fn dce_aggressive() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        dce::DeadCodeElimination::new_aggressive().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        for name in &["%unused", "%l", "%d1", "%d2", "%j", "%j.next", "%o"] {
+            assert!(!printed.contains(&format!("{} =", name)));
+        }
+        assert!(!printed.contains("orphan"));
+        assert!(printed.contains("exit:\t// pred(loop),"));
+        assert!(printed.contains("%i.next = add i32 %i, i32 1"));
+        assert_eq!(run(&mut m), exec::jit::GenericValue::Int32(9));
+    }
+}
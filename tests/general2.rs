@@ -3,7 +3,7 @@ mod x86_64 {
     use cilk::{
         cilk_ir,
         codegen::x64::{asm::print::MachineAsmPrinter, standard_conversion_into_machine_module},
-        ir::{builder, global_val, types, value},
+        ir::{builder, builder::FuncRef, global_val, types, value},
         module::Module,
         *, // for macro
     };
@@ -193,6 +193,164 @@ mod x86_64 {
         );
     }
 
+    #[test]
+    fn asm_global_var_init() {
+        let mut m = Module::new("cilk");
+        let ty = m.types.new_array_ty(types::Type::i32, 4);
+        let g = m.global_vars.new_global_var_with_init(
+            ty,
+            global_val::Linkage::Common,
+            "tbl",
+            global_val::Initializer::Array(vec![
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(3)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(5)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(7)),
+            ]),
+            true,
+        );
+        let g = value::Value::Global(value::GlobalValue {
+            id: g,
+            ty: m.types.new_pointer_ty(ty),
+        });
+        let c = m.global_vars.new_global_var_with_init(
+            types::Type::i32,
+            global_val::Linkage::Common,
+            "cnt",
+            global_val::Initializer::Immediate(value::ImmediateValue::Int32(40)),
+            false,
+        );
+        let c = value::Value::Global(value::GlobalValue {
+            id: c,
+            ty: m.types.new_pointer_ty(types::Type::i32),
+        });
+
+        cilk_ir!(m; define [i32] test [(i32)] {
+            entry:
+                p = gep (%g), [(i32 0), (%arg.0)];
+                i = load (%p);
+                x = load (%c);
+                x = add (%x), (%i);
+                store (%x), (%c);
+                ret (%x);
+        });
+
+        compile_and_run(
+            "#include <assert.h>
+        extern int test(int);
+        extern const int tbl[4];
+        extern int cnt;
+        int main() {
+            assert(tbl[3] == 0);
+            assert(test(1) == 45);
+            assert(test(2) == 52);
+            assert(cnt == 52);
+        }",
+            &mut m,
+        );
+    }
+
+    #[test]
+    fn asm_switch() {
+        let mut m = Module::new("cilk");
+        cilk_ir!(m; define [i32] test [(i32)] {
+            entry:
+                switch (%arg.0), other, [ [(i32 1), a], [(i32 2), b], [(i32 3), a], [(i32 4), b] ];
+            a:
+                ret (i32 10);
+            b:
+                x = mul (%arg.0), (i32 5);
+                ret (%x);
+            other:
+                switch (%arg.0), d, [ [(i32 -50), a], [(i32 700), b] ];
+            d:
+                ret (i32 -1);
+        });
+        compile_and_run(
+            "#include <assert.h>
+        extern int test(int);
+        int main() {
+            assert(test(1) == 10);
+            assert(test(2) == 10);
+            assert(test(3) == 10);
+            assert(test(4) == 20);
+            assert(test(-50) == 10);
+            assert(test(700) == 3500);
+            assert(test(0) == -1);
+            assert(test(5) == -1);
+        }",
+            &mut m,
+        );
+    }
+
+    #[test]
+    fn asm_indirect_call() {
+        let mut m = Module::new("cilk");
+        cilk_ir!(m; define [i32] triple [(i32)] {
+            entry:
+                x = mul (%arg.0), (i32 3);
+                ret (%x);
+        });
+        cilk_ir!(m; define [i32] apply [(ptr fn [i32] [(i32)]), (i32)] {
+            entry:
+                x = call (%arg.0) [(%arg.1)];
+                ret (%x);
+        });
+        cilk_ir!(m; define [i32] apply_triple [(i32)] {
+            entry:
+                f = alloca_ (ptr fn [i32] [(i32)]);
+                store (addr triple), (%f);
+                g = load (%f);
+                x = call (%g) [(%arg.0)];
+                ret (%x);
+        });
+        compile_and_run(
+            "#include <assert.h>
+        extern int apply(int (*)(int), int);
+        extern int apply_triple(int);
+        int square(int x) { return x * x; }
+        int main() {
+            assert(apply(square, 7) == 49);
+            assert(apply_triple(-4) == -12);
+        }",
+            &mut m,
+        );
+    }
+
+    #[test]
+    fn asm_trap() {
+        let mut m = Module::new("cilk");
+        let trap = m.create_function("cilk.trap", types::Type::Void, vec![]);
+        cilk_ir!(m; define [i32] checked_div [(i32), (i32)] {
+            entry:
+                c = icmp eq (%arg.1), (i32 0);
+                br (%c) fail, ok;
+            fail:
+                __ = call (->trap) [];
+                unreachable;
+            ok:
+                x = div (%arg.0), (%arg.1);
+                ret (%x);
+        });
+        compile_and_run(
+            "#include <assert.h>
+        #include <signal.h>
+        #include <sys/wait.h>
+        #include <unistd.h>
+        extern int checked_div(int, int);
+        int main() {
+            int status;
+            assert(checked_div(7, 2) == 3);
+            if (fork() == 0) {
+                checked_div(7, 0);
+                return 0;
+            }
+            wait(&status);
+            assert(WIFSIGNALED(status) && WTERMSIG(status) == SIGILL);
+        }",
+            &mut m,
+        );
+    }
+
     #[test]
     fn asm_load_store_i8() {
         let mut m = Module::new("cilk");
@@ -0,0 +1,110 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{global_val, parser, types, value},
+        *,
+    };
+
+    #[test]
+    fn jit_initialized_global_vars() {
+        let mut m = module::Module::new("cilk");
+        let tbl_ty = m.types.new_array_ty(types::Type::i32, 4);
+        let tbl = m.global_vars.new_global_var_with_init(
+            tbl_ty,
+            global_val::Linkage::Common,
+            "tbl",
+            global_val::Initializer::Array(vec![
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(10)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(20)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(30)),
+            ]),
+            true,
+        );
+        let tbl = value::Value::Global(value::GlobalValue {
+            id: tbl,
+            ty: m.types.new_pointer_ty(tbl_ty),
+        });
+        let cnt = m.global_vars.new_global_var_with_init(
+            types::Type::i32,
+            global_val::Linkage::Common,
+            "cnt",
+            global_val::Initializer::Immediate(value::ImmediateValue::Int32(100)),
+            false,
+        );
+        let cnt = value::Value::Global(value::GlobalValue {
+            id: cnt,
+            ty: m.types.new_pointer_ty(types::Type::i32),
+        });
+        let st_ty =
+            m.types
+                .new_struct_ty(vec![types::Type::i8, types::Type::i32, types::Type::i64]);
+        let st = m.global_vars.new_global_var_with_init(
+            st_ty,
+            global_val::Linkage::Common,
+            "st",
+            global_val::Initializer::Struct(vec![
+                global_val::Initializer::Immediate(value::ImmediateValue::Int8(1)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int32(2000)),
+                global_val::Initializer::Immediate(value::ImmediateValue::Int64(3)),
+            ]),
+            true,
+        );
+        let st = value::Value::Global(value::GlobalValue {
+            id: st,
+            ty: m.types.new_pointer_ty(st_ty),
+        });
+
+        cilk_ir!(m; define [i32] func [(i32)] {
+        entry:
+            p = gep (%tbl), [(i32 0), (%arg.0)];
+            x = load (%p);
+            c = load (%cnt);
+            c = add (%c), (i32 1);
+            store (%c), (%cnt);
+            q = gep (%st), [(i32 0), (i32 1)];
+            y = load (%q);
+            x = add (%x), (%y);
+            x = add (%x), (%c);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for (i, &x) in [10, 20, 30, 0].iter().enumerate() {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(i as i32)]),
+                exec::jit::GenericValue::Int32(x + 2000 + 101 + i as i32)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_initialized_global_vars() {
+        let text = "Module (name: cilk)
+@tbl = common constant [3 x i32] [1, -2]
+@pt = common global struct {i8, f64} {3, 1.5}
+@z = external global [2 x [2 x i16]] zeroinitializer
+@g = common global i32
+
+";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(format!("{:?}", m), text);
+
+        let tbl = m.global_vars.arena.iter().next().unwrap().1;
+        assert!(tbl.is_constant);
+        assert_eq!(
+            tbl.init_bytes(&m.types),
+            vec![1, 0, 0, 0, 254, 255, 255, 255, 0, 0, 0, 0]
+        );
+
+        for bad in &[
+            "@c = common constant i32\n",
+            "@a = common global [1 x i32] [1, 2]\n",
+            "@s = common global struct {i32} {1, 2}\n",
+            "@f = common global i32 1.5\n",
+        ] {
+            assert!(parser::parse_module(bad).is_err(), "{}", bad);
+        }
+    }
+}
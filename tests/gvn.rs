@@ -0,0 +1,124 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{gvn, parser, verify},
+        *,
+    };
+
+    fn run_gvn(src: &str) -> module::Module {
+        let mut m = parser::parse_module(src).unwrap();
+        gvn::GlobalValueNumbering::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        m
+    }
+
+    #[test]
+    fn gvn_full_redundancy() {
+        let mut m = run_gvn(
+            "define i32 main(i32 %a, i32 %b) {
+entry:
+    %s = add i32 %a, i32 %b
+    %c = icmp lt, i32 %a, i32 %b
+    br i1 %c, %then, %else
+then:
+    %s2 = add i32 %b, i32 %a
+    %x = mul i32 %a, i32 3
+    %u = add i32 %a, i32 1
+    br %merge
+else:
+    %y = mul i32 3, i32 %a
+    %v = add i32 %b, i32 1
+    br %merge
+merge:
+    %p = phi i32 %a, %then, i32 %b, %else
+    %z = mul i32 %a, i32 3
+    %w = add i32 %p, i32 1
+    %r1 = add i32 %z, i32 %w
+    %r = add i32 %r1, i32 %s2
+    ret i32 %r
+}",
+        );
+        let printed = format!("{:?}", m);
+        for name in &["%s2 =", "%z =", "%w ="] {
+            assert!(!printed.contains(name));
+        }
+        assert!(printed.contains("phi i32 %x, %then, i32 %y, %else"));
+        assert!(printed.contains("phi i32 %u, %then, i32 %v, %else"));
+        assert!(printed.contains("%r = add i32 %r1, i32 %s"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(
+                func,
+                vec![
+                    exec::jit::GenericValue::Int32(2),
+                    exec::jit::GenericValue::Int32(5)
+                ]
+            ),
+            exec::jit::GenericValue::Int32(16)
+        );
+        assert_eq!(
+            jit.run(
+                func,
+                vec![
+                    exec::jit::GenericValue::Int32(5),
+                    exec::jit::GenericValue::Int32(2)
+                ]
+            ),
+            exec::jit::GenericValue::Int32(25)
+        );
+    }
+
+    #[test]
+    fn gvn_partial_redundancy() {
+        let mut m = run_gvn(
+            "define i32 main(i32 %a, i32 %b) {
+entry:
+    %c = icmp lt, i32 %a, i32 %b
+    br i1 %c, %then, %merge
+then:
+    %x = mul i32 %a, i32 %b
+    br %merge
+merge:
+    %z = mul i32 %b, i32 %a
+    ret i32 %z
+}
+
+define i32 div(i32 %a, i32 %b) {
+entry:
+    %c = icmp ne, i32 %b, i32 0
+    br i1 %c, %then, %merge
+then:
+    %x = div i32 %a, i32 %b
+    br %merge
+merge:
+    %q = div i32 %a, i32 %b
+    ret i32 %q
+}",
+        );
+        let printed = format!("{:?}", m);
+        // The computation is inserted on the critical edge from `entry` to `merge`
+        assert!(!printed.contains("%z ="));
+        assert_eq!(printed.matches("mul i32 %b, i32 %a").count(), 1);
+        assert!(printed.contains("phi i32 %x, %then, i32 %"));
+        // A division is never moved onto a path that didn't divide
+        assert!(printed.contains("%q = div i32 %a, i32 %b"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for &(a, b) in &[(2, 3), (3, 2)] {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(6)
+            );
+        }
+    }
+}
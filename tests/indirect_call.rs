@@ -0,0 +1,203 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::builder::FuncRef,
+        ir::{builder, global_val, parser, types, value, verify},
+        *,
+    };
+
+    fn module_with_callees() -> module::Module {
+        let mut m = module::Module::new("cilk");
+        cilk_ir!(m; define [i32] double [(i32)] {
+        entry:
+            x = mul (%arg.0), (i32 2);
+            ret (%x);
+        });
+        cilk_ir!(m; define [i32] inc [(i32)] {
+        entry:
+            x = add (%arg.0), (i32 1);
+            ret (%x);
+        });
+        cilk_ir!(m; define [i32] neg [(i32)] {
+        entry:
+            x = sub (i32 0), (%arg.0);
+            ret (%x);
+        });
+        m
+    }
+
+    #[test]
+    fn call_through_argument() {
+        let mut m = module_with_callees();
+
+        cilk_ir!(m; define [i32] sum5 [(i32), (i32), (i32), (i32), (i32)] {
+        entry:
+            x = add (%arg.0), (%arg.1);
+            x = add (%x), (%arg.2);
+            x = add (%x), (%arg.3);
+            x = add (%x), (%arg.4);
+            ret (%x);
+        });
+        cilk_ir!(m; define [i32] apply [(ptr fn [i32] [(i32)]), (i32)] {
+        entry:
+            x = call (%arg.0) [(%arg.1)];
+            ret (%x);
+        });
+        cilk_ir!(m; define [i32] apply5 [(ptr fn [i32] [(i32), (i32), (i32), (i32), (i32)]), (i32)] {
+        entry:
+            x = call (%arg.0) [(%arg.1), (i32 1), (%arg.1), (i32 2), (%arg.1)];
+            y = call (%arg.0) [(%x), (%x), (i32 0), (i32 0), (i32 0)];
+            ret (%y);
+        });
+        cilk_ir!(m; define [i32] main [(i32)] {
+        entry:
+            a = call apply [(addr double), (%arg.0)];
+            b = call apply [(addr inc), (%a)];
+            c = call apply5 [(addr sum5), (%b)];
+            ret (%c);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for &x in &[0, 1, 7, -5] {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(x)]),
+                exec::jit::GenericValue::Int32(((x * 2 + 1) * 3 + 3) * 2)
+            );
+        }
+    }
+
+    #[test]
+    fn call_through_local_table() {
+        let mut m = module_with_callees();
+
+        cilk_ir!(m; define [i32] main [(i32), (i32)] {
+        entry:
+            table = alloca_ ([3; ptr fn [i32] [(i32)]]);
+            p = gep (%table), [(i32 0), (i32 0)];
+            store (addr double), (%p);
+            p = gep (%table), [(i32 0), (i32 1)];
+            store (addr inc), (%p);
+            p = gep (%table), [(i32 0), (i32 2)];
+            store (addr neg), (%p);
+            p = gep (%table), [(i32 0), (%arg.0)];
+            f = load (%p);
+            x = call (%f) [(%arg.1)];
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for (i, &expected) in [20, 11, -10].iter().enumerate() {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(i as i32),
+                        exec::jit::GenericValue::Int32(10)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn call_through_global_table() {
+        let mut m = module_with_callees();
+
+        let f_ty = m
+            .types
+            .new_function_ty(types::Type::i32, vec![types::Type::i32]);
+        let f_ptr_ty = m.types.new_pointer_ty(f_ty);
+        let tbl_ty = m.types.new_array_ty(f_ptr_ty, 2);
+        let tbl =
+            m.global_vars
+                .new_global_var_with_name(tbl_ty, global_val::Linkage::Common, "vtable");
+        let tbl = value::Value::Global(value::GlobalValue {
+            id: tbl,
+            ty: m.types.new_pointer_ty(tbl_ty),
+        });
+        let handler = m.global_vars.new_global_var_with_name(
+            f_ptr_ty,
+            global_val::Linkage::Common,
+            "handler",
+        );
+        let handler = value::Value::Global(value::GlobalValue {
+            id: handler,
+            ty: m.types.new_pointer_ty(f_ptr_ty),
+        });
+
+        cilk_ir!(m; define [void] init [] {
+        entry:
+            p = gep (%tbl), [(i32 0), (i32 0)];
+            store (addr double), (%p);
+            p = gep (%tbl), [(i32 0), (i32 1)];
+            store (addr neg), (%p);
+            store (addr inc), (%handler);
+            ret (void);
+        });
+        cilk_ir!(m; define [i32] main [(i32), (i32)] {
+        entry:
+            i = call init [];
+            p = gep (%tbl), [(i32 0), (%arg.0)];
+            f = load (%p);
+            x = call (%f) [(%arg.1)];
+            h = load (%handler);
+            x = call (%h) [(%x)];
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for &(i, x, expected) in &[(0, 4, 9), (1, 4, -3), (0, -1, -1)] {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(i),
+                        exec::jit::GenericValue::Int32(x)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_function_pointers() {
+        let text = "define i32 double(i32) {
+label.0:
+    %0 = mul i32 %arg.0, i32 2
+    ret i32 %0
+}
+define i32 main(i32(i32)*, i32) {
+label.0:
+    %0 = alloca i32(i32)*
+    store i32(i32)* double, i32(i32)** %0
+    %1 = load i32(i32)** %0
+    %2 = call i32(i32)* %1, i32 %arg.1
+    %3 = call i32(i32)* %arg.0, i32 %2
+    %4 = call i32 double, i32 %3
+    ret i32 %4
+}";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("store i32 (i32)* double, i32 (i32)** %0"));
+        assert_eq!(
+            printed,
+            format!("{:?}", parser::parse_module(&printed).unwrap())
+        );
+
+        let text = "define i32 main(i32*) {
+label.0:
+    %0 = call i32* %arg.0
+    ret i32 %0
+}";
+        assert!(parser::parse_module(text).is_err());
+    }
+}
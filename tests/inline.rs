@@ -0,0 +1,105 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{inline, mem2reg, parser, verify},
+        *,
+    };
+
+    const TEXT: &str = "@pt = common global struct {i32, i32} {3, 4}
+define i32 get_y(struct {i32, i32}* %p) {
+entry:
+    %a = getelementptr struct {i32, i32}* %p, i32 0, i32 1
+    %y = load i32* %a
+    ret i32 %y
+}
+define i32 abs(i32 %x) {
+entry:
+    %t = alloca i32
+    store i32 %x, i32* %t
+    %neg = icmp lt, i32 %x, i32 0
+    br i1 %neg, %minus, %plus
+minus:
+    %v = load i32* %t
+    %m = sub i32 0, i32 %v
+    ret i32 %m
+plus:
+    ret i32 %x
+}
+define i32 fact(i32 %n) {
+entry:
+    %z = icmp le, i32 %n, i32 1
+    br i1 %z, %base, %rec
+base:
+    ret i32 1
+rec:
+    %n1 = sub i32 %n, i32 1
+    %r = call i32 fact, i32 %n1
+    %p = mul i32 %r, i32 %n
+    ret i32 %p
+}
+define i32 main(i32 %x) {
+entry:
+    %y = call i32 get_y, struct {i32, i32}* @pt
+    %a = call i32 abs, i32 %x
+    %f = call i32 fact, i32 4
+    %s = add i32 %y, i32 %a
+    %s2 = add i32 %s, i32 %f
+    ret i32 %s2
+}";
+
+    fn run(m: &mut module::Module, x: i32) -> exec::jit::GenericValue {
+        let mut jit = exec::jit::JITExecutor::new(m);
+        let func = jit.find_function_by_name("main").unwrap();
+        jit.run(func, vec![exec::jit::GenericValue::Int32(x)])
+    }
+
+    fn function_text(m: &module::Module, name: &str) -> String {
+        let printed = format!("{:?}", m);
+        let start = printed.find(&format!("define i32 {}(", name)).unwrap();
+        let len = printed[start..].find("\n}").unwrap();
+        printed[start..start + len].to_string()
+    }
+
+    #[test]
+    fn inline_calls() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        let fact = function_text(&m, "fact");
+        inline::Inliner::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let main = function_text(&m, "main");
+        assert!(!main.contains("call i32 get_y"));
+        assert!(!main.contains("call i32 abs"));
+        // The recursive call stays in the inlined copy and in `fact` itself
+        assert!(main.contains("call i32 fact, i32 %n1"));
+        assert_eq!(function_text(&m, "fact"), fact);
+        // Returns from two blocks merge in a phi
+        assert!(main.contains("= phi i32 %m, %minus, i32 %x, %plus"));
+        // The alloca of `abs` moved to the entry block
+        assert!(main.contains("entry:\t// pred(), succ(entry.1),"));
+        assert!(main.contains("    %t = alloca i32"));
+        assert_eq!(run(&mut m, -5), exec::jit::GenericValue::Int32(33));
+
+        mem2reg::Mem2Reg::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        assert!(!function_text(&m, "main").contains("alloca"));
+        assert_eq!(run(&mut m, 7), exec::jit::GenericValue::Int32(35));
+    }
+
+    #[test]
+    fn inline_threshold() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        let before = format!("{:?}", m);
+        inline::Inliner::with_threshold(-10).run_on_module(&mut m);
+        assert_eq!(format!("{:?}", m), before);
+
+        let m = parser::parse_module(TEXT).unwrap();
+        let abs = m.find_function("abs").unwrap();
+        let args = [value::Value::new_imm_int32(1)];
+        // 8 instructions, minus the call and a constant argument
+        assert_eq!(inline::inline_cost(m.function_ref(abs), &args), Some(-1));
+        let fact = m.find_function("fact").unwrap();
+        assert_eq!(inline::inline_cost(m.function_ref(fact), &[]), Some(7 - 6));
+    }
+}
@@ -0,0 +1,269 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{global_val, linker::LinkError, parser, verify},
+        *,
+    };
+
+    const RUNTIME: &str = "@counter = common global i32 5
+define i32 bump(i32) {
+label.0:
+    %0 = load i32* @counter
+    %1 = add i32 %0, i32 %arg.0
+    store i32 %1, i32* @counter
+    ret i32 %1
+}
+define i32 apply(i32 (i32)*, i32) {
+label.0:
+    %0 = call i32 (i32)* %arg.0, i32 %arg.1
+    ret i32 %0
+}";
+
+    // Mentions some types before the shared ones so that their ids differ from the runtime's
+    const USER: &str = "@table = common global [4 x i64]
+@counter = external global i32
+declare i32 bump(i32)
+declare i32 apply(i32 (i32)*, i32)
+define i32 triple(i32) {
+label.0:
+    %0 = mul i32 %arg.0, i32 3
+    ret i32 %0
+}
+define i32 main(i32) {
+label.0:
+    %0 = call i32 bump, i32 %arg.0
+    %1 = call i32 apply, i32 (i32)* triple, i32 %0
+    %2 = call i32 apply, i32 (i32)* bump, i32 %1
+    %3 = load i32* @counter
+    %4 = add i32 %2, i32 %3
+    ret i32 %4
+}";
+
+    fn run_main(mut m: module::Module, arg: i32) -> exec::jit::GenericValue {
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        jit.run(func, vec![exec::jit::GenericValue::Int32(arg)])
+    }
+
+    fn expected(arg: i32) -> i32 {
+        let counter = 5 + arg;
+        let counter = counter + counter * 3;
+        counter * 2
+    }
+
+    #[test]
+    fn link_declarations_to_definitions() {
+        // Declarations in the destination are replaced by definitions from the source
+        let mut m = parser::parse_module(USER).unwrap();
+        m.link_in(parser::parse_module(RUNTIME).unwrap()).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        assert_eq!(m.functions.len(), 4);
+        assert_eq!(m.global_vars.arena.len(), 2);
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("@counter = common global i32 5"));
+        assert!(!printed.contains("declare"));
+        assert_eq!(
+            printed,
+            format!("{:?}", parser::parse_module(&printed).unwrap())
+        );
+        assert_eq!(run_main(m, 2), exec::jit::GenericValue::Int32(expected(2)));
+
+        // Declarations in the source are resolved to definitions in the destination
+        let mut m = parser::parse_module(RUNTIME).unwrap();
+        m.link_in(parser::parse_module(USER).unwrap()).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        assert_eq!(m.functions.len(), 4);
+        assert_eq!(m.global_vars.arena.len(), 2);
+        assert_eq!(
+            run_main(m, -3),
+            exec::jit::GenericValue::Int32(expected(-3))
+        );
+    }
+
+    #[test]
+    fn link_common_global_vars() {
+        let mut m =
+            parser::parse_module("@a = common global i32\n@b = common global i64 7\n").unwrap();
+        m.link_in(
+            parser::parse_module("@a = common global i32 1\n@b = common global i64\n").unwrap(),
+        )
+        .unwrap();
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("@a = common global i32 1\n"));
+        assert!(printed.contains("@b = common global i64 7\n"));
+        assert_eq!(m.global_vars.arena.len(), 2);
+    }
+
+    #[test]
+    fn link_resolved_addresses() {
+        // Addresses resolved in the source refer to its own global variables
+        let mut src = parser::parse_module(
+            "@tbl = common global [2 x i32] [1, 2]
+@p = common global i32* getelementptr ([2 x i32]* @tbl, i32 0, i32 1)
+@fp = common global i32 (i32)* f
+declare i32 f(i32)",
+        )
+        .unwrap();
+        let mut global_vars = src.global_vars.clone();
+        global_vars.resolve_addresses(&src);
+        src.global_vars = global_vars;
+
+        let mut m =
+            parser::parse_module("@a = common global i64\n@b = common global i64\n").unwrap();
+        m.link_in(src).unwrap();
+        let find = |name: &str| {
+            m.global_vars
+                .arena
+                .iter()
+                .find(|(_, g)| g.name == name)
+                .unwrap()
+        };
+        let (tbl, _) = find("tbl");
+        assert_eq!(
+            find("p").1.init,
+            Some(global_val::Initializer::Address(
+                global_val::Symbol::Global(tbl),
+                4
+            ))
+        );
+        assert_eq!(
+            find("fp").1.init,
+            Some(global_val::Initializer::Address(
+                global_val::Symbol::Function("f".to_string()),
+                0
+            ))
+        );
+    }
+
+    #[test]
+    fn link_errors() {
+        let check = |dst: &str, src: &str, err: LinkError, msg: &str| {
+            let mut m = parser::parse_module(dst).unwrap();
+            let before = format!("{:?}", m);
+            let types = m.types.base.borrow().non_primitive_types.len();
+            let e = m.link_in(parser::parse_module(src).unwrap()).unwrap_err();
+            assert_eq!(e, err);
+            assert_eq!(e.to_string(), msg);
+            // Nothing is moved if linking fails
+            assert_eq!(format!("{:?}", m), before);
+            assert_eq!(m.types.base.borrow().non_primitive_types.len(), types);
+        };
+
+        check(
+            RUNTIME,
+            RUNTIME,
+            LinkError::DuplicateSymbol("counter".to_string()),
+            "duplicate symbol 'counter'",
+        );
+        check(
+            "define i32 f() {\nlabel.0:\n    ret i32 0\n}",
+            "define i32 f() {\nlabel.0:\n    ret i32 1\n}",
+            LinkError::DuplicateSymbol("f".to_string()),
+            "duplicate symbol 'f'",
+        );
+        check(
+            RUNTIME,
+            "declare i64 bump(i32)",
+            LinkError::SignatureMismatch {
+                name: "bump".to_string(),
+                expected: "i32 (i32)".to_string(),
+                found: "i64 (i32)".to_string(),
+            },
+            "mismatched types for 'bump': i32 (i32) and i64 (i32)",
+        );
+        check(
+            RUNTIME,
+            "@counter = external global [2 x i32]",
+            LinkError::SignatureMismatch {
+                name: "counter".to_string(),
+                expected: "i32".to_string(),
+                found: "[2 x i32]".to_string(),
+            },
+            "mismatched types for 'counter': i32 and [2 x i32]",
+        );
+        // Comparing the types imports `struct Foo` and its pointer before the mismatch is found
+        check(
+            "declare i32 get(i32)",
+            "struct Foo = {i64, [3 x i8]}
+define i32 get(struct Foo*) {
+label.0:
+    ret i32 0
+}",
+            LinkError::SignatureMismatch {
+                name: "get".to_string(),
+                expected: "i32 (i32)".to_string(),
+                found: "i32 (struct Foo*)".to_string(),
+            },
+            "mismatched types for 'get': i32 (i32) and i32 (struct Foo*)",
+        );
+        // Named structs are matched by name, so their bodies have to agree
+        check(
+            "struct S = {i32}
+declare i32 get(struct S*)",
+            "struct S = {i64, i64}
+define i32 get(struct S*) {
+label.0:
+    ret i32 0
+}",
+            LinkError::StructMismatch {
+                name: "S".to_string(),
+                expected: "{i32}".to_string(),
+                found: "{i64, i64}".to_string(),
+            },
+            "mismatched bodies for 'struct S': {i32} and {i64, i64}",
+        );
+        check(
+            "struct Node = {i32, struct Node*}
+struct List = {struct Node*}
+@list = external global struct List",
+            "struct Node = {i64, struct Node*}
+struct List = {struct Node*}
+@other = common global struct List",
+            LinkError::StructMismatch {
+                name: "Node".to_string(),
+                expected: "{i32, struct Node*}".to_string(),
+                found: "{i64, struct Node*}".to_string(),
+            },
+            "mismatched bodies for 'struct Node': {i32, struct Node*} and {i64, struct Node*}",
+        );
+    }
+
+    #[test]
+    fn link_named_structs() {
+        // Opaque structs take the body from the other module, in either direction
+        let mut m = parser::parse_module(
+            "struct Node = {i32, struct Node*}
+struct Handle = opaque
+declare i32 len(struct Node*, struct Handle*)",
+        )
+        .unwrap();
+        m.link_in(
+            parser::parse_module(
+                "struct Node = opaque
+struct Handle = {i64}
+declare i32 len(struct Node*, struct Handle*)",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        m.link_in(
+            parser::parse_module(
+                "struct Node = {i32, struct Node*}
+define i32 first(struct Node*) {
+label.0:
+    %0 = getelementptr struct Node* %arg.0, i32 0, i32 0
+    %1 = load i32* %0
+    ret i32 %1
+}",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        assert_eq!(
+            m.types.base.borrow().named_structs_to_string(),
+            "struct Node = {i32, struct Node*}\nstruct Handle = {i64}\n"
+        );
+    }
+}
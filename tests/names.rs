@@ -0,0 +1,113 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::{
+            asm::print::MachineAsmPrinter, exec, standard_conversion_into_machine_module,
+        },
+        ir::{builder, opcode, parser, types, value, verify},
+        *,
+    };
+
+    fn sum_module() -> module::Module {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function("sum", types::Type::i32, vec![types::Type::i32]);
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block_named("entry");
+        let loop_ = builder.append_basic_block_named("loop");
+        let body = builder.append_basic_block_named("body");
+        let done = builder.append_basic_block_named("done");
+        let n = builder.get_param(0).unwrap();
+        builder.set_name(n, "n");
+
+        builder.set_insert_point(entry);
+        let acc = builder.build_alloca_named(types::Type::i32, "acc");
+        let i = builder.build_alloca_named(types::Type::i32, "i");
+        builder.build_store(value::Value::new_imm_int32(0), acc);
+        builder.build_store(value::Value::new_imm_int32(0), i);
+        builder.build_br(loop_);
+
+        builder.set_insert_point(loop_);
+        let i_val = builder.build_load_named(i, "i");
+        let cond = builder.build_icmp_named(opcode::ICmpKind::Lt, i_val, n, "0cond");
+        builder.build_cond_br(cond, body, done);
+
+        builder.set_insert_point(body);
+        let acc_val = builder.build_load(acc);
+        builder.set_name(acc_val, "acc val");
+        let acc_new = builder.build_add_named(acc_val, i_val, "acc.new");
+        builder.build_store(acc_new, acc);
+        let i_new = builder.build_add_named(i_val, value::Value::new_imm_int32(1), "i");
+        builder.build_store(i_new, i);
+        builder.build_br(loop_);
+
+        builder.set_insert_point(done);
+        let result = builder.build_load_named(acc, "result");
+        builder.build_ret(result);
+        m
+    }
+
+    #[test]
+    fn names_in_ir() {
+        let m = sum_module();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("define i32 sum(i32 %n)"));
+        assert!(printed.contains("entry:\t// pred(), succ(loop)"));
+        assert!(printed.contains("%i = alloca i32"));
+        // Names are unique in the function and are made valid for the parser
+        assert!(printed.contains("%i.1 = load i32* %i"));
+        assert!(printed.contains("%_0cond = icmp lt, i32 %i.1, i32 %n"));
+        assert!(printed.contains("br i1 %_0cond, %body, %done"));
+        assert!(printed.contains("%acc_val = load i32* %acc"));
+        assert!(printed.contains("%acc.new = add i32 %acc_val, i32 %i.1"));
+        assert!(printed.contains("%i.2 = add i32 %i.1, i32 1"));
+        assert!(printed.contains("br %loop"));
+
+        // Printed names are read back
+        let m2 = parser::parse_module(&printed).unwrap();
+        assert_eq!(format!("{:?}", m2), printed);
+        let mut buf = vec![];
+        m.write_bitcode(&mut buf).unwrap();
+        let mut m3 = module::Module::read_bitcode(buf.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", m3), printed);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m3);
+        let func = jit.find_function_by_name("sum").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(5)]),
+            exec::jit::GenericValue::Int32(10)
+        );
+    }
+
+    #[test]
+    fn names_parse_errors() {
+        let e = parser::parse_module(
+            "define i32 f(i32 %x) {
+entry:
+    %x = add i32 %x, i32 1
+    ret i32 %x
+}",
+        )
+        .unwrap_err();
+        assert_eq!(e.to_string(), "line 3: %x is defined twice");
+
+        let e = parser::parse_module(
+            "define void f() {
+entry:
+    br %exit
+}",
+        )
+        .unwrap_err();
+        assert_eq!(e.to_string(), "line 3: %exit is not defined");
+    }
+
+    #[test]
+    fn names_in_asm() {
+        let mut m = sum_module();
+        let machine_module = standard_conversion_into_machine_module(&mut m);
+        let mut printer = MachineAsmPrinter::new();
+        printer.run_on_module(&machine_module);
+        assert!(printer.output.contains(": # loop\n"));
+        assert!(printer.output.contains(": # done\n"));
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::builder::FuncRef,
+        ir::{builder, parser, types, value},
+        *,
+    };
+
+    #[test]
+    fn parse_roundtrip() {
+        let mut m = module::Module::new("cilk");
+
+        let ty = m.types.new_array_ty(types::Type::i32, 8);
+        let g = m
+            .global_vars
+            .new_global_var_with_name(ty, global_val::Linkage::Common, "g");
+        let g = value::Value::Global(value::GlobalValue {
+            id: g,
+            ty: m.types.new_pointer_ty(ty),
+        });
+
+        let cilk_println_i32 = m.create_function(
+            "cilk.println.i32",
+            types::Type::Void,
+            vec![types::Type::i32],
+        );
+
+        cilk_ir!(m; declare [i32] printf [(ptr i8), ...]);
+
+        cilk_ir!(m; define [i32] func [(i32)] {
+            entry:
+                cond = icmp le (%arg.0), (i32 2);
+                br (%cond) l1, l2;
+            l1:
+                br merge;
+            l2:
+                a1 = sub (%arg.0), (i32 1);
+                r1 = call func [(%a1)];
+                a2 = sub (%arg.0), (i32 2);
+                r2 = call func [(%a2)];
+                r3 = add (%r1), (%r2);
+                br merge;
+            merge:
+                p = phi [ [(i32 1), l1], [(%r3), l2] ];
+                ret (%p);
+        });
+
+        cilk_ir!(m; define [void] main [(i32)] {
+            entry:
+                x = alloca_ ([8; i32]);
+                x = gep (%x), [(i32 0), (i32 1)];
+                store (i32 1), (%x);
+                p = gep (%g), [(i32 0), (%arg.0)];
+                store (i32 123), (%p);
+                i = load (%p);
+                e = sext [i64] (%i);
+                __ = call (->cilk_println_i32) [(%arg.0)];
+                ret (void);
+        });
+
+        let text = format!("{:?}", m);
+        assert!(text.contains("declare i32 printf(i8*, ...)"));
+        let parsed = parser::parse_module(&text).unwrap();
+        assert_eq!(text, format!("{:?}", parsed));
+    }
+
+    #[test]
+    fn parse_roundtrip_after_pass() {
+        let mut m = module::Module::new("cilk");
+
+        let struct_ty = m
+            .types
+            .new_struct_ty(vec![types::Type::i32, types::Type::f64]);
+        let f = m.create_function("f", types::Type::f64, vec![struct_ty]);
+        {
+            let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+            let entry = builder.append_basic_block();
+            builder.set_insert_point(entry);
+            cilk_ir!((builder) {
+                x = gep (%arg.0), [(i32 0), (i32 1)];
+                x = load (%x);
+                ret (%x);
+            });
+        }
+
+        cilk_ir!(m; define [i32] main [] {
+            entry:
+                i = alloca i32;
+                store (i32 0), (%i);
+                br cond;
+            cond:
+                li = load (%i);
+                c = icmp lt (%li), (i32 10);
+                br (%c) body, end;
+            body:
+                inc = add (%li), (i32 1);
+                store (%inc), (%i);
+                br cond;
+            end:
+                ret (%li);
+        });
+
+        ir::mem2reg::Mem2Reg::new().run_on_module(&mut m);
+
+        let text = format!("{:?}", m);
+        let mut parsed = parser::parse_module(&text).unwrap();
+        assert_eq!(text, format!("{:?}", parsed));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut parsed);
+        let main = jit.find_function_by_name("main").unwrap();
+        assert_eq!(jit.run(main, vec![]), exec::jit::GenericValue::Int32(10));
+    }
+
+    #[test]
+    fn parse_and_run() {
+        let mut m = parser::parse_module(
+            "
+define i32 fibo(i32) {
+label.0:
+    %0 = icmp le, i32 %arg.0, i32 2
+    br i1 %0, %label.1, %label.2
+label.1:
+    ret i32 1
+label.2:
+    %3 = sub i32 %arg.0, i32 1
+    %4 = call i32 fibo, i32 %3
+    %5 = sub i32 %arg.0, i32 2
+    %6 = call i32 fibo, i32 %5
+    %7 = add i32 %4, i32 %6
+    ret i32 %7
+}",
+        )
+        .unwrap();
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let fibo = jit.find_function_by_name("fibo").unwrap();
+        assert_eq!(
+            jit.run(fibo, vec![exec::jit::GenericValue::Int32(10)]),
+            exec::jit::GenericValue::Int32(55)
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = parser::parse_module(
+            "define i32 f() {
+label.0:
+    %0 = add i32 1, i32 %1
+    ret i32 %0
+}",
+        )
+        .unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.msg, "%1 is not defined");
+
+        let err = parser::parse_module("define i32 f() {\nlabel.0:\n    ret i7 0\n}").unwrap_err();
+        assert_eq!(err.line, 3);
+
+        let err = parser::parse_module("struct S = {i32}\nstruct S = {i64}").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.msg, "struct S is already defined");
+    }
+
+    #[test]
+    fn parse_error_invalid_operands() {
+        let err = |body: &str| {
+            parser::parse_module(&format!(
+                "struct S = {{i32, f64}}
+struct Handle = opaque
+define i32 f(i32 %i, struct S* %s, struct Handle* %h) {{
+entry:
+{}
+    ret i32 0
+}}",
+                body
+            ))
+            .unwrap_err()
+        };
+
+        let e = err("    %x add i32 1, i32 2");
+        assert_eq!((e.line, e.msg.as_str()), (5, "%x is not defined"));
+        let e = err("    br %label.x");
+        assert_eq!((e.line, e.msg.as_str()), (5, "unknown label %label.x"));
+        let e = err("    %p = getelementptr struct S* %s, i32 0, i32 %i");
+        assert_eq!(e.line, 5);
+        assert!(e.msg.starts_with("invalid getelementptr"));
+        let e = err("    %p = getelementptr struct S* %s, i32 0, i32 2");
+        assert!(e.msg.starts_with("invalid getelementptr"));
+        let e = err("    %p = getelementptr struct Handle* %h, i32 0, i32 0");
+        assert!(e.msg.starts_with("invalid getelementptr"));
+    }
+}
@@ -0,0 +1,243 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::builder::FuncRef,
+        ir::{builder, const_folding, global_val, parser, types, value, verify},
+        *,
+    };
+
+    #[test]
+    fn bitcast_malloc_to_struct() {
+        let mut m = module::Module::new("cilk");
+
+        let ptr_i64 = m.types.new_pointer_ty(types::Type::i64);
+        let malloc = m.create_function("cilk.malloc.i32", ptr_i64, vec![types::Type::i32]);
+
+        let struct_ty = m
+            .types
+            .new_struct_ty(vec![types::Type::i32, types::Type::i64]);
+        let ptr_struct_ty = m.types.new_pointer_ty(struct_ty);
+
+        let main = m.create_function(
+            "main",
+            types::Type::i64,
+            vec![types::Type::i32, types::Type::i64],
+        );
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, main));
+        let entry = builder.append_basic_block();
+        builder.set_insert_point(entry);
+
+        let malloc_ty = builder.func.module.function_ref(malloc).ty;
+        let mem = builder.build_call(
+            value::Value::Function(value::FunctionValue {
+                func_id: malloc,
+                ty: malloc_ty,
+            }),
+            vec![value::Value::new_imm_int32(16)],
+        );
+        let s = builder.build_bitcast(mem, ptr_struct_ty);
+        cilk_ir!((builder) {
+            p = gep (%s), [(i32 0), (i32 0)];
+            store (%arg.0), (%p);
+            p = gep (%s), [(i32 0), (i32 1)];
+            store (%arg.1), (%p);
+            p = gep (%s), [(i32 0), (i32 0)];
+            x = load (%p);
+            x = zext [i64] (%x);
+            p = gep (%s), [(i32 0), (i32 1)];
+            y = load (%p);
+            y = add (%x), (%y);
+            ret (%y);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(
+                func,
+                vec![
+                    exec::jit::GenericValue::Int32(7),
+                    exec::jit::GenericValue::Int64(10000000000)
+                ]
+            ),
+            exec::jit::GenericValue::Int64(10000000007)
+        );
+    }
+
+    #[test]
+    fn bitcast_to_narrower_elements() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] main [(i32)] {
+        entry:
+            x = alloca i32;
+            store (i32 0x01020304), (%x);
+            b = bitcast [ptr i16] (%x);
+            p = gep (%b), [(%arg.0)];
+            y = load (%p);
+            y = sext [i32] (%y);
+            ret (%y);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for (i, &expected) in [0x0304, 0x0102].iter().enumerate() {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(i as i32)]),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn pointer_arithmetic_in_integers() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] main [(i64)] {
+        entry:
+            arr = alloca_ ([4; i32]);
+            p = gep (%arr), [(i32 0), (i32 0)];
+            store (i32 10), (%p);
+            p = gep (%arr), [(i32 0), (i32 1)];
+            store (i32 11), (%p);
+            p = gep (%arr), [(i32 0), (i32 2)];
+            store (i32 12), (%p);
+            p = gep (%arr), [(i32 0), (i32 3)];
+            store (i32 13), (%p);
+            p = gep (%arr), [(i32 0), (i32 0)];
+            i = ptrtoint [i64] (%p);
+            off = mul (%arg.0), (i64 4);
+            i = add (%i), (%off);
+            q = inttoptr [ptr i32] (%i);
+            br next;
+        next:
+            x = load (%q);
+            ret (%x);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for i in 0..4 {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int64(i)]),
+                exec::jit::GenericValue::Int32(10 + i as i32)
+            );
+        }
+    }
+
+    #[test]
+    fn bitcast_const_folding() {
+        let mut m = module::Module::new("cilk");
+
+        let g_id = m.global_vars.new_global_var_with_name(
+            types::Type::i32,
+            global_val::Linkage::Common,
+            "g",
+        );
+        let ptr_i32 = m.types.new_pointer_ty(types::Type::i32);
+        let ptr_i16 = m.types.new_pointer_ty(types::Type::i16);
+        let g = value::Value::Global(value::GlobalValue {
+            id: g_id,
+            ty: ptr_i32,
+        });
+
+        let f = m.create_function("f", types::Type::i16, vec![]);
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        builder.set_insert_point(entry);
+
+        // Casting a global's address only retypes it
+        let b = builder.build_bitcast(g, ptr_i16);
+        assert_eq!(
+            b,
+            value::Value::Global(value::GlobalValue {
+                id: g_id,
+                ty: ptr_i16
+            })
+        );
+        assert_eq!(builder.build_bitcast(g, ptr_i32), g);
+        cilk_ir!((builder) {
+            store (i32 0x51234), (%g);
+            x = load (%b);
+            ret (%x);
+        });
+
+        let text = "@g = common global i32
+define i16 f() {
+label.0:
+    %0 = bitcast i32* @g to i16*
+    %1 = load i16* %0
+    ret i16 %1
+}";
+        let mut m2 = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m2), Ok(()));
+        const_folding::ConstantFolding::new().run_on_module(&mut m2);
+        assert!(!format!("{:?}", m2).contains("bitcast"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("f").unwrap();
+        assert_eq!(
+            jit.run(func, vec![]),
+            exec::jit::GenericValue::Int16(0x1234)
+        );
+    }
+
+    #[test]
+    fn parse_and_verify_pointer_casts() {
+        let text = "define i32 f(i32*, i64) {
+label.0:
+    %0 = bitcast i32* %arg.0 to i8*
+    %1 = ptrtoint i8* %0 to i64
+    %2 = add i64 %1, i64 %arg.1
+    %3 = inttoptr i64 %2 to i32*
+    %4 = load i32* %3
+    ret i32 %4
+}";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("%1 = ptrtoint i8* %0 to i64"));
+        assert_eq!(
+            printed,
+            format!("{:?}", parser::parse_module(&printed).unwrap())
+        );
+
+        for (cast, msg) in &[
+            (
+                "bitcast i32 %arg.0 to i8*",
+                "bitcast: cannot cast i32 to i8*",
+            ),
+            (
+                "ptrtoint i32* %arg.1 to i32",
+                "ptrtoint: cannot cast i32* to i32",
+            ),
+            (
+                "inttoptr i32 %arg.0 to i32*",
+                "inttoptr: cannot cast i32 to i32*",
+            ),
+        ] {
+            let text = format!(
+                "define void f(i32, i32*) {{
+label.0:
+    %0 = {}
+    ret void
+}}",
+                cast
+            );
+            let m = parser::parse_module(&text).unwrap();
+            let errors = verify::verify_module(&m)
+                .unwrap_err()
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(errors, vec![format!("f: label.0: %0: {}", msg)]);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{const_folding, mem2reg, parser, sccp, verify},
+        *,
+    };
+
+    // `x` stays 1, but only because the store of 2 can never run
+    const TEXT: &str = "define i32 main(i32 %n) {
+entry:
+    %x = alloca i32
+    %i = alloca i32
+    store i32 1, i32* %x
+    store i32 0, i32* %i
+    br %loop
+loop:
+    %iv = load i32* %i
+    %c = icmp lt, i32 %iv, i32 %n
+    br i1 %c, %body, %exit
+body:
+    %xv = load i32* %x
+    %one = icmp eq, i32 %xv, i32 1
+    br i1 %one, %same, %other
+same:
+    store i32 1, i32* %x
+    br %latch
+other:
+    store i32 2, i32* %x
+    br %latch
+latch:
+    %next = add i32 %iv, i32 1
+    store i32 %next, i32* %i
+    br %loop
+exit:
+    %r = load i32* %x
+    %s = mul i32 %r, i32 10
+    %t = add i32 %s, i32 %iv
+    ret i32 %t
+}";
+
+    fn run(m: &mut module::Module, n: i32) -> exec::jit::GenericValue {
+        let mut jit = exec::jit::JITExecutor::new(m);
+        let func = jit.find_function_by_name("main").unwrap();
+        jit.run(func, vec![exec::jit::GenericValue::Int32(n)])
+    }
+
+    #[test]
+    fn sccp_through_phis() {
+        let mut m = parser::parse_module(TEXT).unwrap();
+        mem2reg::Mem2Reg::new().run_on_module(&mut m);
+
+        // Plain constant folding can't see through the phis
+        let mut folded = m.clone();
+        const_folding::ConstantFolding::new().run_on_module(&mut folded);
+        assert!(format!("{:?}", folded).contains("other:"));
+
+        sccp::SparseConditionalConstantPropagation::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(!printed.contains("other"));
+        assert!(!printed.contains("icmp eq"));
+        assert!(printed.contains("body:\t// pred(loop), succ(same),"));
+        assert!(printed.contains("    br %same"));
+        assert!(printed.contains("%t = add i32 10, i32 %"));
+        assert_eq!(run(&mut m, 3), exec::jit::GenericValue::Int32(13));
+        assert_eq!(run(&mut m, 0), exec::jit::GenericValue::Int32(10));
+    }
+
+    #[test]
+    fn sccp_switch() {
+        let mut m = parser::parse_module(
+            "define i32 main(i32 %n) {
+entry:
+    %k = add i32 2, i32 1
+    switch i32 %k, %def, i32 1, %a, i32 3, %b
+a:
+    br %join
+b:
+    br %join
+def:
+    br %join
+join:
+    %p = phi i32 %n, %a, i32 5, %b, i32 %n, %def
+    %q = add i32 %p, i32 %n
+    ret i32 %q
+}",
+        )
+        .unwrap();
+        sccp::SparseConditionalConstantPropagation::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("entry:\t// pred(), succ(b),"));
+        assert!(!printed.contains("a:"));
+        assert!(!printed.contains("def:"));
+        assert!(printed.contains("%q = add i32 5, i32 %n"));
+        assert_eq!(run(&mut m, 4), exec::jit::GenericValue::Int32(9));
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{builder, opcode, parser, types, value, verify},
+        *,
+    };
+
+    #[test]
+    fn select_i32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32), (i32)] {
+        entry:
+            c = icmp lt (%arg.0), (%arg.1);
+            x = select (%c), (%arg.0), (%arg.1);
+            c = icmp eq (i32 7), (%arg.0);
+            y = select (%c), (i32 100), (%x);
+            c = icmp ge (%y), (i32 50);
+            z = select (%c), (%y), (i32 -1);
+            z = add (%z), (%x);
+            ret (%z);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(7i32, 9i32), (60, 80), (3, -5), (-100, 200)] {
+            let x = if a < b { a } else { b };
+            let y = if a == 7 { 100 } else { x };
+            let z = if y >= 50 { y } else { -1 };
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(z + x)
+            );
+        }
+    }
+
+    #[test]
+    fn select_i64_unsigned() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i64] func [(i64), (i64)] {
+        entry:
+            c = icmp ult (%arg.0), (%arg.1);
+            x = select (%c), (%arg.1), (%arg.0);
+            c = icmp ugt (%arg.0), (i64 1000);
+            y = select (%c), (i64 1099511627776), (i64 3);
+            x = add (%x), (%y);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(-1i64, 1i64), (5, 2000), (1001, 1001)] {
+            let (ua, ub) = (a as u64, b as u64);
+            let x = if ua < ub { ub } else { ua };
+            let y = if ua > 1000 { 1u64 << 40 } else { 3 };
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int64(a),
+                        exec::jit::GenericValue::Int64(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int64(x.wrapping_add(y) as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn select_f64() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [f64] func [(f64), (f64)] {
+        entry:
+            c = fcmp ult (%arg.0), (%arg.1);
+            x = select (%c), (%arg.0), (%arg.1);
+            c = fcmp ugt (%x), (f64 0.0);
+            y = select (%c), (%x), (f64 -0.5);
+            ret (%y);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(1.5f64, 2.5f64), (3.25, -1.0), (-2.0, -3.0), (8.0, 8.0)] {
+            let x = if a < b { a } else { b };
+            let y = if x > 0.0 { x } else { -0.5 };
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::F64(a),
+                        exec::jit::GenericValue::F64(b)
+                    ]
+                ),
+                exec::jit::GenericValue::F64(y)
+            );
+        }
+    }
+
+    #[test]
+    fn select_f32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [f32] func [(i32), (f32)] {
+        entry:
+            c = icmp le (%arg.0), (i32 0);
+            x = select (%c), (f32 0.25), (%arg.1);
+            x = mul (%x), (f32 2.0);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(0i32, 3.0f32), (1, 3.0), (-4, -1.5)] {
+            let x = if a <= 0 { 0.25 } else { b };
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::F32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::F32(x * 2.0)
+            );
+        }
+    }
+
+    #[test]
+    fn select_i8() {
+        let mut m = parser::parse_module(
+            "define i8 max(i8 %a, i8 %b) {
+entry:
+    %c = icmp gt, i8 %a, i8 %b
+    %x = select i1 %c, i8 %a, i8 %b
+    ret i8 %x
+}
+
+define i8 clamp(i8 %a) {
+entry:
+    %c = icmp lt, i8 %a, i8 0
+    %x = select i1 %c, i8 0, i8 %a
+    %d = icmp gt, i8 %x, i8 100
+    %y = select i1 %d, i8 100, i8 %x
+    ret i8 %y
+}",
+        )
+        .unwrap();
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let max = jit.find_function_by_name("max").unwrap();
+        let clamp = jit.find_function_by_name("clamp").unwrap();
+        let i8 = exec::jit::GenericValue::Int8;
+        for &(a, b) in &[(-128i8, 127i8), (5, -3), (-1, -1), (120, 90)] {
+            assert_eq!(jit.run(max, vec![i8(a), i8(b)]), i8(a.max(b)));
+            assert_eq!(jit.run(clamp, vec![i8(a)]), i8(a.max(0).min(100)));
+        }
+    }
+
+    #[test]
+    fn select_fold_and_parse() {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function("f", types::Type::i32, vec![types::Type::i32]);
+        {
+            let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+            let entry = builder.append_basic_block();
+            builder.set_insert_point(entry);
+
+            let arg = builder.get_param(0).unwrap();
+            let c = builder.build_icmp(opcode::ICmpKind::Ne, arg, value::Value::new_imm_int32(0));
+            let x = builder.build_select(c, arg, arg);
+            assert_eq!(x, arg);
+            let x = builder.build_select(
+                value::Value::new_imm_int32(0),
+                value::Value::new_imm_int32(1),
+                value::Value::new_imm_int32(2),
+            );
+            assert_eq!(x, value::Value::new_imm_int32(2));
+            let x = builder.build_select(c, arg, x);
+            assert!(matches!(x, value::Value::Instruction(_)));
+            builder.build_ret(x);
+        }
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let text = format!("{:?}", m);
+        assert!(text.contains("select"));
+        let parsed = parser::parse_module(&text).unwrap();
+        assert_eq!(text, format!("{:?}", parsed));
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{parser, simplify_cfg, verify},
+        *,
+    };
+
+    fn simplify(src: &str) -> module::Module {
+        let mut m = parser::parse_module(src).unwrap();
+        simplify_cfg::SimplifyCFG::new().run_on_module(&mut m);
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        m
+    }
+
+    #[test]
+    fn simplify_cfg_straight_line() {
+        let mut m = simplify(
+            "define i32 main(i32 %x) {
+entry:
+    %a = add i32 %x, i32 1
+    br %b
+b:
+    %p = phi i32 %a, %entry
+    %c = icmp lt, i32 %p, i32 10
+    br i1 %c, %fwd, %fwd
+fwd:
+    br %d
+d:
+    %m = mul i32 %p, i32 2
+    ret i32 %m
+dead:
+    br %d
+}",
+        );
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("entry:\t// pred(), succ(),"));
+        assert!(printed.contains("%m = mul i32 %a, i32 2"));
+        for name in &["b:", "fwd:", "d:", "dead", "phi", "br"] {
+            assert!(!printed.contains(name));
+        }
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(20)]),
+            exec::jit::GenericValue::Int32(42)
+        );
+    }
+
+    #[test]
+    fn simplify_cfg_forwarding_blocks() {
+        let mut m = simplify(
+            "define i32 main(i32 %x) {
+entry:
+    %c = icmp lt, i32 %x, i32 0
+    br i1 %c, %neg, %pos
+neg:
+    br %merge
+pos:
+    %y = add i32 %x, i32 1
+    %d = icmp lt, i32 %x, i32 10
+    br i1 %d, %merge, %other
+other:
+    br %merge
+merge:
+    %p = phi i32 0, %neg, i32 %y, %pos, i32 %y, %other
+    br %loop
+loop:
+    %q = phi i32 %p, %merge, i32 %q.next, %loop
+    %q.next = add i32 %q, i32 1
+    %e = icmp lt, i32 %q.next, i32 5
+    br i1 %e, %loop, %latch
+latch:
+    br %exit
+exit:
+    ret i32 %q.next
+}",
+        );
+        let printed = format!("{:?}", m);
+        // `pos` already jumps to `merge`, so `other` has to stay to keep the phi well-formed
+        assert!(printed.contains("br i1 %c, %merge, %pos"));
+        assert!(printed.contains("other:"));
+        assert!(!printed.contains("neg"));
+        assert!(printed.contains("%p = phi i32 %y, %pos, i32 %y, %other, i32 0, %entry"));
+        assert!(printed.contains("br i1 %e, %loop, %exit"));
+        assert!(!printed.contains("latch"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(-3)]),
+            exec::jit::GenericValue::Int32(5)
+        );
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(7)]),
+            exec::jit::GenericValue::Int32(9)
+        );
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(20)]),
+            exec::jit::GenericValue::Int32(22)
+        );
+    }
+}
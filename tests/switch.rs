@@ -0,0 +1,384 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        analysis::dom_tree::DominatorTreeConstructor,
+        codegen::x64::exec,
+        ir::{builder, opcode, parser, verify},
+        traits::pass::ModulePassManager,
+        *,
+    };
+
+    #[test]
+    fn switch_jump_table() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32)] {
+        entry:
+            switch (%arg.0), other, [ [(i32 0), l0], [(i32 1), l1], [(i32 2), l2],
+                                      [(i32 3), l1], [(i32 4), merge], [(i32 5), l5] ];
+        l0:
+            br merge;
+        l1:
+            x1 = add (%arg.0), (i32 10);
+            br merge;
+        l2:
+            br merge;
+        l5:
+            x5 = mul (%arg.0), (i32 3);
+            br merge;
+        other:
+            br merge;
+        merge:
+            p = phi [ [(i32 100), l0], [(%x1), l1], [(i32 200), l2], [(i32 400), entry],
+                      [(%x5), l5], [(i32 -1), other] ];
+            ret (%p);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for (x, expected) in vec![
+            (-3, -1),
+            (-1, -1),
+            (0, 100),
+            (1, 11),
+            (2, 200),
+            (3, 13),
+            (4, 400),
+            (5, 15),
+            (6, -1),
+            (1 << 30, -1),
+        ] {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(x)]),
+                exec::jit::GenericValue::Int32(expected),
+                "{}",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn switch_jump_table_i64() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i64] func [(i64)] {
+        entry:
+            switch (%arg.0), other, [ [(i64 -2), a], [(i64 -1), b], [(i64 1), a], [(i64 2), b] ];
+        a:
+            ret (i64 10);
+        b:
+            ret (i64 20);
+        other:
+            ret (%arg.0);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &x in &[-3i64, -2, -1, 0, 1, 2, 3, i64::MIN, i64::MAX] {
+            let expected = match x {
+                -2 | 1 => 10,
+                -1 | 2 => 20,
+                x => x,
+            };
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int64(x)]),
+                exec::jit::GenericValue::Int64(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn switch_compare_tree() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32)] {
+        entry:
+            y = add (%arg.0), (i32 1);
+            switch (%arg.0), merge, [ [(i32 1000), a], [(i32 -7), b], [(i32 10), merge],
+                                      [(i32 100000), a], [(i32 1), c], [(i32 55), b] ];
+        a:
+            br merge;
+        b:
+            z = mul (%y), (i32 2);
+            br merge;
+        c:
+            ret (i32 -100);
+        merge:
+            p = phi [ [(i32 1), a], [(%z), b], [(%y), entry] ];
+            ret (%p);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &x in &[1000, 100000, -7, 55, 10, 1, 0, 2, 11, 999, -8, 56] {
+            let expected = match x {
+                1000 | 100000 => 1,
+                -7 | 55 => (x + 1) * 2,
+                1 => -100,
+                x => x + 1,
+            };
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(x)]),
+                exec::jit::GenericValue::Int32(expected),
+                "{}",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn switch_dispatch_loop() {
+        let mut m = module::Module::new("cilk");
+
+        // A tiny bytecode interpreter: the opcode at `pc` is `pc % 5`
+        cilk_ir!(m; define [i32] func [(i32)] {
+        entry:
+            pc = alloca i32;
+            acc = alloca i32;
+            store (i32 0), (%pc);
+            store (i32 1), (%acc);
+            br dispatch;
+        dispatch:
+            lpc = load (%pc);
+            op = rem (%lpc), (i32 5);
+            switch (%op), bad, [ [(i32 0), inc], [(i32 1), dbl], [(i32 2), inc],
+                                 [(i32 3), sub], [(i32 4), next] ];
+        inc:
+            a = load (%acc);
+            a = add (%a), (i32 1);
+            store (%a), (%acc);
+            br next;
+        dbl:
+            a = load (%acc);
+            a = mul (%a), (i32 2);
+            store (%a), (%acc);
+            br next;
+        sub:
+            a = load (%acc);
+            a = sub (%a), (i32 3);
+            store (%a), (%acc);
+            br next;
+        next:
+            lpc = load (%pc);
+            lpc = add (%lpc), (i32 1);
+            store (%lpc), (%pc);
+            c = icmp lt (%lpc), (%arg.0);
+            br (%c) dispatch, end;
+        bad:
+            ret (i32 -1);
+        end:
+            a = load (%acc);
+            ret (%a);
+        });
+
+        let mut pass_mgr = ModulePassManager::new();
+        pass_mgr.add_pass(ir::mem2reg::Mem2Reg::new());
+        pass_mgr.add_pass(ir::licm::LoopInvariantCodeMotion::new());
+        pass_mgr.add_pass(ir::cse::CommonSubexprElimination::new());
+        pass_mgr.add_pass(ir::merge_ret::MergeReturns::new());
+        pass_mgr.add_pass(ir::codegen_prepare::CodegenPrepare::new());
+        pass_mgr.verify_each_pass();
+        pass_mgr.run_on_module(&mut m);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &n in &[1, 2, 5, 12, 23] {
+            let mut acc = 1;
+            for pc in 0..n {
+                match pc % 5 {
+                    0 | 2 => acc += 1,
+                    1 => acc *= 2,
+                    3 => acc -= 3,
+                    _ => {}
+                }
+            }
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(n)]),
+                exec::jit::GenericValue::Int32(acc)
+            );
+        }
+    }
+
+    #[test]
+    fn switch_loop_latch_passes() {
+        // The loop has three back edges, one of which is a `switch` reaching the header twice
+        let mut m = parser::parse_module(
+            "define i32 main(i32 %n) {
+entry:
+    %i = alloca i32
+    %acc = alloca i32
+    store i32 0, i32* %i
+    store i32 0, i32* %acc
+    br %header
+header:
+    %iv = load i32* %i
+    %c = icmp lt, i32 %iv, i32 %n
+    br i1 %c, %body, %exit
+body:
+    %a = load i32* %acc
+    %k = rem i32 %iv, i32 4
+    %next = add i32 %iv, i32 1
+    store i32 %next, i32* %i
+    switch i32 %k, %odd, i32 0, %header, i32 2, %header, i32 3, %bail
+odd:
+    %a1 = add i32 %a, i32 %iv
+    store i32 %a1, i32* %acc
+    br %header
+bail:
+    %big = icmp gt, i32 %a, i32 20
+    br i1 %big, %early, %header
+early:
+    ret i32 -1
+exit:
+    %r = load i32* %acc
+    ret i32 %r
+}",
+        )
+        .unwrap();
+        let f = m.find_function("main").unwrap();
+
+        {
+            let blocks = &m.function_ref(f).basic_blocks;
+            let b = &blocks.order;
+            let (header, body, odd, bail, early) = (b[1], b[2], b[3], b[4], b[5]);
+            let dom_tree = DominatorTreeConstructor::new(blocks).construct();
+            for &bb in &[body, odd, bail, early] {
+                assert!(dom_tree.dominate_bb(header, bb));
+            }
+            assert!(dom_tree.dominate_bb(body, odd));
+            assert!(dom_tree.dominate_bb(body, bail));
+            assert!(!dom_tree.dominate_bb(odd, bail));
+            for &bb in &[body, odd, bail] {
+                assert!(dom_tree
+                    .dominance_frontier_of(bb)
+                    .unwrap()
+                    .contains(&header));
+            }
+        }
+
+        let mut pass_mgr = ModulePassManager::new();
+        pass_mgr.add_pass(ir::mem2reg::Mem2Reg::new());
+        pass_mgr.add_pass(ir::simplify_loop::SimplifyLoop::new());
+        pass_mgr.add_pass(ir::merge_ret::MergeReturns::new());
+        pass_mgr.verify_each_pass();
+        pass_mgr.run_on_module(&mut m);
+
+        let printed = format!("{:?}", m);
+        assert!(!printed.contains("alloca"));
+        assert_eq!(printed.matches("ret i32").count(), 1);
+        {
+            let func = m.function_ref(f);
+            let header = func.basic_blocks.order[1];
+            // All back edges now go through a single latch
+            assert_eq!(func.basic_blocks.arena[header].pred.len(), 2);
+            let latch = *func.basic_blocks.arena[header]
+                .pred
+                .iter()
+                .find(|&&p| p != func.basic_blocks.order[0])
+                .unwrap();
+            assert_eq!(func.basic_blocks.arena[latch].pred.len(), 3);
+            let body = func.basic_blocks.order[2];
+            let switch = func.basic_blocks.arena[body]
+                .iseq_ref()
+                .last()
+                .unwrap()
+                .get_inst_id()
+                .unwrap();
+            let switch = &func.inst_table[switch];
+            assert_eq!(switch.opcode, opcode::Opcode::Switch);
+            assert_eq!(
+                switch
+                    .operands
+                    .iter()
+                    .filter(|&op| *op == opcode::Operand::BasicBlock(latch))
+                    .count(),
+                2
+            );
+            assert!(!switch
+                .operands
+                .contains(&opcode::Operand::BasicBlock(header)));
+        }
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        for &n in &[0, 1, 2, 5, 8, 12, 30] {
+            let mut expected = 0;
+            for i in 0..n {
+                match i % 4 {
+                    1 => expected += i,
+                    3 if expected > 20 => {
+                        expected = -1;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(n)]),
+                exec::jit::GenericValue::Int32(expected),
+                "{}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn switch_parse_and_verify() {
+        let text = "define i32 f(i32) {
+label.0:
+    switch i32 %arg.0, %label.1, i32 1, %label.2, i32 2, %label.1
+label.1:
+    ret i32 0
+label.2:
+    ret i32 1
+}";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("switch i32 %arg.0, %label.1, i32 1, %label.2, i32 2, %label.1"));
+        assert_eq!(
+            printed,
+            format!("{:?}", parser::parse_module(&printed).unwrap())
+        );
+
+        for (bad, msg) in &[
+            ("i32 1, %label.2, i32 1, %label.1", "duplicate case value"),
+            ("i64 1, %label.2", "case value must be i32"),
+            ("i32 %arg.0, %label.2", "is not a constant"),
+            ("i32 1", "(value, block) pairs"),
+        ] {
+            let text = format!(
+                "define i32 f(i32) {{
+label.0:
+    switch i32 %arg.0, %label.1, {}
+label.1:
+    ret i32 0
+label.2:
+    ret i32 1
+}}",
+                bad
+            );
+            let m = parser::parse_module(&text).unwrap();
+            let errors = verify::verify_module(&m).unwrap_err();
+            assert!(
+                errors.iter().any(|e| e.to_string().contains(msg)),
+                "{}: {:?}",
+                bad,
+                errors
+            );
+        }
+
+        let mut m = module::Module::new("cilk");
+        cilk_ir!(m; define [i32] g [(i32)] {
+        entry:
+            switch (%arg.0), a, [ [(i32 3), b] ];
+        a:
+            ret (i32 0);
+        b:
+            ret (i32 1);
+        });
+        assert_eq!(verify::verify_module(&m), Ok(()));
+    }
+}
@@ -0,0 +1,465 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::common::machine::calling_conv::CallingConv,
+        codegen::x64::{exec, machine::abi::SystemV, machine::register::RegisterClassKind},
+        ir::{builder, opcode, parser, types, value, verify},
+        *,
+    };
+
+    #[test]
+    fn arith_i16() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i16] func [(i16), (i16)] {
+        entry:
+            x = add (%arg.0), (%arg.1);
+            y = sub (%arg.0), (i16 300);
+            z = mul (%x), (%y);
+            w = mul (%z), (i16 3);
+            w = add (%w), (i16 -7);
+            ret (%w);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(1000i16, 2000i16), (-32768, 1), (12, -345)] {
+            let x = a.wrapping_add(b);
+            let y = a.wrapping_sub(300);
+            let w = x.wrapping_mul(y).wrapping_mul(3).wrapping_add(-7);
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int16(a),
+                        exec::jit::GenericValue::Int16(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int16(w)
+            );
+        }
+    }
+
+    #[test]
+    fn ext_trunc_i16() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i16)] {
+        entry:
+            s = sext [i32] (%arg.0);
+            z = zext [i32] (%arg.0);
+            z = shl (%z), (i32 16);
+            x = add (%s), (%z);
+            ret (%x);
+        });
+
+        cilk_ir!(m; define [i16] low_half [(i32)] {
+        entry:
+            x = trunc [i16] (%arg.0);
+            x = add (%x), (i16 1);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &a in &[-1i16, 0x1234, i16::min_value()] {
+            let expected = (a as i32).wrapping_add(((a as u16 as u32) << 16) as i32);
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int16(a)]),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+
+        let low_half = jit.find_function_by_name("low_half").unwrap();
+        for &a in &[0x7654_ffffi32, -1, 41] {
+            assert_eq!(
+                jit.run(low_half, vec![exec::jit::GenericValue::Int32(a)]),
+                exec::jit::GenericValue::Int16((a as i16).wrapping_add(1))
+            );
+        }
+    }
+
+    #[test]
+    fn bitwise_shift_div_ext_i16() {
+        let ops: &[(&str, fn(i16, i16) -> i16)] = &[
+            ("and", |a, b| a & b),
+            ("or", |a, b| a | b),
+            ("xor", |a, b| a ^ b),
+            ("shl", |a, b| a << b),
+            ("lshr", |a, b| ((a as u16) >> b) as i16),
+            ("ashr", |a, b| a >> b),
+            ("div", i16::wrapping_div),
+            ("rem", i16::wrapping_rem),
+            ("udiv", |a, b| (a as u16 / b as u16) as i16),
+            ("urem", |a, b| (a as u16 % b as u16) as i16),
+        ];
+        let mut src = String::new();
+        for (op, _) in ops {
+            src += &format!(
+                "define i16 {op}_rr(i16 %a, i16 %b) {{
+entry:
+    %x = {op} i16 %a, i16 %b
+    ret i16 %x
+}}
+define i16 {op}_ri(i16 %a) {{
+entry:
+    %x = {op} i16 %a, i16 3
+    ret i16 %x
+}}
+define i16 {op}_ir(i16 %b) {{
+entry:
+    %x = {op} i16 -3856, i16 %b
+    ret i16 %x
+}}
+",
+                op = op
+            );
+        }
+        src += "define i16 zext_i8(i8 %a) {
+entry:
+    %x = zext i8 %a to i16
+    ret i16 %x
+}
+define i16 sext_i8(i8 %a) {
+entry:
+    %x = sext i8 %a to i16
+    ret i16 %x
+}
+define i64 sext_i64(i16 %a) {
+entry:
+    %x = sext i16 %a to i64
+    ret i64 %x
+}
+";
+        let mut m = parser::parse_module(&src).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let i16 = exec::jit::GenericValue::Int16;
+        for (op, f) in ops {
+            let inputs: &[(i16, i16)] = if matches!(*op, "shl" | "lshr" | "ashr") {
+                &[(-12345, 0), (30001, 3), (-1, 15), (-3856, 7)]
+            } else {
+                &[(-32768, -1), (-7, 2), (1000, -3), (-30000, 7), (12345, 3)]
+            };
+            let rr = jit.find_function_by_name(&format!("{}_rr", op)).unwrap();
+            let ri = jit.find_function_by_name(&format!("{}_ri", op)).unwrap();
+            let ir = jit.find_function_by_name(&format!("{}_ir", op)).unwrap();
+            for &(a, b) in inputs {
+                assert_eq!(jit.run(rr, vec![i16(a), i16(b)]), i16(f(a, b)), "{}", op);
+                assert_eq!(jit.run(ri, vec![i16(a)]), i16(f(a, 3)), "{}", op);
+                assert_eq!(jit.run(ir, vec![i16(b)]), i16(f(-3856, b)), "{}", op);
+            }
+        }
+
+        let zext = jit.find_function_by_name("zext_i8").unwrap();
+        let sext = jit.find_function_by_name("sext_i8").unwrap();
+        for &a in &[-128i8, -1, 0, 127] {
+            let a8 = exec::jit::GenericValue::Int8(a);
+            assert_eq!(jit.run(zext, vec![a8.clone()]), i16(a as u8 as i16));
+            assert_eq!(jit.run(sext, vec![a8]), i16(a as i16));
+        }
+        let sext_i64 = jit.find_function_by_name("sext_i64").unwrap();
+        for &a in &[-32768i16, -5, 32767] {
+            assert_eq!(
+                jit.run(sext_i64, vec![i16(a)]),
+                exec::jit::GenericValue::Int64(a as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn load_store_i16() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i16] func [(i16)] {
+        entry:
+            a = alloca i16;
+            b = alloca i16;
+            store (%arg.0), (%a);
+            store (i16 -2), (%b);
+            x = load (%a);
+            y = load (%b);
+            c = icmp lt (%x), (%y);
+            br (%c) l1, l2;
+        l1:
+            ret (%x);
+        l2:
+            x = add (%x), (%y);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &a in &[-300i16, 5, 32767] {
+            let expected = if a < -2 { a } else { a.wrapping_add(-2) };
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int16(a)]),
+                exec::jit::GenericValue::Int16(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn arith_f32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [f32] func [(f32), (f32)] {
+        entry:
+            x = add (%arg.0), (%arg.1);
+            y = sub (f32 1.5), (%arg.1);
+            z = mul (%x), (%y);
+            w = div (%z), (f32 4.0);
+            ret (%w);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(1.25f32, 2.5f32), (-3.0, 0.125), (100.0, -7.75)] {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::F32(a),
+                        exec::jit::GenericValue::F32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::F32((a + b) * (1.5 - b) / 4.0)
+            );
+        }
+    }
+
+    #[test]
+    fn load_store_fcmp_f32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [f32] func [(f32)] {
+        entry:
+            a = alloca f32;
+            store (f32 0.5), (%a);
+            c = fcmp ult (%arg.0), (f32 10.0);
+            br (%c) l1, l2;
+        l1:
+            x = load (%a);
+            x = add (%x), (%arg.0);
+            store (%x), (%a);
+            br l2;
+        l2:
+            x = load (%a);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &a in &[3.25f32, 10.0, 42.0, -1.0] {
+            let expected = if a < 10.0 { 0.5 + a } else { 0.5 };
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::F32(a)]),
+                exec::jit::GenericValue::F32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn fpext_fptrunc() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [f64] ext [(f32)] {
+        entry:
+            x = fpext [f64] (%arg.0);
+            x = mul (%x), (f64 0.5);
+            ret (%x);
+        });
+
+        cilk_ir!(m; define [f32] trunc [(f64)] {
+        entry:
+            x = fptrunc [f32] (%arg.0);
+            x = add (%x), (f32 1.0);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let ext = jit.find_function_by_name("ext").unwrap();
+        let trunc = jit.find_function_by_name("trunc").unwrap();
+        for &a in &[1.1f32, -2.5, 1e30] {
+            assert_eq!(
+                jit.run(ext, vec![exec::jit::GenericValue::F32(a)]),
+                exec::jit::GenericValue::F64(a as f64 * 0.5)
+            );
+        }
+        for &a in &[0.1f64, -1234.5678, 1e300] {
+            assert_eq!(
+                jit.run(trunc, vec![exec::jit::GenericValue::F64(a)]),
+                exec::jit::GenericValue::F32(a as f32 + 1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn struct_layout() {
+        let m = module::Module::new("cilk");
+        let ty = m.types.new_struct_ty(vec![
+            types::Type::i8,
+            types::Type::i16,
+            types::Type::f32,
+            types::Type::i16,
+        ]);
+        {
+            let base = m.types.base.borrow();
+            let s = base.as_struct_ty(ty).unwrap();
+            assert_eq!(
+                (0..4)
+                    .map(|i| *s.get_elem_offset(i).unwrap())
+                    .collect::<Vec<_>>(),
+                vec![0, 2, 4, 8]
+            );
+            assert_eq!((s.size(), s.align()), (12, 4));
+        }
+
+        let floats =
+            m.types
+                .new_struct_ty(vec![types::Type::f32, types::Type::f32, types::Type::f32]);
+        let mixed = m
+            .types
+            .new_struct_ty(vec![types::Type::f32, types::Type::i32]);
+        let base = m.types.base.borrow();
+        assert_eq!(
+            SystemV::reg_classes_used_for_passing_byval(base.as_struct_ty(floats).unwrap()),
+            vec![RegisterClassKind::XMM, RegisterClassKind::XMM]
+        );
+        assert_eq!(
+            SystemV::reg_classes_used_for_passing_byval(base.as_struct_ty(mixed).unwrap()),
+            vec![RegisterClassKind::GR64]
+        );
+    }
+
+    #[test]
+    fn const_folding_i16_f32() {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function("f", types::Type::f32, vec![]);
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        builder.set_insert_point(entry);
+
+        let x = builder.build_add(
+            value::Value::new_imm_int16(30000),
+            value::Value::new_imm_int16(1000),
+        );
+        assert_eq!(x, value::Value::new_imm_int16(31000));
+        let x = builder.build_zext(value::Value::new_imm_int16(-5), types::Type::i32);
+        assert_eq!(x, value::Value::new_imm_int32(65531));
+        let x = builder.build_mul(
+            value::Value::new_imm_f32(1.5),
+            value::Value::new_imm_f32(2.0),
+        );
+        assert_eq!(x, value::Value::new_imm_f32(3.0));
+        let x = builder.build_fpext(value::Value::new_imm_f32(0.25), types::Type::f64);
+        assert_eq!(x, value::Value::new_imm_f64(0.25));
+        let x = builder.build_fptrunc(value::Value::new_imm_f64(0.1), types::Type::f32);
+        assert_eq!(x, value::Value::new_imm_f32(0.1));
+    }
+
+    #[test]
+    fn named_struct() {
+        let m = module::Module::new("cilk");
+        let node = m.types.new_named_struct_ty("Node");
+        assert!(m
+            .types
+            .base
+            .borrow()
+            .as_struct_ty(node)
+            .unwrap()
+            .is_opaque());
+        let node_ptr = m.types.new_pointer_ty(node);
+        m.types
+            .set_struct_body(node, vec![types::Type::i32, node_ptr]);
+        assert_eq!(m.types.new_named_struct_ty("Node"), node);
+        assert_eq!(m.types.find_named_struct_ty("Node"), Some(node));
+        assert_eq!(m.types.find_named_struct_ty("Leaf"), None);
+        // Different from an unnamed struct with the same fields
+        assert_ne!(
+            m.types.new_struct_ty(vec![types::Type::i32, node_ptr]),
+            node
+        );
+        assert_eq!(m.types.to_string(node_ptr), "struct Node*");
+
+        let base = m.types.base.borrow();
+        let s = base.as_struct_ty(node).unwrap();
+        assert!(!s.is_opaque());
+        assert_eq!(s.name(), Some("Node"));
+        assert_eq!(s.fields_offset(), &[0, 8]);
+        assert_eq!((s.size(), s.align()), (16, 8));
+    }
+
+    const LIST: &str = "struct Node = {i32, struct Node*}
+struct Handle = opaque
+@handle = external global struct Handle*
+define i32 sum(struct Node*, i32) {
+label.0:
+    %0 = icmp eq, i32 %arg.1, i32 0
+    br i1 %0, %label.1, %label.2
+label.1:
+    ret i32 0
+label.2:
+    %1 = getelementptr struct Node* %arg.0, i32 0, i32 0
+    %2 = load i32* %1
+    %3 = getelementptr struct Node* %arg.0, i32 0, i32 1
+    %4 = load struct Node** %3
+    %5 = sub i32 %arg.1, i32 1
+    %6 = call i32 sum, struct Node* %4, i32 %5
+    %7 = add i32 %2, i32 %6
+    ret i32 %7
+}
+define i32 main() {
+label.0:
+    %0 = alloca struct Node
+    %1 = alloca struct Node
+    %2 = getelementptr struct Node* %0, i32 0, i32 0
+    store i32 3, i32* %2
+    %3 = getelementptr struct Node* %0, i32 0, i32 1
+    store struct Node* %1, struct Node** %3
+    %4 = getelementptr struct Node* %1, i32 0, i32 0
+    store i32 4, i32* %4
+    %5 = getelementptr struct Node* %1, i32 0, i32 1
+    store struct Node* %0, struct Node** %5
+    %6 = call i32 sum, struct Node* %0, i32 5
+    ret i32 %6
+}";
+
+    #[test]
+    fn recursive_struct() {
+        let mut m = parser::parse_module(LIST).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert!(printed.contains("struct Node = {i32, struct Node*}\nstruct Handle = opaque\n"));
+        assert_eq!(
+            format!("{:?}", parser::parse_module(&printed).unwrap()),
+            printed
+        );
+        let mut buf = vec![];
+        m.write_bitcode(&mut buf).unwrap();
+        let m2 = module::Module::read_bitcode(buf.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", m2), printed);
+
+        // Linking gives the opaque struct a body and keeps the cycle
+        let mut m3 = parser::parse_module(
+            "struct Node = opaque
+declare i32 sum(struct Node*, i32)",
+        )
+        .unwrap();
+        m3.link_in(m2).unwrap();
+        let node = m3.types.find_named_struct_ty("Node").unwrap();
+        assert_eq!(
+            m3.types.base.borrow().named_structs_to_string(),
+            "struct Node = {i32, struct Node*}\nstruct Handle = opaque\n"
+        );
+        assert_eq!(
+            m3.types.base.borrow().as_struct_ty(node).unwrap().size(),
+            16
+        );
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(jit.run(func, vec![]), exec::jit::GenericValue::Int32(17));
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::builder::FuncRef,
+        ir::{builder, parser, verify},
+        traits::pass::ModulePassManager,
+        *,
+    };
+
+    fn run_passes(m: &mut module::Module) {
+        let mut pass_mgr = ModulePassManager::new();
+        pass_mgr.add_pass(ir::mem2reg::Mem2Reg::new());
+        pass_mgr.add_pass(ir::licm::LoopInvariantCodeMotion::new());
+        pass_mgr.add_pass(ir::cse::CommonSubexprElimination::new());
+        pass_mgr.add_pass(ir::merge_ret::MergeReturns::new());
+        pass_mgr.add_pass(ir::codegen_prepare::CodegenPrepare::new());
+        pass_mgr.verify_each_pass();
+        pass_mgr.run_on_module(m);
+    }
+
+    #[test]
+    fn unreachable_after_trap() {
+        let mut m = module::Module::new("cilk");
+        let trap = m.create_function("cilk.trap", types::Type::Void, vec![]);
+
+        cilk_ir!(m; define [i32] get [(i32)] {
+        entry:
+            arr = alloca_ ([4; i32]);
+            p = gep (%arr), [(i32 0), (i32 0)];
+            store (i32 0), (%p);
+            p = gep (%arr), [(i32 0), (i32 1)];
+            store (i32 1), (%p);
+            p = gep (%arr), [(i32 0), (i32 2)];
+            store (i32 4), (%p);
+            p = gep (%arr), [(i32 0), (i32 3)];
+            store (i32 9), (%p);
+            br check;
+        check:
+            c = icmp lt (%arg.0), (i32 0);
+            br (%c) fail, check2;
+        check2:
+            c = icmp ge (%arg.0), (i32 4);
+            br (%c) fail, ok;
+        fail:
+            __ = call (->trap) [];
+            unreachable;
+        ok:
+            p = gep (%arr), [(i32 0), (%arg.0)];
+            x = load (%p);
+            ret (%x);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        run_passes(&mut m);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("get").unwrap();
+        for i in 0..4 {
+            assert_eq!(
+                jit.run(func, vec![exec::jit::GenericValue::Int32(i)]),
+                exec::jit::GenericValue::Int32(i * i)
+            );
+        }
+    }
+
+    #[test]
+    fn unreachable_without_return() {
+        let mut m = module::Module::new("cilk");
+        let trap = m.create_function("cilk.trap", types::Type::Void, vec![]);
+
+        // No path returns, so there is nothing for merge_ret to merge
+        cilk_ir!(m; define [i32] never [(i32)] {
+        entry:
+            c = icmp eq (%arg.0), (i32 0);
+            br (%c) a, b;
+        a:
+            __ = call (->trap) [];
+            unreachable;
+        b:
+            unreachable;
+        });
+        cilk_ir!(m; define [i32] main [(i32)] {
+        entry:
+            c = icmp eq (%arg.0), (i32 12345);
+            br (%c) bad, good;
+        bad:
+            x = call never [(%arg.0)];
+            unreachable;
+        good:
+            x = add (%arg.0), (i32 1);
+            ret (%x);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        run_passes(&mut m);
+        assert!(format!("{:?}", m).contains("unreachable"));
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("main").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(4)]),
+            exec::jit::GenericValue::Int32(5)
+        );
+    }
+
+    #[test]
+    fn parse_unreachable() {
+        let text = "define i32 f(i32) {
+label.0:
+    %0 = icmp eq, i32 %arg.0, i32 0
+    br i1 %0, %label.1, %label.2
+label.1:
+    unreachable
+label.2:
+    ret i32 %arg.0
+}";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+        let printed = format!("{:?}", m);
+        assert_eq!(
+            printed,
+            format!("{:?}", parser::parse_module(&printed).unwrap())
+        );
+
+        // A block that is not reachable from the entry may still branch into live code
+        let text = "define i32 f(i32) {
+label.0:
+    %0 = icmp eq, i32 %arg.0, i32 0
+    br i1 %0, %label.2, %label.3
+label.1:
+    br %label.2
+label.2:
+    unreachable
+label.3:
+    ret i32 %arg.0
+}";
+        let m = parser::parse_module(text).unwrap();
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        assert!(parser::parse_module(
+            "define void f() {
+label.0:
+    unreachable i32 0
+}"
+        )
+        .is_err());
+    }
+}
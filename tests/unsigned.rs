@@ -0,0 +1,361 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{builder, opcode, types, value},
+        *,
+    };
+
+    #[test]
+    fn udiv_urem_i32() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32), (i32)] {
+        entry:
+            q = udiv (%arg.0), (%arg.1);
+            r = urem (%arg.0), (%arg.1);
+            c = urem (%arg.0), (i32 10);
+            q = mul (%q), (i32 100);
+            r = add (%r), (%q);
+            r = add (%r), (%c);
+            ret (%r);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(4000000000u32, 7u32), (17, 5), (0xffff_ffff, 0x8000_0000)] {
+            let expected = (a / b)
+                .wrapping_mul(100)
+                .wrapping_add(a % b)
+                .wrapping_add(a % 10);
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a as i32),
+                        exec::jit::GenericValue::Int32(b as i32)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(expected as i32)
+            );
+        }
+    }
+
+    #[test]
+    fn udiv_urem_i64() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i64] func [(i64), (i64)] {
+        entry:
+            q = udiv (%arg.0), (%arg.1);
+            r = urem (%arg.0), (%arg.1);
+            q = xor (%q), (%r);
+            ret (%q);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(u64::max_value(), 3u64), (1 << 40, 12345), (7, 9)] {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int64(a as i64),
+                        exec::jit::GenericValue::Int64(b as i64)
+                    ]
+                ),
+                exec::jit::GenericValue::Int64(((a / b) ^ (a % b)) as i64)
+            );
+        }
+    }
+
+    #[test]
+    fn udiv_i8() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i8] func [(i8), (i8)] {
+        entry:
+            q = udiv (%arg.0), (%arg.1);
+            r = urem (%arg.0), (i8 7);
+            q = xor (%q), (%r);
+            ret (%q);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(250u8, 3u8), (128, 200), (9, 1)] {
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int8(a as i8),
+                        exec::jit::GenericValue::Int8(b as i8)
+                    ]
+                ),
+                exec::jit::GenericValue::Int8(((a / b) ^ (a % 7)) as i8)
+            );
+        }
+    }
+
+    #[test]
+    fn zext_trunc() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i64] func [(i8), (i32)] {
+        entry:
+            a = zext [i32] (%arg.0);
+            a = add (%a), (i32 1);
+            b = zext [i64] (%arg.0);
+            c = zext [i64] (%arg.1);
+            d = zext [i64] (%a);
+            b = shl (%b), (i64 32);
+            c = add (%c), (%b);
+            c = add (%c), (%d);
+            ret (%c);
+        });
+
+        cilk_ir!(m; define [i8] low_byte [(i64)] {
+        entry:
+            x = trunc [i32] (%arg.0);
+            x = add (%x), (i32 1);
+            y = trunc [i8] (%x);
+            z = trunc [i8] (%arg.0);
+            y = xor (%y), (%z);
+            ret (%y);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(-1i8, -1i32), (100, 5), (-128, i32::min_value())] {
+            let expected =
+                ((a as u8 as u64) << 32) + (b as u32 as u64) + (a as u8 as u32 + 1) as u64;
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int8(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int64(expected as i64)
+            );
+        }
+
+        let low_byte = jit.find_function_by_name("low_byte").unwrap();
+        for &a in &[0x1234_5678_9abc_def0i64, -1, 0xff] {
+            let expected = ((a as i32).wrapping_add(1) as i8) ^ (a as i8);
+            assert_eq!(
+                jit.run(low_byte, vec![exec::jit::GenericValue::Int64(a)]),
+                exec::jit::GenericValue::Int8(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn unsigned_icmp() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32), (i32)] {
+        entry:
+            r = alloca i32;
+            store (i32 0), (%r);
+            c = icmp ult (%arg.0), (%arg.1);
+            br (%c) l1, l2;
+        l1:
+            store (i32 1), (%r);
+            br l2;
+        l2:
+            c = icmp ugt (%arg.0), (i32 100);
+            br (%c) l3, l4;
+        l3:
+            x = load (%r);
+            x = add (%x), (i32 2);
+            store (%x), (%r);
+            br l4;
+        l4:
+            c = icmp ule (i32 7), (%arg.1);
+            br (%c) l5, l6;
+        l5:
+            x = load (%r);
+            x = add (%x), (i32 4);
+            store (%x), (%r);
+            br l6;
+        l6:
+            c = icmp uge (%arg.0), (%arg.1);
+            br (%c) l7, l8;
+        l7:
+            x = load (%r);
+            x = add (%x), (i32 8);
+            store (%x), (%r);
+            br l8;
+        l8:
+            x = load (%r);
+            ret (%x);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        for &(a, b) in &[(-1i32, 1i32), (1, -1), (7, 7), (200, 3), (3, 200)] {
+            let (ua, ub) = (a as u32, b as u32);
+            let expected = (ua < ub) as i32
+                + 2 * (ua > 100) as i32
+                + 4 * (7 <= ub) as i32
+                + 8 * (ua >= ub) as i32;
+            assert_eq!(
+                jit.run(
+                    func,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn icmp_as_value() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] ult [(i32), (i32)] {
+        entry:
+            c = icmp ult (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            ret (%x);
+        });
+
+        cilk_ir!(m; define [i32] ult_i8 [(i8), (i8)] {
+        entry:
+            c = icmp ult (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            ret (%x);
+        });
+
+        cilk_ir!(m; define [i32] all [(i32), (i32)] {
+        entry:
+            c = icmp eq (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            r = add (%x), (i32 0);
+            c = icmp ne (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 1);
+            r = add (%r), (%x);
+            c = icmp lt (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 2);
+            r = add (%r), (%x);
+            c = icmp le (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 3);
+            r = add (%r), (%x);
+            c = icmp gt (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 4);
+            r = add (%r), (%x);
+            c = icmp ge (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 5);
+            r = add (%r), (%x);
+            c = icmp ult (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 6);
+            r = add (%r), (%x);
+            c = icmp ule (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 7);
+            r = add (%r), (%x);
+            c = icmp ugt (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 8);
+            r = add (%r), (%x);
+            c = icmp uge (%arg.0), (%arg.1);
+            x = zext [i32] (%c);
+            x = shl (%x), (i32 9);
+            r = add (%r), (%x);
+            ret (%r);
+        });
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let ult = jit.find_function_by_name("ult").unwrap();
+        let ult_i8 = jit.find_function_by_name("ult_i8").unwrap();
+        let all = jit.find_function_by_name("all").unwrap();
+        for &(a, b) in &[(-1i32, 1i32), (1, -1), (7, 7), (200, 3), (3, 200)] {
+            let (ua, ub) = (a as u32, b as u32);
+            assert_eq!(
+                jit.run(
+                    ult,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32((ua < ub) as i32)
+            );
+            assert_eq!(
+                jit.run(
+                    ult_i8,
+                    vec![
+                        exec::jit::GenericValue::Int8(a as i8),
+                        exec::jit::GenericValue::Int8(b as i8)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(((a as u8) < (b as u8)) as i32)
+            );
+            let expected = [
+                a == b,
+                a != b,
+                a < b,
+                a <= b,
+                a > b,
+                a >= b,
+                ua < ub,
+                ua <= ub,
+                ua > ub,
+                ua >= ub,
+            ]
+            .iter()
+            .enumerate()
+            .fold(0, |r, (i, &c)| r + ((c as i32) << i));
+            assert_eq!(
+                jit.run(
+                    all,
+                    vec![
+                        exec::jit::GenericValue::Int32(a),
+                        exec::jit::GenericValue::Int32(b)
+                    ]
+                ),
+                exec::jit::GenericValue::Int32(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn unsigned_const_folding() {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function("f", types::Type::i32, vec![]);
+        let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+        let entry = builder.append_basic_block();
+        builder.set_insert_point(entry);
+
+        let x = builder.build_udiv(
+            value::Value::new_imm_int32(-8),
+            value::Value::new_imm_int32(3),
+        );
+        assert_eq!(x, value::Value::new_imm_int32((0xffff_fff8u32 / 3) as i32));
+        let x = builder.build_urem(
+            value::Value::new_imm_int8(-1),
+            value::Value::new_imm_int8(10),
+        );
+        assert_eq!(x, value::Value::new_imm_int8(5));
+        let x = builder.build_zext(value::Value::new_imm_int8(-1), types::Type::i32);
+        assert_eq!(x, value::Value::new_imm_int32(255));
+        let x = builder.build_trunc(value::Value::new_imm_int32(0x1ff), types::Type::i8);
+        assert_eq!(x, value::Value::new_imm_int8(-1));
+        let x = builder.build_udiv(
+            value::Value::new_imm_int32(1),
+            value::Value::new_imm_int32(0),
+        );
+        assert!(matches!(x, value::Value::Instruction(_)));
+    }
+}
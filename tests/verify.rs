@@ -0,0 +1,191 @@
+#[cfg(feature = "x86_64")]
+mod x86_64 {
+    use cilk::{
+        codegen::x64::exec,
+        ir::{builder, parser, verify},
+        traits::pass::{ModulePassManager, ModulePassTrait},
+        *,
+    };
+
+    #[test]
+    fn verify_each_pass() {
+        let mut m = module::Module::new("cilk");
+
+        cilk_ir!(m; define [i32] func [(i32)] {
+        entry:
+            i = alloca i32;
+            s = alloca i32;
+            store (i32 0), (%i);
+            store (i32 0), (%s);
+            br cond;
+        cond:
+            li = load (%i);
+            c = icmp lt (%li), (%arg.0);
+            br (%c) body, end;
+        body:
+            k = add (%arg.0), (i32 1);
+            k = add (%k), (i32 2);
+            k = mul (%k), (i32 4);
+            ls = load (%s);
+            ls = add (%ls), (%k);
+            store (%ls), (%s);
+            inc = add (%li), (i32 1);
+            store (%inc), (%i);
+            br cond;
+        end:
+            ls = load (%s);
+            ret (%ls);
+        });
+
+        assert_eq!(verify::verify_module(&m), Ok(()));
+
+        let mut pass_mgr = ModulePassManager::new();
+        pass_mgr.add_pass(ir::mem2reg::Mem2Reg::new());
+        pass_mgr.add_pass(ir::licm::LoopInvariantCodeMotion::new());
+        pass_mgr.add_pass(ir::cse::CommonSubexprElimination::new());
+        pass_mgr.add_pass(ir::merge_ret::MergeReturns::new());
+        pass_mgr.add_pass(ir::const_folding::ConstantFolding::new());
+        pass_mgr.add_pass(ir::inst_combine::InstructionCombine::new());
+        pass_mgr.add_pass(ir::codegen_prepare::CodegenPrepare::new());
+        pass_mgr.verify_each_pass();
+        pass_mgr.run_on_module(&mut m);
+
+        let mut jit = exec::jit::JITExecutor::new(&mut m);
+        let func = jit.find_function_by_name("func").unwrap();
+        assert_eq!(
+            jit.run(func, vec![exec::jit::GenericValue::Int32(10)]),
+            exec::jit::GenericValue::Int32(520)
+        );
+    }
+
+    #[test]
+    fn verify_each_pass_error() {
+        // Removes the terminator of every entry block
+        struct BreakTerminators {}
+
+        impl ModulePassTrait for BreakTerminators {
+            type M = module::Module;
+
+            fn name(&self) -> &'static str {
+                "BreakTerminators"
+            }
+
+            fn run_on_module(&mut self, module: &mut Self::M) {
+                for (_, f) in &mut module.functions {
+                    if let Some(&entry) = f.basic_blocks.order.first() {
+                        let ret = f.basic_blocks.arena[entry].iseq_ref().last().copied();
+                        f.remove_inst(ret.unwrap().get_inst_id().unwrap());
+                    }
+                }
+            }
+        }
+
+        let mut m = parser::parse_module(
+            "define i32 f(i32 %x) {
+entry:
+    %y = add i32 %x, i32 1
+    ret i32 %y
+}",
+        )
+        .unwrap();
+        let mut pass_mgr = ModulePassManager::new();
+        pass_mgr.add_pass(ir::const_folding::ConstantFolding::new());
+        pass_mgr.add_pass(BreakTerminators {});
+        pass_mgr.verify_each_pass();
+        let err = pass_mgr.try_run_on_module(&mut m).unwrap_err();
+        assert_eq!(err.pass, "BreakTerminators");
+        assert_eq!(err.errors.len(), 1);
+        let e = &err.errors[0];
+        assert_eq!(e.func_name, "f");
+        assert!(e.block.is_some());
+        assert_eq!(e.msg, "block does not end in a terminator");
+        assert_eq!(
+            e.to_string(),
+            "f: label.0: %0: block does not end in a terminator"
+        );
+    }
+
+    #[test]
+    fn verify_missing_terminator() {
+        let mut m = module::Module::new("cilk");
+        let f = m.create_function("f", types::Type::i32, vec![types::Type::i32]);
+        {
+            let mut builder = builder::Builder::new(builder::FunctionIdWithModule::new(&mut m, f));
+            let entry = builder.append_basic_block();
+            builder.set_insert_point(entry);
+            cilk_ir!((builder) {
+                x = add (%arg.0), (i32 1);
+            });
+        }
+
+        let errors = verify::verify_module(&m).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].func, f);
+        assert_eq!(errors[0].inst.map(|id| id.index()), Some(0));
+        assert_eq!(
+            errors[0].to_string(),
+            "f: label.0: %0: block does not end in a terminator"
+        );
+    }
+
+    #[test]
+    fn verify_phi_and_dominance() {
+        let m = parser::parse_module(
+            "
+define i32 f(i32) {
+label.0:
+    %0 = icmp le, i32 %arg.0, i32 2
+    br i1 %0, %label.1, %label.2
+label.1:
+    %2 = add i32 %arg.0, i32 1
+    br %label.3
+label.2:
+    %4 = add i32 %2, i32 2
+    br %label.3
+label.3:
+    %6 = phi i32 %2, %label.1
+    ret i32 %6
+}",
+        )
+        .unwrap();
+
+        let errors = verify::verify_module(&m)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                "f: label.2: %4: %2 does not dominate this use",
+                "f: label.3: %6: no incoming value for label.2",
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_var_arg_call() {
+        let m = parser::parse_module(
+            "
+declare i32 printf(i8*, ...)
+define i32 f(i8*) {
+label.0:
+    %0 = call i32 printf, i8* %arg.0, i32 1, f64 2.5
+    %1 = call i32 printf, i8* %arg.0
+    %2 = call i32 printf
+    ret i32 %0
+}",
+        )
+        .unwrap();
+
+        let errors = verify::verify_module(&m)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec!["f: label.0: %2: call: expected at least 1 arguments, found 0"]
+        );
+    }
+}